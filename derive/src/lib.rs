@@ -0,0 +1,241 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Expr, Fields, GenericArgument, Index, Lit, LitInt, LitStr,
+    PathArguments, Result, Type,
+};
+
+
+struct TableAttributes {
+    id: String,
+    delimiter: String,
+    terminator: String,
+    percent_size: String,
+}
+
+struct ColumnAttributes {
+    name: String,
+    sql_type: LitStr,
+    size: LitInt,
+    generator: Expr,
+    key: bool,
+    null_rate: Option<Lit>,
+}
+
+
+fn number_literal(lit: &Lit) -> Result<String> {
+    match lit {
+        Lit::Float(x) => Ok(x.base10_digits().to_string()),
+        Lit::Int(x) => Ok(x.base10_digits().to_string()),
+        Lit::Str(x) if x.value().parse::<f64>().is_ok() => Ok(x.value()),
+        _ => Err(Error::new_spanned(lit, "expected a number")),
+    }
+}
+
+
+fn parse_table_attributes(input: &DeriveInput) -> Result<TableAttributes> {
+    let mut attributes = TableAttributes {
+        id: input.ident.to_string(),
+        delimiter: "|".into(),
+        terminator: "\n".into(),
+        percent_size: "1.0".into(),
+    };
+
+    for attr in input.attrs.iter().filter(|x| x.path().is_ident("synthetic")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("id") {
+                attributes.id = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("delimiter") {
+                attributes.delimiter = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("terminator") {
+                attributes.terminator = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("percent_size") {
+                attributes.percent_size = number_literal(&meta.value()?.parse::<Lit>()?)?;
+            } else {
+                return Err(meta.error("unsupported synthetic table attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(attributes)
+}
+
+
+fn parse_column_attributes(field: &syn::Field) -> Result<ColumnAttributes> {
+    let mut name = field.ident.as_ref().map(|x| x.to_string());
+    let mut sql_type = None;
+    let mut size = None;
+    let mut generator = None;
+    let mut key = false;
+    let mut null_rate = None;
+
+    for attr in field.attrs.iter().filter(|x| x.path().is_ident("synthetic")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("sql_type") {
+                sql_type = Some(meta.value()?.parse::<LitStr>()?);
+            } else if meta.path.is_ident("size") {
+                size = Some(meta.value()?.parse::<LitInt>()?);
+            } else if meta.path.is_ident("generator") {
+                generator = Some(meta.value()?.parse::<Expr>()?);
+            } else if meta.path.is_ident("key") {
+                key = true;
+            } else if meta.path.is_ident("null_rate") {
+                null_rate = Some(meta.value()?.parse::<Lit>()?);
+            } else {
+                return Err(meta.error("unsupported synthetic column attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(ColumnAttributes {
+        name: name.ok_or_else(|| Error::new_spanned(field, "tuple struct fields need a `name`"))?,
+        sql_type: sql_type.ok_or_else(|| Error::new_spanned(field, "missing `sql_type`"))?,
+        size: size.ok_or_else(|| Error::new_spanned(field, "missing `size`"))?,
+        generator: generator.ok_or_else(|| Error::new_spanned(field, "missing `generator`"))?,
+        key,
+        null_rate,
+    })
+}
+
+
+fn column_tokens(column: &ColumnAttributes) -> Result<TokenStream2> {
+    let ColumnAttributes { name, sql_type, size, generator, .. } = column;
+
+    let key = column.key.then(|| quote! { .as_key() });
+    let null_rate = match &column.null_rate {
+        Some(lit) => {
+            let null_rate = number_literal(lit)?.parse::<f64>()
+                .map_err(|_| Error::new_spanned(lit, "expected a number"))?;
+            Some(quote! { .with_null_rate(#null_rate) })
+        }
+        None => None,
+    };
+
+    Ok(quote! {
+        ::diplomski_projekt::Column::new(
+            #name.to_string(),
+            #size,
+            ::diplomski_projekt::types::ColumnType::from(#sql_type),
+            #generator,
+        )#key #null_rate
+    })
+}
+
+
+// Inner type of an `Option<T>` field, nullable columns are read into options.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else { return None };
+    match arguments.args.first()? {
+        GenericArgument::Type(x) => Some(x),
+        _ => None,
+    }
+}
+
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let (fields, named) = match &input.data {
+        Data::Struct(x) => match &x.fields {
+            Fields::Named(x) => (x.named.iter().collect::<Vec<_>>(), true),
+            Fields::Unnamed(x) => (x.unnamed.iter().collect::<Vec<_>>(), false),
+            Fields::Unit => (vec![], true),
+        },
+        _ => return Err(Error::new_spanned(&input.ident, "SyntheticTable can only be derived for structs")),
+    };
+
+    let table = parse_table_attributes(&input)?;
+    let attributes = fields.iter()
+        .map(|x| parse_column_attributes(x))
+        .collect::<Result<Vec<_>>>()?;
+    let columns = attributes.iter()
+        .map(column_tokens)
+        .collect::<Result<Vec<_>>>()?;
+
+    let values = fields.iter()
+        .zip(attributes.iter())
+        .enumerate()
+        .map(|(i, (field, column))| {
+            let name = &column.name;
+            let (ty, nullable) = match (&column.null_rate, option_inner(&field.ty)) {
+                (Some(_), Some(inner)) => (inner, true),
+                (Some(lit), None) => return Err(Error::new_spanned(lit, "fields with `null_rate` must be `Option<T>`")),
+                (None, _) => (&field.ty, false),
+            };
+            let parse = quote! {
+                value.parse::<#ty>()
+                    .map_err(|_| ::diplomski_projekt::RowError::InvalidValue {
+                        column: #name.to_string(),
+                        value: value.clone(),
+                    })?
+            };
+            let parse = if nullable {
+                quote! {
+                    if ::diplomski_projekt::types::NullRepresentation::is_null_value(value) {
+                        None
+                    } else {
+                        Some(#parse)
+                    }
+                }
+            } else {
+                parse
+            };
+
+            Ok(quote! {
+                {
+                    let value = row.get(#i)
+                        .ok_or_else(|| ::diplomski_projekt::RowError::MissingValue { column: #name.to_string() })?;
+                    #parse
+                }
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let construct = if named {
+        let idents = fields.iter().map(|x| &x.ident);
+        quote! { Self { #(#idents: #values),* } }
+    } else {
+        let indices = (0..fields.len()).map(Index::from);
+        quote! { Self { #(#indices: #values),* } }
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let TableAttributes { id, delimiter, terminator, percent_size } = table;
+
+    Ok(quote! {
+        impl #impl_generics ::diplomski_projekt::SyntheticTable for #ident #type_generics #where_clause {
+            fn synthetic_table() -> ::diplomski_projekt::Table {
+                ::diplomski_projekt::Table::new(
+                    #id.to_string(),
+                    vec![#(#columns),*],
+                    #delimiter.to_string(),
+                    <::diplomski_projekt::Decimal as ::core::str::FromStr>::from_str(#percent_size)
+                        .expect("percent_size is checked to be a number at compile time"),
+                )
+                .with_terminator(::diplomski_projekt::types::RecordTerminator::from(#terminator))
+            }
+
+            fn from_row(row: &[String]) -> ::core::result::Result<Self, ::diplomski_projekt::RowError> {
+                Ok(#construct)
+            }
+        }
+    })
+}
+
+
+#[proc_macro_derive(SyntheticTable, attributes(synthetic))]
+pub fn derive_synthetic_table(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,137 @@
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::{ExportFile, Table};
+use crate::generators;
+
+
+#[derive(Error, Debug)]
+pub enum AblationError {
+    #[error("No table {table} to disable.")]
+    UnknownTable { table: String },
+    #[error("Table {table} has no column {column} to disable.")]
+    UnknownColumn { table: String, column: String },
+}
+
+
+/// What a disabled column emits instead of generated values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Placeholder {
+    /// The table's null representation.
+    #[default]
+    Null,
+    Constant(String),
+}
+
+
+impl Table {
+    /// Emits the placeholder instead of generating the column, its declared size is kept so the row counts of an
+    /// ablation run match the full run.
+    pub fn with_disabled_column(mut self, column: &str, placeholder: &Placeholder) -> Result<Self> {
+        let index = self.columns.iter()
+            .position(|x| x.name == column)
+            .ok_or_else(|| Error::from(AblationError::UnknownColumn {
+                table: self.id_value.clone(),
+                column: column.to_string(),
+            }))?;
+
+        self.disable_column_at(index, placeholder);
+        Ok(self)
+    }
+
+
+    /// Emits the placeholder for every column of the table.
+    pub fn with_disabled(mut self, placeholder: &Placeholder) -> Self {
+        for index in 0..self.columns.len() {
+            self.disable_column_at(index, placeholder);
+        }
+        self
+    }
+
+
+    fn disable_column_at(&mut self, index: usize, placeholder: &Placeholder) {
+        match placeholder {
+            Placeholder::Null => {
+                self.replace_generator(index, generators::constant(""));
+                self.columns[index].null_rate = 1.0;
+            }
+            Placeholder::Constant(x) => {
+                self.replace_generator(index, generators::constant(x.clone()));
+                self.columns[index].null_rate = 0.0;
+            }
+        }
+
+        // A placeholder repeats, so constraints on the column can not hold any more.
+        self.unique.retain(|x| !x.contains(&index));
+        self.update_value_spaces();
+    }
+}
+
+
+impl ExportFile {
+    /// Disables a whole table, `table`, or one of its columns, `table.column`, for ablation runs that isolate the
+    /// cost of expensive generators without editing the spec.
+    pub fn with_disabled(mut self, target: &str, placeholder: &Placeholder) -> Result<Self> {
+        let (table, column) = match self.tables.iter().any(|x| x.id_value == target) {
+            true => (target, None),
+            false => match target.rsplit_once('.') {
+                Some((table, column)) => (table, Some(column)),
+                None => (target, None),
+            },
+        };
+
+        let index = self.tables.iter()
+            .position(|x| x.id_value == table)
+            .ok_or_else(|| Error::from(AblationError::UnknownTable { table: table.to_string() }))?;
+        let disabled = self.tables.remove(index);
+        let disabled = match column {
+            Some(column) => disabled.with_disabled_column(column, placeholder)?,
+            None => disabled.with_disabled(placeholder),
+        };
+        self.tables.insert(index, disabled);
+
+        Ok(self)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::Column;
+    use crate::types::ColumnType;
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    #[test]
+    fn disabled_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("a".into(), 3, ColumnType::Char(3), simple_generator),
+                Column::new("b".into(), 3, ColumnType::Char(3), simple_generator).unique(true),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        );
+        let ef = ExportFile::new(vec![t], 1024, 1).unwrap();
+        let row_count = ef.table_row_count(&ef.tables[0], 0).unwrap();
+
+        let ef = ef.with_disabled("A.b", &Placeholder::Constant("-".into())).unwrap();
+        assert_eq!(ef.tables[0].generate_rows(2).unwrap(), "A|ABC|-\nA|ABC|-\n");
+        assert!(ef.tables[0].unique_constraints().is_empty());
+        assert_eq!(ef.table_row_count(&ef.tables[0], 0).unwrap(), row_count);
+
+        let ef = ef.with_disabled("A", &Placeholder::Null).unwrap();
+        assert_eq!(ef.tables[0].generate_rows(1).unwrap(), "A||\n");
+
+        match ef.with_disabled("A.c", &Placeholder::Null) {
+            Err(x) => assert!(matches!(x.downcast_ref::<AblationError>(), Some(AblationError::UnknownColumn { .. }))),
+            Ok(_) => panic!("unknown columns must fail to disable"),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+use thiserror::Error;
+
+use crate::{generators, Column, ExportFile, Table};
+use crate::generators::adtech::AdFunnel;
+use crate::types::{ColumnSize, ColumnType};
+use crate::values::parse_timestamp_micros;
+
+pub const PRESETS: [&str; 3] = ["impressions", "clicks", "conversions"];
+/// Users ads are shown to.
+pub const USERS: u64 = 100_000;
+/// Event time of the first impression.
+pub const START: &str = "2025-03-01 00:00:00";
+
+
+#[derive(Error, Debug)]
+pub enum AdPresetError {
+    #[error("Unknown preset {preset}, expected impressions, clicks or conversions.")]
+    UnknownPreset { preset: String },
+    #[error("Table {table} is needed to check the funnel.")]
+    MissingTable { table: String },
+    #[error("Table {table} has no column {column} to check the funnel with.")]
+    MissingColumn { table: String, column: String },
+    #[error("Row {id} of table {table} breaks the funnel, {reason}.")]
+    Broken { table: String, id: String, reason: String },
+}
+
+
+fn funnel() -> Result<AdFunnel> {
+    AdFunnel::new(USERS, START)
+}
+
+
+/// Columns of a preset. Clicks hold the id, ad and user of an impression they follow within the click window,
+/// conversions the id and user of a click they follow within the attribution window.
+pub fn preset_columns(preset: &str) -> Result<Vec<Column>> {
+    let funnel = funnel()?;
+    let id = |name: &str, generator| Column::new(name.into(), ColumnSize::range(1, 10), ColumnType::Int, generator);
+    let time = |name: &str, generator| Column::new(name.into(), 26, ColumnType::Timestamp, generator);
+
+    Ok(match preset {
+        "impressions" => vec![
+            id("id", generators::sequence(1, 1)).as_key(),
+            id("campaign_id", funnel.impression_campaign_id()),
+            id("ad_id", funnel.impression_ad_id()),
+            id("user_id", funnel.impression_user_id()),
+            Column::new("placement".into(), ColumnSize::range(4, 7), ColumnType::Varchar(7),
+                        funnel.impression_placement()),
+            time("shown_at", funnel.impression_time()),
+            Column::new("cost".into(), 8, ColumnType::Decimal(8, 6), funnel.impression_cost()),
+        ],
+        "clicks" => vec![
+            id("id", generators::sequence(1, 1)).as_key(),
+            id("impression_id", funnel.click_impression_id()),
+            id("ad_id", funnel.click_ad_id()),
+            id("user_id", funnel.click_user_id()),
+            time("clicked_at", funnel.click_time()),
+        ],
+        "conversions" => vec![
+            id("id", generators::sequence(1, 1)).as_key(),
+            id("click_id", funnel.conversion_click_id()),
+            id("user_id", funnel.conversion_user_id()),
+            Column::new("conversion_type".into(), ColumnSize::range(6, 11), ColumnType::Varchar(11),
+                        funnel.conversion_type()),
+            time("converted_at", funnel.conversion_time()),
+            Column::new("revenue".into(), ColumnSize::range(4, 6), ColumnType::Decimal(10, 2),
+                        funnel.conversion_revenue()),
+        ],
+        _ => return Err(Error::from(AdPresetError::UnknownPreset { preset: preset.into() })),
+    })
+}
+
+
+/// Every preset table, sized by the funnel rates in `export_file`.
+pub fn tables() -> Result<Vec<Table>> {
+    PRESETS.iter()
+        .zip(["0.97", "0.025", "0.005"])
+        .map(|(preset, percent_size)| {
+            Ok(Table::new(preset.to_string(), preset_columns(preset)?, "|".into(), Decimal::from_str(percent_size)?))
+        })
+        .collect()
+}
+
+
+/// The ad funnel export, impressions filling the files with clicks and conversions at the funnel's rates.
+pub fn export_file(file_size_bytes: u64, number_of_files: u64) -> Result<ExportFile> {
+    let funnel = funnel()?;
+    ExportFile::new(tables()?, file_size_bytes, number_of_files)?
+        .with_row_ratio("clicks", "impressions", funnel.clicks_per_impression())?
+        .with_row_ratio("conversions", "clicks", funnel.conversions_per_click())
+}
+
+
+/// Checks the rows of every file of an ad funnel export together, `rows[i]` are all rows of `tables[i]`. Clicks
+/// have to follow an impression of the same ad and user within the click window, conversions a click of the same
+/// user within the attribution window, and no impression is clicked twice.
+pub fn check_funnel(tables: &[Table], rows: &[Vec<Vec<String>>]) -> Result<()> {
+    let funnel = funnel()?;
+    let table = |id: &str| {
+        tables.iter()
+            .position(|x| x.id_value == id)
+            .ok_or_else(|| Error::from(AdPresetError::MissingTable { table: id.into() }))
+    };
+    let position = |index: usize, name: &str| {
+        tables[index].columns.iter()
+            .position(|x| x.name == name)
+            .map(|x| x + 1)
+            .ok_or_else(|| {
+                Error::from(AdPresetError::MissingColumn { table: tables[index].id_value.clone(), column: name.into() })
+            })
+    };
+    let broken = |index: usize, id: &str, reason: String| {
+        Error::from(AdPresetError::Broken { table: tables[index].id_value.clone(), id: id.into(), reason })
+    };
+
+    // Every row of a child holds a parent row it follows within `window` seconds, with the same user and ad.
+    let follows = |(child, time): (usize, &str), (parent, parent_time): (usize, &str), column: &str, window: u64| {
+        let ad = |index: usize| position(index, "ad_id").ok();
+        let fields = |index: usize, time: &str| -> Result<[usize; 3]> {
+            Ok([position(index, "id")?, position(index, "user_id")?, position(index, time)?])
+        };
+        let [id, user, time] = fields(child, time)?;
+        let [parent_id, parent_user, parent_time] = fields(parent, parent_time)?;
+        let parents: HashMap<&str, &Vec<String>> = rows[parent].iter()
+            .map(|x| (x[parent_id].as_str(), x))
+            .collect();
+        let reference = position(child, column)?;
+        let mut seen = HashSet::new();
+
+        for row in rows[child].iter() {
+            let Some(parent_row) = parents.get(row[reference].as_str()) else {
+                return Err(broken(child, &row[id], format!("{} {} does not exist", column, row[reference])));
+            };
+            let same_ad = match (ad(child), ad(parent)) {
+                (Some(x), Some(y)) => row[x] == parent_row[y],
+                _ => true,
+            };
+            let parent = format!("{} {}", column, row[reference]);
+            if row[user] != parent_row[parent_user] || !same_ad {
+                return Err(broken(child, &row[id], format!("its user or ad differ from {}", parent)));
+            }
+            let Some((at, after)) = parse_timestamp_micros(&row[time])
+                .zip(parse_timestamp_micros(&parent_row[parent_time])) else {
+                return Err(broken(child, &row[id], format!("its time or that of {} is not a time", parent)));
+            };
+            if at <= after || at > after + window as i64 * 1_000_000 {
+                return Err(broken(child, &row[id], format!("it is outside the window of {}", parent)));
+            }
+            if ad(child).is_some() && !seen.insert(row[reference].as_str()) {
+                return Err(broken(child, &row[id], format!("{} is clicked twice", parent)));
+            }
+        }
+        Ok(())
+    };
+
+    let (impressions, clicks, conversions) = (table("impressions")?, table("clicks")?, table("conversions")?);
+    follows((clicks, "clicked_at"), (impressions, "shown_at"), "impression_id", funnel.click_window())?;
+    follows((conversions, "converted_at"), (clicks, "clicked_at"), "click_id", funnel.conversion_window())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ad_funnel_preset_test() {
+        let ef = export_file(500_000, 2).unwrap().with_seed(3);
+        let files = (0..2).map(|x| ef.generate_file_rows(x).unwrap()).collect::<Vec<_>>();
+        let rows: Vec<Vec<Vec<String>>> = (0..PRESETS.len())
+            .map(|x| files.iter().flat_map(|y| y[x].clone()).collect())
+            .collect();
+
+        assert!(rows[0].len() > 5000);
+        assert_eq!(rows[1].len(), (rows[0].len() as f64 / 50.0).round() as usize);
+        assert_eq!(rows[2].len(), (rows[1].len() as f64 / 20.0).round() as usize);
+        check_funnel(&ef.tables, &rows).unwrap();
+
+        let mut late = rows.clone();
+        late[2][0][5] = "2030-01-01 00:00:00.000000".into();
+        assert!(check_funnel(&ef.tables, &late).is_err());
+        let mut stranger = rows.clone();
+        stranger[1][0][4] = "0".into();
+        assert!(check_funnel(&ef.tables, &stranger).is_err());
+        assert!(preset_columns("bids").is_err());
+    }
+}
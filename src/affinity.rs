@@ -0,0 +1,138 @@
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::{ExportFile, ExportFileError, Table};
+
+
+#[derive(Error, Debug)]
+pub enum AffinityError {
+    #[error("Table {table} has to be pinned to at least 1 file.")]
+    NoFiles { table: String },
+    #[error("Table {table} is pinned to file {file}, the export has {files} files.")]
+    FileOutOfRange { table: String, file: u64, files: u64 },
+}
+
+
+impl ExportFile {
+    /// Pins `table` to `files`, e.g. a reference table to file 0, so its rows are spread over those files only and it
+    /// is empty in the others. Tables referencing it in other files sample keys of its rows generated again, which are
+    /// the rows written when the export is seeded or its keys come from the row index, e.g. a sequence.
+    pub fn with_affinity(mut self, table: &str, files: &[u64]) -> Result<Self> {
+        if !self.tables.iter().any(|x| x.id_value == table) {
+            return Err(Error::from(ExportFileError::UnknownTable { table: table.into() }));
+        }
+        if files.is_empty() {
+            return Err(Error::from(AffinityError::NoFiles { table: table.into() }));
+        }
+        if let Some(file) = files.iter().find(|x| **x >= self.number_of_files) {
+            return Err(Error::from(AffinityError::FileOutOfRange {
+                table: table.into(),
+                file: *file,
+                files: self.number_of_files,
+            }));
+        }
+
+        let mut files = files.to_vec();
+        files.sort_unstable();
+        files.dedup();
+        self.affinity.insert(table.into(), files);
+        Ok(self)
+    }
+
+
+    /// Files `table` is pinned to, `None` for tables in every file.
+    pub fn affinity(&self, table: &str) -> Option<&[u64]> {
+        self.affinity.get(table).map(|x| x.as_slice())
+    }
+
+
+    // Files the table has rows in, how many of them come before file `file_index` and whether it is one of them.
+    pub(crate) fn table_files(&self, table: &Table, file_index: u64) -> (u64, u64, bool) {
+        match self.affinity.get(&table.id_value) {
+            Some(files) => {
+                (files.len() as u64, files.partition_point(|x| *x < file_index) as u64, files.contains(&file_index))
+            }
+            None => (self.number_of_files, file_index, true),
+        }
+    }
+
+
+    // The table and the tables it references, directly or through other tables.
+    pub(crate) fn with_references(&self, table_index: usize) -> Vec<bool> {
+        let mut wanted = vec![false; self.tables.len()];
+        let mut pending = vec![table_index];
+        while let Some(index) = pending.pop() {
+            if wanted[index] {
+                continue;
+            }
+            wanted[index] = true;
+            pending.extend(self.tables[index].columns.iter()
+                .filter_map(|x| x.reference.as_ref())
+                .filter_map(|x| self.tables.iter().position(|y| y.id_value == x.table)));
+        }
+
+        wanted
+    }
+
+
+    // Rows of a pinned table in all of its files, generated again with the tables it references.
+    pub(crate) fn pinned_rows(&self, table_index: usize) -> Result<Vec<Vec<String>>> {
+        let wanted = self.with_references(table_index);
+        let files = self.affinity(&self.tables[table_index].id_value).unwrap_or_default();
+        let mut rows = vec![];
+        for file in files.iter() {
+            rows.extend(self.generate_tables(*file, &wanted)?.swap_remove(table_index).unwrap_or_default());
+        }
+        Ok(rows)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column, SizeSpec};
+    use crate::types::ColumnType;
+
+    #[test]
+    fn affinity_test() {
+        let countries = Table::new(
+            "countries".into(),
+            vec![Column::new("id".into(), 3, ColumnType::Int, generators::sequence(1, 1)).as_key()],
+            "|".into(),
+            Decimal::from_str("0.2").unwrap(),
+        );
+        let orders = Table::new(
+            "orders".into(),
+            vec![
+                Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)),
+                Column::new("country_id".into(), 12, ColumnType::Varchar(12), generators::constant(""))
+                    .references("countries", "id"),
+            ],
+            "|".into(),
+            Decimal::from_str("0.8").unwrap(),
+        );
+        let ef = ExportFile::new(vec![countries, orders], 3_000, 3).unwrap()
+            .with_seed(8)
+            .with_size_spec(SizeSpec::Rows(HashMap::from([("countries".to_string(), 10)]))).unwrap()
+            .with_affinity("countries", &[0]).unwrap();
+        let files: Vec<Vec<Vec<Vec<String>>>> = (0..3).map(|x| ef.generate_file_rows(x).unwrap()).collect();
+
+        assert_eq!(files[0][0].len(), 10);
+        assert!(files[1][0].is_empty() && files[2][0].is_empty());
+        assert_eq!(ef.table_total_rows(&ef.tables[0]).unwrap(), 10);
+        // Orders of every file reference the countries written to file 0.
+        let keys: HashSet<&String> = files[0][0].iter().map(|x| &x[1]).collect();
+        assert!(files.iter().all(|x| !x[1].is_empty() && x[1].iter().all(|y| keys.contains(&y[2]))));
+
+        let ef = ef.with_affinity("orders", &[2, 1, 2]).unwrap();
+        assert_eq!(ef.affinity("orders"), Some([1, 2].as_slice()));
+        assert_eq!(ef.table_row_count(&ef.tables[1], 0).unwrap(), 0);
+        assert_eq!(ef.table_row_offset(&ef.tables[1], 2).unwrap(), ef.table_row_count(&ef.tables[1], 1).unwrap());
+        assert!(ef.with_affinity("orders", &[3]).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::ExportFile;
+use crate::compression::{crc32_update, deflate_encoder, gzip_encoder};
+
+const ZIP_LOCAL_HEADER: u32 = 0x04034b50;
+const ZIP_DATA_DESCRIPTOR: u32 = 0x08074b50;
+const ZIP_CENTRAL_HEADER: u32 = 0x02014b50;
+const ZIP_END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+const ZIP_VERSION: u16 = 20;
+// Sizes and checksums follow the data, so files are streamed in one pass.
+const ZIP_FLAGS: u16 = 0x0008;
+const ZIP_DEFLATE: u16 = 8;
+// 1980-01-01 00:00 in MS-DOS format, a fixed time keeps archives of seeded exports identical.
+const ZIP_DATE: u16 = 0x0021;
+const TAR_BLOCK: usize = 512;
+const CHUNK_SIZE: usize = 64 * 1024;
+
+
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    #[error("Unknown archive format {format}, expected zip or tar.gz.")]
+    UnknownFormat { format: String },
+    #[error("{path} is larger than 4 GiB, which ZIP archives without ZIP64 can not hold, use tar.gz instead.")]
+    ZipTooLarge { path: String },
+    #[error("File name {name} is longer than the 100 bytes a tar header can hold.")]
+    TarNameTooLong { name: String },
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarGz => "tar.gz",
+        }
+    }
+}
+
+impl FromStr for ArchiveFormat {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        match format.to_ascii_lowercase().as_str() {
+            "zip" => Ok(ArchiveFormat::Zip),
+            "tar.gz" | "tgz" => Ok(ArchiveFormat::TarGz),
+            _ => Err(Error::from(ArchiveError::UnknownFormat { format: format.into() })),
+        }
+    }
+}
+
+
+struct Counter<W: Write> {
+    writer: W,
+    bytes: u64,
+}
+
+impl<W: Write> Write for Counter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+
+struct ZipEntry {
+    name: String,
+    crc: u32,
+    compressed: u32,
+    size: u32,
+    offset: u32,
+}
+
+
+// Files of the folder with manifests first, hidden files like checkpoints are left out.
+fn archive_files(folder_path: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(folder_path)?
+        .map(|x| Ok(x?.path()))
+        .collect::<Result<Vec<PathBuf>>>()?
+        .into_iter()
+        .filter(|x| x.is_file() && !file_name(x).starts_with('.'))
+        .collect();
+    files.sort_by_key(|x| (!file_name(x).starts_with("manifest"), file_name(x)));
+
+    Ok(files)
+}
+
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+
+fn write_zip<W: Write>(files: &[PathBuf], writer: W) -> Result<W> {
+    let mut out = Counter { writer, bytes: 0 };
+    let mut entries: Vec<ZipEntry> = vec![];
+    let too_large = |path: &Path| Error::from(ArchiveError::ZipTooLarge { path: path.display().to_string() });
+
+    for path in files {
+        let name = file_name(path);
+        let offset = u32::try_from(out.bytes).map_err(|_| too_large(path))?;
+
+        out.write_all(&ZIP_LOCAL_HEADER.to_le_bytes())?;
+        for field in [ZIP_VERSION, ZIP_FLAGS, ZIP_DEFLATE, 0, ZIP_DATE] {
+            out.write_all(&field.to_le_bytes())?;
+        }
+        out.write_all(&[0; 12])?;
+        out.write_all(&(name.len() as u16).to_le_bytes())?;
+        out.write_all(&0u16.to_le_bytes())?;
+        out.write_all(name.as_bytes())?;
+
+        let start = out.bytes;
+        let mut crc = 0;
+        let mut size: u64 = 0;
+        let mut file = File::open(path)?;
+        let mut buffer = vec![0; CHUNK_SIZE];
+        let mut deflate = deflate_encoder(&mut out);
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            crc = crc32_update(crc, &buffer[..read]);
+            size += read as u64;
+            deflate.write_all(&buffer[..read])?;
+        }
+        deflate.finish()?;
+
+        let compressed = u32::try_from(out.bytes - start).map_err(|_| too_large(path))?;
+        let size = u32::try_from(size).map_err(|_| too_large(path))?;
+        for field in [ZIP_DATA_DESCRIPTOR, crc, compressed, size] {
+            out.write_all(&field.to_le_bytes())?;
+        }
+
+        entries.push(ZipEntry { name, crc, compressed, size, offset });
+    }
+
+    let directory_offset = out.bytes;
+    for entry in entries.iter() {
+        out.write_all(&ZIP_CENTRAL_HEADER.to_le_bytes())?;
+        for field in [ZIP_VERSION, ZIP_VERSION, ZIP_FLAGS, ZIP_DEFLATE, 0, ZIP_DATE] {
+            out.write_all(&field.to_le_bytes())?;
+        }
+        for field in [entry.crc, entry.compressed, entry.size] {
+            out.write_all(&field.to_le_bytes())?;
+        }
+        for field in [entry.name.len() as u16, 0, 0, 0, 0] {
+            out.write_all(&field.to_le_bytes())?;
+        }
+        out.write_all(&0u32.to_le_bytes())?;
+        out.write_all(&entry.offset.to_le_bytes())?;
+        out.write_all(entry.name.as_bytes())?;
+    }
+
+    let directory_size = out.bytes - directory_offset;
+    let directory_offset = u32::try_from(directory_offset)
+        .map_err(|_| Error::from(ArchiveError::ZipTooLarge { path: "The archive".into() }))?;
+    out.write_all(&ZIP_END_OF_CENTRAL_DIRECTORY.to_le_bytes())?;
+    for field in [0, 0, entries.len() as u16, entries.len() as u16] {
+        out.write_all(&field.to_le_bytes())?;
+    }
+    out.write_all(&(directory_size as u32).to_le_bytes())?;
+    out.write_all(&directory_offset.to_le_bytes())?;
+    out.write_all(&0u16.to_le_bytes())?;
+
+    out.flush()?;
+    Ok(out.writer)
+}
+
+
+// Ustar header with the file's name and size, everything else fixed so archives are reproducible.
+fn tar_header(name: &str, size: u64) -> Result<[u8; TAR_BLOCK]> {
+    if name.len() > 100 {
+        return Err(Error::from(ArchiveError::TarNameTooLong { name: name.into() }));
+    }
+
+    let mut header = [0; TAR_BLOCK];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[100..108].copy_from_slice(b"0000644\0");
+    header[108..116].copy_from_slice(b"0000000\0");
+    header[116..124].copy_from_slice(b"0000000\0");
+    header[124..136].copy_from_slice(format!("{:011o}\0", size).as_bytes());
+    header[136..148].copy_from_slice(b"00000000000\0");
+    header[148..156].copy_from_slice(b"        ");
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    let checksum: u32 = header.iter().map(|x| *x as u32).sum();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+    Ok(header)
+}
+
+
+fn write_tar<W: Write>(files: &[PathBuf], mut writer: W) -> Result<W> {
+    for path in files {
+        let size = fs::metadata(path)?.len();
+        writer.write_all(&tar_header(&file_name(path), size)?)?;
+        io::copy(&mut File::open(path)?, &mut writer)?;
+
+        let padding = (TAR_BLOCK - (size % TAR_BLOCK as u64) as usize) % TAR_BLOCK;
+        writer.write_all(&vec![0; padding])?;
+    }
+
+    writer.write_all(&[0; 2 * TAR_BLOCK])?;
+    Ok(writer)
+}
+
+
+/// Packages the files of an export folder into `<folder>.zip` or `<folder>.tar.gz` next to it, manifests first.
+pub fn package(folder_path: &Path, format: ArchiveFormat) -> Result<PathBuf> {
+    let files = archive_files(folder_path)?;
+    let path = folder_path.with_file_name(format!("{}.{}", file_name(folder_path), format.extension()));
+    let file = BufWriter::new(File::create(&path)?);
+
+    match format {
+        ArchiveFormat::Zip => write_zip(&files, file)?.flush()?,
+        ArchiveFormat::TarGz => write_tar(&files, gzip_encoder(file))?.finish()?.flush()?,
+    }
+
+    Ok(path)
+}
+
+
+impl ExportFile {
+    /// Packages the folder into a single archive once every file of the export is written.
+    pub fn with_archive(mut self, archive: Option<ArchiveFormat>) -> Self {
+        self.archive = archive;
+        self
+    }
+
+
+    pub(crate) fn package_export(&self, folder_path: &Path) -> Result<()> {
+        if let Some(format) = self.archive {
+            package(folder_path, format)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{Column, Table};
+    use crate::types::ColumnType;
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    fn u32_at(data: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn package_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("column".into(), 3, ColumnType::Char(3), simple_generator)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+        let ef = ExportFile::new(vec![t], 60, 2).unwrap()
+            .with_manifest(true)
+            .with_archive(Some(ArchiveFormat::Zip));
+
+        let root = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        let folder = root.join("export");
+        ef.generate_all_files(&folder).unwrap();
+        let zip = fs::read(root.join("export.zip")).unwrap();
+        let content = fs::read(ef.file_path(&folder, 0)).unwrap();
+        let tar = write_tar(&archive_files(&folder).unwrap(), Vec::new()).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        let end = zip.len() - 22;
+        assert_eq!(u32_at(&zip, 0), ZIP_LOCAL_HEADER);
+        assert_eq!(&zip[30..43], b"manifest.json");
+        assert_eq!(u32_at(&zip, end), ZIP_END_OF_CENTRAL_DIRECTORY);
+        assert_eq!(u16::from_le_bytes([zip[end + 10], zip[end + 11]]), 3);
+
+        // The second central directory entry is file 0.
+        let directory = u32_at(&zip, end + 16) as usize;
+        let second = directory + 46 + "manifest.json".len();
+        assert_eq!(&zip[second + 46..second + 46 + 15], b"file_30_2_0.txt");
+        assert_eq!(u32_at(&zip, second + 16), crc32_update(0, &content));
+
+        assert_eq!(&tar[..13], b"manifest.json");
+        assert_eq!(&tar[257..262], b"ustar");
+        assert_eq!(tar.len() % TAR_BLOCK, 0);
+        assert!("rar".parse::<ArchiveFormat>().is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::ExportFile;
+use crate::interleave::InterleaveMode;
+
+/// Rows rendered and written at a time when tables are streamed.
+pub const CHUNK_ROWS: usize = 4096;
+const CHUNK_BYTES: usize = 64 * 1024;
+
+
+impl ExportFile {
+    /// Streams the uncompressed export into `writer` as `generate_export` would return it. Tables without
+    /// references in a blocked file are generated a chunk of rows at a time, otherwise the file's rows are
+    /// generated first. Generation runs on the polling thread, so a busy runtime should poll this from a
+    /// blocking task.
+    pub async fn generate_export_to_async_writer<W: AsyncWrite + Unpin + ?Sized>(&self, writer: &mut W) -> Result<()> {
+        let streamed = self.interleave == InterleaveMode::Blocked
+            && self.tables.iter().all(|x| x.columns.iter().all(|y| y.reference.is_none()));
+
+        if streamed {
+            for table in self.tables.iter() {
+                let mut rows = table.rows_from(self.table_row_offset(table, 0)?, self.table_row_count(table, 0)?);
+                loop {
+                    let chunk = rows.by_ref().take(CHUNK_ROWS).collect::<Result<Vec<Vec<String>>>>()?;
+                    if chunk.is_empty() {
+                        break;
+                    }
+
+                    let text = table.rows_to_string(&chunk);
+                    let data = self.encoding.encode(&text)?;
+                    writer.write_all(&data).await?;
+                    self.progress_rows(&table.id_value, chunk.len() as u64, data.len() as u64);
+                }
+            }
+        } else {
+            let rows = self.generate_file_rows(0)?;
+            let (data, bytes) = self.interleave_file(0, &rows);
+            for chunk in self.encoding.encode(&data)?.chunks(CHUNK_BYTES) {
+                writer.write_all(chunk).await?;
+            }
+            for ((table, rows), bytes) in self.tables.iter().zip(rows.iter()).zip(bytes) {
+                self.progress_rows(&table.id_value, rows.len() as u64, bytes);
+            }
+        }
+
+        Ok(writer.flush().await?)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{Context, Poll, Waker};
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column, Table};
+    use crate::types::ColumnType;
+
+    // Accepts at most 7 bytes per call and every other call is pending, like a slow socket.
+    #[derive(Default)]
+    struct SlowSink {
+        data: Vec<u8>,
+        calls: u64,
+    }
+
+    impl AsyncWrite for SlowSink {
+        fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            self.calls += 1;
+            if self.calls.is_multiple_of(2) {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+
+            let written = buf.len().min(7);
+            self.data.extend_from_slice(&buf[..written]);
+            Poll::Ready(Ok(written))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(x) = future.as_mut().poll(&mut cx) {
+                return x;
+            }
+        }
+    }
+
+    fn table(id: &str, column: Column) -> Table {
+        Table::new(id.into(), vec![column], "|".into(), Decimal::from_str("0.5").unwrap())
+    }
+
+    fn written(export_file: &ExportFile) -> String {
+        let mut sink = SlowSink::default();
+        block_on(export_file.generate_export_to_async_writer(&mut sink)).unwrap();
+        String::from_utf8(sink.data).unwrap()
+    }
+
+    #[test]
+    fn generate_export_to_async_writer_test() {
+        let customers = table("A", Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).as_key());
+        let products = table("B", Column::new("price".into(), 8, ColumnType::Double, generators::uniform(0.0, 9.0)));
+        let orders = table("B", Column::new("customer_id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).references("A", "id"));
+
+        let streamed = ExportFile::new(vec![customers.clone(), products], 100_000, 1).unwrap().with_seed(1);
+        assert_eq!(written(&streamed), streamed.generate_export().unwrap());
+
+        let referencing = ExportFile::new(vec![customers, orders], 400, 1).unwrap().with_seed(1);
+        assert_eq!(written(&referencing), referencing.generate_export().unwrap());
+    }
+}
@@ -0,0 +1,206 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+use thiserror::Error;
+
+use crate::{ExportFile, Table};
+use crate::schema::AvroType;
+use crate::values::{parse_bool, parse_date, parse_timestamp_micros};
+
+const MAGIC: &[u8; 4] = b"Obj\x01";
+const BLOCK_SIZE: usize = 4096;
+
+
+#[derive(Error, Debug)]
+pub enum AvroError {
+    #[error("Value {value} of column {column} can not be stored as {avro_type:?}.")]
+    InvalidValue { column: String, value: String, avro_type: AvroType },
+}
+
+
+impl AvroType {
+    fn encode(&self, value: &str, buffer: &mut Vec<u8>) -> Option<()> {
+        match self {
+            AvroType::Boolean => buffer.push(parse_bool(value)? as u8),
+            AvroType::Int => write_long(value.trim().parse::<i32>().ok()? as i64, buffer),
+            AvroType::Long => write_long(value.trim().parse::<i64>().ok()?, buffer),
+            AvroType::Double => buffer.extend(value.trim().parse::<f64>().ok()?.to_le_bytes()),
+            AvroType::Decimal { precision, scale } => {
+                let mut decimal = Decimal::from_str(value.trim()).ok()?;
+                decimal.rescale(*scale);
+                let unscaled = decimal.mantissa();
+                if unscaled.unsigned_abs() >= 10u128.pow(*precision) {
+                    return None;
+                }
+
+                let bytes = unscaled.to_be_bytes();
+                let sign = if unscaled < 0 { 0xff } else { 0x00 };
+                let mut start = 0;
+                while start < bytes.len() - 1
+                    && bytes[start] == sign
+                    && (bytes[start + 1] & 0x80) == (sign & 0x80) {
+                    start += 1;
+                }
+                write_bytes(&bytes[start..], buffer);
+            }
+            AvroType::Date => write_long(parse_date(value.trim())?, buffer),
+            AvroType::TimestampMicros => write_long(parse_timestamp_micros(value.trim())?, buffer),
+            AvroType::String => write_bytes(value.as_bytes(), buffer),
+        }
+
+        Some(())
+    }
+}
+
+
+fn write_long(value: i64, buffer: &mut Vec<u8>) {
+    let mut value = ((value << 1) ^ (value >> 63)) as u64;
+    while value >= 0x80 {
+        buffer.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+fn write_bytes(value: &[u8], buffer: &mut Vec<u8>) {
+    write_long(value.len() as i64, buffer);
+    buffer.extend(value);
+}
+
+impl Table {
+    pub fn generate_avro(&self, file_size_bytes: u64) -> Result<Vec<u8>> {
+        self.rows_to_avro(&self.generate_table_vec(file_size_bytes)?)
+    }
+
+
+    fn rows_to_avro(&self, rows: &[Vec<String>]) -> Result<Vec<u8>> {
+        let types = self.columns.iter()
+            .map(|x| AvroType::from_column_type(&x.column_type))
+            .collect::<Vec<AvroType>>();
+        let sync_marker: [u8; 16] = rand::random();
+        for (i, row) in rows.iter().enumerate() {
+            self.check_row_length(i, row)?;
+        }
+
+        let mut file = MAGIC.to_vec();
+        write_long(2, &mut file);
+        write_bytes(b"avro.schema", &mut file);
+        write_bytes(self.avro_schema().to_string().as_bytes(), &mut file);
+        write_bytes(b"avro.codec", &mut file);
+        write_bytes(b"null", &mut file);
+        write_long(0, &mut file);
+        file.extend(sync_marker);
+
+        for block in rows.chunks(BLOCK_SIZE) {
+            let mut records = Vec::new();
+
+            for row in block {
+                // Column 0 of a generated row is the table id, which is the record name.
+                for ((column, avro_type), value) in self.columns.iter().zip(types.iter()).zip(row.iter().skip(1)) {
+                    if column.is_nullable() {
+                        let null = self.is_null(column, value);
+                        write_long(if null { 0 } else { 1 }, &mut records);
+                        if null {
+                            continue;
+                        }
+                    }
+                    avro_type.encode(value, &mut records)
+                        .ok_or_else(|| Error::from(AvroError::InvalidValue {
+                            column: column.name.clone(),
+                            value: value.clone(),
+                            avro_type: *avro_type,
+                        }))?;
+                }
+            }
+
+            write_long(block.len() as i64, &mut file);
+            write_long(records.len() as i64, &mut file);
+            file.extend(records);
+            file.extend(sync_marker);
+        }
+
+        Ok(file)
+    }
+
+
+    pub fn generate_avro_to_file(&self, file_size_bytes: u64, path: &Path) -> Result<()> {
+        let avro = self.generate_avro(file_size_bytes)?;
+        let mut file = File::create(path)?;
+        file.write_all(avro.as_ref())?;
+        Ok(())
+    }
+}
+
+
+impl ExportFile {
+    pub fn generate_avro(&self, folder_path: &Path) -> Result<()> {
+        self.write_table_files(folder_path, "avro", |table, rows| table.rows_to_avro(rows))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::Column;
+
+    fn int_generator() -> Result<String> {
+        Ok("42".into())
+    }
+
+    fn text_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    #[test]
+    fn encode_test() {
+        let mut buffer = Vec::new();
+        AvroType::Long.encode("-3", &mut buffer).unwrap();
+        AvroType::String.encode("ab", &mut buffer).unwrap();
+        AvroType::Decimal { precision: 5, scale: 2 }.encode("-1.28", &mut buffer).unwrap();
+
+        assert_eq!(buffer, vec![0x05, 0x04, b'a', b'b', 0x02, 0x80]);
+        assert!(AvroType::Int.encode("ABC", &mut Vec::new()).is_none());
+    }
+
+    #[test]
+    fn generate_avro_nullable_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("id".into(), 2, "INT".into(), text_generator).with_null_rate(1.0)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+
+        assert_eq!(t.avro_schema()["fields"][0]["type"], json!(["null", "int"]));
+
+        let avro = t.generate_avro(50).unwrap();
+        let header_end = avro.len() - 16 - 10 - 2;
+
+        assert_eq!(&avro[header_end..header_end + 2], &[20, 20]);
+        assert_eq!(&avro[header_end + 2..header_end + 12], &[0; 10]);
+    }
+
+    #[test]
+    fn generate_avro_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("id".into(), 2, "INT".into(), int_generator)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+
+        let avro = t.generate_avro(50).unwrap();
+
+        let header_end = avro.len() - 16 - 10 - 2;
+
+        assert_eq!(&avro[..4], MAGIC);
+        assert_eq!(&avro[header_end..header_end + 2], &[20, 20]);
+        assert_eq!(&avro[header_end - 16..header_end], &avro[avro.len() - 16..]);
+    }
+}
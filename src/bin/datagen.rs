@@ -0,0 +1,20 @@
+use std::env;
+use std::process::ExitCode;
+
+use diplomski_projekt::cli::{run, CliArgs, CliError, USAGE};
+
+fn main() -> ExitCode {
+    let result = CliArgs::parse(env::args().skip(1)).and_then(run);
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(x) if x.is::<CliError>() => {
+            eprintln!("error: {}\n\n{}", x, USAGE);
+            ExitCode::FAILURE
+        }
+        Err(x) => {
+            eprintln!("error: {}", x);
+            ExitCode::FAILURE
+        }
+    }
+}
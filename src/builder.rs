@@ -0,0 +1,579 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+
+use crate::{Column, ExportFile, ExportFileError, SizeSpec, Table};
+use crate::ablation::Placeholder;
+use crate::archive::ArchiveFormat;
+use crate::compression::Compression;
+use crate::corruption::CorruptionProfile;
+use crate::ddl::{Dialect, IdentifierQuoting, TypeMapping};
+use crate::encoding::Encoding;
+use crate::events::EventClock;
+use crate::file_name::FileNameTemplate;
+use crate::interleave::InterleaveMode;
+use crate::metadata::{FileCallback, FileMetadata};
+use crate::progress::ProgressSink;
+use crate::scd::Scd2;
+use crate::stress::NumericStress;
+use crate::throttle::Throttle;
+use crate::random::RandomSource;
+use crate::output_format::OutputFormat;
+use crate::types::{Annotations, NullRepresentation, RecordTerminator};
+
+
+pub struct TableBuilder {
+    id_value: String,
+    columns: Vec<Column>,
+    delimiter: String,
+    terminator: RecordTerminator,
+    output_format: OutputFormat,
+    qualifier: Option<String>,
+    percent_size: Option<Decimal>,
+    unique: Vec<Vec<String>>,
+    corruption: CorruptionProfile,
+    numeric_stress: NumericStress,
+    event_time: Option<(String, EventClock)>,
+    scd2: Option<Scd2>,
+    row_group: u64,
+    annotations: Annotations,
+}
+
+impl TableBuilder {
+    pub fn new(id_value: impl Into<String>) -> Self {
+        TableBuilder {
+            id_value: id_value.into(),
+            columns: vec![],
+            delimiter: "|".into(),
+            terminator: RecordTerminator::Lf,
+            output_format: OutputFormat::Delimited,
+            qualifier: None,
+            percent_size: None,
+            unique: vec![],
+            corruption: CorruptionProfile::new(),
+            numeric_stress: NumericStress::new(),
+            event_time: None,
+            scd2: None,
+            row_group: 1,
+            annotations: Annotations::default(),
+        }
+    }
+
+    pub fn add_column(mut self, column: Column) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    pub fn delimiter(mut self, delimiter: impl Into<String>) -> Self {
+        self.delimiter = delimiter.into();
+        self
+    }
+
+    pub fn terminator(mut self, terminator: RecordTerminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    pub fn percent_size(mut self, percent_size: Decimal) -> Self {
+        self.percent_size = Some(percent_size);
+        self
+    }
+
+    pub fn output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+
+    pub fn qualifier(mut self, qualifier: impl Into<String>) -> Self {
+        self.qualifier = Some(qualifier.into());
+        self
+    }
+
+    pub fn annotations(mut self, annotations: Annotations) -> Self {
+        self.annotations = annotations;
+        self
+    }
+
+    pub fn corruption(mut self, corruption: CorruptionProfile) -> Self {
+        self.corruption = corruption;
+        self
+    }
+
+    pub fn numeric_stress(mut self, numeric_stress: NumericStress) -> Self {
+        self.numeric_stress = numeric_stress;
+        self
+    }
+
+    pub fn unique(mut self, columns: &[&str]) -> Self {
+        self.unique.push(columns.iter().map(|x| x.to_string()).collect());
+        self
+    }
+
+    /// Generates `column` as advancing event times, see [`Table::with_event_time`].
+    pub fn event_time(mut self, column: impl Into<String>, clock: EventClock) -> Self {
+        self.event_time = Some((column.into(), clock));
+        self
+    }
+
+    /// Keeps rows together in groups, see [`Table::with_row_group`].
+    pub fn row_group(mut self, rows: u64) -> Self {
+        self.row_group = rows;
+        self
+    }
+
+    /// Generates the table as slowly changing dimension history, see [`Table::with_scd2`].
+    pub fn scd2(mut self, scd2: Scd2) -> Self {
+        self.scd2 = Some(scd2);
+        self
+    }
+
+    pub fn build(self) -> Result<Table> {
+        let mut names: HashSet<&str> = HashSet::new();
+        for column in self.columns.iter() {
+            if !names.insert(column.name.as_str()) {
+                return Err(Error::from(ExportFileError::DuplicateColumns {
+                    table: self.id_value.clone(),
+                    column: column.name.clone(),
+                }));
+            }
+        }
+
+        let percent_size = self.percent_size
+            .ok_or_else(|| Error::from(ExportFileError::MissingPercentSize { table: self.id_value.clone() }))?;
+
+        let mut table = Table::new(self.id_value, self.columns, self.delimiter, percent_size)
+            .with_terminator(self.terminator)
+            .with_output_format(self.output_format)
+            .with_qualifier(self.qualifier.as_deref().unwrap_or(""))
+            .with_corruption(self.corruption)
+            .with_numeric_stress(self.numeric_stress)
+            .with_row_group(self.row_group)
+            .with_annotations(self.annotations);
+        for columns in self.unique.iter() {
+            table = table.with_unique(&columns.iter().map(|x| x.as_str()).collect::<Vec<&str>>())?;
+        }
+        if let Some((column, clock)) = self.event_time {
+            table = table.with_event_time(&column, clock)?;
+        }
+        if let Some(scd2) = self.scd2 {
+            table = table.with_scd2(scd2)?;
+        }
+
+        Ok(table)
+    }
+}
+
+
+#[derive(Default)]
+pub struct ExportFileBuilder {
+    pub(crate) tables: Vec<Table>,
+    pub(crate) target_bytes: Option<u64>,
+    pub(crate) files: Option<u64>,
+    namespace: Option<Option<String>>,
+    compression: Compression,
+    size_spec: SizeSpec,
+    null_representation: NullRepresentation,
+    delimiter: Option<String>,
+    terminator: Option<RecordTerminator>,
+    output_format: Option<OutputFormat>,
+    qualifier: Option<String>,
+    encoding: Encoding,
+    file_callback: Option<FileCallback>,
+    progress: Vec<Arc<dyn ProgressSink>>,
+    random: Option<Arc<dyn RandomSource>>,
+    seed: Option<u64>,
+    interleave: InterleaveMode,
+    file_name: Option<FileNameTemplate>,
+    manifest: bool,
+    report: bool,
+    control_batch: Option<String>,
+    max_rows_per_file: Option<u64>,
+    chunk_bytes: Option<u64>,
+    throttle: Option<Throttle>,
+    checkpoint: bool,
+    measured_sizing: bool,
+    identifier_quoting: Vec<(Dialect, IdentifierQuoting)>,
+    type_mapping: TypeMapping,
+    constraints_ddl: bool,
+    views: Vec<(String, String)>,
+    archive: Option<ArchiveFormat>,
+    disabled: Vec<String>,
+    placeholder: Placeholder,
+    pub(crate) normalize_percent_size: bool,
+    scale_factor: Option<f64>,
+    row_ratios: Vec<(String, String, f64)>,
+    affinity: Vec<(String, Vec<u64>)>,
+}
+
+impl ExportFileBuilder {
+    pub fn new() -> Self {
+        ExportFileBuilder::default()
+    }
+
+    pub fn add_table(mut self, table: Table) -> Self {
+        self.tables.push(table);
+        self
+    }
+
+    pub fn target_bytes(mut self, target_bytes: u64) -> Self {
+        self.target_bytes = Some(target_bytes);
+        self
+    }
+
+    pub fn files(mut self, files: u64) -> Self {
+        self.files = Some(files);
+        self
+    }
+
+    pub fn namespace(mut self, namespace: Option<String>) -> Self {
+        self.namespace = Some(namespace);
+        self
+    }
+
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn size_spec(mut self, size_spec: SizeSpec) -> Self {
+        self.size_spec = size_spec;
+        self
+    }
+
+    pub fn null_representation(mut self, null_representation: NullRepresentation) -> Self {
+        self.null_representation = null_representation;
+        self
+    }
+
+    /// Overrides the delimiter of every table.
+    pub fn delimiter(mut self, delimiter: impl Into<String>) -> Self {
+        self.delimiter = Some(delimiter.into());
+        self
+    }
+
+    /// Overrides the record terminator of every table.
+    pub fn terminator(mut self, terminator: RecordTerminator) -> Self {
+        self.terminator = Some(terminator);
+        self
+    }
+
+    /// Qualifies the tables that have no qualifier of their own.
+    pub fn qualifier(mut self, qualifier: impl Into<String>) -> Self {
+        self.qualifier = Some(qualifier.into());
+        self
+    }
+
+    /// Overrides the output format of every table.
+    pub fn output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = Some(output_format);
+        self
+    }
+
+    pub fn file_callback(mut self, callback: impl Fn(&FileMetadata) -> Result<()> + Send + Sync + 'static) -> Self {
+        self.file_callback = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn progress(mut self, progress: impl ProgressSink + 'static) -> Self {
+        self.progress.push(Arc::new(progress));
+        self
+    }
+
+    pub fn interleave(mut self, interleave: InterleaveMode) -> Self {
+        self.interleave = interleave;
+        self
+    }
+
+    pub fn file_name_template(mut self, file_name: FileNameTemplate) -> Self {
+        self.file_name = Some(file_name);
+        self
+    }
+
+    pub fn manifest(mut self, manifest: bool) -> Self {
+        self.manifest = manifest;
+        self
+    }
+
+    pub fn report(mut self, report: bool) -> Self {
+        self.report = report;
+        self
+    }
+
+    /// Writes a control file of the batch, see [`ExportFile::with_control_table`].
+    pub fn control_table(mut self, batch_id: impl Into<String>) -> Self {
+        self.control_batch = Some(batch_id.into());
+        self
+    }
+
+    pub fn checkpoint(mut self, checkpoint: bool) -> Self {
+        self.checkpoint = checkpoint;
+        self
+    }
+
+    /// Sizes tables by a sample of their generated rows, see [`ExportFile::with_measured_sizing`].
+    pub fn measured_sizing(mut self, measured_sizing: bool) -> Self {
+        self.measured_sizing = measured_sizing;
+        self
+    }
+
+    pub fn identifier_quoting(mut self, dialect: Dialect, quoting: IdentifierQuoting) -> Self {
+        self.identifier_quoting.push((dialect, quoting));
+        self
+    }
+
+    /// Uses `type_name` for `column_type` in the dialect's DDL, see [`TypeMapping`].
+    pub fn type_name(mut self, dialect: Dialect, column_type: &str, type_name: impl Into<String>) -> Self {
+        self.type_mapping.insert(dialect, column_type, type_name);
+        self
+    }
+
+    pub fn constraints_ddl(mut self, constraints_ddl: bool) -> Self {
+        self.constraints_ddl = constraints_ddl;
+        self
+    }
+
+    pub fn view(mut self, name: impl Into<String>, query: impl Into<String>) -> Self {
+        self.views.push((name.into(), query.into()));
+        self
+    }
+
+    /// Disables a table or a `table.column`, see [`ExportFile::with_disabled`].
+    pub fn disable(mut self, target: impl Into<String>) -> Self {
+        self.disabled.push(target.into());
+        self
+    }
+
+    /// What disabled columns emit, null by default.
+    pub fn placeholder(mut self, placeholder: Placeholder) -> Self {
+        self.placeholder = placeholder;
+        self
+    }
+
+    pub fn archive(mut self, archive: ArchiveFormat) -> Self {
+        self.archive = Some(archive);
+        self
+    }
+
+    pub fn max_rows_per_file(mut self, max_rows: u64) -> Self {
+        self.max_rows_per_file = Some(max_rows);
+        self
+    }
+
+    pub fn chunk_bytes(mut self, chunk_bytes: u64) -> Self {
+        self.chunk_bytes = Some(chunk_bytes);
+        self
+    }
+
+    pub fn throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    pub fn random_source(mut self, random: Arc<dyn RandomSource>) -> Self {
+        self.random = Some(random);
+        self
+    }
+
+    /// Rescales `percent_size` of the tables to sum to 1 instead of requiring it, see [`ExportFile::new_normalized`].
+    pub fn normalize_percent_size(mut self, normalize_percent_size: bool) -> Self {
+        self.normalize_percent_size = normalize_percent_size;
+        self
+    }
+
+    /// Multiplies every table's size, see [`ExportFile::with_scale_factor`].
+    pub fn scale_factor(mut self, scale_factor: f64) -> Self {
+        self.scale_factor = Some(scale_factor);
+        self
+    }
+
+    pub fn row_ratio(mut self, table: impl Into<String>, of: impl Into<String>, ratio: f64) -> Self {
+        self.row_ratios.push((table.into(), of.into(), ratio));
+        self
+    }
+
+    /// Pins a table to some of the files, see [`ExportFile::with_affinity`].
+    pub fn affinity(mut self, table: impl Into<String>, files: Vec<u64>) -> Self {
+        self.affinity.push((table.into(), files));
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn build(self) -> Result<ExportFile> {
+        let target_bytes = self.target_bytes
+            .ok_or(Error::from(ExportFileError::MissingTargetBytes))?;
+        let files = self.files.unwrap_or(1);
+
+        let tables = self.tables.into_iter()
+            .map(|x| match &self.qualifier {
+                Some(qualifier) if x.qualifiers.is_empty() => x.with_qualifier(qualifier),
+                _ => x,
+            })
+            .collect();
+
+        let export_file = match self.normalize_percent_size {
+            true => ExportFile::new_normalized(tables, target_bytes, files)?,
+            false => ExportFile::new(tables, target_bytes, files)?,
+        };
+        let mut export_file = export_file
+            .with_compression(self.compression)
+            .with_encoding(self.encoding)
+            .with_null_representation(self.null_representation)
+            .with_interleave(self.interleave)
+            .with_manifest(self.manifest)
+            .with_report(self.report)
+            .with_checkpoint(self.checkpoint)
+            .with_measured_sizing(self.measured_sizing)
+            .with_type_mapping(self.type_mapping)
+            .with_constraints_ddl(self.constraints_ddl)
+            .with_archive(self.archive)
+            .with_size_spec(self.size_spec)?;
+        if let Some(scale_factor) = self.scale_factor {
+            export_file = export_file.with_scale_factor(scale_factor)?;
+        }
+        for (table, of, ratio) in self.row_ratios.iter() {
+            export_file = export_file.with_row_ratio(table, of, *ratio)?;
+        }
+        for (table, files) in self.affinity.iter() {
+            export_file = export_file.with_affinity(table, files)?;
+        }
+        if let Some(seed) = self.seed {
+            export_file = export_file.with_seed(seed);
+        }
+        if let Some(batch_id) = self.control_batch {
+            export_file = export_file.with_control_table(batch_id);
+        }
+        if let Some(namespace) = self.namespace {
+            export_file = export_file.with_namespace(namespace)?;
+        }
+        if let Some(random) = self.random {
+            export_file = export_file.with_random_source(random);
+        }
+        if let Some(delimiter) = self.delimiter {
+            export_file = export_file.with_delimiter(&delimiter);
+        }
+        if let Some(terminator) = self.terminator {
+            export_file = export_file.with_terminator(terminator);
+        }
+        if let Some(output_format) = self.output_format {
+            export_file = export_file.with_output_format(output_format);
+        }
+        for target in self.disabled.iter() {
+            export_file = export_file.with_disabled(target, &self.placeholder)?;
+        }
+        for (name, query) in self.views {
+            export_file = export_file.with_view(name, query);
+        }
+        for (dialect, quoting) in self.identifier_quoting {
+            export_file = export_file.with_identifier_quoting(dialect, quoting);
+        }
+        if let Some(file_name) = self.file_name {
+            export_file = export_file.with_file_name_template(file_name);
+        }
+        if let Some(max_rows) = self.max_rows_per_file {
+            export_file = export_file.with_max_rows_per_file(max_rows)?;
+        }
+        if let Some(chunk_bytes) = self.chunk_bytes {
+            export_file = export_file.with_chunk_bytes(chunk_bytes)?;
+        }
+        if let Some(throttle) = self.throttle {
+            export_file = export_file.with_throttle(throttle)?;
+        }
+        export_file.file_callback = self.file_callback;
+        export_file.progress = self.progress;
+
+        export_file.build_schema()?;
+
+        Ok(export_file)
+    }
+}
+
+
+impl Table {
+    pub fn builder(id_value: impl Into<String>) -> TableBuilder {
+        TableBuilder::new(id_value)
+    }
+}
+
+
+impl ExportFile {
+    pub fn builder() -> ExportFileBuilder {
+        ExportFileBuilder::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ColumnType;
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    fn column(name: &str) -> Column {
+        Column::new(name.into(), 3, ColumnType::Char(3), simple_generator)
+    }
+
+    #[test]
+    fn table_builder_test() {
+        let t = Table::builder("A")
+            .add_column(column("a"))
+            .add_column(column("b"))
+            .delimiter(";")
+            .percent_size(Decimal::from_str("1.0").unwrap())
+            .build()
+            .unwrap();
+
+        assert_eq!(t.generate_table_row().unwrap(), "A;ABC;ABC\n");
+
+        let duplicate = Table::builder("A")
+            .add_column(column("a"))
+            .add_column(column("a"))
+            .percent_size(Decimal::from_str("1.0").unwrap())
+            .build();
+
+        assert!(duplicate.is_err());
+        assert!(Table::builder("A").add_column(column("a")).build().is_err());
+    }
+
+    #[test]
+    fn export_file_builder_test() {
+        let t = Table::builder("A")
+            .add_column(column("a"))
+            .percent_size(Decimal::from_str("0.5").unwrap())
+            .build()
+            .unwrap();
+
+        let ef = ExportFile::builder()
+            .add_table(t.clone())
+            .add_table(t.clone())
+            .target_bytes(1024)
+            .files(2)
+            .build();
+
+        match ef {
+            Err(x) => assert!(matches!(
+                x.downcast_ref::<ExportFileError>(),
+                Some(ExportFileError::DuplicateTables { .. })
+            )),
+            Ok(_) => panic!("duplicate tables must fail to build"),
+        }
+
+        assert!(ExportFile::builder().add_table(t.clone()).files(2).build().is_err());
+        assert!(ExportFile::builder().add_table(t.clone()).target_bytes(1024).files(0).build().is_err());
+        assert!(ExportFile::builder().add_table(t.clone()).target_bytes(1024).build().is_err());
+        assert!(ExportFile::builder().add_table(t).target_bytes(1024).normalize_percent_size(true).build().is_ok());
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Error, Result};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::ExportFile;
+use crate::metadata::{FileMetadata, TableMetadata};
+use crate::report::GenerationReport;
+
+pub const CHECKPOINT_FILE: &str = ".checkpoint.jsonl";
+
+
+#[derive(Error, Debug)]
+pub enum CheckpointError {
+    #[error("No checkpoint {path} to resume from.")]
+    Missing { path: String },
+    #[error("Checkpoint {path} was written by a different export, its {field} does not match.")]
+    Mismatch { path: String, field: String },
+}
+
+
+fn checkpoint_name(suffix: &str) -> String {
+    format!(".checkpoint{}.jsonl", suffix)
+}
+
+
+fn metadata_to_json(metadata: &FileMetadata) -> Value {
+    json!({
+        "name": metadata.path.file_name().map(|x| x.to_string_lossy().to_string()),
+        "bytes": metadata.bytes,
+        "checksum": metadata.checksum,
+        "sha256": metadata.sha256,
+        "tables": metadata.tables.iter()
+            .map(|x| json!({"table": x.table, "rows": x.rows, "bytes": x.bytes}))
+            .collect::<Vec<Value>>(),
+    })
+}
+
+
+fn metadata_from_json(folder_path: &Path, file_index: u64, value: &Value) -> Option<FileMetadata> {
+    Some(FileMetadata {
+        path: folder_path.join(value["name"].as_str()?),
+        file_index,
+        tables: value["tables"].as_array()?.iter()
+            .map(|x| Some(TableMetadata {
+                table: x["table"].as_str()?.into(),
+                rows: x["rows"].as_u64()?,
+                bytes: x["bytes"].as_u64()?,
+            }))
+            .collect::<Option<Vec<TableMetadata>>>()?,
+        bytes: value["bytes"].as_u64()?,
+        checksum: value["checksum"].as_u64()? as u32,
+        sha256: value["sha256"].as_str()?.into(),
+        duration: Duration::ZERO,
+    })
+}
+
+
+/// Appends a line per completed file so an interrupted export can be resumed.
+pub(crate) struct Checkpoint {
+    file: Mutex<File>,
+}
+
+impl Checkpoint {
+    /// Starts a new checkpoint when the export checkpoints, a resumed export always appends to its checkpoint.
+    pub(crate) fn open(export_file: &ExportFile, folder_path: &Path, suffix: &str, resumed: bool) -> Result<Option<Checkpoint>> {
+        let path = folder_path.join(checkpoint_name(suffix));
+
+        if resumed {
+            // Records start on a new line even after a line cut short by the failure.
+            let complete = fs::read(&path)?.ends_with(b"\n");
+            let mut file = OpenOptions::new().append(true).open(path)?;
+            if !complete {
+                writeln!(file)?;
+            }
+            return Ok(Some(Checkpoint { file: Mutex::new(file) }));
+        }
+        if !export_file.checkpoint {
+            return Ok(None);
+        }
+
+        let mut file = File::create(path)?;
+        writeln!(file, "{}", export_file.checkpoint_header())?;
+        Ok(Some(Checkpoint { file: Mutex::new(file) }))
+    }
+
+    pub(crate) fn record(&self, export_file: &ExportFile, file_index: u64, metadata: &[FileMetadata]) -> Result<()> {
+        let line = json!({
+            "file_index": file_index,
+            // Every value is derived from the seed and its row and column, so the seed is the whole random state.
+            "seed": export_file.seed,
+            "files": metadata.iter().map(metadata_to_json).collect::<Vec<Value>>(),
+        });
+
+        let mut file = self.file.lock().map_err(|_| Error::msg("checkpoint lock poisoned"))?;
+        writeln!(file, "{}", line)?;
+        Ok(file.sync_data()?)
+    }
+}
+
+
+impl ExportFile {
+    /// Records every completed file in `.checkpoint.jsonl` so `resume` can skip it after a failure.
+    pub fn with_checkpoint(mut self, checkpoint: bool) -> Self {
+        self.checkpoint = checkpoint;
+        self
+    }
+
+
+    fn checkpoint_header(&self) -> Value {
+        json!({
+            "files": self.number_of_files,
+            "file_size_bytes": self.file_size_bytes,
+            "seed": self.seed,
+            "namespace": self.namespace,
+        })
+    }
+
+
+    /// Generates the files a checkpointed `generate_all_files` did not complete, files that are missing or have
+    /// the wrong size are generated again.
+    pub fn resume(&self, folder_path: &Path) -> Result<GenerationReport> {
+        let report = self.resume_files(folder_path, (0..self.number_of_files).collect(), "")?;
+        self.package_export(folder_path)?;
+        Ok(report)
+    }
+
+
+    pub(crate) fn resume_files(&self, folder_path: &Path, file_indices: Vec<u64>, suffix: &str)
+        -> Result<GenerationReport> {
+        let path = folder_path.join(checkpoint_name(suffix));
+        let content = fs::read_to_string(&path)
+            .map_err(|_| Error::from(CheckpointError::Missing { path: path.display().to_string() }))?;
+        let mut lines = content.lines();
+
+        let header: Value = serde_json::from_str(lines.next().unwrap_or_default()).unwrap_or_default();
+        let expected = self.checkpoint_header();
+        for field in ["files", "file_size_bytes", "seed", "namespace"] {
+            if header.get(field) != expected.get(field) {
+                return Err(Error::from(CheckpointError::Mismatch { path: path.display().to_string(), field: field.into() }));
+            }
+        }
+
+        // A line cut short by the failure does not parse and its file is generated again.
+        let mut completed: BTreeMap<u64, Vec<FileMetadata>> = BTreeMap::new();
+        for line in lines.filter_map(|x| serde_json::from_str::<Value>(x).ok()) {
+            let Some(file_index) = line["file_index"].as_u64() else { continue };
+            let files = line["files"].as_array()
+                .and_then(|x| x.iter().map(|y| metadata_from_json(folder_path, file_index, y)).collect::<Option<Vec<FileMetadata>>>());
+
+            match files {
+                Some(files) if files.iter().all(|x| fs::metadata(&x.path).is_ok_and(|y| y.len() == x.bytes)) => {
+                    completed.insert(file_index, files);
+                }
+                _ => {
+                    completed.remove(&file_index);
+                }
+            }
+        }
+
+        let requested: HashSet<u64> = file_indices.iter().copied().collect();
+        completed.retain(|x, _| requested.contains(x));
+        let remaining = file_indices.into_iter().filter(|x| !completed.contains_key(x)).collect();
+
+        self.generate_files(folder_path, remaining, suffix, Some(completed.into_values().flatten().collect()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column, Table};
+    use crate::manifest::MANIFEST_FILE;
+    use crate::types::ColumnType;
+
+    fn export_file() -> ExportFile {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).as_key()],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+        ExportFile::new(vec![t], 400, 4).unwrap().with_seed(7).with_checkpoint(true).with_manifest(true)
+    }
+
+    #[test]
+    fn resume_test() {
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        let ef = export_file();
+        ef.generate_all_files(&folder).unwrap();
+        let expected: Vec<Vec<u8>> = (0..4).map(|x| fs::read(ef.file_path(&folder, x)).unwrap()).collect();
+        let manifest = fs::read_to_string(folder.join(MANIFEST_FILE)).unwrap();
+
+        // An interrupted export: file 0 was not recorded, file 2 is missing, file 3 is truncated and so is the last line.
+        let checkpoint = folder.join(CHECKPOINT_FILE);
+        let content = fs::read_to_string(&checkpoint).unwrap();
+        let kept: Vec<&str> = content.lines().filter(|x| !x.contains("\"file_index\":0")).collect();
+        fs::write(&checkpoint, kept.join("\n") + "\n{\"file_index\":").unwrap();
+        fs::remove_file(ef.file_path(&folder, 2)).unwrap();
+        fs::write(ef.file_path(&folder, 3), "A|").unwrap();
+        fs::remove_file(folder.join(MANIFEST_FILE)).unwrap();
+
+        ef.resume(&folder).unwrap();
+        let resumed: Vec<Vec<u8>> = (0..4).map(|x| fs::read(ef.file_path(&folder, x)).unwrap()).collect();
+        let resumed_manifest = fs::read_to_string(folder.join(MANIFEST_FILE)).unwrap();
+
+        assert!(export_file().with_seed(8).resume(&folder).is_err());
+        fs::remove_dir_all(&folder).unwrap();
+
+        assert_eq!(resumed, expected);
+        assert_eq!(resumed_manifest, manifest);
+        assert!(export_file().resume(&folder).is_err());
+    }
+}
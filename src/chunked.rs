@@ -0,0 +1,261 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::{ExportFile, Table};
+use crate::metadata::{ChecksumWriter, FileMetadata, TableMetadata};
+
+/// Bytes of rows generated at once for a file, 64 MiB.
+pub const DEFAULT_CHUNK_BYTES: u64 = 64 << 20;
+// Chunks waiting for the writer, generation blocks once the writer falls this far behind.
+pub(crate) const CHUNKS_IN_FLIGHT: usize = 2;
+
+// Rows of each table of a file, `None` for tables generated chunk by chunk.
+type KeptRows = Vec<Option<Vec<Vec<String>>>>;
+
+
+#[derive(Error, Debug)]
+pub enum ChunkError {
+    #[error("Chunk size has to be at least 1 byte.")]
+    ZeroBytes,
+    #[error("The writer of {path} stopped before every chunk was written.")]
+    WriterStopped { path: String },
+}
+
+
+impl Table {
+    /// Rows of a chunk of `chunk_bytes`, at least 1.
+    pub fn chunk_rows(&self, chunk_bytes: u64) -> u64 {
+        (chunk_bytes / self.row_size_bytes.max(1)).max(1)
+    }
+}
+
+
+impl ExportFile {
+    /// Bytes of rows generated and held in memory at once while writing a file. Peak memory of a file is a few
+    /// chunks plus the rows of tables other tables reference or with regenerated unique constraints, which are kept
+    /// whole, see [`Table::with_unique`].
+    pub fn with_chunk_bytes(mut self, chunk_bytes: u64) -> Result<Self> {
+        if chunk_bytes == 0 {
+            return Err(Error::from(ChunkError::ZeroBytes));
+        }
+
+        self.chunk_bytes = chunk_bytes;
+        Ok(self)
+    }
+
+
+    /// Writes a file with blocked tables in chunks, each generated in parallel and sent in order to a writer thread
+    /// through a bounded channel. The file is byte for byte the one written from all of its rows at once.
+    pub(crate) fn write_chunked(&self, path: &Path, file_index: u64) -> Result<Vec<FileMetadata>> {
+        let started = Instant::now();
+        let file = BufWriter::new(File::create(path)?);
+        let (tables, (bytes, checksum, sha256)) = self.write_chunks(file, file_index, path)?;
+
+        Ok(vec![FileMetadata {
+            path: path.to_path_buf(),
+            file_index,
+            tables,
+            bytes,
+            checksum,
+            sha256,
+            duration: started.elapsed(),
+        }])
+    }
+
+
+    /// Compresses and encodes the chunks of a file with blocked tables into `out`, returning the metadata of its
+    /// tables and the written bytes, CRC-32 and SHA-256. `path` names the file in errors.
+    pub(crate) fn write_chunks<W: Write + Send>(
+        &self,
+        out: W,
+        file_index: u64,
+        path: &Path,
+    ) -> Result<(Vec<TableMetadata>, (u64, u32, String))> {
+        let (tables, rows) = self.chunked_tables(file_index)?;
+
+        let (sender, receiver) = mpsc::sync_channel::<String>(CHUNKS_IN_FLIGHT);
+        let (metadata, written) = thread::scope(|scope| {
+            let writer = scope.spawn(move || -> Result<(u64, u32, String)> {
+                let mut encoder = self.compression.encoder(ChecksumWriter::new(out))?;
+                for chunk in receiver {
+                    encoder.write_all(&self.encoding.encode(&chunk)?)?;
+                }
+                Ok(encoder.finish()?.finish()?)
+            });
+
+            let metadata = tables.iter()
+                .zip(rows.iter())
+                .map(|(table, rows)| self.send_chunks(table, rows.as_deref(), file_index, &sender, path))
+                .collect::<Result<Vec<TableMetadata>>>();
+            drop(sender);
+
+            let written = writer.join().unwrap_or_else(|_| {
+                Err(Error::from(ChunkError::WriterStopped { path: path.display().to_string() }))
+            });
+            (metadata, written)
+        });
+        // An error of the writer stops the chunks, so it explains a failed send better than the send itself.
+        let written = written?;
+
+        Ok((metadata?, written))
+    }
+
+
+    /// Tables of a file with the reference pools of the file, and the rows of the tables that are kept whole.
+    pub(crate) fn chunked_tables(&self, file_index: u64) -> Result<(Vec<Table>, KeptRows)> {
+        // Referenced tables keep their rows for the reference pools of their children, regenerated unique constraints
+        // and duplicated keys need all rows of a table in the file in order. Permuted unique constraints are unique
+        // by row index and are streamed like any other table.
+        let mut rows: KeptRows = vec![None; self.tables.len()];
+        let mut tables: Vec<Option<Table>> = vec![None; self.tables.len()];
+        for index in self.generation_order()? {
+            let table = self.with_reference_pools(&self.tables[index], &rows, file_index)?;
+            let referenced = self.tables.iter()
+                .flat_map(|x| x.columns.iter())
+                .any(|x| x.reference.as_ref().is_some_and(|y| y.table == table.id_value));
+
+            if referenced || table.is_sequential() {
+                rows[index] = Some(self.generate_table_rows(index, &table, file_index)?);
+            } else if !table.unique.is_empty() {
+                table.check_value_space(self.table_total_rows(&table)?)?;
+            }
+            tables[index] = Some(table);
+        }
+
+        Ok((tables.into_iter().flatten().collect(), rows))
+    }
+
+
+    // Generated rows of a table or its kept `rows` in chunks to the writer.
+    fn send_chunks(
+        &self,
+        table: &Table,
+        rows: Option<&[Vec<String>]>,
+        file_index: u64,
+        sender: &mpsc::SyncSender<String>,
+        path: &Path,
+    ) -> Result<TableMetadata> {
+        let offset = self.table_row_offset(table, file_index)?;
+        let count = self.table_row_count(table, file_index)?;
+        let chunk_rows = match &self.throttle {
+            Some(pacer) => table.chunk_rows(self.chunk_bytes).min(pacer.throttle.rows_per_second(table)),
+            None => table.chunk_rows(self.chunk_bytes),
+        };
+        let mut metadata = TableMetadata { table: table.id_value.clone(), rows: count, bytes: 0 };
+
+        for start in (0..count).step_by(chunk_rows as usize) {
+            let length = chunk_rows.min(count - start);
+            let data = match rows {
+                Some(rows) => table.rows_to_string(&rows[start as usize..(start + length) as usize]),
+                None => table.generate_rows_from(offset + start, length)
+                    .inspect_err(|_| self.count_error(&table.id_value))?,
+            };
+            let bytes = self.encoding.encoded_len(&data);
+
+            sender.send(data)
+                .map_err(|_| Error::from(ChunkError::WriterStopped { path: path.display().to_string() }))?;
+            self.progress_rows(&table.id_value, length, bytes);
+            metadata.bytes += bytes;
+        }
+
+        Ok(metadata)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::fs;
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column};
+    use crate::compression::Compression;
+    use crate::types::ColumnType;
+
+    #[test]
+    fn chunked_test() {
+        let customers = Table::new(
+            "A".into(),
+            vec![Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).as_key()],
+            "|".into(),
+            Decimal::from_str("0.3").unwrap(),
+        );
+        let orders = Table::new(
+            "B".into(),
+            vec![
+                Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)),
+                Column::new("customer_id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).references("A", "id"),
+                Column::new("amount".into(), 6, ColumnType::Int, generators::random_int(0, 999_999)),
+            ],
+            "|".into(),
+            Decimal::from_str("0.7").unwrap(),
+        );
+        let ef = ExportFile::new(vec![customers, orders], 5_000, 2).unwrap()
+            .with_seed(5)
+            .with_compression(Compression::Gzip)
+            .with_chunk_bytes(50)
+            .unwrap();
+
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        fs::create_dir_all(&folder).unwrap();
+        let rows = ef.generate_file_rows(1).unwrap();
+        let (data, table_bytes) = ef.interleave_file(1, &rows);
+        let (_, _, sha256) = ef.write_encoded(&folder.join("whole.txt.gz"), &data).unwrap();
+        let metadata = ef.write_chunked(&folder.join("chunked.txt.gz"), 1).unwrap();
+        let whole = fs::read(folder.join("whole.txt.gz")).unwrap();
+        let chunked = fs::read(folder.join("chunked.txt.gz")).unwrap();
+        fs::remove_dir_all(&folder).unwrap();
+
+        // Orders take chunks of 2 rows.
+        assert!(rows[1].len() > 20);
+        assert_eq!(whole, chunked);
+        assert_eq!(metadata[0].sha256, sha256);
+        assert_eq!(metadata[0].tables.iter().map(|x| x.bytes).collect::<Vec<u64>>(), table_bytes);
+        let counts: Vec<usize> = metadata[0].tables.iter().map(|x| x.rows as usize).collect();
+        assert_eq!(counts, vec![rows[0].len(), rows[1].len()]);
+        assert!(ef.with_chunk_bytes(0).is_err());
+    }
+
+    #[test]
+    fn chunked_unique_test() {
+        // Codes are streamed in chunks, ranks are regenerated and kept whole.
+        let table = |id: &str, column: Column| Table::new(
+            id.into(),
+            vec![column.unique(true)],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+        let ef = ExportFile::new(vec![
+            table("A", Column::new("code".into(), 4, ColumnType::Int, generators::random_int(1, 1_000))),
+            table("B", Column::new("rank".into(), 3, ColumnType::Int, generators::zipf(500, 0.5))),
+        ], 2_000, 3).unwrap()
+            .with_chunk_bytes(50)
+            .unwrap();
+
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_all_files(&folder).unwrap();
+        let rows: Vec<(String, String)> = (0..3)
+            .flat_map(|x| fs::read_to_string(ef.file_path(&folder, x)).unwrap()
+                .lines()
+                .map(|y| y.split_once('|').map(|(a, b)| (a.to_string(), b.to_string())).unwrap())
+                .collect::<Vec<(String, String)>>())
+            .collect();
+        fs::remove_dir_all(&folder).unwrap();
+
+        for id in ["A", "B"] {
+            let values: Vec<&String> = rows.iter().filter(|x| x.0 == id).map(|x| &x.1).collect();
+            assert!(values.len() > 60);
+            assert_eq!(values.iter().collect::<HashSet<_>>().len(), values.len());
+        }
+    }
+}
@@ -0,0 +1,628 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+use thiserror::Error;
+
+use crate::ablation::Placeholder;
+use crate::archive::ArchiveFormat;
+use crate::builder::ExportFileBuilder;
+use crate::delta::DeltaSpec;
+use crate::compression::Compression;
+use crate::config::{compression_from_str, parse_size};
+use crate::encoding::Encoding;
+use crate::file_name::FileNameTemplate;
+use crate::health::Heartbeat;
+use crate::profile::GeneratorProfile;
+use crate::shard::Shard;
+use crate::sizing::SizeReport;
+use crate::throttle::Throttle;
+
+pub const USAGE: &str = "\
+Usage: datagen --config <file> [options]
+
+Options:
+    --config <file>         JSON export config
+    --out <folder>          Output folder, defaults to ./export
+    --size <size>           Total data size, e.g. 512MB or 10GB
+    --files <count>         Number of files
+    --scale-factor <sf>     Multiply every table's size, e.g. 10 for ten times the configured data
+    --format <format>       csv, parquet, avro, tlv, fhir, FHIR NDJSON of healthcare presets, or mt940, bank
+                            statements of the journal_entry preset
+    --compression <codec>   none, gzip or zstd
+    --encoding <encoding>   utf8, latin1, cp037, cp500 or cp1140 for text files
+    --seed <seed>           Seed for reproducible output
+    --size-tolerance <pct>  Size tables by sampled rows, report actual against target size and fail outside this percentage
+    --shard <index/count>   Only generate this shard's files, also read from DIPLOMSKI_SHARD
+    --health-port <port>    Serve progress and liveness over HTTP, also read from DIPLOMSKI_HEALTH_PORT
+    --heartbeat-file <file> Rewrite this file with progress after every file, also read from DIPLOMSKI_HEARTBEAT_FILE
+    --metrics-port <port>   Serve Prometheus metrics, needs the prometheus feature, also read from DIPLOMSKI_METRICS_PORT
+    --file-name <template>  File names with {table}, {index}, {date}, {ext}, {size} and {files} placeholders
+    --per-table             Write every table to its own <table>_<n> files, csv only
+    --max-rows <count>      Spill rows over this count into <name>_part<n> files, csv only
+    --chunk-size <size>     Bytes of rows generated at once per file, e.g. 16MB, 64MB by default
+    --throttle <rate>       Write no faster than e.g. 500rows/s or 1MB/s, to trickle data in over hours
+    --partition-by <column> Write a <column>=<value> file per distinct value of the column, csv only
+    --hive                  With --partition-by, write <table>/<column>=<value>/part-<n> directories instead
+    --progress              Show a progress bar, needs the indicatif feature
+    --disable <target>      Emit placeholders for a table or table.column instead of generating it, repeatable
+    --placeholder <value>   Value emitted by disabled columns, null when not set
+    --profile               Report the time spent in every column generator, the most expensive first
+    --tui                   Browse the config, preview rows and generate interactively, needs the tui feature
+    --fixture <rows>        Write a consistent fixture with this many rows per table in every enabled format
+    --archive <format>      Package the output folder into a zip or tar.gz archive next to it, csv only
+    --checkpoint            Record completed files so an interrupted export can be resumed, csv only
+    --resume                Only generate the files the checkpoint in --out does not list as completed
+    --s3 <bucket[/prefix]>  Upload the files to S3 instead of --out, needs the s3 feature and AWS_* variables, csv only
+    --database-url <url>    Load the rows into postgres:// or mysql:// tables instead of --out, needs that feature
+    --delta <manifest>      Write a delta.txt of I, U and D rows following the export this manifest describes to --out
+    --delta-rows <i,u,d>    Rows every table inserts, updates and deletes in a delta, defaults to 100,10,10
+    --plan                  Report the rows, bytes, memory and time of the export instead of generating it
+    --validate              Report every problem of the config with its code instead of generating, exit 1 on errors
+    --manifest              Also write manifest.json with every file's size, rows and SHA-256, csv only
+    --report                Also write report.json with rows, bytes, wall time, MB/s and generator errors, csv only
+    --control-batch <id>    Also write control.txt with batch id, rows, bytes and time of every file and table, csv only
+    --help                  Print this message
+";
+
+
+#[derive(Error, Debug)]
+pub enum CliError {
+    #[error("Missing value for {flag}.")]
+    MissingValue { flag: String },
+    #[error("Invalid value {value} for {flag}.")]
+    InvalidValue { flag: String, value: String },
+    #[error("Unknown argument {argument}.")]
+    UnknownArgument { argument: String },
+    #[error("Missing required --config.")]
+    MissingConfig,
+    #[error("Format {format} is not available, enable the {feature} feature.")]
+    FormatNotEnabled { format: String, feature: String },
+    #[error("Progress bar is not available, enable the indicatif feature.")]
+    ProgressNotEnabled,
+    #[error("Terminal UI is not available, enable the tui feature.")]
+    TuiNotEnabled,
+    #[error("Metrics are not available, enable the prometheus feature.")]
+    MetricsNotEnabled,
+    #[error("S3 upload is not available, enable the s3 feature.")]
+    S3NotEnabled,
+    #[error("Loading into {scheme} is not available, enable the {scheme} feature.")]
+    DatabaseNotEnabled { scheme: String },
+    #[error("--database-url is not supported together with --shard, --per-table, --partition-by or --s3.")]
+    DatabaseUnsupported,
+    #[error("--s3 is only supported for csv output without --shard, --per-table or --partition-by.")]
+    S3Unsupported,
+    #[error("Sharding is only supported for csv output.")]
+    ShardedBinaryFormat,
+    #[error("Sharding is not supported together with --per-table.")]
+    ShardedPerTable,
+    #[error("Sharding is not supported together with --partition-by.")]
+    ShardedPartition,
+    #[error("--per-table and --partition-by can not be used together.")]
+    PartitionedPerTable,
+    #[error("--resume is only supported for csv output without --per-table or --partition-by.")]
+    ResumeUnsupported,
+    #[error("Partitioning by column is only supported for csv output, not {format}.")]
+    PartitionedBinaryFormat { format: String },
+    #[error("Config has {errors} error(s).")]
+    InvalidConfig { errors: usize },
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Parquet,
+    Avro,
+    Tlv,
+    Fhir,
+    Mt940,
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliArgs {
+    pub config: PathBuf,
+    pub out: PathBuf,
+    pub size: Option<u64>,
+    pub files: Option<u64>,
+    pub scale_factor: Option<f64>,
+    pub format: Format,
+    pub compression: Option<Compression>,
+    pub encoding: Option<Encoding>,
+    pub seed: Option<u64>,
+    pub size_tolerance: Option<Decimal>,
+    pub shard: Option<Shard>,
+    pub health_port: Option<u16>,
+    pub heartbeat_file: Option<PathBuf>,
+    pub metrics_port: Option<u16>,
+    pub file_name: Option<FileNameTemplate>,
+    pub per_table: bool,
+    pub partition_by: Option<String>,
+    pub hive: bool,
+    pub max_rows: Option<u64>,
+    pub chunk_size: Option<u64>,
+    pub throttle: Option<Throttle>,
+    pub disable: Vec<String>,
+    pub placeholder: Option<String>,
+    pub progress: bool,
+    pub tui: bool,
+    pub profile: bool,
+    pub manifest: bool,
+    pub report: bool,
+    pub control_batch: Option<String>,
+    pub checkpoint: bool,
+    pub resume: bool,
+    pub archive: Option<ArchiveFormat>,
+    pub fixture: Option<u64>,
+    pub delta: Option<PathBuf>,
+    pub delta_spec: DeltaSpec,
+    pub s3: Option<String>,
+    pub database_url: Option<String>,
+    pub validate: bool,
+    pub plan: bool,
+    pub help: bool,
+}
+
+impl CliArgs {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<CliArgs> {
+        let mut config = None;
+        let mut parsed = CliArgs {
+            config: PathBuf::new(),
+            out: PathBuf::from("./export"),
+            size: None,
+            files: None,
+            scale_factor: None,
+            format: Format::Csv,
+            compression: None,
+            encoding: None,
+            seed: None,
+            size_tolerance: None,
+            shard: None,
+            health_port: None,
+            heartbeat_file: None,
+            metrics_port: None,
+            file_name: None,
+            per_table: false,
+            partition_by: None,
+            hive: false,
+            max_rows: None,
+            chunk_size: None,
+            throttle: None,
+            disable: vec![],
+            placeholder: None,
+            progress: false,
+            tui: false,
+            profile: false,
+            manifest: false,
+            report: false,
+            control_batch: None,
+            checkpoint: false,
+            resume: false,
+            archive: None,
+            fixture: None,
+            delta: None,
+            delta_spec: DeltaSpec { inserts: 100, updates: 10, deletes: 10 },
+            s3: None,
+            database_url: None,
+            validate: false,
+            plan: false,
+            help: false,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((x, y)) if x.starts_with("--") => (x.to_string(), Some(y.to_string())),
+                _ => (arg.clone(), None),
+            };
+
+            if flag == "--help" || flag == "-h" {
+                parsed.help = true;
+                continue;
+            }
+            if flag == "--progress" {
+                parsed.progress = true;
+                continue;
+            }
+            if flag == "--tui" {
+                parsed.tui = true;
+                continue;
+            }
+            if flag == "--profile" {
+                parsed.profile = true;
+                continue;
+            }
+            if flag == "--per-table" {
+                parsed.per_table = true;
+                continue;
+            }
+            if flag == "--hive" {
+                parsed.hive = true;
+                continue;
+            }
+            if flag == "--manifest" {
+                parsed.manifest = true;
+                continue;
+            }
+            if flag == "--report" {
+                parsed.report = true;
+                continue;
+            }
+            if flag == "--checkpoint" {
+                parsed.checkpoint = true;
+                continue;
+            }
+            if flag == "--resume" {
+                parsed.resume = true;
+                continue;
+            }
+            if flag == "--validate" {
+                parsed.validate = true;
+                continue;
+            }
+            if flag == "--plan" {
+                parsed.plan = true;
+                continue;
+            }
+
+            let value = match inline.or_else(|| args.next()) {
+                Some(x) => x,
+                None => return Err(Error::from(CliError::MissingValue { flag })),
+            };
+            let invalid = || Error::from(CliError::InvalidValue { flag: flag.clone(), value: value.clone() });
+
+            match flag.as_str() {
+                "--config" => config = Some(PathBuf::from(&value)),
+                "--out" => parsed.out = PathBuf::from(&value),
+                "--size" => parsed.size = Some(parse_size(&value).map_err(|_| invalid())?),
+                "--files" => parsed.files = Some(value.parse().map_err(|_| invalid())?),
+                "--scale-factor" => parsed.scale_factor = Some(value.parse().map_err(|_| invalid())?),
+                "--seed" => parsed.seed = Some(value.parse().map_err(|_| invalid())?),
+                "--compression" => parsed.compression = Some(compression_from_str(&value).map_err(|_| invalid())?),
+                "--encoding" => parsed.encoding = Some(value.parse().map_err(|_| invalid())?),
+                "--size-tolerance" => parsed.size_tolerance = Some(
+                    Decimal::from_str(value.trim_end_matches('%')).map_err(|_| invalid())? / Decimal::ONE_HUNDRED
+                ),
+                "--shard" => parsed.shard = Some(value.parse().map_err(|_| invalid())?),
+                "--health-port" => parsed.health_port = Some(value.parse().map_err(|_| invalid())?),
+                "--metrics-port" => parsed.metrics_port = Some(value.parse().map_err(|_| invalid())?),
+                "--file-name" => parsed.file_name = Some(value.parse().map_err(|_| invalid())?),
+                "--fixture" => parsed.fixture = Some(value.parse().map_err(|_| invalid())?),
+                "--archive" => parsed.archive = Some(value.parse().map_err(|_| invalid())?),
+                "--delta" => parsed.delta = Some(PathBuf::from(&value)),
+                "--delta-rows" => parsed.delta_spec = value.parse().map_err(|_| invalid())?,
+                "--max-rows" => parsed.max_rows = Some(value.parse().map_err(|_| invalid())?),
+                "--throttle" => parsed.throttle = Some(value.parse().map_err(|_| invalid())?),
+                "--chunk-size" => parsed.chunk_size = Some(parse_size(&value).map_err(|_| invalid())?),
+                "--partition-by" => parsed.partition_by = Some(value),
+                "--disable" => parsed.disable.push(value),
+                "--placeholder" => parsed.placeholder = Some(value),
+                "--control-batch" => parsed.control_batch = Some(value),
+                "--s3" => parsed.s3 = Some(value),
+                "--database-url" => parsed.database_url = Some(value),
+                "--heartbeat-file" => parsed.heartbeat_file = Some(PathBuf::from(&value)),
+                "--format" => parsed.format = match value.to_ascii_lowercase().as_str() {
+                    "csv" | "txt" | "text" => Format::Csv,
+                    "parquet" => Format::Parquet,
+                    "avro" => Format::Avro,
+                    "tlv" => Format::Tlv,
+                    "mt940" => Format::Mt940,
+                    "fhir" | "ndjson" => Format::Fhir,
+                    _ => return Err(invalid()),
+                },
+                _ => return Err(Error::from(CliError::UnknownArgument { argument: arg })),
+            }
+        }
+
+        match config {
+            Some(x) => parsed.config = x,
+            None if parsed.help => {}
+            None => return Err(Error::from(CliError::MissingConfig)),
+        }
+
+        Ok(parsed)
+    }
+}
+
+
+pub fn run(args: CliArgs) -> Result<()> {
+    if args.help {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+    if args.tui {
+        #[cfg(feature = "tui")]
+        return crate::tui::Tui::from_args(&args)?.run();
+        #[cfg(not(feature = "tui"))]
+        return Err(Error::from(CliError::TuiNotEnabled));
+    }
+
+    let mut builder = ExportFileBuilder::from_json(&fs::read_to_string(&args.config)?)?;
+    if let Some(size) = args.size {
+        builder = builder.target_bytes(size);
+    }
+    if let Some(files) = args.files {
+        builder = builder.files(files);
+    }
+    if let Some(scale_factor) = args.scale_factor {
+        builder = builder.scale_factor(scale_factor);
+    }
+    if let Some(seed) = args.seed {
+        builder = builder.seed(seed);
+    }
+    if let Some(compression) = args.compression {
+        builder = builder.compression(compression);
+    }
+    if let Some(encoding) = args.encoding {
+        builder = builder.encoding(encoding);
+    }
+    if let Some(file_name) = args.file_name {
+        builder = builder.file_name_template(file_name);
+    }
+    if args.manifest {
+        builder = builder.manifest(true);
+    }
+    if args.report {
+        builder = builder.report(true);
+    }
+    if let Some(batch_id) = args.control_batch {
+        builder = builder.control_table(batch_id);
+    }
+    if args.size_tolerance.is_some() {
+        builder = builder.measured_sizing(true);
+    }
+    if args.checkpoint {
+        builder = builder.checkpoint(true);
+    }
+    if let Some(archive) = args.archive {
+        builder = builder.archive(archive);
+    }
+    if let Some(max_rows) = args.max_rows {
+        builder = builder.max_rows_per_file(max_rows);
+    }
+    if let Some(chunk_size) = args.chunk_size {
+        builder = builder.chunk_bytes(chunk_size);
+    }
+    if let Some(throttle) = args.throttle {
+        builder = builder.throttle(throttle);
+    }
+    for target in args.disable.iter() {
+        builder = builder.disable(target);
+    }
+    if let Some(placeholder) = args.placeholder.clone() {
+        builder = builder.placeholder(Placeholder::Constant(placeholder));
+    }
+    if args.validate {
+        let report = builder.validate();
+        eprint!("{}", report);
+        let errors = report.errors().count();
+        return match errors {
+            0 => Ok(()),
+            _ => Err(Error::from(CliError::InvalidConfig { errors })),
+        };
+    }
+    let mut export_file = builder.build()?;
+    if args.plan {
+        eprint!("{}", export_file.plan()?);
+        return Ok(());
+    }
+
+    let shard = match args.shard {
+        Some(x) => Some(x),
+        None => Shard::from_env()?,
+    };
+
+    let files_total = match shard {
+        Some(shard) => shard.files(export_file.number_of_files).len() as u64,
+        None => export_file.number_of_files,
+    };
+    let heartbeat = match (args.health_port, args.heartbeat_file) {
+        (None, None) => Heartbeat::from_env(files_total)?,
+        (port, file) => Heartbeat::start(files_total, port, file)?,
+    };
+    if let Some(heartbeat) = heartbeat {
+        export_file = export_file.with_heartbeat(heartbeat);
+    }
+    if args.progress {
+        #[cfg(feature = "indicatif")]
+        {
+            export_file = export_file.with_progress(crate::progress::ProgressBar::new());
+        }
+        #[cfg(not(feature = "indicatif"))]
+        return Err(Error::from(CliError::ProgressNotEnabled));
+    }
+    #[cfg(feature = "prometheus")]
+    {
+        let metrics = match args.metrics_port {
+            Some(port) => {
+                let metrics = Arc::new(crate::metrics::Metrics::new());
+                metrics.serve(("0.0.0.0", port))?;
+                Some(metrics)
+            }
+            None => crate::metrics::Metrics::from_env()?,
+        };
+        if let Some(metrics) = metrics {
+            export_file = export_file.with_progress(metrics);
+        }
+    }
+    #[cfg(not(feature = "prometheus"))]
+    if args.metrics_port.is_some() {
+        return Err(Error::from(CliError::MetricsNotEnabled));
+    }
+
+    let size_report = args.size_tolerance.map(|x| Arc::new(SizeReport::new().with_tolerance(x)));
+    if let Some(size_report) = &size_report {
+        export_file = export_file.with_progress(size_report.clone());
+    }
+
+    let profile = args.profile.then(|| Arc::new(GeneratorProfile::new()));
+    if let Some(profile) = &profile {
+        export_file = export_file.with_profile(profile.clone());
+    }
+
+    if let Some(rows) = args.fixture {
+        return export_file.generate_fixture(&args.out, rows);
+    }
+    if let Some(previous) = args.delta.as_deref() {
+        return export_file.generate_delta(previous, args.delta_spec, &args.out);
+    }
+
+    if let Some(url) = args.database_url.as_deref() {
+        if shard.is_some() || args.per_table || args.partition_by.is_some() || args.s3.is_some() {
+            return Err(Error::from(CliError::DatabaseUnsupported));
+        }
+        return match url.split("://").next().unwrap_or_default() {
+            #[cfg(feature = "postgres")]
+            "postgres" | "postgresql" => {
+                export_file.generate_all_files_to_postgres(&crate::sinks::postgres::PostgresConfig::from_url(url)?)
+            }
+            #[cfg(feature = "mysql")]
+            "mysql" => export_file.generate_all_files_to_mysql(&crate::sinks::mysql::MySqlConfig::from_url(url)?),
+            #[allow(unreachable_patterns)]
+            "postgres" | "postgresql" => Err(Error::from(CliError::DatabaseNotEnabled { scheme: "postgres".into() })),
+            #[allow(unreachable_patterns)]
+            "mysql" => Err(Error::from(CliError::DatabaseNotEnabled { scheme: "mysql".into() })),
+            _ => Err(Error::from(CliError::InvalidValue { flag: "--database-url".into(), value: url.into() })),
+        };
+    }
+
+    if args.s3.is_some() && (args.format != Format::Csv || shard.is_some() || args.per_table || args.partition_by.is_some()) {
+        return Err(Error::from(CliError::S3Unsupported));
+    }
+    #[cfg(not(feature = "s3"))]
+    if args.s3.is_some() {
+        return Err(Error::from(CliError::S3NotEnabled));
+    }
+    #[cfg(feature = "s3")]
+    if let Some(target) = args.s3.as_deref() {
+        let (bucket, prefix) = target.split_once('/').unwrap_or((target, ""));
+        return export_file.generate_all_files_to_s3(bucket, prefix, &crate::sinks::s3::S3Config::from_env()?);
+    }
+
+    if args.resume && (args.per_table || args.partition_by.is_some() || args.format != Format::Csv) {
+        return Err(Error::from(CliError::ResumeUnsupported));
+    }
+
+    if let Some(column) = args.partition_by.as_deref() {
+        return match (args.format, shard) {
+            _ if args.per_table => Err(Error::from(CliError::PartitionedPerTable)),
+            (Format::Csv, None) if args.hive => export_file.generate_hive_partitioned_files(&args.out, column),
+            (Format::Csv, None) => export_file.generate_partitioned_files(&args.out, column),
+            (Format::Csv, Some(_)) => Err(Error::from(CliError::ShardedPartition)),
+            (format, _) => Err(Error::from(CliError::PartitionedBinaryFormat { format: format!("{:?}", format).to_lowercase() })),
+        };
+    }
+
+    let result = match (args.format, shard) {
+        (Format::Csv, Some(_)) if args.per_table => Err(Error::from(CliError::ShardedPerTable)),
+        (Format::Csv, None) if args.per_table => export_file.generate_files_per_table(&args.out),
+        (Format::Csv, Some(shard)) if args.resume => export_file.resume_shard(&args.out, shard).map(|_| ()),
+        (Format::Csv, None) if args.resume => export_file.resume(&args.out).map(|_| ()),
+        (Format::Csv, Some(shard)) => export_file.generate_shard(&args.out, shard).map(|_| ()),
+        (Format::Csv, None) => export_file.generate_all_files(&args.out),
+        (_, Some(_)) => Err(Error::from(CliError::ShardedBinaryFormat)),
+        (Format::Tlv, None) => export_file.generate_tlv(&args.out),
+        (Format::Mt940, None) => export_file.generate_mt940(&args.out),
+        #[cfg(feature = "parquet")]
+        (Format::Parquet, None) => export_file.generate_parquet(&args.out),
+        #[cfg(feature = "avro")]
+        (Format::Avro, None) => export_file.generate_avro(&args.out),
+        #[cfg(feature = "healthcare")]
+        (Format::Fhir, None) => export_file.generate_fhir_ndjson(&args.out),
+        #[allow(unreachable_patterns)]
+        (format, None) => {
+            let format = format!("{:?}", format).to_lowercase();
+            let feature = if format == "fhir" { "healthcare".into() } else { format.clone() };
+            Err(Error::from(CliError::FormatNotEnabled { format, feature }))
+        }
+    };
+
+    if let (Ok(()), Some(profile)) = (&result, &profile) {
+        eprint!("{}", profile);
+    }
+
+    match size_report {
+        Some(size_report) => {
+            result?;
+            eprintln!("{}", size_report);
+            size_report.check()
+        }
+        None => result,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(x: &[&str]) -> Vec<String> {
+        x.iter().map(|y| y.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_test() {
+        let parsed = CliArgs::parse(args(&[
+            "--config", "export.json", "--size=10GB", "--files", "8", "--out", "./out", "--format", "csv", "--seed", "42",
+        ])).unwrap();
+
+        assert_eq!(parsed.config, PathBuf::from("export.json"));
+        assert_eq!(parsed.size, Some(10 * (1 << 30)));
+        assert_eq!(parsed.files, Some(8));
+        assert_eq!(parsed.out, PathBuf::from("./out"));
+        assert_eq!(parsed.seed, Some(42));
+
+        assert!(CliArgs::parse(args(&["--size", "10GB"])).is_err());
+        assert!(CliArgs::parse(args(&["--config", "export.json", "--files", "many"])).is_err());
+        assert!(CliArgs::parse(args(&["--config", "export.json", "--max-rows", "-1"])).is_err());
+        assert_eq!(CliArgs::parse(args(&["--config", "export.json", "--archive", "tgz"])).unwrap().archive, Some(ArchiveFormat::TarGz));
+        assert_eq!(CliArgs::parse(args(&["--config", "export.json", "--s3", "bucket/exports"])).unwrap().s3.as_deref(), Some("bucket/exports"));
+        assert!(CliArgs::parse(args(&["--config", "export.json", "--verbose", "1"])).is_err());
+        assert!(CliArgs::parse(args(&["--help"])).unwrap().help);
+        assert!(CliArgs::parse(args(&["--per-table", "--config", "export.json"])).unwrap().per_table);
+        assert!(CliArgs::parse(args(&["--manifest", "--config", "export.json"])).unwrap().manifest);
+        assert!(CliArgs::parse(args(&["--hive", "--config", "export.json"])).unwrap().hive);
+        assert_eq!(CliArgs::parse(args(&["--config", "export.json", "--scale-factor", "10"])).unwrap().scale_factor, Some(10.0));
+        assert!(CliArgs::parse(args(&["--resume", "--config", "export.json"])).unwrap().resume);
+        assert!(CliArgs::parse(args(&["--profile", "--config", "export.json"])).unwrap().profile);
+        assert_eq!(
+            CliArgs::parse(args(&["--partition-by=country", "--config", "export.json"])).unwrap().partition_by,
+            Some("country".into())
+        );
+        assert!(CliArgs::parse(args(&["--config", "export.json", "--file-name", "{table}.csv"])).is_err());
+        assert_eq!(
+            CliArgs::parse(args(&["--config", "export.json", "--size-tolerance", "2.5%"])).unwrap().size_tolerance,
+            Some(Decimal::new(25, 3))
+        );
+        assert_eq!(
+            CliArgs::parse(args(&["--config", "export.json", "--disable", "A", "--disable=B.b"])).unwrap().disable,
+            vec!["A".to_string(), "B.b".to_string()]
+        );
+        assert_eq!(
+            CliArgs::parse(args(&["--config", "export.json", "--delta-rows", "1,2,3"])).unwrap().delta_spec,
+            DeltaSpec { inserts: 1, updates: 2, deletes: 3 }
+        );
+    }
+
+    #[test]
+    fn run_test() {
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        let config = folder.join("export.json");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(&config, r#"{"tables": [{"id": "A", "percent_size": 1, "columns": [
+            {"name": "id", "type": "INT", "size": 4, "generator": "sequence"}
+        ]}]}"#).unwrap();
+
+        let result = run(CliArgs::parse(args(&[
+            "--config", config.to_str().unwrap(), "--out", folder.join("out").to_str().unwrap(), "--size", "40", "--files", "2",
+        ])).unwrap());
+        let files = fs::read_dir(folder.join("out")).map(|x| x.count()).unwrap_or(0);
+        fs::remove_dir_all(&folder).unwrap();
+
+        result.unwrap();
+        assert_eq!(files, 2);
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+
+use crate::{checked_u64, ExportFile, ExportFileError, SizeSpec, Table};
+use crate::chunked::DEFAULT_CHUNK_BYTES;
+use crate::ddl::View;
+
+
+// Scales weights so they sum to exactly 1, the rounding remainder goes to the last weight.
+pub(crate) fn normalize(weights: &[Decimal]) -> Result<Vec<Decimal>> {
+    let sum: Decimal = weights.iter().sum();
+
+    if sum <= Decimal::ZERO {
+        return Err(Error::from(ExportFileError::SumPercentSizeIncorrect { sum_percent_size: sum }));
+    }
+
+    let mut normalized: Vec<Decimal> = weights.iter().map(|x| x / sum).collect();
+    let remainder = Decimal::ONE - normalized.iter().sum::<Decimal>();
+    if let Some(last) = normalized.last_mut() {
+        *last += remainder;
+    }
+
+    Ok(normalized)
+}
+
+
+impl ExportFile {
+    /// Merges exports weighting each one by its own total data size.
+    pub fn merge(exports: Vec<ExportFile>, number_of_files: u64) -> Result<ExportFile> {
+        let data_size_bytes = checked_u64(
+            exports.iter().map(|x| x.file_size_bytes as u128 * x.number_of_files as u128).sum(),
+            "Merged data size",
+        )?;
+        let weighted = exports.into_iter()
+            .map(|x| {
+                let weight = Decimal::from(x.file_size_bytes) * Decimal::from(x.number_of_files);
+                (x, weight)
+            })
+            .collect();
+
+        ExportFile::merge_weighted(weighted, data_size_bytes, number_of_files)
+    }
+
+
+    pub fn merge_weighted(
+        exports: Vec<(ExportFile, Decimal)>,
+        data_size_bytes: u64,
+        number_of_files: u64,
+    ) -> Result<ExportFile> {
+        let mut seen: HashSet<String> = HashSet::new();
+        for (export, _) in exports.iter() {
+            for table in export.tables.iter() {
+                if !seen.insert(table.id_value.clone()) {
+                    return Err(Error::from(ExportFileError::DuplicateTables { table: table.id_value.clone() }));
+                }
+            }
+        }
+
+        let export_weights = normalize(&exports.iter().map(|x| x.1).collect::<Vec<Decimal>>())?;
+        let table_weights = normalize(
+            &exports.iter()
+                .zip(export_weights.iter())
+                .flat_map(|((export, _), weight)| export.tables.iter().map(move |x| x.percent_size * weight))
+                .collect::<Vec<Decimal>>()
+        )?;
+
+        let namespace = exports.first().and_then(|x| x.0.namespace.clone());
+        let compression = exports.first().map(|x| x.0.compression).unwrap_or_default();
+        let interleave = exports.first().map(|x| x.0.interleave).unwrap_or_default();
+        let file_name = exports.first().and_then(|x| x.0.file_name.clone());
+        let manifest = exports.first().is_some_and(|x| x.0.manifest);
+        let report = exports.first().is_some_and(|x| x.0.report);
+        let control_batch = exports.first().and_then(|x| x.0.control_batch.clone());
+        let checkpoint = exports.first().is_some_and(|x| x.0.checkpoint);
+        let archive = exports.first().and_then(|x| x.0.archive);
+        let encoding = exports.first().map(|x| x.0.encoding).unwrap_or_default();
+        let measured_sizing = exports.first().is_some_and(|x| x.0.measured_sizing);
+        let identifier_quoting = exports.first().map(|x| x.0.identifier_quoting.clone()).unwrap_or_default();
+        let type_mapping = exports.first().map(|x| x.0.type_mapping.clone()).unwrap_or_default();
+        let constraints_ddl = exports.first().is_some_and(|x| x.0.constraints_ddl);
+        let views: Vec<View> = exports.iter().flat_map(|x| x.0.views.clone()).collect();
+        let max_rows_per_file = exports.first().and_then(|x| x.0.max_rows_per_file);
+        let chunk_bytes = exports.first().map(|x| x.0.chunk_bytes).unwrap_or(DEFAULT_CHUNK_BYTES);
+        let throttle = exports.first().and_then(|x| x.0.throttle.as_ref().map(|y| y.throttle));
+        let file_callback = exports.first().and_then(|x| x.0.file_callback.clone());
+        let progress = exports.first().map(|x| x.0.progress.clone()).unwrap_or_default();
+        let mut rows: HashMap<String, u64> = HashMap::new();
+        let mut tables: Vec<Table> = vec![];
+
+        for (export, _) in exports.into_iter() {
+            if let SizeSpec::Rows(x) = export.size_spec {
+                rows.extend(x);
+            }
+            tables.extend(export.tables);
+        }
+
+        for (table, weight) in tables.iter_mut().zip(table_weights) {
+            table.percent_size = weight;
+        }
+
+        let size_spec = if rows.is_empty() { SizeSpec::Bytes } else { SizeSpec::Rows(rows) };
+
+        let mut export_file = ExportFile::new(tables, data_size_bytes, number_of_files)?
+            .with_namespace(namespace)?
+            .with_compression(compression)
+            .with_interleave(interleave)
+            .with_manifest(manifest)
+            .with_report(report)
+            .with_checkpoint(checkpoint)
+            .with_archive(archive)
+            .with_encoding(encoding)
+            .with_size_spec(size_spec)?;
+        export_file.file_callback = file_callback;
+        export_file.file_name = file_name;
+        export_file.progress = progress;
+        export_file.max_rows_per_file = max_rows_per_file;
+        export_file.chunk_bytes = chunk_bytes;
+        if let Some(throttle) = throttle {
+            export_file = export_file.with_throttle(throttle)?;
+        }
+        export_file.measured_sizing = measured_sizing;
+        export_file.identifier_quoting = identifier_quoting;
+        export_file.type_mapping = type_mapping;
+        export_file.constraints_ddl = constraints_ddl;
+        export_file.views = views;
+        export_file.control_batch = control_batch;
+
+        Ok(export_file)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Column;
+    use crate::types::ColumnType;
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    fn table(id: &str, percent_size: &str) -> Table {
+        Table::new(
+            id.into(),
+            vec![Column::new("column".into(), 3, ColumnType::Char(3), simple_generator)],
+            "|".into(),
+            Decimal::from_str(percent_size).unwrap(),
+        )
+    }
+
+    #[test]
+    fn normalize_test() {
+        let weights = normalize(&[Decimal::ONE, Decimal::ONE, Decimal::ONE]).unwrap();
+
+        assert_eq!(weights.iter().sum::<Decimal>(), Decimal::ONE);
+        assert!(normalize(&[Decimal::ZERO]).is_err());
+    }
+
+    #[test]
+    fn merge_test() {
+        let sales = ExportFile::new(vec![table("A", "0.5"), table("B", "0.5")], 3000, 1).unwrap();
+        let logs = ExportFile::new(vec![table("C", "1.0")], 1000, 1).unwrap();
+
+        let merged = ExportFile::merge(vec![sales, logs], 2).unwrap();
+        let weights: Vec<Decimal> = merged.tables.iter().map(|x| x.percent_size).collect();
+
+        assert_eq!(merged.file_size_bytes, 2000);
+        assert_eq!(
+            weights,
+            vec![
+                Decimal::from_str("0.375").unwrap(),
+                Decimal::from_str("0.375").unwrap(),
+                Decimal::from_str("0.25").unwrap(),
+            ]
+        );
+
+        let duplicate = ExportFile::merge(
+            vec![
+                ExportFile::new(vec![table("A", "1.0")], 1000, 1).unwrap(),
+                ExportFile::new(vec![table("A", "1.0")], 1000, 1).unwrap(),
+            ],
+            1,
+        );
+        assert!(duplicate.is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use std::io;
+use std::io::Write;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    /// Zstandard frame compressed at `level`, 1 to 22 where 0 picks the library's default.
+    Zstd { level: i32 },
+}
+
+impl Compression {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Compression::None => "txt",
+            Compression::Gzip => "txt.gz",
+            Compression::Zstd { .. } => "txt.zst",
+        }
+    }
+
+    pub fn encoder<W: Write>(&self, writer: W) -> io::Result<Encoder<W>> {
+        match self {
+            Compression::None => Ok(Encoder::Plain(writer)),
+            Compression::Gzip => Ok(Encoder::Gzip(gzip_encoder(writer))),
+            Compression::Zstd { level } => Ok(Encoder::Zstd(ZstdEncoder::new(writer, *level)?)),
+        }
+    }
+}
+
+
+pub enum Encoder<W: Write> {
+    Plain(W),
+    Gzip(GzipEncoder<W>),
+    Zstd(ZstdEncoder<W>),
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn finish(self) -> io::Result<W> {
+        match self {
+            Encoder::Plain(mut x) => {
+                x.flush()?;
+                Ok(x)
+            }
+            Encoder::Gzip(x) => {
+                let mut x = x.finish()?;
+                x.flush()?;
+                Ok(x)
+            }
+            Encoder::Zstd(x) => x.finish(),
+        }
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Encoder::Plain(x) => x.write(buf),
+            Encoder::Gzip(x) => x.write(buf),
+            Encoder::Zstd(x) => x.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Encoder::Plain(x) => x.flush(),
+            Encoder::Gzip(x) => x.flush(),
+            Encoder::Zstd(x) => x.flush(),
+        }
+    }
+}
+
+
+pub(crate) fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new_with_initial(crc);
+    hasher.update(data);
+    hasher.finalize()
+}
+
+
+/// Raw DEFLATE stream, as stored in ZIP files.
+pub type DeflateEncoder<W> = flate2::write::DeflateEncoder<W>;
+
+pub type GzipEncoder<W> = flate2::write::GzEncoder<W>;
+
+
+pub(crate) fn deflate_encoder<W: Write>(writer: W) -> DeflateEncoder<W> {
+    DeflateEncoder::new(writer, flate2::Compression::default())
+}
+
+
+pub(crate) fn gzip_encoder<W: Write>(writer: W) -> GzipEncoder<W> {
+    GzipEncoder::new(writer, flate2::Compression::default())
+}
+
+
+pub type ZstdEncoder<W> = zstd::stream::write::Encoder<'static, W>;
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    #[test]
+    fn crc32_test() {
+        assert_eq!(crc32_update(0, b"123456789"), 0xCBF43926);
+    }
+
+    #[test]
+    fn gzip_test() {
+        let data = "A|ABC|ABC\n".repeat(10_000);
+        let mut encoder = Compression::Gzip.encoder(Vec::new()).unwrap();
+        encoder.write_all(data.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert_eq!(&compressed[..3], &[0x1f, 0x8b, 8]);
+        assert!(compressed.len() < data.len() / 10);
+        assert_eq!(&compressed[compressed.len() - 4..], &(data.len() as u32).to_le_bytes());
+
+        let mut decompressed = String::new();
+        flate2::read::GzDecoder::new(compressed.as_slice()).read_to_string(&mut decompressed).unwrap();
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn zstd_test() {
+        let data = "A|ABC|ABC\n".repeat(10_000);
+        let mut encoder = Compression::Zstd { level: 3 }.encoder(Vec::new()).unwrap();
+        encoder.write_all(data.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert!(compressed.len() < data.len() / 10);
+        assert_eq!(zstd::stream::decode_all(compressed.as_slice()).unwrap(), data.as_bytes());
+    }
+}
@@ -0,0 +1,925 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{generators, Column, ExportFile, SizeSpec, Table};
+use crate::ablation::Placeholder;
+use crate::builder::ExportFileBuilder;
+use crate::compression::Compression;
+use crate::corruption::CorruptionProfile;
+use crate::events::EventClock;
+use crate::external::ExternalSource;
+use crate::generators::Generator;
+use crate::output_format::{Alignment, OutputFormat};
+use crate::scd::Scd2;
+use crate::stress::NumericStress;
+use crate::transform::Transform;
+use crate::types::{Annotations, ColumnSize, ColumnType, NullRepresentation, RecordTerminator};
+
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Config is missing field {field}.")]
+    MissingField { field: String },
+    #[error("Config field {field} is invalid: {reason}.")]
+    InvalidField { field: String, reason: String },
+    #[error("Unknown generator {name}.")]
+    UnknownGenerator { name: String },
+}
+
+
+fn invalid(field: &str, reason: &str) -> Error {
+    Error::from(ConfigError::InvalidField { field: field.into(), reason: reason.into() })
+}
+
+
+fn field<'a>(value: &'a Value, field: &str) -> Result<&'a Value> {
+    value.get(field).ok_or_else(|| Error::from(ConfigError::MissingField { field: field.into() }))
+}
+
+
+fn string(value: &Value, name: &str) -> Result<String> {
+    field(value, name)?.as_str()
+        .map(|x| x.to_string())
+        .ok_or_else(|| invalid(name, "expected a string"))
+}
+
+
+fn u64_field(value: &Value, name: &str) -> Result<u64> {
+    field(value, name)?.as_u64().ok_or_else(|| invalid(name, "expected a non negative integer"))
+}
+
+
+/// Sizes like `1024`, `512KB`, `1.5GB` or `10GiB`, units are powers of 1024.
+pub fn parse_size(size: &str) -> Result<u64> {
+    let size = size.trim();
+    let split = size.find(|x: char| x.is_ascii_alphabetic()).unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        _ => return Err(invalid("size", "unknown unit")),
+    };
+
+    Decimal::from_str(number.trim()).map_err(|_| invalid("size", "expected a number"))?
+        .checked_mul(Decimal::from(multiplier))
+        .ok_or_else(|| invalid("size", "out of range"))?
+        .floor()
+        .to_u64()
+        .ok_or_else(|| invalid("size", "out of range"))
+}
+
+
+fn args(spec: &Value) -> Vec<Value> {
+    spec.get("args").and_then(|x| x.as_array()).cloned().unwrap_or_default()
+}
+
+
+fn f64_arg(args: &[Value], index: usize) -> Result<f64> {
+    args.get(index).and_then(|x| x.as_f64()).ok_or_else(|| invalid("generator.args", "expected a number"))
+}
+
+
+fn i64_arg(args: &[Value], index: usize) -> Result<i64> {
+    args.get(index).and_then(|x| x.as_i64()).ok_or_else(|| invalid("generator.args", "expected an integer"))
+}
+
+
+fn f64_array_arg(args: &[Value], index: usize) -> Result<Vec<f64>> {
+    args.get(index)
+        .and_then(|x| x.as_array())
+        .and_then(|x| x.iter().map(|y| y.as_f64()).collect::<Option<Vec<f64>>>())
+        .ok_or_else(|| invalid("generator.args", "expected an array of numbers"))
+}
+
+
+fn string_arg(args: &[Value], index: usize) -> Result<String> {
+    args.get(index)
+        .and_then(|x| x.as_str().map(|y| y.to_string()).or_else(|| x.as_number().map(|y| y.to_string())))
+        .ok_or_else(|| invalid("generator.args", "expected a string"))
+}
+
+
+/// `"name"` or `{"name": ..., "args": [...]}` naming one of the built in generators.
+pub fn generator_from_spec(spec: &Value) -> Result<Generator> {
+    let name = match spec {
+        Value::String(x) => x.clone(),
+        _ => string(spec, "name")?,
+    };
+    let args = args(spec);
+
+    let generator = match name.as_str() {
+        "sequence" => generators::sequence(i64_arg(&args, 0).unwrap_or(1), i64_arg(&args, 1).unwrap_or(1)),
+        "row_number" => generators::row_number(i64_arg(&args, 0).unwrap_or(0).max(0) as u64),
+        "constant" => generators::constant(string_arg(&args, 0)?),
+        "one_of" => generators::one_of((0..args.len()).map(|x| string_arg(&args, x)).collect::<Result<Vec<String>>>()?),
+        "random_int" => generators::random_int(i64_arg(&args, 0)?, i64_arg(&args, 1)?),
+        "random_string" if args.len() > 1 => {
+            generators::random_string_between(i64_arg(&args, 0)?.max(0) as usize, i64_arg(&args, 1)?.max(0) as usize)
+        }
+        "random_string" => generators::random_string(i64_arg(&args, 0)?.max(0) as usize),
+        "uniform" => generators::uniform(f64_arg(&args, 0)?, f64_arg(&args, 1)?),
+        "normal" => generators::normal(f64_arg(&args, 0)?, f64_arg(&args, 1)?),
+        "lognormal" => generators::lognormal(f64_arg(&args, 0)?, f64_arg(&args, 1)?),
+        "exponential" => generators::exponential(f64_arg(&args, 0)?),
+        "histogram" => generators::histogram(f64_array_arg(&args, 0)?, f64_array_arg(&args, 1)?),
+        "random_decimal" => {
+            generators::random_decimal(i64_arg(&args, 0)?.max(0) as u32, i64_arg(&args, 1).unwrap_or(0).max(0) as u32)
+        }
+        "random_date" => generators::random_date(&string_arg(&args, 0)?, &string_arg(&args, 1)?),
+        "random_timestamp" => generators::random_timestamp(&string_arg(&args, 0)?, &string_arg(&args, 1)?),
+        "difference" => generators::difference(string_arg(&args, 0)?, string_arg(&args, 1)?),
+        "one_of_for" => {
+            let values = args.get(1)
+                .and_then(|x| x.as_object())
+                .ok_or_else(|| invalid("generator.args", "expected an object of value lists"))?
+                .iter()
+                .map(|(key, value)| {
+                    let choices = value.as_array()
+                        .ok_or_else(|| invalid("generator.args", "expected an object of value lists"))?
+                        .iter()
+                        .map(|x| x.as_str().map(|y| y.to_string()))
+                        .collect::<Option<Vec<String>>>()
+                        .ok_or_else(|| invalid("generator.args", "expected string values"))?;
+                    Ok((key.clone(), choices))
+                })
+                .collect::<Result<HashMap<String, Vec<String>>>>()?;
+            generators::one_of_for(string_arg(&args, 0)?, values)
+        }
+        "zipf" => generators::zipf(i64_arg(&args, 0)?.max(0) as u64, f64_arg(&args, 1)?),
+        #[cfg(feature = "fake")]
+        "person_name" | "first_name" | "last_name" | "address" | "phone" => {
+            let locale: generators::Locale = string_arg(&args, 0).unwrap_or_else(|_| "en".into()).parse()?;
+            match name.as_str() {
+                "person_name" => generators::person_name(locale),
+                "first_name" => generators::first_name(locale),
+                "last_name" => generators::last_name(locale),
+                "address" => generators::address(locale),
+                _ => generators::phone(locale),
+            }
+        }
+        "account_code" => generators::finance::account_code(),
+        "account_name" => generators::finance::account_name(),
+        "account_type" => generators::finance::account_type(),
+        "transaction_id" | "entry_side" | "entry_amount" | "posting_date" => {
+            let entry = generators::finance::DoubleEntry::new(i64_arg(&args, 0)?.max(0) as u64)?;
+            match name.as_str() {
+                "transaction_id" => entry.transaction_id(),
+                "entry_side" => entry.side(),
+                "entry_amount" => entry.amount(),
+                _ => entry.posting_date(&string_arg(&args, 1)?, &string_arg(&args, 2)?)?,
+            }
+        }
+        "customer_email" => generators::retail::customer_email(),
+        "product_sku" => generators::retail::product_sku(),
+        "product_name" => generators::retail::product_name(),
+        "product_category" => generators::retail::product_category(),
+        "product_price" | "line_order_id" | "line_product_id" | "line_quantity" | "line_unit_price" | "line_amount"
+        | "order_total" | "payment_order_id" | "payment_amount" => {
+            let basket = generators::retail::Basket::new(
+                i64_arg(&args, 0)?.max(0) as u64,
+                i64_arg(&args, 1)?.max(0) as u64,
+            )?;
+            match name.as_str() {
+                "product_price" => basket.product_price(),
+                "line_order_id" => basket.line_order_id(),
+                "line_product_id" => basket.line_product_id(),
+                "line_quantity" => basket.line_quantity(),
+                "line_unit_price" => basket.line_unit_price(),
+                "line_amount" => basket.line_amount(),
+                "order_total" => basket.order_total_amount(),
+                "payment_order_id" => basket.payment_order_id(),
+                _ => basket.payment_amount(),
+            }
+        }
+        "reading_device_id" | "reading_value" | "reading_status" | "device_model" | "device_sensor_type"
+        | "device_unit" | "device_site" => {
+            let fleet = generators::iot::SensorFleet::new(i64_arg(&args, 0)?.max(0) as u64)?;
+            match name.as_str() {
+                "reading_device_id" => fleet.reading_device_id(),
+                "reading_value" => fleet.reading_value(),
+                "reading_status" => fleet.reading_status(),
+                "device_model" => fleet.device_model(),
+                "device_sensor_type" => fleet.device_sensor_type(),
+                "device_unit" => fleet.device_unit(),
+                _ => fleet.device_site(),
+            }
+        }
+        "impression_campaign_id" | "impression_ad_id" | "impression_user_id" | "impression_placement"
+        | "impression_time" | "impression_cost" | "click_impression_id" | "click_ad_id" | "click_user_id"
+        | "click_time" | "conversion_click_id" | "conversion_user_id" | "conversion_type" | "conversion_time"
+        | "conversion_revenue" => {
+            let funnel = generators::adtech::AdFunnel::new(i64_arg(&args, 0)?.max(0) as u64, &string_arg(&args, 1)?)?;
+            match name.as_str() {
+                "impression_campaign_id" => funnel.impression_campaign_id(),
+                "impression_ad_id" => funnel.impression_ad_id(),
+                "impression_user_id" => funnel.impression_user_id(),
+                "impression_placement" => funnel.impression_placement(),
+                "impression_time" => funnel.impression_time(),
+                "impression_cost" => funnel.impression_cost(),
+                "click_impression_id" => funnel.click_impression_id(),
+                "click_ad_id" => funnel.click_ad_id(),
+                "click_user_id" => funnel.click_user_id(),
+                "click_time" => funnel.click_time(),
+                "conversion_click_id" => funnel.conversion_click_id(),
+                "conversion_user_id" => funnel.conversion_user_id(),
+                "conversion_type" => funnel.conversion_type(),
+                "conversion_time" => funnel.conversion_time(),
+                _ => funnel.conversion_revenue(),
+            }
+        }
+        "survey_screened_in" | "survey_question" => {
+            let questionnaire = generators::survey::Questionnaire::new(
+                i64_arg(&args, 0)?.max(0) as u64,
+                i64_arg(&args, 1)?.max(0) as u64,
+            )?;
+            match name.as_str() {
+                "survey_screened_in" => questionnaire.screened_in(),
+                _ => questionnaire.question(i64_arg(&args, 2)?.max(0) as u64),
+            }
+        }
+        "cell_id" => generators::telecom::tower_cell_id(),
+        "cell_lac" => generators::telecom::tower_lac(),
+        "cell_latitude" => generators::telecom::tower_latitude(),
+        "cell_longitude" => generators::telecom::tower_longitude(),
+        "caller" | "callee" | "call_type" | "call_start" | "call_end" | "call_duration" | "call_cell_id"
+        | "call_lac" | "call_end_cell_id" => {
+            let detail = generators::telecom::CallDetail::new(
+                i64_arg(&args, 0)?.max(0) as u64,
+                i64_arg(&args, 1)?.max(0) as u64,
+            )?;
+            match name.as_str() {
+                "caller" => detail.caller(),
+                "callee" => detail.callee(),
+                "call_type" => detail.call_type(),
+                "call_start" => detail.start_time(),
+                "call_end" => detail.end_time(),
+                "call_duration" => detail.duration(),
+                "call_cell_id" => detail.cell_id(),
+                "call_lac" => detail.lac(),
+                _ => detail.end_cell_id(),
+            }
+        }
+        #[cfg(feature = "healthcare")]
+        "administrative_gender" => generators::healthcare::administrative_gender(),
+        #[cfg(feature = "healthcare")]
+        "medical_record_number" => generators::healthcare::medical_record_number(),
+        #[cfg(feature = "healthcare")]
+        "loinc_code" => generators::healthcare::loinc_code(),
+        #[cfg(feature = "healthcare")]
+        "icd10_code" => generators::healthcare::icd10_code(),
+        #[cfg(feature = "healthcare")]
+        "encounter_class" => generators::healthcare::encounter_class(),
+        #[cfg(feature = "healthcare")]
+        "observation_value" => generators::healthcare::observation_value(string_arg(&args, 0)?),
+        #[cfg(feature = "healthcare")]
+        "observation_unit" => generators::healthcare::observation_unit(string_arg(&args, 0)?),
+        _ => return Err(Error::from(ConfigError::UnknownGenerator { name })),
+    };
+
+    // Preset generators keep the spec they were made from, built in ones name their arguments in full.
+    Ok(match generator.spec() {
+        Some(_) => generator,
+        None => generator.with_spec(spec.clone()),
+    })
+}
+
+
+fn corruption_from_config(config: &Value) -> Result<CorruptionProfile> {
+    let probability = |name: &str| -> Result<f64> {
+        match config.get(name) {
+            Some(x) => x.as_f64().ok_or_else(|| invalid(name, "expected a probability")),
+            None => Ok(0.0),
+        }
+    };
+
+    Ok(CorruptionProfile::new()
+        .with_truncated_rows(probability("truncated_rows")?)
+        .with_wrong_delimiter_count(probability("wrong_delimiter_count")?)
+        .with_invalid_dates(probability("invalid_dates")?)
+        .with_non_numeric(probability("non_numeric")?)
+        .with_duplicate_keys(probability("duplicate_keys")?))
+}
+
+
+fn numeric_stress_from_config(config: &Value) -> Result<NumericStress> {
+    let probability = |name: &str| -> Result<f64> {
+        match config.get(name) {
+            Some(x) => x.as_f64().ok_or_else(|| invalid(name, "expected a probability")),
+            None => Ok(0.0),
+        }
+    };
+    let separator = |name: &str| -> Result<Option<char>> {
+        match config.get(name) {
+            None => Ok(None),
+            Some(x) => {
+                let mut chars = x.as_str().unwrap_or_default().chars();
+                match (chars.next(), chars.next()) {
+                    (Some(x), None) => Ok(Some(x)),
+                    _ => Err(invalid(name, "expected a single character")),
+                }
+            }
+        }
+    };
+
+    Ok(NumericStress::new()
+        .with_separators(
+            probability("separators")?,
+            separator("decimal_separator")?.unwrap_or(','),
+            separator("thousands_separator")?,
+        )
+        .with_plus_sign(probability("plus_sign")?)
+        .with_spaces(probability("spaces")?)
+        .with_quotes(probability("quotes")?))
+}
+
+
+fn annotations_from_config(config: &Value) -> Result<Annotations> {
+    let mut annotations = Annotations::new();
+
+    if config.get("description").is_some() {
+        annotations = annotations.with_description(string(config, "description")?);
+    }
+    if config.get("owner").is_some() {
+        annotations = annotations.with_owner(string(config, "owner")?);
+    }
+    if let Some(tags) = config.get("tags") {
+        for tag in tags.as_array().ok_or_else(|| invalid("tags", "expected an array"))? {
+            annotations = annotations.with_tag(tag.as_str().ok_or_else(|| invalid("tags", "expected strings"))?);
+        }
+    }
+    if config.get("pii").is_some() {
+        annotations = annotations.with_pii(string(config, "pii")?.parse()?);
+    }
+    if let Some(properties) = config.get("properties") {
+        for (key, value) in properties.as_object().ok_or_else(|| invalid("properties", "expected an object"))? {
+            let value = value.as_str().ok_or_else(|| invalid("properties", "expected string values"))?;
+            annotations = annotations.with_property(key, value);
+        }
+    }
+
+    Ok(annotations)
+}
+
+
+// Either a format name or an object like `{"name": "fixed_width", "padding": "0", "alignment": "right"}` or
+// `{"name": "key_value", "separator": ":"}`.
+fn output_format_from_config(config: &Value) -> Result<OutputFormat> {
+    if let Some(name) = config.as_str() {
+        return name.parse();
+    }
+
+    match string(config, "name")?.parse()? {
+        OutputFormat::FixedWidth { mut padding, mut alignment } => {
+            if config.get("padding").is_some() {
+                let padding_string = string(config, "padding")?;
+                let mut chars = padding_string.chars();
+                padding = match (chars.next(), chars.next()) {
+                    (Some(x), None) => x,
+                    _ => return Err(invalid("output_format.padding", "expected a single character")),
+                };
+            }
+            if config.get("alignment").is_some() {
+                alignment = string(config, "alignment")?.parse::<Alignment>()?;
+            }
+            Ok(OutputFormat::FixedWidth { padding, alignment })
+        }
+        OutputFormat::KeyValue { mut separator } => {
+            if config.get("separator").is_some() {
+                let separator_string = string(config, "separator")?;
+                let mut chars = separator_string.chars();
+                separator = match (chars.next(), chars.next()) {
+                    (Some(x), None) => x,
+                    _ => return Err(invalid("output_format.separator", "expected a single character")),
+                };
+            }
+            Ok(OutputFormat::KeyValue { separator })
+        }
+        x => Ok(x),
+    }
+}
+
+
+// A number of characters or `{"min": ..., "max": ..., "avg": ...}`, the average defaults to the midpoint.
+fn column_size_from_config(size: &Value) -> Result<ColumnSize> {
+    if let Some(size) = size.as_u64() {
+        return Ok(ColumnSize::Fixed(size));
+    }
+
+    let range = ColumnSize::range(u64_field(size, "min")?, u64_field(size, "max")?);
+    if size.get("avg").is_none() {
+        return Ok(range);
+    }
+
+    let (min, max) = (range.min(), range.max());
+    Ok(ColumnSize::Range { min, max, avg: u64_field(size, "avg")?.clamp(min, max) })
+}
+
+
+// `{"manifest": ..., "table": ..., "column": ...}` or `{"extract": ..., "delimiter": ..., "column": ...}`.
+fn external_source_from_config(config: &Value) -> Result<ExternalSource> {
+    if config.get("extract").is_some() {
+        return Ok(ExternalSource::Extract {
+            path: PathBuf::from(string(config, "extract")?),
+            delimiter: config.get("delimiter").and_then(|x| x.as_str()).unwrap_or(",").to_string(),
+            column: string(config, "column")?,
+        });
+    }
+
+    Ok(ExternalSource::Export {
+        manifest: PathBuf::from(string(config, "manifest")?),
+        table: string(config, "table")?,
+        column: string(config, "column")?,
+    })
+}
+
+
+// A transform name or `{"prefix": ...}` and `{"suffix": ...}`.
+fn transform_from_config(config: &Value) -> Result<Transform> {
+    match config {
+        Value::String(x) => x.parse::<Transform>(),
+        x if x.get("prefix").is_some() => Ok(Transform::Prefix(string(x, "prefix")?)),
+        x if x.get("suffix").is_some() => Ok(Transform::Suffix(string(x, "suffix")?)),
+        _ => Err(invalid("transforms", "expected a transform name, a prefix or a suffix")),
+    }
+}
+
+
+pub(crate) fn column_from_config(config: &Value) -> Result<Column> {
+    // Columns without a generator or a size take the defaults of their type.
+    let column_type = ColumnType::from(string(config, "type")?.as_str());
+    let (generator, size) = generators::for_type(&column_type);
+    let mut column = Column::new(
+        string(config, "name")?,
+        match config.get("size") {
+            Some(x) => column_size_from_config(x)?,
+            None => size,
+        },
+        column_type,
+        match config.get("generator") {
+            Some(x) => generator_from_spec(x)?,
+            None => generator,
+        },
+    );
+
+    if config.get("key").and_then(|x| x.as_bool()).unwrap_or(false) {
+        column = column.as_key();
+    }
+    if !config.get("dictionary").and_then(|x| x.as_bool()).unwrap_or(true) {
+        column = column.without_dictionary();
+    }
+    if let Some(default) = config.get("default") {
+        column = column.with_default(match default {
+            Value::String(x) => x.clone(),
+            x => x.to_string(),
+        });
+    }
+    if config.get("unique").and_then(|x| x.as_bool()).unwrap_or(false) {
+        column = column.unique(true);
+    }
+    if config.get("not_null").and_then(|x| x.as_bool()).unwrap_or(false) {
+        column = column.as_not_null();
+    }
+    if config.get("identity").and_then(|x| x.as_bool()).unwrap_or(false) {
+        column = column.as_identity();
+    }
+    if config.get("check").is_some() {
+        column = column.with_check(string(config, "check")?);
+    }
+    if let Some(null_rate) = config.get("null_rate") {
+        column = column.with_null_rate(null_rate.as_f64().ok_or_else(|| invalid("null_rate", "expected a number"))?);
+    }
+    for transform in config.get("transforms").and_then(|x| x.as_array()).into_iter().flatten() {
+        column = column.with_transform(transform_from_config(transform)?);
+    }
+    if let Some(external) = config.get("external") {
+        column = column.draws_from(&external_source_from_config(external)?)?;
+    }
+    if let Some(reference) = config.get("references") {
+        column = column.references(string(reference, "table")?, string(reference, "column")?);
+    }
+
+    Ok(column.with_annotations(annotations_from_config(config)?))
+}
+
+
+fn preset_columns(preset: &str) -> Result<Vec<Column>> {
+    match preset {
+        x if crate::finance::PRESETS.contains(&x) => crate::finance::preset_columns(x),
+        x if crate::telecom::PRESETS.contains(&x) => crate::telecom::preset_columns(x),
+        x if crate::retail::PRESETS.contains(&x) => crate::retail::preset_columns(x),
+        x if crate::iot::PRESETS.contains(&x) => crate::iot::preset_columns(x),
+        x if crate::adtech::PRESETS.contains(&x) => crate::adtech::preset_columns(x),
+        x if crate::survey::PRESETS.contains(&x) => crate::survey::preset_columns(x),
+        #[cfg(feature = "healthcare")]
+        x if crate::healthcare::PRESETS.contains(&x) => crate::healthcare::preset_columns(x),
+        _ => Err(invalid("preset", "unknown preset")),
+    }
+}
+
+
+pub(crate) fn table_from_config(config: &Value) -> Result<Table> {
+    // Saved definitions write the share as a string, so it is read back exactly.
+    let percent_size = match field(config, "percent_size")? {
+        Value::String(x) => Decimal::from_str(x),
+        x => Decimal::from_str(&x.to_string()),
+    }
+        .map_err(|_| invalid("percent_size", "expected a number"))?;
+
+    // A preset names the table and brings its columns, e.g. `{"preset": "patient", "percent_size": 0.2}`.
+    let preset = match config.get("preset") {
+        Some(_) => Some(string(config, "preset")?),
+        None => None,
+    };
+    let id = match (&preset, config.get("id")) {
+        (Some(preset), None) => preset.clone(),
+        _ => string(config, "id")?,
+    };
+
+    let mut builder = Table::builder(id)
+        .percent_size(percent_size);
+
+    if let Some(delimiter) = config.get("delimiter") {
+        builder = builder.delimiter(delimiter.as_str().ok_or_else(|| invalid("delimiter", "expected a string"))?);
+    }
+    if config.get("terminator").is_some() {
+        builder = builder.terminator(RecordTerminator::from(string(config, "terminator")?.as_str()));
+    }
+    if let Some(output_format) = config.get("output_format") {
+        builder = builder.output_format(output_format_from_config(output_format)?);
+    }
+
+    builder = builder.annotations(annotations_from_config(config)?);
+    if let Some(corruption) = config.get("corruption") {
+        builder = builder.corruption(corruption_from_config(corruption)?);
+    }
+    if let Some(numeric_stress) = config.get("numeric_stress") {
+        builder = builder.numeric_stress(numeric_stress_from_config(numeric_stress)?);
+    }
+    if let Some(event_time) = config.get("event_time") {
+        let number = |name: &str, default: f64| match event_time.get(name) {
+            Some(x) => x.as_f64().ok_or_else(|| invalid(name, "expected a number")),
+            None => Ok(default),
+        };
+        let clock = EventClock::new(&string(event_time, "start")?, number("rate", 1.0)?)?
+            .with_jitter(number("jitter", 0.0)?);
+        builder = builder.event_time(string(event_time, "column")?, clock);
+    }
+    if let Some(scd2) = config.get("scd2") {
+        let mut history = Scd2::new(
+            string(scd2, "key")?,
+            string(scd2, "valid_from")?,
+            string(scd2, "valid_to")?,
+            string(scd2, "current")?,
+        );
+        if scd2.get("versions").is_some() {
+            history = history.with_versions(u64_field(scd2, "versions")?);
+        }
+        if scd2.get("start").is_some() {
+            history = history.with_start(&string(scd2, "start")?)?;
+        }
+        if scd2.get("max_days").is_some() {
+            history = history.with_max_days(u64_field(scd2, "max_days")?);
+        }
+        builder = builder.scd2(history);
+    }
+    if config.get("qualifier").is_some() {
+        builder = builder.qualifier(string(config, "qualifier")?);
+    }
+    if let Some(row_group) = preset.as_deref().map(crate::finance::preset_row_group) {
+        builder = builder.row_group(row_group);
+    }
+    if config.get("row_group").is_some() {
+        builder = builder.row_group(u64_field(config, "row_group")?);
+    }
+
+    for columns in config.get("unique").and_then(|x| x.as_array()).into_iter().flatten() {
+        let columns = columns.as_array()
+            .and_then(|x| x.iter().map(|y| y.as_str()).collect::<Option<Vec<&str>>>())
+            .ok_or_else(|| invalid("unique", "expected arrays of column names"))?;
+        builder = builder.unique(&columns);
+    }
+
+    for column in preset.as_deref().map(preset_columns).transpose()?.into_iter().flatten() {
+        builder = builder.add_column(column);
+    }
+    let columns = match (&preset, config.get("columns")) {
+        (Some(_), None) => vec![],
+        _ => field(config, "columns")?.as_array().ok_or_else(|| invalid("columns", "expected an array"))?.clone(),
+    };
+    for column in columns.iter() {
+        builder = builder.add_column(column_from_config(column)?);
+    }
+
+    builder.build()
+}
+
+
+pub fn compression_from_str(compression: &str) -> Result<Compression> {
+    match compression.to_ascii_lowercase().as_str() {
+        "none" => Ok(Compression::None),
+        "gzip" | "gz" => Ok(Compression::Gzip),
+        "zstd" | "zst" => Ok(Compression::Zstd { level: 3 }),
+        _ => Err(invalid("compression", "expected none, gzip or zstd")),
+    }
+}
+
+
+impl ExportFileBuilder {
+    /// Builder with every setting from a JSON config, flags can still override them before `build`.
+    pub fn from_json(config: &str) -> Result<ExportFileBuilder> {
+        ExportFileBuilder::from_config(&serde_json::from_str(config)?)
+    }
+
+    /// Builder with every setting from a parsed JSON config, see [`ExportFileBuilder::from_json`].
+    pub fn from_config(config: &Value) -> Result<ExportFileBuilder> {
+        let mut builder = ExportFile::builder();
+
+        for table in field(config, "tables")?.as_array().ok_or_else(|| invalid("tables", "expected an array"))? {
+            builder = builder.add_table(table_from_config(table)?);
+            if let Some(row_ratio) = table.get("row_ratio") {
+                let ratio = field(row_ratio, "ratio")?.as_f64().ok_or_else(|| invalid("ratio", "expected a number"))?;
+                builder = builder.row_ratio(string(table, "id")?, string(row_ratio, "of")?, ratio);
+            }
+            if let Some(affinity) = table.get("affinity") {
+                let files = affinity.as_array()
+                    .ok_or_else(|| invalid("affinity", "expected an array of files"))?
+                    .iter()
+                    .map(|x| x.as_u64().ok_or_else(|| invalid("affinity", "expected file indices")))
+                    .collect::<Result<Vec<u64>>>()?;
+                builder = builder.affinity(string(table, "id")?, files);
+            }
+        }
+        if let Some(normalize) = config.get("normalize_percent_size") {
+            builder = builder.normalize_percent_size(
+                normalize.as_bool().ok_or_else(|| invalid("normalize_percent_size", "expected a boolean"))?
+            );
+        }
+        if let Some(scale_factor) = config.get("scale_factor") {
+            builder = builder.scale_factor(
+                scale_factor.as_f64().ok_or_else(|| invalid("scale_factor", "expected a number"))?
+            );
+        }
+        if let Some(size) = config.get("size") {
+            builder = builder.target_bytes(match size {
+                Value::String(x) => parse_size(x)?,
+                x => x.as_u64().ok_or_else(|| invalid("size", "expected a size"))?,
+            });
+        }
+        if config.get("files").is_some() {
+            builder = builder.files(u64_field(config, "files")?);
+        }
+        if let Some(rows) = config.get("rows") {
+            let rows = rows.as_object()
+                .ok_or_else(|| invalid("rows", "expected an object of tables"))?
+                .iter()
+                .map(|(table, rows)| {
+                    Ok((table.clone(), rows.as_u64().ok_or_else(|| invalid("rows", "expected row counts"))?))
+                })
+                .collect::<Result<HashMap<String, u64>>>()?;
+            builder = builder.size_spec(SizeSpec::Rows(rows));
+        }
+        if config.get("seed").is_some() {
+            builder = builder.seed(u64_field(config, "seed")?);
+        }
+        // Keys of a saved definition keep the namespace they were generated with, null leaves them bare.
+        if let Some(namespace) = config.get("namespace") {
+            builder = builder.namespace(match namespace {
+                Value::Null => None,
+                _ => Some(string(config, "namespace")?),
+            });
+        }
+        if config.get("compression").is_some() {
+            builder = builder.compression(compression_from_str(&string(config, "compression")?)?);
+        }
+        if config.get("file_name").is_some() {
+            builder = builder.file_name_template(string(config, "file_name")?.parse()?);
+        }
+        if config.get("max_rows_per_file").is_some() {
+            builder = builder.max_rows_per_file(u64_field(config, "max_rows_per_file")?);
+        }
+        if let Some(chunk_size) = config.get("chunk_size") {
+            builder = builder.chunk_bytes(match chunk_size {
+                Value::String(x) => parse_size(x)?,
+                x => x.as_u64().ok_or_else(|| invalid("chunk_size", "expected a size"))?,
+            });
+        }
+        if config.get("throttle").is_some() {
+            builder = builder.throttle(string(config, "throttle")?.parse()?);
+        }
+        if config.get("archive").is_some() {
+            builder = builder.archive(string(config, "archive")?.parse()?);
+        }
+        if let Some(checkpoint) = config.get("checkpoint") {
+            builder = builder.checkpoint(checkpoint.as_bool().ok_or_else(|| invalid("checkpoint", "expected a boolean"))?);
+        }
+        if let Some(manifest) = config.get("manifest") {
+            builder = builder.manifest(manifest.as_bool().ok_or_else(|| invalid("manifest", "expected a boolean"))?);
+        }
+        if let Some(report) = config.get("report") {
+            builder = builder.report(report.as_bool().ok_or_else(|| invalid("report", "expected a boolean"))?);
+        }
+        if config.get("control_batch").is_some() {
+            builder = builder.control_table(string(config, "control_batch")?);
+        }
+        if let Some(measured_sizing) = config.get("measured_sizing") {
+            builder = builder.measured_sizing(
+                measured_sizing.as_bool().ok_or_else(|| invalid("measured_sizing", "expected a boolean"))?
+            );
+        }
+        if let Some(identifier_quoting) = config.get("identifier_quoting") {
+            let dialects = identifier_quoting.as_object()
+                .ok_or_else(|| invalid("identifier_quoting", "expected an object of dialects"))?;
+            for (dialect, quoting) in dialects {
+                let quoting = quoting.as_str().ok_or_else(|| invalid("identifier_quoting", "expected a string"))?;
+                builder = builder.identifier_quoting(dialect.parse()?, quoting.parse()?);
+            }
+        }
+        if let Some(constraints_ddl) = config.get("constraints_ddl") {
+            builder = builder.constraints_ddl(
+                constraints_ddl.as_bool().ok_or_else(|| invalid("constraints_ddl", "expected a boolean"))?
+            );
+        }
+        if let Some(disabled) = config.get("disable") {
+            for target in disabled.as_array().ok_or_else(|| invalid("disable", "expected an array"))? {
+                builder = builder.disable(target.as_str().ok_or_else(|| invalid("disable", "expected strings"))?);
+            }
+        }
+        if config.get("placeholder").is_some() {
+            builder = builder.placeholder(Placeholder::Constant(string(config, "placeholder")?));
+        }
+        if let Some(views) = config.get("views") {
+            for view in views.as_array().ok_or_else(|| invalid("views", "expected an array"))? {
+                builder = builder.view(string(view, "name")?, string(view, "query")?);
+            }
+        }
+        if let Some(type_names) = config.get("type_names") {
+            let dialects = type_names.as_object()
+                .ok_or_else(|| invalid("type_names", "expected an object of dialects"))?;
+            for (dialect, names) in dialects {
+                let names = names.as_object().ok_or_else(|| invalid("type_names", "expected an object of types"))?;
+                for (column_type, type_name) in names {
+                    let type_name = type_name.as_str().ok_or_else(|| invalid("type_names", "expected a string"))?;
+                    builder = builder.type_name(dialect.parse()?, column_type, type_name);
+                }
+            }
+        }
+        if config.get("qualifier").is_some() {
+            builder = builder.qualifier(string(config, "qualifier")?);
+        }
+        if config.get("encoding").is_some() {
+            builder = builder.encoding(string(config, "encoding")?.parse()?);
+        }
+        if config.get("interleave").is_some() {
+            builder = builder.interleave(string(config, "interleave")?.parse()?);
+        }
+        if config.get("delimiter").is_some() {
+            builder = builder.delimiter(string(config, "delimiter")?);
+        }
+        if config.get("terminator").is_some() {
+            builder = builder.terminator(RecordTerminator::from(string(config, "terminator")?.as_str()));
+        }
+        if let Some(output_format) = config.get("output_format") {
+            builder = builder.output_format(output_format_from_config(output_format)?);
+        }
+        if config.get("null_representation").is_some() {
+            builder = builder.null_representation(match string(config, "null_representation")?.as_str() {
+                "" => NullRepresentation::Empty,
+                "\\N" => NullRepresentation::BackslashN,
+                "NULL" => NullRepresentation::Null,
+                x => NullRepresentation::Custom(x.into()),
+            });
+        }
+
+        Ok(builder)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddl::Dialect;
+
+    const CONFIG: &str = r#"{
+        "size": "1KB",
+        "files": 2,
+        "seed": 42,
+        "qualifier": "analytics.staging",
+        "identifier_quoting": {"postgres": "always"},
+        "type_names": {"sqlserver": {"double": "FLOAT(53)"}},
+        "constraints_ddl": true,
+        "views": [{"name": "analytics.large_orders", "query": "SELECT * FROM sales.orders WHERE amount > 100"}],
+        "tables": [
+            {
+                "id": "customers",
+                "percent_size": 0.5,
+                "output_format": {"name": "fixed_width", "padding": "0", "alignment": "right"},
+                "columns": [
+                    {"name": "id", "type": "INT", "size": 4, "generator": {"name": "sequence", "args": [1, 1]}, "identity": true},
+                    {"name": "segment", "type": "CHAR(1)", "size": 1, "generator": {"name": "one_of", "args": ["A", "B"]},
+                     "description": "Customer segment", "tags": ["marketing"], "pii": "indirect", "default": "'A'",
+                     "transforms": ["lower"]}
+                ]
+            },
+            {
+                "id": "orders",
+                "percent_size": 0.5,
+                "delimiter": ",",
+                "terminator": "crlf",
+                "qualifier": "sales",
+                "unique": [["customer_id", "amount"]],
+                "row_ratio": {"of": "customers", "ratio": 2},
+                "numeric_stress": {"separators": 0.2, "thousands_separator": ".", "quotes": 0.1},
+                "event_time": {"column": "placed_at", "start": "2024-01-01 08:00:00", "rate": 10, "jitter": 0.5},
+                "columns": [
+                    {"name": "customer_id", "type": "INT", "size": 4, "generator": "sequence", "references": {"table": "customers", "column": "id"}},
+                    {"name": "amount", "type": "DOUBLE", "size": 8, "generator": {"name": "exponential", "args": [0.1]}, "null_rate": 0.1, "check": "amount >= 0"},
+                    {"name": "note", "type": "VARCHAR(10)", "size": {"min": 2, "max": 10, "avg": 4}, "generator": {"name": "random_string", "args": [2, 10]},
+                     "unique": true},
+                    {"name": "placed_at", "type": "TIMESTAMP", "size": 26, "generator": {"name": "constant", "args": [""]}},
+                    {"name": "shipped_on", "type": "DATE"}
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn parse_size_test() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("10GB").unwrap(), 10 * (1 << 30));
+        assert_eq!(parse_size("1.5 KiB").unwrap(), 1536);
+        assert!(parse_size("10XB").is_err());
+        assert!(parse_size("70000000000000000000000000000TB").is_err());
+        assert!(parse_size("16777216TB").is_err());
+    }
+
+    #[test]
+    fn from_json_test() {
+        let ef = ExportFileBuilder::from_json(CONFIG).unwrap().build().unwrap();
+
+        assert_eq!(ef.number_of_files, 2);
+        assert_eq!(ef.file_size_bytes, 512);
+        assert_eq!(ef.tables[1].delimiter, ",");
+        assert_eq!(ef.tables[1].terminator, RecordTerminator::CrLf);
+        assert_eq!(ef.tables[0].qualified_name(), "analytics.staging.customers");
+        assert_eq!(ef.tables[1].qualified_name(), "sales.orders");
+        assert_eq!(ef.table_identifier(Dialect::Postgres, &ef.tables[1]), "\"sales\".\"orders\"");
+        assert_eq!(ef.table_identifier(Dialect::MySql, &ef.tables[1]), "sales.orders");
+        assert!(ef.get_schema_ddl(Dialect::SqlServer).unwrap().contains("    amount FLOAT(53),\n"));
+        assert!(ef.get_schema_ddl(Dialect::MySql).unwrap().ends_with(
+            "ALTER TABLE sales.orders ADD CONSTRAINT ck_orders_amount CHECK (amount >= 0);\n\
+             ALTER TABLE sales.orders ADD CONSTRAINT fk_orders_customer_id FOREIGN KEY (customer_id) \
+             REFERENCES analytics.staging.customers (id);\n\
+             \nCREATE VIEW analytics.large_orders AS SELECT * FROM sales.orders WHERE amount > 100;\n"
+        ));
+        assert!(ef.tables[1].columns[1].is_nullable());
+        assert!(ef.tables[0].columns[0].is_identity());
+        assert_eq!(ef.tables[0].columns[1].default.as_deref(), Some("'A'"));
+        assert_eq!(ef.tables[1].columns[2].size, ColumnSize::Range { min: 2, max: 10, avg: 4 });
+        assert_eq!(ef.tables[0].output_format, OutputFormat::FixedWidth { padding: '0', alignment: Alignment::Right });
+        assert_eq!(
+            ef.tables[0].columns[1].annotations,
+            Annotations::new()
+                .with_description("Customer segment")
+                .with_tag("marketing")
+                .with_pii(crate::types::Pii::Indirect)
+        );
+        assert_eq!(ef.table_total_rows(&ef.tables[1]).unwrap(), 2 * ef.table_total_rows(&ef.tables[0]).unwrap());
+        assert_eq!(
+            ef.tables[1].numeric_stress,
+            NumericStress::new().with_separators(0.2, ',', Some('.')).with_quotes(0.1)
+        );
+        assert!(numeric_stress_from_config(&serde_json::json!({"decimal_separator": ", "})).is_err());
+        assert_eq!(ef.tables[0].columns[1].transforms, vec![Transform::Lower]);
+        assert_eq!(
+            transform_from_config(&serde_json::json!({"suffix": "-x"})).unwrap(),
+            Transform::Suffix("-x".into())
+        );
+        assert!(transform_from_config(&serde_json::json!({"infix": "x"})).is_err());
+        assert_eq!(
+            external_source_from_config(&serde_json::json!({"extract": "dim.csv", "column": "code"})).unwrap(),
+            ExternalSource::Extract { path: PathBuf::from("dim.csv"), delimiter: ",".into(), column: "code".into() }
+        );
+        assert!(external_source_from_config(&serde_json::json!({"manifest": "export/manifest.json"})).is_err());
+        assert_eq!(ef.tables[1].unique_constraints(), vec![vec!["note"], vec!["customer_id", "amount"]]);
+        assert!(ef.tables[1].generate_rows_vec(1).unwrap()[0][4].starts_with("2024-01-01 08:00:00."));
+        assert_eq!(ef.tables[1].columns[4].size, ColumnSize::Fixed(10));
+        assert_eq!(ef.tables[1].generate_rows_vec(1).unwrap()[0][5].len(), 10);
+        assert!(ef.generate_export().is_ok());
+
+        assert!(ExportFileBuilder::from_json(r#"{"tables": [{"id": "A"}]}"#).is_err());
+        assert!(generator_from_spec(&serde_json::json!("unknown")).is_err());
+        assert!(generator_from_spec(&serde_json::json!({"name": "histogram", "args": [[0, 10, 100], [3, 1]]})).is_ok());
+        assert!(generator_from_spec(&serde_json::json!({"name": "histogram", "args": [[0, 10], "x"]})).is_err());
+    }
+}
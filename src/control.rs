@@ -0,0 +1,110 @@
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+
+use crate::ExportFile;
+use crate::file_name::civil_from_days;
+use crate::report::GenerationReport;
+
+pub const CONTROL_FILE: &str = "control.txt";
+/// Columns of a control row, in the order they are written.
+pub const CONTROL_COLUMNS: [&str; 7] = [
+    "batch_id", "file_name", "file_index", "table", "rows", "bytes", "generated_at",
+];
+
+pub(crate) fn control_name(suffix: &str) -> String {
+    format!("control{}.txt", suffix)
+}
+
+
+/// UTC time as `YYYY-MM-DD HH:MM:SS`.
+pub fn utc_timestamp(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map(|x| x.as_secs()).unwrap_or(0);
+    let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year, month, day, seconds % 86400 / 3600, seconds % 3600 / 60, seconds % 60
+    )
+}
+
+
+impl GenerationReport {
+    /// A row per table of every file the run wrote, see [`CONTROL_COLUMNS`].
+    pub fn control_rows(&self, batch_id: &str) -> Vec<Vec<String>> {
+        let generated_at = utc_timestamp(self.generated_at);
+
+        self.files.iter()
+            .flat_map(|file| file.tables.iter().map(move |table| (file, table)))
+            .map(|(file, table)| vec![
+                batch_id.to_string(),
+                file.path.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default(),
+                file.file_index.to_string(),
+                table.table.clone(),
+                table.rows.to_string(),
+                table.bytes.to_string(),
+                generated_at.clone(),
+            ])
+            .collect()
+    }
+}
+
+
+impl ExportFile {
+    /// Also writes a control file of batch `batch_id` next to the files once every file is written, as load
+    /// frameworks expecting a control record feed read it, with a row per file and table of the run. Rows are
+    /// delimited like the first table.
+    pub fn with_control_table(mut self, batch_id: impl Into<String>) -> Self {
+        self.control_batch = Some(batch_id.into());
+        self
+    }
+
+
+    pub(crate) fn write_control(&self, folder_path: &Path, name: &str, report: &GenerationReport) -> Result<()> {
+        let Some(batch_id) = &self.control_batch else {
+            return Ok(());
+        };
+        let delimiter = self.tables.first().map(|x| x.delimiter.as_str()).unwrap_or("|");
+        let data: String = report.control_rows(batch_id).iter()
+            .map(|x| x.join(delimiter) + "\n")
+            .collect();
+
+        Ok(fs::write(folder_path.join(name), data)?)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column, Table};
+    use crate::types::ColumnType;
+
+    #[test]
+    fn control_table_test() {
+        let table = |id: &str| Table::new(
+            id.into(),
+            vec![Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1))],
+            ";".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        let ef = ExportFile::new(vec![table("A"), table("B")], 1_000, 2).unwrap().with_control_table("batch_7");
+        let report = ef.generate_all_files_with_report(&folder).unwrap();
+        let control = fs::read_to_string(folder.join(CONTROL_FILE)).unwrap();
+        fs::remove_dir_all(&folder).unwrap();
+
+        let rows: Vec<Vec<&str>> = control.lines().map(|x| x.split(';').collect()).collect();
+        assert_eq!(rows.len(), 4);
+        assert!(rows.iter().all(|x| x.len() == CONTROL_COLUMNS.len() && x[0] == "batch_7"));
+        assert_eq!(rows[3][1..4], [ef.file_path(Path::new(""), 1).to_str().unwrap(), "1", "B"]);
+        assert_eq!(rows.iter().map(|x| x[4].parse::<u64>().unwrap()).sum::<u64>(), report.rows());
+        assert_eq!(utc_timestamp(UNIX_EPOCH + Duration::from_secs(951_827_696)), "2000-02-29 12:34:56");
+    }
+}
@@ -0,0 +1,367 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+#[cfg(any(feature = "parquet", feature = "avro"))]
+use anyhow::Error;
+use anyhow::Result;
+use thiserror::Error;
+
+use crate::{Column, ExportFile, Table};
+use crate::random::{mix, RandomSource, SeededRandom};
+use crate::types::ColumnType;
+
+// Row decisions get their own stream so they do not depend on the seed of any column.
+const CORRUPTION_STREAM: u64 = u64::MAX / 2;
+
+
+pub(crate) fn corruptions_name(suffix: &str) -> String {
+    format!("corruptions{}.csv", suffix)
+}
+
+
+#[derive(Error, Debug)]
+pub enum CorruptionError {
+    #[error("Row {row} of table {table} has {values} of its {columns} values, truncated rows can only be written as text.")]
+    TruncatedRow { table: String, row: usize, values: usize, columns: usize },
+}
+
+
+/// A way a row is made invalid on purpose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Corruption {
+    /// Trailing values are dropped.
+    TruncatedRow,
+    /// An extra empty value, so the row has one delimiter too many.
+    WrongDelimiterCount,
+    /// A date or timestamp column holds a date that does not exist.
+    InvalidDate,
+    /// A numeric column holds text.
+    NonNumeric,
+    /// Key columns repeat the keys of the previous row generated in the same call, e.g. of the same file.
+    DuplicateKey,
+}
+
+impl fmt::Display for Corruption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Corruption::TruncatedRow => write!(f, "truncated_row"),
+            Corruption::WrongDelimiterCount => write!(f, "wrong_delimiter_count"),
+            Corruption::InvalidDate => write!(f, "invalid_date"),
+            Corruption::NonNumeric => write!(f, "non_numeric"),
+            Corruption::DuplicateKey => write!(f, "duplicate_key"),
+        }
+    }
+}
+
+
+/// Probabilities of every corruption for each row of a table, the same rows are corrupted for a seed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CorruptionProfile {
+    pub truncated_rows: f64,
+    pub wrong_delimiter_count: f64,
+    pub invalid_dates: f64,
+    pub non_numeric: f64,
+    pub duplicate_keys: f64,
+}
+
+fn clamp_probability(probability: f64) -> f64 {
+    if probability.is_nan() { 0.0 } else { probability.clamp(0.0, 1.0) }
+}
+
+impl CorruptionProfile {
+    pub fn new() -> Self {
+        CorruptionProfile::default()
+    }
+
+    pub fn with_truncated_rows(mut self, probability: f64) -> Self {
+        self.truncated_rows = clamp_probability(probability);
+        self
+    }
+
+    pub fn with_wrong_delimiter_count(mut self, probability: f64) -> Self {
+        self.wrong_delimiter_count = clamp_probability(probability);
+        self
+    }
+
+    pub fn with_invalid_dates(mut self, probability: f64) -> Self {
+        self.invalid_dates = clamp_probability(probability);
+        self
+    }
+
+    pub fn with_non_numeric(mut self, probability: f64) -> Self {
+        self.non_numeric = clamp_probability(probability);
+        self
+    }
+
+    pub fn with_duplicate_keys(mut self, probability: f64) -> Self {
+        self.duplicate_keys = clamp_probability(probability);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        [self.truncated_rows, self.wrong_delimiter_count, self.invalid_dates, self.non_numeric, self.duplicate_keys]
+            .iter()
+            .all(|x| *x <= 0.0)
+    }
+}
+
+
+impl Table {
+    /// Corrupts rows with the profile's probabilities, rows are generated valid and then broken. Truncated rows and
+    /// wrong delimiter counts only make sense for text output.
+    pub fn with_corruption(mut self, corruption: CorruptionProfile) -> Self {
+        self.corruption = corruption;
+        self
+    }
+
+
+    fn corruption_random(&self, row_index: u64) -> SeededRandom {
+        SeededRandom::new(mix(mix(self.seed.unwrap_or(0), row_index), CORRUPTION_STREAM))
+    }
+
+
+    fn columns_where(&self, predicate: impl Fn(&Column) -> bool) -> Vec<usize> {
+        self.columns.iter().enumerate().filter(|(_, x)| predicate(x)).map(|(i, _)| i).collect()
+    }
+
+
+    // Every probability is drawn for every row, so which rows get a corruption does not depend on the others. The
+    // first row of a call has no previous row to duplicate the keys of.
+    fn corruptions_with(&self, random: &SeededRandom, first_row_index: u64, row_index: u64) -> Vec<Corruption> {
+        let profile = &self.corruption;
+        let temporal = self.columns.iter().any(|x| x.column_type.is_temporal());
+        let numeric = self.columns.iter().any(|x| x.column_type.is_numeric());
+        let applicable = [
+            (Corruption::InvalidDate, profile.invalid_dates, temporal),
+            (Corruption::NonNumeric, profile.non_numeric, numeric),
+            (
+                Corruption::DuplicateKey,
+                profile.duplicate_keys,
+                row_index > first_row_index && self.columns.iter().any(|x| x.key),
+            ),
+            (Corruption::WrongDelimiterCount, profile.wrong_delimiter_count, true),
+            (Corruption::TruncatedRow, profile.truncated_rows, !self.columns.is_empty()),
+        ];
+
+        applicable.into_iter()
+            .filter(|(_, probability, applies)| random.chance(*probability) && *applies)
+            .map(|(corruption, _, _)| corruption)
+            .collect()
+    }
+
+
+    /// Corruptions of the row at `row_index` generated in a call starting at `first_row_index`, empty for valid rows.
+    pub fn corruptions_at(&self, first_row_index: u64, row_index: u64) -> Vec<Corruption> {
+        if self.corruption.is_empty() {
+            return vec![];
+        }
+
+        self.corruptions_with(&self.corruption_random(row_index), first_row_index, row_index)
+    }
+
+
+    /// Whether rows have to be generated in order in one call, for regenerated unique constraints or duplicated keys.
+    pub(crate) fn is_sequential(&self) -> bool {
+        self.has_regenerated_unique() || self.corruption.duplicate_keys > 0.0
+    }
+
+
+    /// Copies the keys of `previous` into the row if it is corrupted with a duplicate key.
+    pub(crate) fn duplicate_key(&self, first_row_index: u64, row_index: u64, row: &mut [String], previous: &[String]) {
+        if !self.corruptions_at(first_row_index, row_index).contains(&Corruption::DuplicateKey) {
+            return;
+        }
+
+        for column in self.columns_where(|x| x.key) {
+            row[column + 1] = previous[column + 1].clone();
+        }
+    }
+
+
+    // In order, so a run of duplicated rows repeats the keys of the row before the run.
+    pub(crate) fn duplicate_keys(&self, first_row_index: u64, rows: &mut [Vec<String>]) {
+        if self.corruption.duplicate_keys <= 0.0 {
+            return;
+        }
+
+        for offset in 1..rows.len() {
+            let (before, after) = rows.split_at_mut(offset);
+            self.duplicate_key(first_row_index, first_row_index + offset as u64, &mut after[0], &before[offset - 1]);
+        }
+    }
+
+
+    /// Fails for a truncated row, `row` is its position among the rows written.
+    #[cfg(any(feature = "parquet", feature = "avro"))]
+    pub(crate) fn check_row_length(&self, row: usize, values: &[String]) -> Result<()> {
+        if values.len() > self.columns.len() {
+            return Ok(());
+        }
+
+        Err(Error::from(CorruptionError::TruncatedRow {
+            table: self.id_value.clone(),
+            row,
+            values: values.len().saturating_sub(1),
+            columns: self.columns.len(),
+        }))
+    }
+
+
+    // Keys were already duplicated by `duplicate_keys`, which needs the previous row.
+    pub(crate) fn corrupt_row(&self, first_row_index: u64, row_index: u64, row: &mut Vec<String>) -> Result<()> {
+        self.stress_row(row_index, row);
+        if self.corruption.is_empty() {
+            return Ok(());
+        }
+
+        let random = self.corruption_random(row_index);
+        let pick = |columns: Vec<usize>| columns[random.below(columns.len() as u64) as usize];
+
+        for corruption in self.corruptions_with(&random, first_row_index, row_index) {
+            match corruption {
+                Corruption::InvalidDate => {
+                    let column = pick(self.columns_where(|x| x.column_type.is_temporal()));
+                    row[column + 1] = match self.columns[column].column_type {
+                        ColumnType::Timestamp => "2023-02-30 25:61:00".into(),
+                        _ => "2023-02-30".into(),
+                    };
+                }
+                Corruption::NonNumeric => {
+                    row[pick(self.columns_where(|x| x.column_type.is_numeric())) + 1] = "N/A".into();
+                }
+                Corruption::DuplicateKey => {}
+                Corruption::WrongDelimiterCount => row.push("".into()),
+                Corruption::TruncatedRow => row.truncate(1 + random.below(self.columns.len() as u64) as usize),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+
+impl ExportFile {
+    // One line per corrupted row with its file, table, index among the table's rows in that file and corruptions.
+    pub(crate) fn write_corruptions(&self, folder_path: &Path, name: &str, file_indices: &[u64]) -> Result<()> {
+        let mut sidecar = String::from("file,table,row,corruptions\n");
+
+        for &file_index in file_indices {
+            for table in self.tables.iter().filter(|x| !x.corruption.is_empty()) {
+                let offset = self.table_row_offset(table, file_index)?;
+                for row in 0..self.table_row_count(table, file_index)? {
+                    let corruptions = table.corruptions_at(offset, offset + row);
+                    if corruptions.is_empty() {
+                        continue;
+                    }
+                    sidecar += &format!(
+                        "{},{},{},{}\n",
+                        file_index,
+                        table.id_value,
+                        row,
+                        corruptions.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(";"),
+                    );
+                }
+            }
+        }
+
+        fs::write(folder_path.join(name), sidecar)?;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::generators;
+
+    #[test]
+    fn corrupt_row_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).as_key(),
+                Column::new("day".into(), 10, ColumnType::Date, generators::constant("2024-01-01")),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        );
+
+        let corrupted = t.clone()
+            .with_corruption(CorruptionProfile::new().with_invalid_dates(1.0).with_duplicate_keys(1.0));
+        assert_eq!(corrupted.generate_rows(2).unwrap(), "A|1|2023-02-30\nA|1|2023-02-30\n");
+        assert_eq!(corrupted.corruptions_at(0, 0), vec![Corruption::InvalidDate]);
+        assert_eq!(corrupted.corruptions_at(0, 1), vec![Corruption::InvalidDate, Corruption::DuplicateKey]);
+        assert_eq!(corrupted.corruptions_at(1, 1), vec![Corruption::InvalidDate]);
+
+        let corrupted = t.clone().with_corruption(CorruptionProfile::new().with_wrong_delimiter_count(0.5));
+        let rows = corrupted.generate_rows_vec(100).unwrap();
+        for (i, row) in rows.iter().enumerate() {
+            let expected = if corrupted.corruptions_at(0, i as u64).is_empty() { 3 } else { 4 };
+            assert_eq!(row.len(), expected);
+        }
+        assert!(rows.iter().any(|x| x.len() == 3));
+
+        // Unseeded keys are random, a duplicate copies the previous row's key instead of generating another.
+        let random_key = Table::new(
+            "A".into(),
+            vec![Column::new("id".into(), 6, ColumnType::Int, generators::random_int(0, 999_999)).as_key()],
+            "|".into(),
+            Decimal::ONE,
+        )
+            .with_corruption(CorruptionProfile::new().with_duplicate_keys(1.0));
+        let rows = random_key.generate_rows_vec(50).unwrap();
+        assert!(rows.iter().all(|x| x[1] == rows[0][1]));
+        let rows: Vec<Vec<String>> = random_key.rows_from(10, 50).collect::<Result<_>>().unwrap();
+        assert!(rows.iter().all(|x| x[1] == rows[0][1]));
+        assert!(random_key.par_rows(100).is_err());
+
+        let truncated = t.with_corruption(CorruptionProfile::new().with_truncated_rows(0.5));
+        let rows = truncated.generate_rows_vec(20).unwrap();
+        assert!(rows.iter().any(|x| x.len() < 3));
+
+        // Rows of the parent that lost their key are left out of the keys its children sample.
+        let child = Table::new(
+            "B".into(),
+            vec![Column::new("a_id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).references("A", "id")],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+        let mut parent = truncated;
+        parent.percent_size = Decimal::from_str("0.5").unwrap();
+        let ef = ExportFile::new(vec![parent, child], 400, 1).unwrap().with_seed(2);
+        let rows = ef.generate_file_rows(0).unwrap();
+        let keys: Vec<&String> = rows[0].iter().filter_map(|x| x.get(1)).collect();
+        assert!(rows[1].iter().all(|x| keys.contains(&&x[1])));
+    }
+
+    #[test]
+    fn corruptions_sidecar_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1000, 1))],
+            "|".into(),
+            Decimal::ONE,
+        )
+            .with_corruption(CorruptionProfile::new().with_non_numeric(0.3));
+        let ef = ExportFile::new(vec![t], 700, 2).unwrap().with_seed(3);
+
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_all_files(&folder).unwrap();
+        let sidecar = fs::read_to_string(folder.join("corruptions.csv")).unwrap();
+        let second = fs::read_to_string(ef.file_path(&folder, 1)).unwrap();
+        fs::remove_dir_all(&folder).unwrap();
+
+        let listed: Vec<&str> = sidecar.lines().skip(1).filter(|x| x.starts_with("1,")).collect();
+        let invalid: Vec<String> = second.lines()
+            .enumerate()
+            .filter(|(_, x)| x.ends_with("N/A"))
+            .map(|(i, _)| format!("1,A,{},non_numeric", i))
+            .collect();
+        assert!(!invalid.is_empty());
+        assert_eq!(listed, invalid);
+    }
+}
@@ -0,0 +1,666 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::{ExportFile, Table};
+use crate::types::ColumnType;
+
+const RESERVED: &[&str] = &[
+    "ALL", "ALTER", "AND", "ANY", "AS", "ASC", "BETWEEN", "BY", "CASE", "CHECK", "COLUMN", "CONSTRAINT", "CREATE",
+    "CROSS", "CURRENT_DATE", "CURRENT_TIME", "CURRENT_TIMESTAMP", "CURRENT_USER", "DEFAULT", "DELETE", "DESC",
+    "DISTINCT", "DROP", "ELSE", "END", "EXISTS", "FALSE", "FETCH", "FOR", "FOREIGN", "FROM", "GRANT", "GROUP",
+    "HAVING", "IN", "INNER", "INSERT", "INTERSECT", "INTO", "IS", "JOIN", "LEFT", "LIKE", "NOT", "NULL", "ON", "OR",
+    "ORDER", "OUTER", "PRIMARY", "REFERENCES", "RIGHT", "SELECT", "SET", "TABLE", "THEN", "TO", "TRUE", "UNION",
+    "UNIQUE", "UPDATE", "USER", "USING", "VALUES", "WHEN", "WHERE", "WITH",
+];
+const POSTGRES_RESERVED: &[&str] = &[
+    "ANALYSE", "ANALYZE", "ARRAY", "ASYMMETRIC", "BOTH", "CAST", "COLLATE", "DEFERRABLE", "DO", "EXCEPT",
+    "INITIALLY", "LATERAL", "LEADING", "LIMIT", "LOCALTIME", "LOCALTIMESTAMP", "OFFSET", "ONLY", "PLACING",
+    "RETURNING", "SESSION_USER", "SOME", "SYMMETRIC", "TRAILING", "VARIADIC", "WINDOW",
+];
+const MYSQL_RESERVED: &[&str] = &[
+    "CONDITION", "DATABASE", "DESCRIBE", "DIV", "DUAL", "EXCEPT", "GROUPS", "INDEX", "INTERVAL", "KEY", "KEYS",
+    "LIMIT", "LOAD", "LOCK", "MOD", "RANGE", "RANK", "READ", "RENAME", "REPLACE", "ROW_NUMBER", "SCHEMA", "SHOW",
+    "WINDOW",
+];
+const ORACLE_RESERVED: &[&str] = &[
+    "ACCESS", "AUDIT", "CLUSTER", "COMMENT", "COMPRESS", "CONNECT", "DATE", "DECIMAL", "EXCLUSIVE", "FILE", "FLOAT",
+    "IDENTIFIED", "INCREMENT", "INDEX", "INITIAL", "INTEGER", "LEVEL", "LOCK", "LONG", "MINUS", "MODE", "MODIFY",
+    "NOWAIT", "NUMBER", "OFFLINE", "ONLINE", "OPTION", "PCTFREE", "PRIOR", "RAW", "RENAME", "RESOURCE", "ROW", "ROWID",
+    "ROWNUM", "ROWS", "SESSION", "SHARE", "SIZE", "SMALLINT", "START", "SYNONYM", "SYSDATE", "TRIGGER", "UID",
+    "VALIDATE", "VARCHAR", "VARCHAR2", "VIEW", "WHENEVER",
+];
+const SNOWFLAKE_RESERVED: &[&str] = &[
+    "CONNECT", "ILIKE", "INCREMENT", "LATERAL", "MINUS", "QUALIFY", "REGEXP", "RLIKE", "ROW", "ROWS", "SAMPLE", "SOME",
+    "START", "TABLESAMPLE", "TRY_CAST",
+];
+const SQL_SERVER_RESERVED: &[&str] = &[
+    "BACKUP", "BREAK", "BROWSE", "BULK", "CHECKPOINT", "CLUSTERED", "COMPUTE", "CONTAINS", "CONTINUE", "DATABASE",
+    "DBCC", "DENY", "DISK", "DUMP", "EXEC", "EXECUTE", "FILE", "FILLFACTOR", "GOTO", "HOLDLOCK", "IDENTITY",
+    "IDENTITY_INSERT", "INDEX", "KEY", "KILL", "LINENO", "LOAD", "MERGE", "NOCHECK", "NONCLUSTERED", "OFFSETS", "OPEN",
+    "OVER", "PERCENT", "PIVOT", "PLAN", "PRINT", "PROC", "PROCEDURE", "PUBLIC", "RAISERROR", "READ", "RECONFIGURE",
+    "RESTORE", "RETURN", "REVERT", "REVOKE", "ROWCOUNT", "RULE", "SAVE", "SCHEMA", "SHUTDOWN", "STATISTICS", "TOP",
+    "TRAN", "TRANSACTION", "TRIGGER", "TRUNCATE", "UNPIVOT", "USE", "VIEW", "WAITFOR", "WHILE",
+];
+
+
+#[derive(Error, Debug)]
+pub enum DdlError {
+    #[error("Unknown dialect {dialect}, expected postgres, mysql, oracle, snowflake or sqlserver.")]
+    UnknownDialect { dialect: String },
+    #[error("Unknown identifier quoting {quoting}, expected reserved, preserve_case or always.")]
+    UnknownQuoting { quoting: String },
+}
+
+
+/// When identifiers in generated SQL are quoted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IdentifierQuoting {
+    /// Reserved words and names that are not plain identifiers.
+    #[default]
+    Reserved,
+    /// Also names the dialect would fold to another case, so they keep their case in the database.
+    PreserveCase,
+    Always,
+}
+
+impl FromStr for IdentifierQuoting {
+    type Err = Error;
+
+    fn from_str(quoting: &str) -> Result<Self> {
+        match quoting.to_ascii_lowercase().replace('-', "_").as_str() {
+            "reserved" => Ok(IdentifierQuoting::Reserved),
+            "preserve_case" => Ok(IdentifierQuoting::PreserveCase),
+            "always" => Ok(IdentifierQuoting::Always),
+            _ => Err(Error::from(DdlError::UnknownQuoting { quoting: quoting.into() })),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    Postgres,
+    MySql,
+    Oracle,
+    Snowflake,
+    SqlServer,
+}
+
+impl Dialect {
+    pub fn type_name(&self, column_type: &ColumnType) -> String {
+        match (self, column_type) {
+            (Dialect::Postgres, ColumnType::Int) => "INTEGER".into(),
+            (Dialect::Postgres, ColumnType::Decimal(precision, scale)) => format!("NUMERIC({},{})", precision, scale),
+
+            (Dialect::MySql, ColumnType::Real) => "FLOAT".into(),
+            (Dialect::MySql, ColumnType::Double) => "DOUBLE".into(),
+            (Dialect::MySql, ColumnType::Timestamp) => "DATETIME".into(),
+
+            (Dialect::Oracle, ColumnType::Boolean) => "NUMBER(1)".into(),
+            (Dialect::Oracle, ColumnType::SmallInt) => "NUMBER(5)".into(),
+            (Dialect::Oracle, ColumnType::Int) => "NUMBER(10)".into(),
+            (Dialect::Oracle, ColumnType::BigInt) => "NUMBER(19)".into(),
+            (Dialect::Oracle, ColumnType::Real) => "BINARY_FLOAT".into(),
+            (Dialect::Oracle, ColumnType::Double) => "BINARY_DOUBLE".into(),
+            (Dialect::Oracle, ColumnType::Decimal(precision, scale)) => format!("NUMBER({},{})", precision, scale),
+            (Dialect::Oracle, ColumnType::Varchar(length)) => format!("VARCHAR2({})", length),
+            (Dialect::Oracle, ColumnType::Text) => "CLOB".into(),
+
+            (Dialect::Snowflake, ColumnType::Int) => "INTEGER".into(),
+            (Dialect::Snowflake, ColumnType::Real | ColumnType::Double) => "FLOAT".into(),
+            (Dialect::Snowflake, ColumnType::Decimal(precision, scale)) => format!("NUMBER({},{})", precision, scale),
+            (Dialect::Snowflake, ColumnType::Text) => "VARCHAR".into(),
+            (Dialect::Snowflake, ColumnType::Timestamp) => "TIMESTAMP_NTZ".into(),
+
+            (Dialect::SqlServer, ColumnType::Boolean) => "BIT".into(),
+            (Dialect::SqlServer, ColumnType::Double) => "FLOAT".into(),
+            (Dialect::SqlServer, ColumnType::Text) => "VARCHAR(MAX)".into(),
+            (Dialect::SqlServer, ColumnType::Timestamp) => "DATETIME2".into(),
+
+            (_, column_type) => column_type.to_string(),
+        }
+    }
+
+    pub fn identity_clause(&self) -> &'static str {
+        match self {
+            Dialect::Postgres | Dialect::Oracle => "GENERATED ALWAYS AS IDENTITY",
+            Dialect::MySql => "AUTO_INCREMENT PRIMARY KEY",
+            Dialect::Snowflake | Dialect::SqlServer => "IDENTITY(1,1)",
+        }
+    }
+
+    pub fn is_reserved(&self, name: &str) -> bool {
+        let name = name.to_ascii_uppercase();
+        let dialect = match self {
+            Dialect::Postgres => POSTGRES_RESERVED,
+            Dialect::MySql => MYSQL_RESERVED,
+            Dialect::Oracle => ORACLE_RESERVED,
+            Dialect::Snowflake => SNOWFLAKE_RESERVED,
+            Dialect::SqlServer => SQL_SERVER_RESERVED,
+        };
+
+        RESERVED.contains(&name.as_str()) || dialect.contains(&name.as_str())
+    }
+
+    // Unquoted names are folded to lower case by Postgres and to upper case by Oracle and Snowflake, MySQL and
+    // SQL Server keep them.
+    fn folds_case(&self, name: &str) -> bool {
+        match self {
+            Dialect::Postgres => name.chars().any(|x| x.is_ascii_uppercase()),
+            Dialect::MySql | Dialect::SqlServer => false,
+            Dialect::Oracle | Dialect::Snowflake => name.chars().any(|x| x.is_ascii_lowercase()),
+        }
+    }
+
+    pub fn quote_identifier(&self, name: &str, quoting: IdentifierQuoting) -> String {
+        let plain = name.starts_with(|x: char| x.is_ascii_alphabetic() || x == '_')
+            && name.chars().all(|x| x.is_ascii_alphanumeric() || x == '_');
+        let quote = match quoting {
+            IdentifierQuoting::Always => true,
+            IdentifierQuoting::PreserveCase if self.folds_case(name) => true,
+            _ => !plain || self.is_reserved(name),
+        };
+        if !quote {
+            return name.into();
+        }
+
+        let (open, close) = match self {
+            Dialect::MySql => ('`', '`'),
+            Dialect::SqlServer => ('[', ']'),
+            _ => ('"', '"'),
+        };
+        format!("{}{}{}", open, name.replace(close, &close.to_string().repeat(2)), close)
+    }
+}
+
+impl FromStr for Dialect {
+    type Err = Error;
+
+    fn from_str(dialect: &str) -> Result<Self> {
+        match dialect.to_ascii_lowercase().as_str() {
+            "postgres" | "postgresql" => Ok(Dialect::Postgres),
+            "mysql" => Ok(Dialect::MySql),
+            "oracle" => Ok(Dialect::Oracle),
+            "snowflake" => Ok(Dialect::Snowflake),
+            "sqlserver" | "sql_server" | "mssql" => Ok(Dialect::SqlServer),
+            _ => Err(Error::from(DdlError::UnknownDialect { dialect: dialect.into() })),
+        }
+    }
+}
+
+
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+
+// Key of a column type in user type names, the type without its length, precision or scale.
+fn type_key(column_type: &ColumnType) -> String {
+    match column_type {
+        ColumnType::Double => "DOUBLE".into(),
+        ColumnType::Decimal(..) => "DECIMAL".into(),
+        ColumnType::Char(_) => "CHAR".into(),
+        ColumnType::Varchar(_) => "VARCHAR".into(),
+        ColumnType::Custom(name) => name.to_uppercase(),
+        column_type => column_type.to_string(),
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct View {
+    pub name: String,
+    pub query: String,
+}
+
+
+/// User type names per dialect, keyed by column type without arguments, e.g. `VARCHAR` or `DECIMAL`. The
+/// names can use `{length}`, `{precision}` and `{scale}` placeholders.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeMapping {
+    names: HashMap<(Dialect, String), String>,
+}
+
+impl TypeMapping {
+    pub fn new() -> Self {
+        TypeMapping::default()
+    }
+
+    pub fn with_type_name(mut self, dialect: Dialect, column_type: &str, type_name: impl Into<String>) -> Self {
+        self.insert(dialect, column_type, type_name);
+        self
+    }
+
+    pub fn insert(&mut self, dialect: Dialect, column_type: &str, type_name: impl Into<String>) {
+        self.names.insert((dialect, column_type.trim().to_uppercase()), type_name.into());
+    }
+
+    /// The user type name if there is one, the dialect's otherwise.
+    pub fn type_name(&self, dialect: Dialect, column_type: &ColumnType) -> String {
+        let Some(name) = self.names.get(&(dialect, type_key(column_type))) else {
+            return dialect.type_name(column_type);
+        };
+
+        match column_type {
+            ColumnType::Decimal(precision, scale) => name
+                .replace("{precision}", &precision.to_string())
+                .replace("{scale}", &scale.to_string()),
+            ColumnType::Char(length) | ColumnType::Varchar(length) => name.replace("{length}", &length.to_string()),
+            _ => name.clone(),
+        }
+    }
+}
+
+
+// Whether MySQL can declare the table's identity column `AUTO_INCREMENT PRIMARY KEY`, when it is the key or the
+// table has no key.
+fn is_identity_key(table: &Table) -> bool {
+    let mut keys = table.columns.iter().filter(|x| x.key);
+    match (keys.next(), keys.next()) {
+        (None, _) => true,
+        (Some(key), None) => key.identity,
+        _ => false,
+    }
+}
+
+
+impl ExportFile {
+    /// Quoting of identifiers in the SQL generated for the dialect, the other dialects are not affected.
+    pub fn with_identifier_quoting(mut self, dialect: Dialect, quoting: IdentifierQuoting) -> Self {
+        self.identifier_quoting.insert(dialect, quoting);
+        self
+    }
+
+
+    /// Overrides the type names of the DDL, see [`TypeMapping`].
+    pub fn with_type_mapping(mut self, type_mapping: TypeMapping) -> Self {
+        self.type_mapping = type_mapping;
+        self
+    }
+
+
+    pub fn identifier(&self, dialect: Dialect, name: &str) -> String {
+        dialect.quote_identifier(name, self.identifier_quoting.get(&dialect).copied().unwrap_or_default())
+    }
+
+
+    pub fn table_identifier(&self, dialect: Dialect, table: &Table) -> String {
+        table.qualifiers.iter()
+            .chain(std::iter::once(&table.id_value))
+            .map(|x| self.identifier(dialect, x))
+            .collect::<Vec<String>>()
+            .join(".")
+    }
+
+
+    /// Identity columns are declared by the dialect's identity clause instead of their default. Annotations become
+    /// inline `COMMENT`s in MySQL, `MS_Description` extended properties in SQL Server and
+    /// `COMMENT ON` statements elsewhere.
+    pub fn get_schema_ddl(&self, dialect: Dialect) -> Result<String> {
+        self.build_schema()?;
+
+        let mut ddl = self.tables.iter()
+            .map(|table| {
+                let inline = dialect == Dialect::MySql;
+                let identity_key = inline && is_identity_key(table);
+                let name = self.table_identifier(dialect, table);
+                let mut ddl = format!(
+                    "CREATE TABLE {} (\n{}\n){};\n",
+                    name,
+                    table.columns.iter()
+                        .map(|x| format!(
+                            "    {} {}{}{}{}",
+                            self.identifier(dialect, &x.name),
+                            self.type_mapping.type_name(dialect, &x.column_type),
+                            match &x.default {
+                                // MySQL needs an index on an identity column that is not the primary key.
+                                _ if x.identity && inline && !identity_key => " AUTO_INCREMENT UNIQUE".into(),
+                                _ if x.identity => format!(" {}", dialect.identity_clause()),
+                                Some(default) => format!(" DEFAULT {}", default),
+                                None => "".into(),
+                            },
+                            if x.is_nullable() { "" } else { " NOT NULL" },
+                            match x.annotations.comment() {
+                                Some(comment) if inline => format!(" COMMENT {}", quote(&comment)),
+                                _ => "".into(),
+                            }
+                        ))
+                        .collect::<Vec<String>>()
+                        .join(",\n"),
+                    match table.annotations.comment() {
+                        Some(comment) if inline => format!(" COMMENT={}", quote(&comment)),
+                        _ => "".into(),
+                    }
+                );
+
+                if dialect == Dialect::SqlServer {
+                    let schema = table.qualifiers.last().map(|x| x.as_str()).unwrap_or("dbo");
+                    let property = |comment: String, column: Option<&str>| format!(
+                        "EXEC sp_addextendedproperty 'MS_Description', {}, 'SCHEMA', {}, 'TABLE', {}{};\n",
+                        quote(&comment),
+                        quote(schema),
+                        quote(&table.id_value),
+                        column.map(|x| format!(", 'COLUMN', {}", quote(x))).unwrap_or_default(),
+                    );
+
+                    if let Some(comment) = table.annotations.comment() {
+                        ddl += &property(comment, None);
+                    }
+                    for column in table.columns.iter() {
+                        if let Some(comment) = column.annotations.comment() {
+                            ddl += &property(comment, Some(&column.name));
+                        }
+                    }
+                } else if !inline {
+                    if let Some(comment) = table.annotations.comment() {
+                        ddl += &format!("COMMENT ON TABLE {} IS {};\n", name, quote(&comment));
+                    }
+                    for column in table.columns.iter() {
+                        if let Some(comment) = column.annotations.comment() {
+                            ddl += &format!(
+                                "COMMENT ON COLUMN {}.{} IS {};\n",
+                                name,
+                                self.identifier(dialect, &column.name),
+                                quote(&comment)
+                            );
+                        }
+                    }
+                }
+
+                ddl
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let constraints = if self.constraints_ddl { self.get_constraints_ddl(dialect) } else { "".into() };
+        for statements in [constraints, self.get_views_ddl(dialect)] {
+            if !statements.is_empty() {
+                ddl += "\n";
+                ddl += &statements;
+            }
+        }
+
+        Ok(ddl)
+    }
+
+
+    /// Also emits the constraints of [`ExportFile::get_constraints_ddl`] from [`ExportFile::get_schema_ddl`].
+    pub fn with_constraints_ddl(mut self, constraints_ddl: bool) -> Self {
+        self.constraints_ddl = constraints_ddl;
+        self
+    }
+
+
+    /// A view emitted after the tables, `query` is used as written.
+    pub fn with_view(mut self, name: impl Into<String>, query: impl Into<String>) -> Self {
+        self.views.push(View { name: name.into(), query: query.into() });
+        self
+    }
+
+
+    /// `ALTER TABLE` statements adding primary keys from key columns, unique and check constraints and foreign keys
+    /// from references, run after loading the data so rows do not have to be loaded in reference order.
+    pub fn get_constraints_ddl(&self, dialect: Dialect) -> String {
+        let mut ddl = String::new();
+
+        for table in self.tables.iter() {
+            let name = self.table_identifier(dialect, table);
+            let columns = |names: &[&str]| names.iter()
+                .map(|x| self.identifier(dialect, x))
+                .collect::<Vec<String>>()
+                .join(", ");
+            let mut add = |constraint: String, definition: String| {
+                let constraint = self.identifier(dialect, &constraint);
+                ddl += &format!("ALTER TABLE {} ADD CONSTRAINT {} {};\n", name, constraint, definition);
+            };
+
+            // A MySQL identity column that is the key is already declared the primary key.
+            let keys: Vec<&str> = table.columns.iter().filter(|x| x.key).map(|x| x.name.as_str()).collect();
+            let identity_key = dialect == Dialect::MySql && is_identity_key(table);
+            if !keys.is_empty() && !identity_key {
+                add(format!("pk_{}", table.id_value), format!("PRIMARY KEY ({})", columns(&keys)));
+            }
+            for (i, unique) in table.unique_constraints().iter().enumerate() {
+                add(format!("uq_{}_{}", table.id_value, i + 1), format!("UNIQUE ({})", columns(unique)));
+            }
+            for column in table.columns.iter() {
+                if let Some(check) = &column.check {
+                    add(format!("ck_{}_{}", table.id_value, column.name), format!("CHECK ({})", check));
+                }
+            }
+            for column in table.columns.iter() {
+                let Some(reference) = &column.reference else {
+                    continue;
+                };
+                let referenced = match self.tables.iter().find(|x| x.id_value == reference.table) {
+                    Some(x) => self.table_identifier(dialect, x),
+                    None => self.identifier(dialect, &reference.table),
+                };
+                add(
+                    format!("fk_{}_{}", table.id_value, column.name),
+                    format!(
+                        "FOREIGN KEY ({}) REFERENCES {} ({})",
+                        self.identifier(dialect, &column.name),
+                        referenced,
+                        self.identifier(dialect, &reference.column),
+                    ),
+                );
+            }
+        }
+
+        ddl
+    }
+
+
+    /// `CREATE VIEW` statements of the views added with [`ExportFile::with_view`].
+    pub fn get_views_ddl(&self, dialect: Dialect) -> String {
+        self.views.iter()
+            .map(|x| {
+                let name = x.name.split('.').map(|y| self.identifier(dialect, y)).collect::<Vec<String>>().join(".");
+                format!("CREATE VIEW {} AS {};\n", name, x.query.trim().trim_end_matches(';'))
+            })
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{Column, Table};
+    use crate::types::Annotations;
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    fn export_file() -> ExportFile {
+        let t1 = Table::new(
+            "A".into(),
+            vec![
+                Column::new("code".into(), 3, ColumnType::Char(3), simple_generator),
+                Column::new("amount".into(), 3, ColumnType::Decimal(10, 2), simple_generator),
+            ],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+        let t2 = Table::new(
+            "B".into(),
+            vec![Column::new("name".into(), 3, ColumnType::Varchar(20), simple_generator).with_null_rate(0.1)],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+
+        ExportFile::new(vec![t1, t2], 1024 * 1024, 1).unwrap()
+    }
+
+    #[test]
+    fn get_schema_ddl_test() {
+        let ef = export_file();
+
+        assert_eq!(
+            ef.get_schema_ddl(Dialect::Postgres).unwrap(),
+            "CREATE TABLE A (\n    code CHAR(3) NOT NULL,\n    amount NUMERIC(10,2) NOT NULL\n);\n\nCREATE TABLE B (\n    name VARCHAR(20)\n);\n"
+        );
+        assert!(ef.get_schema_ddl(Dialect::Oracle).unwrap().contains("name VARCHAR2(20)\n"));
+        assert!(ef.get_schema_ddl(Dialect::MySql).unwrap().contains("amount DECIMAL(10,2) NOT NULL"));
+
+        let qualified = ef.with_table_qualifier("A", "analytics.staging").unwrap();
+        assert!(qualified.get_schema_ddl(Dialect::Postgres).unwrap().starts_with("CREATE TABLE analytics.staging.A (\n"));
+        assert!(qualified.with_table_qualifier("C", "analytics").is_err());
+    }
+
+    #[test]
+    fn type_mapping_test() {
+        let ef = export_file();
+
+        assert!(ef.get_schema_ddl(Dialect::Snowflake).unwrap().contains("    amount NUMBER(10,2) NOT NULL\n"));
+        assert!(ef.get_schema_ddl(Dialect::SqlServer).unwrap().contains("    name VARCHAR(20)\n"));
+
+        let ef = ef.with_type_mapping(
+            TypeMapping::new()
+                .with_type_name(Dialect::SqlServer, "varchar", "NVARCHAR({length})")
+                .with_type_name(Dialect::Snowflake, "DECIMAL", "NUMBER({precision}, {scale})")
+        );
+        assert!(ef.get_schema_ddl(Dialect::SqlServer).unwrap().contains("    name NVARCHAR(20)\n"));
+        assert!(ef.get_schema_ddl(Dialect::Snowflake).unwrap().contains("    amount NUMBER(10, 2) NOT NULL\n"));
+        assert!(ef.get_schema_ddl(Dialect::Postgres).unwrap().contains("    name VARCHAR(20)\n"));
+        assert_eq!("mssql".parse::<Dialect>().unwrap(), Dialect::SqlServer);
+        assert_eq!(Dialect::SqlServer.quote_identifier("a]b", IdentifierQuoting::Reserved), "[a]]b]");
+    }
+
+    #[test]
+    fn identifier_quoting_test() {
+        let t = Table::new(
+            "Order".into(),
+            vec![
+                Column::new("select".into(), 3, ColumnType::Char(3), simple_generator),
+                Column::new("total amount".into(), 3, ColumnType::Int, simple_generator),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        );
+        let ef = ExportFile::new(vec![t], 1024, 1).unwrap();
+
+        assert_eq!(
+            ef.get_schema_ddl(Dialect::MySql).unwrap(),
+            "CREATE TABLE `Order` (\n    `select` CHAR(3) NOT NULL,\n    `total amount` INT NOT NULL\n);\n"
+        );
+        assert_eq!(Dialect::Oracle.quote_identifier("size", IdentifierQuoting::Reserved), "\"size\"");
+        assert_eq!(Dialect::Postgres.quote_identifier("a\"b", IdentifierQuoting::Reserved), "\"a\"\"b\"");
+
+        let ef = ef.with_identifier_quoting(Dialect::Postgres, IdentifierQuoting::PreserveCase);
+        assert!(ef.get_schema_ddl(Dialect::Postgres).unwrap().starts_with("CREATE TABLE \"Order\" (\n    \"select\" CHAR(3)"));
+        assert_eq!(ef.identifier(Dialect::Oracle, "code"), "code");
+        assert_eq!(Dialect::Oracle.quote_identifier("code", IdentifierQuoting::PreserveCase), "\"code\"");
+        assert_eq!(Dialect::MySql.quote_identifier("Code", IdentifierQuoting::Always), "`Code`");
+        assert!("sqlite".parse::<Dialect>().is_err());
+    }
+
+    #[test]
+    fn default_identity_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("id".into(), 4, ColumnType::Int, simple_generator).as_identity(),
+                Column::new("status".into(), 3, ColumnType::Char(3), simple_generator).with_default("'NEW'"),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        );
+        let ef = ExportFile::new(vec![t], 1024, 1).unwrap();
+
+        assert_eq!(
+            ef.get_schema_ddl(Dialect::Postgres).unwrap(),
+            "CREATE TABLE A (\n    id INTEGER GENERATED ALWAYS AS IDENTITY NOT NULL,\n    \
+             status CHAR(3) DEFAULT 'NEW' NOT NULL\n);\n"
+        );
+        assert!(ef.get_schema_ddl(Dialect::MySql).unwrap().contains("    id INT AUTO_INCREMENT PRIMARY KEY NOT NULL,\n"));
+        assert!(ef.get_schema_ddl(Dialect::SqlServer).unwrap().contains("    id INT IDENTITY(1,1) NOT NULL,\n"));
+    }
+
+    #[test]
+    fn identity_not_key_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("id".into(), 4, ColumnType::Int, simple_generator).as_identity(),
+                Column::new("code".into(), 3, ColumnType::Char(3), simple_generator).as_key(),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        );
+        let ef = ExportFile::new(vec![t], 1024, 1).unwrap();
+
+        assert!(ef.get_schema_ddl(Dialect::MySql).unwrap().contains("    id INT AUTO_INCREMENT UNIQUE NOT NULL,\n"));
+        assert_eq!(ef.get_constraints_ddl(Dialect::MySql), "ALTER TABLE A ADD CONSTRAINT pk_A PRIMARY KEY (code);\n");
+    }
+
+    #[test]
+    fn constraints_ddl_test() {
+        let t1 = Table::new(
+            "A".into(),
+            vec![Column::new("id".into(), 4, ColumnType::Int, simple_generator).as_key()],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+        let t2 = Table::new(
+            "B".into(),
+            vec![
+                Column::new("a_id".into(), 4, ColumnType::Int, simple_generator).references("A", "id"),
+                Column::new("amount".into(), 4, ColumnType::Int, simple_generator).with_check("amount >= 0"),
+            ],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+        let ef = ExportFile::new(vec![t1, t2], 1024, 1).unwrap();
+
+        assert_eq!(
+            ef.get_constraints_ddl(Dialect::Postgres),
+            "ALTER TABLE A ADD CONSTRAINT pk_A PRIMARY KEY (id);\n\
+             ALTER TABLE B ADD CONSTRAINT ck_B_amount CHECK (amount >= 0);\n\
+             ALTER TABLE B ADD CONSTRAINT fk_B_a_id FOREIGN KEY (a_id) REFERENCES A (id);\n"
+        );
+        assert!(!ef.get_schema_ddl(Dialect::Postgres).unwrap().contains("ALTER TABLE"));
+
+        let ef = ef.with_constraints_ddl(true).with_view("totals", "SELECT a_id, SUM(amount) FROM B GROUP BY a_id;");
+        assert!(ef.get_schema_ddl(Dialect::Postgres).unwrap().ends_with(
+            "REFERENCES A (id);\n\nCREATE VIEW totals AS SELECT a_id, SUM(amount) FROM B GROUP BY a_id;\n"
+        ));
+    }
+
+    #[test]
+    fn annotations_ddl_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("code".into(), 3, ColumnType::Char(3), simple_generator)
+                    .with_annotations(Annotations::new().with_description("Customer's code").with_tag("pii")),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        )
+            .with_annotations(Annotations::new().with_description("Customers").with_owner("sales"));
+        let ef = ExportFile::new(vec![t], 1024, 1).unwrap();
+
+        assert_eq!(
+            ef.get_schema_ddl(Dialect::Postgres).unwrap(),
+            "CREATE TABLE A (\n    code CHAR(3) NOT NULL\n);\nCOMMENT ON TABLE A IS 'Customers; owner: sales';\n\
+             COMMENT ON COLUMN A.code IS 'Customer''s code; tags: pii';\n"
+        );
+        assert_eq!(
+            ef.get_schema_ddl(Dialect::MySql).unwrap(),
+            "CREATE TABLE A (\n    code CHAR(3) NOT NULL COMMENT 'Customer''s code; tags: pii'\n) \
+             COMMENT='Customers; owner: sales';\n"
+        );
+        assert!(ef.get_schema_ddl(Dialect::SqlServer).unwrap().ends_with(
+            "EXEC sp_addextendedproperty 'MS_Description', 'Customer''s code; tags: pii', 'SCHEMA', 'dbo', 'TABLE', 'A', \
+             'COLUMN', 'code';\n"
+        ));
+    }
+}
@@ -0,0 +1,353 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Error, Result};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use crate::{Column, ExportFile, SizeSpec, Table};
+use crate::builder::ExportFileBuilder;
+use crate::compression::Compression;
+use crate::config::{column_from_config, table_from_config};
+use crate::encoding::{CodePage, Encoding};
+use crate::interleave::InterleaveMode;
+use crate::output_format::{Alignment, OutputFormat};
+use crate::transform::Transform;
+use crate::types::{ColumnSize, RecordTerminator};
+
+
+#[derive(Error, Debug)]
+pub enum DefinitionError {
+    #[error("Column {column} of table {table} has a generator that is not one of the built in generators, so it can \
+             not be saved.")]
+    NoGeneratorSpec { table: String, column: String },
+}
+
+
+// Definitions are written in the JSON config format, so a saved definition is also a config for the CLI.
+impl Column {
+    fn to_config(&self, table: &str) -> Result<Value> {
+        let generator = self.generator.spec().ok_or_else(|| {
+            Error::from(DefinitionError::NoGeneratorSpec { table: table.into(), column: self.name.clone() })
+        })?;
+        let mut config = Map::new();
+        config.insert("name".into(), self.name.clone().into());
+        config.insert("type".into(), self.column_type.to_string().into());
+        config.insert("size".into(), match self.size {
+            ColumnSize::Fixed(x) => x.into(),
+            ColumnSize::Range { min, max, avg } => json!({"min": min, "max": max, "avg": avg}),
+        });
+        config.insert("generator".into(), generator.clone());
+
+        for (name, set) in [
+            ("key", self.key),
+            ("unique", self.unique),
+            ("not_null", self.not_null),
+            ("identity", self.identity),
+        ] {
+            if set {
+                config.insert(name.into(), true.into());
+            }
+        }
+        if !self.dictionary {
+            config.insert("dictionary".into(), false.into());
+        }
+        if self.null_rate > 0.0 {
+            config.insert("null_rate".into(), self.null_rate.into());
+        }
+        if let Some(default) = &self.default {
+            config.insert("default".into(), default.clone().into());
+        }
+        if let Some(check) = &self.check {
+            config.insert("check".into(), check.clone().into());
+        }
+        if !self.transforms.is_empty() {
+            let transforms = self.transforms.iter()
+                .map(|x| match x {
+                    Transform::Prefix(x) => json!({"prefix": x}),
+                    Transform::Suffix(x) => json!({"suffix": x}),
+                    Transform::Upper => "upper".into(),
+                    Transform::Lower => "lower".into(),
+                    Transform::Title => "title".into(),
+                    Transform::TrimLeft => "trim_left".into(),
+                    Transform::TrimRight => "trim_right".into(),
+                })
+                .collect::<Vec<Value>>();
+            config.insert("transforms".into(), transforms.into());
+        }
+        if let Some(reference) = &self.reference {
+            config.insert("references".into(), json!({"table": reference.table, "column": reference.column}));
+        }
+        config.extend(self.annotations.to_json());
+
+        Ok(Value::Object(config))
+    }
+}
+
+
+fn output_format_config(output_format: &OutputFormat) -> Value {
+    match output_format {
+        OutputFormat::Delimited => "delimited".into(),
+        OutputFormat::FixedWidth { padding, alignment } => json!({
+            "name": "fixed_width",
+            "padding": padding.to_string(),
+            "alignment": if *alignment == Alignment::Right { "right" } else { "left" },
+        }),
+        OutputFormat::KeyValue { separator } => json!({"name": "key_value", "separator": separator.to_string()}),
+    }
+}
+
+
+impl Table {
+    fn to_config(&self) -> Result<Value> {
+        let mut config = Map::new();
+        config.insert("id".into(), self.id_value.clone().into());
+        config.insert("percent_size".into(), self.percent_size.to_string().into());
+        config.insert("delimiter".into(), self.delimiter.clone().into());
+        config.insert("terminator".into(), match &self.terminator {
+            RecordTerminator::Lf => "lf",
+            RecordTerminator::CrLf => "crlf",
+            RecordTerminator::Custom(x) => x.as_str(),
+        }.into());
+        if self.output_format != OutputFormat::Delimited {
+            config.insert("output_format".into(), output_format_config(&self.output_format));
+        }
+        if !self.qualifiers.is_empty() {
+            config.insert("qualifier".into(), self.qualifiers.join(".").into());
+        }
+        if self.row_group > 1 {
+            config.insert("row_group".into(), self.row_group.into());
+        }
+
+        // Single columns marked unique are saved with the column.
+        let unique: Vec<Value> = self.unique_constraints().into_iter()
+            .filter(|x| !matches!(x.as_slice(), [y] if self.columns.iter().any(|z| z.name == *y && z.unique)))
+            .map(|x| x.into())
+            .collect();
+        if !unique.is_empty() {
+            config.insert("unique".into(), unique.into());
+        }
+        if !self.corruption.is_empty() {
+            let corruption = &self.corruption;
+            config.insert("corruption".into(), json!({
+                "truncated_rows": corruption.truncated_rows,
+                "wrong_delimiter_count": corruption.wrong_delimiter_count,
+                "invalid_dates": corruption.invalid_dates,
+                "non_numeric": corruption.non_numeric,
+                "duplicate_keys": corruption.duplicate_keys,
+            }));
+        }
+        if !self.numeric_stress.is_empty() {
+            let stress = &self.numeric_stress;
+            let mut numeric_stress = json!({
+                "separators": stress.separators,
+                "decimal_separator": stress.decimal_separator.to_string(),
+                "plus_sign": stress.plus_sign,
+                "spaces": stress.spaces,
+                "quotes": stress.quotes,
+            });
+            if let Some(separator) = stress.thousands_separator {
+                numeric_stress["thousands_separator"] = separator.to_string().into();
+            }
+            config.insert("numeric_stress".into(), numeric_stress);
+        }
+        config.extend(self.annotations.to_json());
+
+        let columns = self.columns.iter()
+            .map(|x| x.to_config(&self.id_value))
+            .collect::<Result<Vec<Value>>>()?;
+        config.insert("columns".into(), columns.into());
+
+        Ok(Value::Object(config))
+    }
+}
+
+
+impl ExportFile {
+    /// The tables and the settings that decide what is generated as a JSON config, generators as the names and
+    /// arguments of built in generators. Where and how fast files are written, e.g. file names, archives or the
+    /// throttle, is left to the run.
+    pub fn to_config(&self) -> Result<Value> {
+        let mut config = Map::new();
+        config.insert("size".into(), (self.file_size_bytes * self.number_of_files).into());
+        config.insert("files".into(), self.number_of_files.into());
+        if let Some(seed) = self.seed {
+            config.insert("seed".into(), seed.into());
+        }
+        config.insert("namespace".into(), self.namespace.clone().into());
+        if let SizeSpec::Rows(rows) = &self.size_spec {
+            config.insert("rows".into(), json!(rows));
+        }
+        config.insert("compression".into(), match self.compression {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Zstd { .. } => "zstd",
+        }.into());
+        config.insert("encoding".into(), match self.encoding {
+            Encoding::Utf8 => "utf8",
+            Encoding::Latin1 => "latin1",
+            Encoding::Ebcdic(CodePage::Cp037) => "cp037",
+            Encoding::Ebcdic(CodePage::Cp500) => "cp500",
+            Encoding::Ebcdic(CodePage::Cp1140) => "cp1140",
+        }.into());
+        config.insert("interleave".into(), match self.interleave {
+            InterleaveMode::Blocked => "blocked",
+            InterleaveMode::RoundRobin => "round_robin",
+            InterleaveMode::Shuffled => "shuffled",
+        }.into());
+        if let Some(table) = self.tables.first() {
+            config.insert("null_representation".into(), table.null_representation.as_str().into());
+        }
+        if let Some(max_rows) = self.max_rows_per_file {
+            config.insert("max_rows_per_file".into(), max_rows.into());
+        }
+        config.insert("chunk_size".into(), self.chunk_bytes.into());
+        for (name, set) in [
+            ("manifest", self.manifest),
+            ("report", self.report),
+            ("checkpoint", self.checkpoint),
+            ("measured_sizing", self.measured_sizing),
+            ("constraints_ddl", self.constraints_ddl),
+        ] {
+            config.insert(name.into(), set.into());
+        }
+        if !self.views.is_empty() {
+            let views: Vec<Value> = self.views.iter().map(|x| json!({"name": x.name, "query": x.query})).collect();
+            config.insert("views".into(), views.into());
+        }
+
+        let tables = self.tables.iter()
+            .map(|x| {
+                let mut table = x.to_config()?;
+                if let Some((of, ratio)) = self.row_ratios.get(&x.id_value) {
+                    table["row_ratio"] = json!({"of": of, "ratio": ratio});
+                }
+                if let Some(files) = self.affinity(&x.id_value) {
+                    table["affinity"] = json!(files);
+                }
+                Ok(table)
+            })
+            .collect::<Result<Vec<Value>>>()?;
+        config.insert("tables".into(), tables.into());
+
+        Ok(Value::Object(config))
+    }
+
+
+    /// Writes the definition to `path` as pretty printed JSON, see [`ExportFile::to_config`].
+    pub fn save_definition(&self, path: &Path) -> Result<()> {
+        Ok(fs::write(path, serde_json::to_string_pretty(&self.to_config()?)?)?)
+    }
+
+
+    /// An export from a definition saved with [`ExportFile::save_definition`] or any JSON config.
+    pub fn load_definition(path: &Path) -> Result<ExportFile> {
+        ExportFileBuilder::from_json(&fs::read_to_string(path)?)?.build()
+    }
+}
+
+
+impl Serialize for Column {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_config("").map_err(ser::Error::custom)?.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Column {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        column_from_config(&Value::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+
+impl Serialize for Table {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_config().map_err(ser::Error::custom)?.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Table {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        table_from_config(&Value::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+
+impl Serialize for ExportFile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_config().map_err(ser::Error::custom)?.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ExportFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ExportFileBuilder::from_config(&Value::deserialize(deserializer)?)
+            .and_then(|x| x.build())
+            .map_err(de::Error::custom)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::generators;
+    use crate::types::ColumnType;
+
+    fn export_file() -> ExportFile {
+        let segments = generators::one_of(vec!["A".into(), "B".into()]);
+        let customers = Table::builder("customers")
+            .percent_size(Decimal::from_str("0.4").unwrap())
+            .add_column(Column::new("id".into(), ColumnSize::range(1, 10), ColumnType::Int, generators::sequence(1, 1))
+                .as_key())
+            .add_column(Column::new("segment".into(), 1, ColumnType::Char(1), segments)
+                .with_transform(Transform::Lower))
+            .add_column(Column::from_type("joined".into(), ColumnType::Date).with_null_rate(0.2))
+            .build()
+            .unwrap();
+        let orders = Table::builder("orders")
+            .percent_size(Decimal::from_str("0.6").unwrap())
+            .delimiter(",")
+            .output_format(OutputFormat::key_value())
+            .add_column(Column::new("customer_id".into(), ColumnSize::range(1, 10), ColumnType::Int,
+                                    generators::sequence(1, 1)).references("customers", "id"))
+            .add_column(Column::new("amount".into(), 8, ColumnType::Double, generators::exponential(0.1)))
+            .build()
+            .unwrap();
+
+        ExportFile::new(vec![customers, orders], 4_000, 2).unwrap()
+            .with_seed(5)
+            .with_row_ratio("orders", "customers", 3.0).unwrap()
+            .with_size_spec(SizeSpec::Rows(HashMap::from([("customers".to_string(), 40)]))).unwrap()
+            .with_affinity("customers", &[0]).unwrap()
+    }
+
+    #[test]
+    fn definition_round_trip_test() {
+        let path = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}.json", rand::random::<u32>()));
+        let ef = export_file();
+        ef.save_definition(&path).unwrap();
+        let loaded = ExportFile::load_definition(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // The same rows, keys in the same namespace.
+        for x in 0..2 {
+            assert_eq!(loaded.generate_file_rows(x).unwrap(), ef.generate_file_rows(x).unwrap());
+        }
+        assert_eq!(loaded.to_config().unwrap(), ef.to_config().unwrap());
+        let json = serde_json::to_string(&ef).unwrap();
+        let deserialized: ExportFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.get_schema_json_str().unwrap(), ef.get_schema_json_str().unwrap());
+        let column: Column = serde_json::from_value(serde_json::to_value(&ef.tables[0].columns[1]).unwrap()).unwrap();
+        assert_eq!(column.generator.spec(), ef.tables[0].columns[1].generator.spec());
+
+        // Generators made from closures can not be written down.
+        let closure = Column::new("x".into(), 1, ColumnType::Char(1), || Ok("x".to_string()));
+        assert!(serde_json::to_value(&closure).is_err());
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{Error, Result};
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use crate::{ExportFile, Table};
+use crate::manifest::MANIFEST_FILE;
+use crate::output_format::OutputFormat;
+use crate::random::{mix, RandomSource, SeededRandom};
+
+pub const DELTA_FILE: &str = "delta.txt";
+
+// Changed values and the choice of changed rows get their own streams so a delta never repeats the base export.
+const UPDATE_STREAM: u64 = u64::MAX / 7;
+const CHOICE_STREAM: u64 = u64::MAX / 11;
+
+
+#[derive(Error, Debug)]
+pub enum DeltaError {
+    #[error("Deltas need a seeded export, otherwise regenerated keys differ from the exported ones.")]
+    Unseeded,
+    #[error("Manifest {path} has no rows per table.")]
+    InvalidManifest { path: String },
+    #[error("Table {table} has no key column to update or delete rows by.")]
+    NoKey { table: String },
+    #[error("Can not update and delete {requested} rows of table {table}, the previous export has only {available}.")]
+    NotEnoughRows { table: String, requested: u64, available: u64 },
+    #[error("Invalid delta {spec}, expected <inserts>,<updates>,<deletes>.")]
+    InvalidSpec { spec: String },
+}
+
+
+/// Rows of every table a delta inserts, updates and deletes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeltaSpec {
+    pub inserts: u64,
+    pub updates: u64,
+    pub deletes: u64,
+}
+
+impl FromStr for DeltaSpec {
+    type Err = Error;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let invalid = || Error::from(DeltaError::InvalidSpec { spec: spec.into() });
+        let counts = spec.split(',')
+            .map(|x| x.trim().parse::<u64>().map_err(|_| invalid()))
+            .collect::<Result<Vec<u64>>>()?;
+
+        match counts[..] {
+            [inserts, updates, deletes] => Ok(DeltaSpec { inserts, updates, deletes }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl Operation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Insert => "I",
+            Operation::Update => "U",
+            Operation::Delete => "D",
+        }
+    }
+}
+
+
+// Rows of every table in the previous export, a delta's manifest carries the total after it was applied.
+fn previous_rows(path: &Path) -> Result<HashMap<String, u64>> {
+    let manifest: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    let invalid = || Error::from(DeltaError::InvalidManifest { path: path.to_string_lossy().to_string() });
+
+    if let Some(total_rows) = manifest["total_rows"].as_object() {
+        return total_rows.iter()
+            .map(|(x, y)| Ok((x.clone(), y.as_u64().ok_or_else(invalid)?)))
+            .collect();
+    }
+
+    let mut rows: HashMap<String, u64> = HashMap::new();
+    for file in manifest["files"].as_array().ok_or_else(invalid)? {
+        for (table, count) in file["rows"].as_object().ok_or_else(invalid)? {
+            *rows.entry(table.clone()).or_default() += count.as_u64().ok_or_else(invalid)?;
+        }
+    }
+    Ok(rows)
+}
+
+
+impl Table {
+    // Key values of the row as exported, every other column changed or emptied.
+    fn changed_row(&self, row_index: u64, operation: Operation) -> Result<Vec<String>> {
+        let original = self.generate_values_at(row_index)?;
+        let keys: Vec<usize> = (0..self.columns.len()).filter(|x| self.columns[*x].key).collect();
+
+        let mut row = match operation {
+            Operation::Delete => {
+                let mut row = vec![self.null_representation.as_str().to_string(); original.len()];
+                row[0] = original[0].clone();
+                row
+            }
+            _ => {
+                let mut changed = self.clone();
+                changed.seed = self.seed.map(|x| mix(x, UPDATE_STREAM));
+                changed.generate_values_at(row_index)?
+            }
+        };
+
+        for x in keys.iter() {
+            row[x + 1] = original[x + 1].clone();
+        }
+        if operation == Operation::Update {
+            self.regenerate_dependents(&keys, row_index, &mut row)?;
+        }
+        Ok(row)
+    }
+
+    fn operation_to_string(&self, operation: Operation, row: &[String]) -> String {
+        match self.output_format {
+            OutputFormat::Delimited | OutputFormat::KeyValue { .. } => format!("{}{}{}", operation.as_str(), self.delimiter, self.row_to_string(row)),
+            OutputFormat::FixedWidth { .. } => format!("{}{}", operation.as_str(), self.row_to_string(row)),
+        }
+    }
+}
+
+
+impl ExportFile {
+    /// Writes a follow-up to the export described by `previous_manifest` into `folder_path`: every table gets
+    /// `inserts` new rows after the previous ones, `updates` changed versions of previous keys and `deletes` markers
+    /// holding only a previous key, each row prefixed with I, U or D. The delta's own manifest can be passed back in
+    /// for the next delta.
+    pub fn generate_delta(&self, previous_manifest: &Path, spec: DeltaSpec, folder_path: &Path) -> Result<()> {
+        if self.tables.iter().any(|x| x.seed.is_none()) {
+            return Err(Error::from(DeltaError::Unseeded));
+        }
+        let previous = previous_rows(previous_manifest)?;
+
+        let mut inserted: Vec<Option<Vec<Vec<String>>>> = vec![None; self.tables.len()];
+        let mut data = String::new();
+        let mut rows = Map::new();
+        let mut total_rows = Map::new();
+
+        for index in self.generation_order()? {
+            let table = self.with_reference_pools(&self.tables[index], &inserted, 0)?;
+            let available = previous.get(&table.id_value).copied().unwrap_or(0);
+            let changes = spec.updates.saturating_add(spec.deletes);
+
+            if changes > 0 && !table.columns.iter().any(|x| x.key) {
+                return Err(Error::from(DeltaError::NoKey { table: table.id_value.clone() }));
+            }
+            if changes > available {
+                return Err(Error::from(DeltaError::NotEnoughRows {
+                    table: table.id_value.clone(),
+                    requested: changes,
+                    available,
+                }));
+            }
+
+            // Floyd's algorithm picks the changed rows, shuffling them splits updates from deletes.
+            let random = SeededRandom::new(mix(mix(table.seed.unwrap_or(0), available), CHOICE_STREAM));
+            let mut chosen: BTreeSet<u64> = BTreeSet::new();
+            for upper in available - changes..available {
+                let candidate = random.below(upper + 1);
+                if !chosen.insert(candidate) {
+                    chosen.insert(upper);
+                }
+            }
+            let mut chosen: Vec<u64> = chosen.into_iter().collect();
+            for x in (1..chosen.len()).rev() {
+                chosen.swap(x, random.below(x as u64 + 1) as usize);
+            }
+            let (updates, deletes) = chosen.split_at(spec.updates as usize);
+
+            let inserts = table.generate_rows_vec_from(available, spec.inserts)?;
+            for row in inserts.iter() {
+                data.push_str(&table.operation_to_string(Operation::Insert, row));
+            }
+            let mut updated: Vec<Vec<String>> = vec![];
+            for (operation, indices) in [(Operation::Update, updates), (Operation::Delete, deletes)] {
+                let mut indices = indices.to_vec();
+                indices.sort_unstable();
+                for row_index in indices {
+                    let row = table.changed_row(row_index, operation)?;
+                    data.push_str(&table.operation_to_string(operation, &row));
+                    if operation == Operation::Update {
+                        updated.push(row);
+                    }
+                }
+            }
+
+            rows.insert(table.id_value.clone(), json!(spec.inserts.saturating_add(changes)));
+            total_rows.insert(table.id_value.clone(), json!(available.saturating_add(spec.inserts)));
+            inserted[index] = Some(inserts.into_iter().chain(updated).collect());
+        }
+
+        fs::create_dir_all(folder_path)?;
+        let (bytes, _, sha256) = self.write_encoded(&folder_path.join(DELTA_FILE), &data)?;
+
+        let tables: Map<String, Value> = self.tables.iter()
+            .map(|x| (x.id_value.clone(), json!(x.qualified_name())))
+            .collect();
+        let manifest = json!({
+            "tables": tables,
+            "files": [{"name": DELTA_FILE, "file_index": 0, "bytes": bytes, "rows": rows, "sha256": sha256}],
+            "total_bytes": bytes,
+            "total_rows": total_rows,
+            "delta": {"inserts": spec.inserts, "updates": spec.updates, "deletes": spec.deletes},
+        });
+
+        Ok(fs::write(folder_path.join(MANIFEST_FILE), serde_json::to_string_pretty(&manifest)?)?)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column};
+    use crate::types::ColumnType;
+
+    #[test]
+    fn delta_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).as_key(),
+                Column::new("amount".into(), 4, ColumnType::Int, generators::random_int(1000, 9999)),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        );
+        let ef = ExportFile::new(vec![t], 400, 2).unwrap().with_seed(7).with_manifest(true);
+        let total = ef.table_total_rows(&ef.tables[0]).unwrap();
+
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_all_files(&folder.join("base")).unwrap();
+        let spec = DeltaSpec { inserts: 3, updates: 2, deletes: 1 };
+        ef.generate_delta(&folder.join("base").join(MANIFEST_FILE), spec, &folder.join("delta")).unwrap();
+        ef.generate_delta(&folder.join("delta").join(MANIFEST_FILE), spec, &folder.join("next")).unwrap();
+        let delta = fs::read_to_string(folder.join("delta").join(DELTA_FILE)).unwrap();
+        let next = fs::read_to_string(folder.join("next").join(DELTA_FILE)).unwrap();
+        let manifest: Value =
+            serde_json::from_str(&fs::read_to_string(folder.join("next").join(MANIFEST_FILE)).unwrap()).unwrap();
+        fs::remove_dir_all(&folder).unwrap();
+
+        let lines: Vec<Vec<&str>> = delta.lines().map(|x| x.split('|').collect()).collect();
+        let operations: Vec<&str> = lines.iter().map(|x| x[0]).collect();
+        assert_eq!(operations, vec!["I", "I", "I", "U", "U", "D"]);
+        assert_eq!(lines[0][2], (total + 1).to_string());
+        for line in lines[3..].iter() {
+            assert!(line[2].parse::<u64>().unwrap() <= total);
+        }
+        assert_eq!(lines[5][3], "");
+        assert_ne!(lines[3][3], ef.tables[0].generate_values_at(lines[3][2].parse::<u64>().unwrap() - 1).unwrap()[2]);
+
+        assert!(next.starts_with(&format!("I|A|{}|", total + 4)));
+        assert_eq!(manifest["total_rows"]["A"], total + 6);
+
+        assert_eq!("5, 0,2".parse::<DeltaSpec>().unwrap(), DeltaSpec { inserts: 5, updates: 0, deletes: 2 });
+        assert!("5,0".parse::<DeltaSpec>().is_err());
+        let spec = DeltaSpec { inserts: 0, updates: total, deletes: 1 };
+        assert!(ef.generate_delta(&folder.join("missing.json"), spec, &folder).is_err());
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use rayon::prelude::*;
+
+use crate::Table;
+use crate::generators::{Generator, RowContext};
+use crate::random::{RandomSource, SeededRandom};
+
+const PROBE_ROWS: usize = 1024;
+const MAX_VALUES: usize = 64;
+
+
+#[derive(Debug)]
+pub struct Dictionary {
+    values: Vec<String>,
+    positions: HashMap<String, u32>,
+    // Number of leading values a `one_of` generator picks from uniformly, 0 when indices can not be drawn directly.
+    choices: u64,
+}
+
+impl Dictionary {
+    fn new(values: Vec<String>, choices: u64) -> Self {
+        let mut positions: HashMap<String, u32> = HashMap::new();
+        for (i, value) in values.iter().enumerate() {
+            positions.entry(value.clone()).or_insert(i as u32);
+        }
+
+        Dictionary { values, positions, choices }
+    }
+
+    // A generator is treated as low cardinality when probing it yields few distinct values and
+    // every one of them repeats, which suggests the probe has seen the whole value space. The probe is seeded so
+    // it finds the same values in every run.
+    pub(crate) fn probe(generator: &Generator) -> Option<Arc<Dictionary>> {
+        if generator.is_by_row_index() {
+            return None;
+        }
+        let mut counts: HashMap<String, u32> = HashMap::new();
+
+        for row_index in 0..PROBE_ROWS as u64 {
+            *counts.entry(generator.generate(&RowContext { row_index, random: &SeededRandom::new(row_index) }).ok()?).or_insert(0) += 1;
+            if counts.len() > MAX_VALUES {
+                return None;
+            }
+        }
+
+        if counts.values().any(|x| *x < 2) {
+            return None;
+        }
+
+        let mut values: Vec<String> = counts.into_keys().collect();
+        values.sort();
+
+        Some(Arc::new(Dictionary::new(values, 0)))
+    }
+
+    pub fn values(&self) -> &[String] {
+        self.values.as_slice()
+    }
+
+    pub fn value(&self, index: u32) -> &str {
+        self.values[index as usize].as_str()
+    }
+
+    fn position(&self, value: &str) -> Option<u32> {
+        self.positions.get(value).copied()
+    }
+}
+
+
+impl Table {
+    // Dictionary of every value the column can take as written, `one_of` values are known up front and other
+    // generators are probed. Columns without one are generated plain.
+    fn column_dictionary(&self, column_index: usize) -> Option<Arc<Dictionary>> {
+        let column = &self.columns[column_index];
+        if !column.dictionary || column.generator.depends_on_row() || self.is_permuted(column_index) {
+            return None;
+        }
+
+        let (values, choices) = match column.generator.spec() {
+            Some(x) if x["name"] == "one_of" => {
+                let values: Vec<String> = x["args"].as_array()?.iter().map(|y| y.as_str().map(String::from)).collect::<Option<_>>()?;
+                let choices = values.len() as u64;
+                (values, choices)
+            }
+            _ => (Dictionary::probe(&column.generator)?.values.clone(), 0),
+        };
+        let mut values: Vec<String> = values.into_iter()
+            .map(|x| self.namespaced(column_index, column.transform(x)))
+            .collect();
+        if column.is_nullable() {
+            values.push(self.null_representation.as_str().to_string());
+        }
+        if values.len() > u32::MAX as usize {
+            return None;
+        }
+
+        Some(Arc::new(Dictionary::new(values, choices)))
+    }
+
+
+    // Index of the column's value in the dictionary, None when the value is missing from a probed dictionary.
+    // Indices of `one_of` values are drawn with the random numbers `generate_value` would draw.
+    fn generate_index(&self, column_index: usize, row_index: u64, dictionary: &Dictionary) -> Result<Option<u32>> {
+        if dictionary.choices == 0 {
+            return Ok(dictionary.position(&self.generate_value(column_index, row_index)?));
+        }
+
+        let column = &self.columns[column_index];
+        let value_random = self.value_random(row_index, column_index);
+        let random: &dyn RandomSource = match &value_random {
+            Some(x) => x,
+            None => self.random.as_ref(),
+        };
+
+        if column.generator.is_null_row(row_index) || (column.null_rate > 0.0 && random.chance(column.null_rate)) {
+            return Ok(dictionary.position(self.null_representation.as_str()));
+        }
+        Ok(Some(random.below(dictionary.choices) as u32))
+    }
+
+
+    pub(crate) fn generate_column_data(&self, column_index: usize, row_count: u64) -> Result<ColumnData> {
+        if let Some(dictionary) = self.column_dictionary(column_index) {
+            let indices = (0..row_count)
+                .into_par_iter()
+                .map(|x| self.generate_index(column_index, x, &dictionary))
+                .collect::<Result<Option<Vec<u32>>>>()?;
+
+            // A value the probe did not see, the column is generated again without the dictionary.
+            if let Some(indices) = indices {
+                return Ok(ColumnData::Dictionary { dictionary, indices });
+            }
+        }
+
+        Ok(ColumnData::Plain(
+            (0..row_count)
+                .into_par_iter()
+                .map(|x| self.generate_value(column_index, x))
+                .collect::<Result<Vec<String>>>()?
+        ))
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub enum ColumnData {
+    Plain(Vec<String>),
+    Dictionary { dictionary: Arc<Dictionary>, indices: Vec<u32> },
+}
+
+impl ColumnData {
+    pub fn len(&self) -> usize {
+        match self {
+            ColumnData::Plain(x) => x.len(),
+            ColumnData::Dictionary { indices, .. } => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, row: usize) -> &str {
+        match self {
+            ColumnData::Plain(x) => x[row].as_str(),
+            ColumnData::Dictionary { dictionary, indices } => dictionary.value(indices[row]),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use rand::Rng;
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column};
+
+    fn constant_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    fn enum_generator() -> Result<String> {
+        Ok(["A", "B", "C"][rand::thread_rng().gen_range(0..3)].into())
+    }
+
+    fn unique_generator() -> Result<String> {
+        Ok(rand::random::<u64>().to_string())
+    }
+
+    #[test]
+    fn probe_test() {
+        let dictionary = Dictionary::probe(&Generator::from(enum_generator)).unwrap();
+        assert_eq!(dictionary.values(), &["A", "B", "C"]);
+
+        let dictionary = Dictionary::probe(&Generator::from(constant_generator)).unwrap();
+        assert_eq!(dictionary.values(), &["ABC"]);
+
+        assert!(Dictionary::probe(&Generator::from(unique_generator)).is_none());
+    }
+
+    #[test]
+    fn column_data_test() {
+        // Value 5000 is past the rows probed, so the dictionary misses it and the column is generated plain.
+        let rare = Generator::new(|context| Ok(if context.row_index == 5000 { "D" } else { "A" }.to_string()));
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("one_of".into(), 1, "CHAR[1]".into(), generators::one_of(vec!["A".into(), "B".into()]))
+                    .with_null_rate(0.5),
+                Column::new("rare".into(), 1, "CHAR[1]".into(), rare),
+            ],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        ).with_seed(3);
+
+        let columns = t.generate_table_columns(6 * 6000).unwrap();
+        let rows = t.generate_table_vec(6 * 6000).unwrap();
+
+        assert!(matches!(&columns[0], ColumnData::Dictionary { dictionary, .. } if dictionary.values() == ["A", "B", ""]));
+        assert!(matches!(columns[1], ColumnData::Plain(_)));
+        assert_eq!(columns[0].len(), 6000);
+        assert!((0..6000).all(|x| columns[0].get(x) == rows[x][1] && columns[1].get(x) == rows[x][2]));
+        assert_eq!(columns[1].get(5000), "D");
+    }
+}
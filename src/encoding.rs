@@ -0,0 +1,189 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+
+#[derive(Error, Debug)]
+pub enum EncodingError {
+    #[error("Unknown encoding {encoding}, expected utf8, latin1, cp037, cp500 or cp1140.")]
+    UnknownEncoding { encoding: String },
+    #[error("Character {character:?} can not be written as {encoding}.")]
+    Unmappable { character: char, encoding: Encoding },
+}
+
+
+// Latin-1 code points to EBCDIC bytes, both code pages cover all of Latin-1.
+const CP037: [u8; 256] = [
+    0x00, 0x01, 0x02, 0x03, 0x37, 0x2d, 0x2e, 0x2f, 0x16, 0x05, 0x25, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x3c, 0x3d, 0x32, 0x26, 0x18, 0x19, 0x3f, 0x27, 0x1c, 0x1d, 0x1e, 0x1f,
+    0x40, 0x5a, 0x7f, 0x7b, 0x5b, 0x6c, 0x50, 0x7d, 0x4d, 0x5d, 0x5c, 0x4e, 0x6b, 0x60, 0x4b, 0x61,
+    0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0x7a, 0x5e, 0x4c, 0x7e, 0x6e, 0x6f,
+    0x7c, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xd1, 0xd2, 0xd3, 0xd4, 0xd5, 0xd6,
+    0xd7, 0xd8, 0xd9, 0xe2, 0xe3, 0xe4, 0xe5, 0xe6, 0xe7, 0xe8, 0xe9, 0xba, 0xe0, 0xbb, 0xb0, 0x6d,
+    0x79, 0x81, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x91, 0x92, 0x93, 0x94, 0x95, 0x96,
+    0x97, 0x98, 0x99, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xc0, 0x4f, 0xd0, 0xa1, 0x07,
+    0x20, 0x21, 0x22, 0x23, 0x24, 0x15, 0x06, 0x17, 0x28, 0x29, 0x2a, 0x2b, 0x2c, 0x09, 0x0a, 0x1b,
+    0x30, 0x31, 0x1a, 0x33, 0x34, 0x35, 0x36, 0x08, 0x38, 0x39, 0x3a, 0x3b, 0x04, 0x14, 0x3e, 0xff,
+    0x41, 0xaa, 0x4a, 0xb1, 0x9f, 0xb2, 0x6a, 0xb5, 0xbd, 0xb4, 0x9a, 0x8a, 0x5f, 0xca, 0xaf, 0xbc,
+    0x90, 0x8f, 0xea, 0xfa, 0xbe, 0xa0, 0xb6, 0xb3, 0x9d, 0xda, 0x9b, 0x8b, 0xb7, 0xb8, 0xb9, 0xab,
+    0x64, 0x65, 0x62, 0x66, 0x63, 0x67, 0x9e, 0x68, 0x74, 0x71, 0x72, 0x73, 0x78, 0x75, 0x76, 0x77,
+    0xac, 0x69, 0xed, 0xee, 0xeb, 0xef, 0xec, 0xbf, 0x80, 0xfd, 0xfe, 0xfb, 0xfc, 0xad, 0xae, 0x59,
+    0x44, 0x45, 0x42, 0x46, 0x43, 0x47, 0x9c, 0x48, 0x54, 0x51, 0x52, 0x53, 0x58, 0x55, 0x56, 0x57,
+    0x8c, 0x49, 0xcd, 0xce, 0xcb, 0xcf, 0xcc, 0xe1, 0x70, 0xdd, 0xde, 0xdb, 0xdc, 0x8d, 0x8e, 0xdf,
+];
+
+const CP500: [u8; 256] = [
+    0x00, 0x01, 0x02, 0x03, 0x37, 0x2d, 0x2e, 0x2f, 0x16, 0x05, 0x25, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x3c, 0x3d, 0x32, 0x26, 0x18, 0x19, 0x3f, 0x27, 0x1c, 0x1d, 0x1e, 0x1f,
+    0x40, 0x4f, 0x7f, 0x7b, 0x5b, 0x6c, 0x50, 0x7d, 0x4d, 0x5d, 0x5c, 0x4e, 0x6b, 0x60, 0x4b, 0x61,
+    0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0x7a, 0x5e, 0x4c, 0x7e, 0x6e, 0x6f,
+    0x7c, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xd1, 0xd2, 0xd3, 0xd4, 0xd5, 0xd6,
+    0xd7, 0xd8, 0xd9, 0xe2, 0xe3, 0xe4, 0xe5, 0xe6, 0xe7, 0xe8, 0xe9, 0x4a, 0xe0, 0x5a, 0x5f, 0x6d,
+    0x79, 0x81, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x91, 0x92, 0x93, 0x94, 0x95, 0x96,
+    0x97, 0x98, 0x99, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xc0, 0xbb, 0xd0, 0xa1, 0x07,
+    0x20, 0x21, 0x22, 0x23, 0x24, 0x15, 0x06, 0x17, 0x28, 0x29, 0x2a, 0x2b, 0x2c, 0x09, 0x0a, 0x1b,
+    0x30, 0x31, 0x1a, 0x33, 0x34, 0x35, 0x36, 0x08, 0x38, 0x39, 0x3a, 0x3b, 0x04, 0x14, 0x3e, 0xff,
+    0x41, 0xaa, 0xb0, 0xb1, 0x9f, 0xb2, 0x6a, 0xb5, 0xbd, 0xb4, 0x9a, 0x8a, 0xba, 0xca, 0xaf, 0xbc,
+    0x90, 0x8f, 0xea, 0xfa, 0xbe, 0xa0, 0xb6, 0xb3, 0x9d, 0xda, 0x9b, 0x8b, 0xb7, 0xb8, 0xb9, 0xab,
+    0x64, 0x65, 0x62, 0x66, 0x63, 0x67, 0x9e, 0x68, 0x74, 0x71, 0x72, 0x73, 0x78, 0x75, 0x76, 0x77,
+    0xac, 0x69, 0xed, 0xee, 0xeb, 0xef, 0xec, 0xbf, 0x80, 0xfd, 0xfe, 0xfb, 0xfc, 0xad, 0xae, 0x59,
+    0x44, 0x45, 0x42, 0x46, 0x43, 0x47, 0x9c, 0x48, 0x54, 0x51, 0x52, 0x53, 0x58, 0x55, 0x56, 0x57,
+    0x8c, 0x49, 0xcd, 0xce, 0xcb, 0xcf, 0xcc, 0xe1, 0x70, 0xdd, 0xde, 0xdb, 0xdc, 0x8d, 0x8e, 0xdf,
+];
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodePage {
+    /// US and Canada.
+    Cp037,
+    /// International Latin-1.
+    Cp500,
+    /// Cp037 with the euro sign in place of the currency sign.
+    Cp1140,
+}
+
+
+/// Character encoding of written text files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Latin1,
+    Ebcdic(CodePage),
+}
+
+impl Encoding {
+    /// Every character takes one byte, so row sizes count characters instead of UTF-8 bytes.
+    pub fn is_single_byte(&self) -> bool {
+        !matches!(self, Encoding::Utf8)
+    }
+
+    fn encode_char(&self, character: char) -> Option<u8> {
+        let latin1 = |x: char| u8::try_from(u32::from(x)).ok();
+
+        match self {
+            Encoding::Utf8 => None,
+            Encoding::Latin1 => latin1(character),
+            Encoding::Ebcdic(CodePage::Cp037) => latin1(character).map(|x| CP037[x as usize]),
+            Encoding::Ebcdic(CodePage::Cp500) => latin1(character).map(|x| CP500[x as usize]),
+            Encoding::Ebcdic(CodePage::Cp1140) => match character {
+                '€' => Some(CP037[0xa4]),
+                '\u{a4}' => None,
+                x => latin1(x).map(|y| CP037[y as usize]),
+            },
+        }
+    }
+
+    pub fn encode<'a>(&self, text: &'a str) -> Result<Cow<'a, [u8]>> {
+        if *self == Encoding::Utf8 {
+            return Ok(Cow::Borrowed(text.as_bytes()));
+        }
+
+        text.chars()
+            .map(|x| self.encode_char(x)
+                .ok_or_else(|| Error::from(EncodingError::Unmappable { character: x, encoding: *self })))
+            .collect::<Result<Vec<u8>>>()
+            .map(Cow::Owned)
+    }
+
+    /// Bytes `text` takes once encoded.
+    pub fn encoded_len(&self, text: &str) -> u64 {
+        if self.is_single_byte() { text.chars().count() as u64 } else { text.len() as u64 }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Encoding::Utf8 => write!(f, "UTF-8"),
+            Encoding::Latin1 => write!(f, "Latin-1"),
+            Encoding::Ebcdic(CodePage::Cp037) => write!(f, "EBCDIC 037"),
+            Encoding::Ebcdic(CodePage::Cp500) => write!(f, "EBCDIC 500"),
+            Encoding::Ebcdic(CodePage::Cp1140) => write!(f, "EBCDIC 1140"),
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = Error;
+
+    fn from_str(encoding: &str) -> Result<Self> {
+        match encoding.to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "utf8" => Ok(Encoding::Utf8),
+            "latin1" | "iso88591" => Ok(Encoding::Latin1),
+            "ebcdic" | "cp037" => Ok(Encoding::Ebcdic(CodePage::Cp037)),
+            "cp500" => Ok(Encoding::Ebcdic(CodePage::Cp500)),
+            "cp1140" => Ok(Encoding::Ebcdic(CodePage::Cp1140)),
+            _ => Err(Error::from(EncodingError::UnknownEncoding { encoding: encoding.into() })),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{Column, ExportFile, Table};
+
+    #[test]
+    fn encode_test() {
+        let ebcdic = Encoding::Ebcdic(CodePage::Cp037);
+
+        assert_eq!(ebcdic.encode("A|1\n").unwrap().as_ref(), &[0xc1, 0x4f, 0xf1, 0x25]);
+        assert!(Encoding::Latin1.encode("Žé").is_err());
+        assert_eq!(Encoding::Latin1.encode("é").unwrap().as_ref(), &[0xe9]);
+        assert_eq!("cp1140".parse::<Encoding>().unwrap().encode("€").unwrap().as_ref(), &[0x9f]);
+        assert_eq!(Encoding::Latin1.encoded_len("éa"), 2);
+        assert!("ebcdic-930".parse::<Encoding>().is_err());
+    }
+
+    #[test]
+    fn generate_latin1_test() {
+        fn accented_generator() -> Result<String> {
+            Ok("éüß".into())
+        }
+
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("column".into(), 3, "CHAR[3]".into(), accented_generator)],
+            "|".into(),
+            Decimal::ONE,
+        );
+        let ef = ExportFile::new(vec![t], 60, 1).unwrap().with_encoding(Encoding::Latin1);
+
+        let path = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_export_to_file(&path).unwrap();
+        let written = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // A row takes 6 bytes in Latin-1 instead of the 9 it takes in UTF-8.
+        assert_eq!(written, b"A|\xe9\xfc\xdf\n".repeat(10));
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::Table;
+use crate::generators::Generator;
+use crate::types::ColumnType;
+use crate::values::{format_date, format_timestamp_micros, parse_timestamp_micros};
+
+
+#[derive(Error, Debug)]
+pub enum EventError {
+    #[error("Invalid event start {start}, expected YYYY-MM-DD HH:MM:SS.")]
+    InvalidStart { start: String },
+    #[error("Event rate must be a positive number of events per second, got {rate}.")]
+    InvalidRate { rate: f64 },
+    #[error("Table {table} has no column {column} for event time.")]
+    UnknownColumn { table: String, column: String },
+    #[error("Column {column} of table {table} is {column_type}, event time needs a DATE or TIMESTAMP column.")]
+    NotTemporal { table: String, column: String, column_type: String },
+}
+
+
+/// Time of the `n`th event of a stream, `rate` events per second from `start` with each event moved later by up to
+/// `jitter` of the interval, so times never go back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventClock {
+    start_micros: i64,
+    rate: f64,
+    jitter: f64,
+}
+
+impl EventClock {
+    pub fn new(start: &str, rate: f64) -> Result<Self> {
+        let start_micros = parse_timestamp_micros(start)
+            .ok_or_else(|| Error::from(EventError::InvalidStart { start: start.into() }))?;
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(Error::from(EventError::InvalidRate { rate }));
+        }
+
+        Ok(EventClock { start_micros, rate, jitter: 0.0 })
+    }
+
+    /// Fraction of the interval between events, clamped to `0.0..1.0`.
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = if jitter.is_nan() { 0.0 } else { jitter.clamp(0.0, 1.0 - f64::EPSILON) };
+        self
+    }
+
+    pub fn micros_at(&self, event_index: u64, jitter: f64) -> i64 {
+        let interval = 1e6 / self.rate;
+        let offset = (event_index as f64 + self.jitter * jitter) * interval;
+        self.start_micros.saturating_add(offset as i64)
+    }
+
+    pub fn generator(self, column_type: &ColumnType) -> Generator {
+        let date = *column_type == ColumnType::Date;
+        Generator::new(move |context| {
+            let micros = self.micros_at(context.row_index, context.random.next_f64());
+            Ok(match date {
+                true => format_date(micros.div_euclid(86_400_000_000)),
+                false => format_timestamp_micros(micros),
+            })
+        })
+    }
+}
+
+
+impl Table {
+    /// Generates `column` from the clock by row index, rows of every file continue where the previous file ended so
+    /// the table reads like an event stream ordered by that column.
+    pub fn with_event_time(mut self, column: &str, clock: EventClock) -> Result<Self> {
+        let index = self.columns.iter()
+            .position(|x| x.name == column)
+            .ok_or_else(|| Error::from(EventError::UnknownColumn {
+                table: self.id_value.clone(),
+                column: column.into(),
+            }))?;
+
+        let column_type = self.columns[index].column_type.clone();
+        if !column_type.is_temporal() {
+            return Err(Error::from(EventError::NotTemporal {
+                table: self.id_value.clone(),
+                column: column.into(),
+                column_type: column_type.to_string(),
+            }));
+        }
+
+        self.replace_generator(index, clock.generator(&column_type));
+        Ok(self)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column, ExportFile};
+
+    #[test]
+    fn event_time_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)),
+                Column::new("at".into(), 26, ColumnType::Timestamp, generators::constant("")),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        );
+        let clock = EventClock::new("2024-01-01 00:00:00", 4.0).unwrap().with_jitter(0.9);
+        let ef = ExportFile::new(vec![t.clone().with_event_time("at", clock).unwrap()], 4000, 3)
+            .unwrap()
+            .with_seed(1);
+
+        let times: Vec<String> = (0..3)
+            .flat_map(|x| ef.generate_file_rows(x).unwrap().remove(0))
+            .map(|x| x[2].clone())
+            .collect();
+        assert_eq!(times.len() as u64, ef.table_total_rows(&ef.tables[0]).unwrap());
+        assert!(times.windows(2).all(|x| x[0] <= x[1]));
+        assert!(times[0].starts_with("2024-01-01 00:00:00."));
+        assert!(times[4].starts_with("2024-01-01 00:00:01."));
+
+        assert!(EventClock::new("yesterday", 1.0).is_err());
+        assert!(EventClock::new("2024-01-01", 0.0).is_err());
+        assert!(t.clone().with_event_time("id", clock).is_err());
+        assert!(t.with_event_time("missing", clock).is_err());
+    }
+}
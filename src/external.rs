@@ -0,0 +1,166 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Error, Result};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{generators, Column};
+
+
+#[derive(Error, Debug)]
+pub enum ExternalError {
+    #[error("Manifest {manifest} lists no columns of table {table}, write it with a manifest of this version.")]
+    UnknownTable { manifest: String, table: String },
+    #[error("Table {table} in {manifest} is not delimited, only delimited files can be read back.")]
+    NotDelimited { manifest: String, table: String },
+    #[error("{dataset} has no column {column}.")]
+    UnknownColumn { dataset: String, column: String },
+    #[error("File {file} is compressed, only uncompressed files can be read back.")]
+    Compressed { file: String },
+    #[error("{dataset} has no values of column {column}.")]
+    Empty { dataset: String, column: String },
+}
+
+
+/// A column of a dataset already on disk that new values are drawn from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternalSource {
+    /// A table of an export written with a manifest, read from the files the manifest lists.
+    Export { manifest: PathBuf, table: String, column: String },
+    /// A delimited extract with a header row, e.g. a dimension unloaded from a real database.
+    Extract { path: PathBuf, delimiter: String, column: String },
+}
+
+impl ExternalSource {
+    pub fn values(&self) -> Result<Vec<String>> {
+        let (source, column, values) = match self {
+            ExternalSource::Export { manifest, table, column } => {
+                (manifest, column, export_values(manifest, table, column)?)
+            }
+            ExternalSource::Extract { path, delimiter, column } => {
+                (path, column, extract_values(path, delimiter, column)?)
+            }
+        };
+
+        if values.is_empty() {
+            return Err(Error::from(ExternalError::Empty {
+                dataset: source.to_string_lossy().to_string(),
+                column: column.clone(),
+            }));
+        }
+        Ok(values)
+    }
+}
+
+
+fn export_values(manifest_path: &Path, table: &str, column: &str) -> Result<Vec<String>> {
+    let manifest: Value = serde_json::from_str(&fs::read_to_string(manifest_path)?)?;
+    let name = manifest_path.to_string_lossy().to_string();
+
+    let position = manifest["columns"][table].as_array()
+        .ok_or_else(|| Error::from(ExternalError::UnknownTable { manifest: name.clone(), table: table.into() }))?
+        .iter()
+        .position(|x| x.as_str() == Some(column))
+        .ok_or_else(|| Error::from(ExternalError::UnknownColumn {
+            dataset: format!("Table {} in {}", table, name),
+            column: column.into(),
+        }))?;
+    let delimiter = manifest["delimiters"][table].as_str()
+        .ok_or_else(|| Error::from(ExternalError::NotDelimited { manifest: name.clone(), table: table.into() }))?;
+
+    let folder = manifest_path.parent().unwrap_or(Path::new("."));
+    let prefix = format!("{}{}", table, delimiter);
+    let mut values = vec![];
+    for file in manifest["files"].as_array().into_iter().flatten() {
+        if file["rows"][table].as_u64().unwrap_or(0) == 0 {
+            continue;
+        }
+        let file_name = file["name"].as_str().unwrap_or_default();
+        if [".gz", ".zst"].iter().any(|x| file_name.ends_with(x)) {
+            return Err(Error::from(ExternalError::Compressed { file: file_name.into() }));
+        }
+
+        let content = fs::read(folder.join(file_name))?;
+        values.extend(
+            String::from_utf8_lossy(&content).lines()
+                .filter(|x| x.starts_with(&prefix))
+                .filter_map(|x| x.split(delimiter).nth(position + 1).map(|y| y.to_string()))
+        );
+    }
+
+    Ok(values)
+}
+
+
+fn extract_values(path: &Path, delimiter: &str, column: &str) -> Result<Vec<String>> {
+    let content = fs::read(path)?;
+    let content = String::from_utf8_lossy(&content);
+    let mut lines = content.lines();
+
+    let position = lines.next()
+        .and_then(|x| x.split(delimiter).position(|y| y.trim() == column))
+        .ok_or_else(|| Error::from(ExternalError::UnknownColumn {
+            dataset: path.to_string_lossy().to_string(),
+            column: column.into(),
+        }))?;
+
+    Ok(lines.filter_map(|x| x.split(delimiter).nth(position).map(|y| y.to_string())).collect())
+}
+
+
+impl Column {
+    /// Draws every value from the values of `source`, each row of the source equally likely, so the column joins
+    /// against a dataset generated by an earlier run or extracted from elsewhere.
+    pub fn draws_from(mut self, source: &ExternalSource) -> Result<Self> {
+        self.generator = generators::one_of(source.values()?);
+        Ok(self)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{ExportFile, Table};
+    use crate::manifest::MANIFEST_FILE;
+    use crate::types::ColumnType;
+
+    #[test]
+    fn draws_from_test() {
+        let customers = Table::new(
+            "customers".into(),
+            vec![
+                Column::new("name".into(), 3, ColumnType::Char(3), generators::constant("ABC")),
+                Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).as_key(),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        );
+        let ef = ExportFile::new(vec![customers], 200, 2).unwrap().with_manifest(true);
+
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_all_files(&folder).unwrap();
+        fs::write(folder.join("dim.csv"), "code;label\nX1;first\nX2;second\n").unwrap();
+        let manifest = folder.join(MANIFEST_FILE);
+        let source =
+            ExternalSource::Export { manifest: manifest.clone(), table: "customers".into(), column: "id".into() };
+        let ids = source.values().unwrap();
+        let column = Column::new("customer_id".into(), 4, ColumnType::Int, generators::constant(""));
+        let orders = Table::new("orders".into(), vec![column.draws_from(&source).unwrap()], "|".into(), Decimal::ONE);
+        let path = folder.join("dim.csv");
+        let codes = ExternalSource::Extract { path, delimiter: ";".into(), column: "code".into() }.values();
+        let missing = ExternalSource::Export { manifest, table: "customers".into(), column: "email".into() }.values();
+        fs::remove_dir_all(&folder).unwrap();
+
+        let total = ef.table_total_rows(&ef.tables[0]).unwrap();
+        assert_eq!(ids, (1..=total).map(|x| x.to_string()).collect::<Vec<String>>());
+        for row in orders.generate_rows_vec(20).unwrap() {
+            assert!(ids.contains(&row[1]));
+        }
+        assert_eq!(codes.unwrap(), vec!["X1", "X2"]);
+        assert!(missing.is_err());
+    }
+}
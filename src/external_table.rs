@@ -0,0 +1,286 @@
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::{ExportFile, Table};
+use crate::ddl::Dialect;
+use crate::encoding::Encoding;
+use crate::output_format::OutputFormat;
+use crate::types::{ColumnType, RecordTerminator};
+
+/// Every row starts with the table it belongs to, external tables read it into this column.
+pub const RECORD_TYPE_COLUMN: &str = "record_type";
+
+
+#[derive(Error, Debug)]
+pub enum ExternalTableError {
+    #[error("Table {table} is not delimited, external tables read delimited files.")]
+    NotDelimited { table: String },
+    #[error("{target} can not read table {table}, {reason}.")]
+    Unsupported { target: String, table: String, reason: String },
+}
+
+
+/// Engine reading the files written per table, see [`ExportFile::generate_files_per_table`], and where they are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternalTarget {
+    /// Folder the files are written to as the engine sees it, e.g. `hdfs:///data/export` or `s3://bucket/export`.
+    Hive { location: String },
+    SparkSql { location: String },
+    Trino { location: String },
+    /// Snowflake stage the files are uploaded to, e.g. `@exports/run_1`.
+    SnowflakeStage { stage: String },
+}
+
+impl ExternalTarget {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExternalTarget::Hive { .. } => "Hive",
+            ExternalTarget::SparkSql { .. } => "Spark SQL",
+            ExternalTarget::Trino { .. } => "Trino",
+            ExternalTarget::SnowflakeStage { .. } => "Snowflake",
+        }
+    }
+
+    fn location(&self) -> &str {
+        match self {
+            ExternalTarget::Hive { location }
+            | ExternalTarget::SparkSql { location }
+            | ExternalTarget::Trino { location } => location.trim_end_matches('/'),
+            ExternalTarget::SnowflakeStage { stage } => stage.trim_end_matches('/'),
+        }
+    }
+
+    /// Type of a column in the engine, types it does not know are read as strings.
+    pub fn type_name(&self, column_type: &ColumnType) -> String {
+        match (self, column_type) {
+            (ExternalTarget::SnowflakeStage { .. }, column_type) => Dialect::Snowflake.type_name(column_type),
+
+            (ExternalTarget::Trino { .. }, ColumnType::Int) => "INTEGER".into(),
+            (ExternalTarget::Trino { .. }, ColumnType::Text | ColumnType::Custom(_)) => "VARCHAR".into(),
+
+            // Spark reads CHAR and VARCHAR as strings that are not padded or checked.
+            (ExternalTarget::SparkSql { .. }, ColumnType::Char(_) | ColumnType::Varchar(_)) => "STRING".into(),
+            (_, ColumnType::Text | ColumnType::Custom(_)) => "STRING".into(),
+
+            (_, ColumnType::Real) => if matches!(self, ExternalTarget::Trino { .. }) { "REAL" } else { "FLOAT" }.into(),
+            (_, ColumnType::Double) => "DOUBLE".into(),
+            (_, column_type) => column_type.to_string(),
+        }
+    }
+
+    // Hive, Spark and Trino quote every name, Snowflake only the names that need it.
+    fn identifier(&self, export_file: &ExportFile, name: &str) -> String {
+        match self {
+            ExternalTarget::Hive { .. } | ExternalTarget::SparkSql { .. } => format!("`{}`", name.replace('`', "``")),
+            ExternalTarget::Trino { .. } => format!("\"{}\"", name.replace('"', "\"\"")),
+            ExternalTarget::SnowflakeStage { .. } => export_file.identifier(Dialect::Snowflake, name),
+        }
+    }
+
+    // Trino doubles quotes, the others escape them and backslashes with a backslash.
+    fn quote(&self, text: &str) -> String {
+        match self {
+            ExternalTarget::Trino { .. } => format!("'{}'", text.replace('\'', "''")),
+            _ => format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'")),
+        }
+    }
+}
+
+
+// Snowflake matches file names with a regular expression instead of a glob.
+fn pattern_regex(pattern: &str) -> String {
+    let mut regex = ".*".to_string();
+    for x in pattern.chars() {
+        match x {
+            '*' => regex.push_str("[^/]*"),
+            x if x.is_ascii_alphanumeric() || "_-/=".contains(x) => regex.push(x),
+            '\\' | ']' | '^' => regex.push_str(&format!("\\{}", x)),
+            x => regex.push_str(&format!("[{}]", x)),
+        }
+    }
+
+    regex
+}
+
+
+impl ExportFile {
+    /// `CREATE EXTERNAL TABLE` statements over the files [`ExportFile::generate_files_per_table`] writes, with their
+    /// delimiter, null representation and compression. Rows start with the table name, read into
+    /// [`RECORD_TYPE_COLUMN`]. Hive and Trino read every file of a folder, so the file name template has to give every
+    /// table a folder of its own, e.g. `{table}/part_{index}.{ext}`, Spark and Snowflake match the file names.
+    pub fn get_external_table_ddl(&self, target: ExternalTarget) -> Result<String> {
+        self.build_schema()?;
+
+        Ok(self.tables.iter()
+            .map(|x| self.external_table_ddl(&target, x))
+            .collect::<Result<Vec<String>>>()?
+            .join("\n"))
+    }
+
+
+    fn external_table_ddl(&self, target: &ExternalTarget, table: &Table) -> Result<String> {
+        let unsupported = |reason: String| Error::from(ExternalTableError::Unsupported {
+            target: target.name().into(),
+            table: table.id_value.clone(),
+            reason,
+        });
+        if table.output_format != OutputFormat::Delimited {
+            return Err(Error::from(ExternalTableError::NotDelimited { table: table.id_value.clone() }));
+        }
+        let snowflake = matches!(target, ExternalTarget::SnowflakeStage { .. });
+        let spark = matches!(target, ExternalTarget::SparkSql { .. });
+        if !snowflake && !spark && table.delimiter.chars().count() != 1 {
+            return Err(unsupported(format!("its delimiter {:?} is not a single character", table.delimiter)));
+        }
+        match &table.terminator {
+            RecordTerminator::Lf => (),
+            RecordTerminator::CrLf if snowflake || spark => (),
+            RecordTerminator::Custom(_) if snowflake => (),
+            x => return Err(unsupported(format!("its rows end in {:?}", x.as_str()))),
+        }
+        let encoding = match self.encoding {
+            Encoding::Utf8 => None,
+            Encoding::Latin1 if snowflake || spark => Some("ISO-8859-1"),
+            x => return Err(unsupported(format!("its files are written as {}", x))),
+        };
+
+        // The folder of the table's files below the location and the names of its files in that folder.
+        let pattern = self.table_file_pattern(table);
+        let (folder, files) = match pattern.rsplit_once('/') {
+            Some((folder, files)) => (Some(folder), files),
+            None => (None, pattern.as_str()),
+        };
+        let location = match folder {
+            Some(folder) => format!("{}/{}", target.location(), folder),
+            None => target.location().into(),
+        };
+        let template_folder = self.table_file_template().rsplit_once('/').map(|x| x.0).unwrap_or_default();
+        let own_folder = template_folder.contains("{table}")
+            && !template_folder.contains("{index}")
+            && !template_folder.contains("{date}");
+        if !own_folder && matches!(target, ExternalTarget::Hive { .. } | ExternalTarget::Trino { .. }) {
+            return Err(unsupported(
+                "it reads every file of a folder, use a file name template like {table}/part_{index}.{ext}".into()
+            ));
+        }
+
+        let name = table.qualifiers.iter()
+            .chain(std::iter::once(&table.id_value))
+            .map(|x| target.identifier(self, x))
+            .collect::<Vec<String>>()
+            .join(".");
+        let columns = match target {
+            ExternalTarget::SnowflakeStage { .. } => table.columns.iter()
+                .enumerate()
+                .map(|(i, x)| {
+                    // The first field is the record type.
+                    let type_name = self.type_mapping.type_name(Dialect::Snowflake, &x.column_type);
+                    let name = target.identifier(self, &x.name);
+                    format!("    {} {} AS (VALUE:c{}::{})", name, type_name, i + 2, type_name)
+                })
+                .collect::<Vec<String>>(),
+            _ => std::iter::once(format!(
+                "    {} {}",
+                target.identifier(self, RECORD_TYPE_COLUMN),
+                target.type_name(&ColumnType::Text)
+            ))
+                .chain(table.columns.iter().map(|x| {
+                    format!("    {} {}", target.identifier(self, &x.name), target.type_name(&x.column_type))
+                }))
+                .collect(),
+        }.join(",\n");
+
+        let delimiter = target.quote(&table.delimiter);
+        let null = target.quote(table.null_representation.as_str());
+        Ok(match target {
+            ExternalTarget::Hive { .. } => format!(
+                "CREATE EXTERNAL TABLE {} (\n{}\n)\nROW FORMAT DELIMITED FIELDS TERMINATED BY {} NULL DEFINED AS {}\n\
+                 STORED AS TEXTFILE\nLOCATION {};\n",
+                name, columns, delimiter, null, target.quote(&location),
+            ),
+            ExternalTarget::SparkSql { .. } => format!(
+                "CREATE TABLE {} (\n{}\n)\nUSING CSV\nOPTIONS (sep {}, nullValue {}, pathGlobFilter {}{})\n\
+                 LOCATION {};\n",
+                name,
+                columns,
+                delimiter,
+                null,
+                target.quote(files),
+                encoding.map(|x| format!(", encoding {}", target.quote(x))).unwrap_or_default(),
+                target.quote(&location),
+            ),
+            ExternalTarget::Trino { .. } => format!(
+                "CREATE TABLE {} (\n{}\n)\nWITH (\n    external_location = {},\n    format = 'TEXTFILE',\n    \
+                 textfile_field_separator = {},\n    null_format = {}\n);\n",
+                name, columns, target.quote(&location), delimiter, null,
+            ),
+            ExternalTarget::SnowflakeStage { .. } => format!(
+                "CREATE EXTERNAL TABLE {} (\n{}\n)\nLOCATION = {}/\nPATTERN = {}\nAUTO_REFRESH = FALSE\n\
+                 FILE_FORMAT = (TYPE = CSV FIELD_DELIMITER = {} RECORD_DELIMITER = {} NULL_IF = ({}){});\n",
+                name,
+                columns,
+                location,
+                target.quote(&pattern_regex(&pattern)),
+                delimiter,
+                target.quote(table.terminator.as_str()),
+                null,
+                encoding.map(|x| format!(" ENCODING = {}", target.quote(x))).unwrap_or_default(),
+            ),
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column};
+    use crate::file_name::FileNameTemplate;
+    use crate::types::NullRepresentation;
+
+    #[test]
+    fn external_table_ddl_test() {
+        let table = Table::new(
+            "customers".into(),
+            vec![
+                Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)),
+                Column::new("name".into(), 12, ColumnType::Varchar(12), generators::constant("Ana")),
+                Column::new("score".into(), 6, ColumnType::Decimal(6, 2), generators::constant("1.50")),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        ).with_null_representation(NullRepresentation::BackslashN);
+        let ef = ExportFile::new(vec![table], 1_000, 2).unwrap();
+
+        let spark = ef.get_external_table_ddl(ExternalTarget::SparkSql { location: "s3://bucket/export/".into() })
+            .unwrap();
+        assert_eq!(spark, "CREATE TABLE `customers` (\n    `record_type` STRING,\n    `id` INT,\n    \
+                           `name` STRING,\n    `score` DECIMAL(6,2)\n)\nUSING CSV\n\
+                           OPTIONS (sep '|', nullValue '\\\\N', pathGlobFilter 'customers_*.txt')\n\
+                           LOCATION 's3://bucket/export';\n");
+        let stage = ExternalTarget::SnowflakeStage { stage: "@exports".into() };
+        let snowflake = ef.get_external_table_ddl(stage).unwrap();
+        assert!(snowflake.contains("    score NUMBER(6,2) AS (VALUE:c4::NUMBER(6,2))\n)\nLOCATION = @exports/\n\
+                                    PATTERN = '.*customers_[^/]*[.]txt'\n"));
+        // Hive reads whole folders, the tables share one.
+        let hive = ExternalTarget::Hive { location: "hdfs:///export".into() };
+        assert!(ef.get_external_table_ddl(hive.clone()).is_err());
+
+        let ef = ef.with_file_name_template(FileNameTemplate::new("{table}/part_{index}.{ext}").unwrap());
+        let ddl = ef.get_external_table_ddl(hive).unwrap();
+        assert!(ddl.starts_with("CREATE EXTERNAL TABLE `customers` (\n    `record_type` STRING,\n    `id` INT,\n"));
+        assert!(ddl.ends_with("NULL DEFINED AS '\\\\N'\nSTORED AS TEXTFILE\nLOCATION 'hdfs:///export/customers';\n"));
+        let trino = ef.get_external_table_ddl(ExternalTarget::Trino { location: "s3://bucket".into() }).unwrap();
+        assert!(trino.contains("    \"record_type\" VARCHAR,\n") && trino.contains("'s3://bucket/customers'"));
+
+        // The files are where the DDL says.
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_files_per_table(&folder).unwrap();
+        assert!(folder.join("customers").join("part_1.txt").exists());
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
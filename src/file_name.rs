@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::{ExportFile, Table};
+use crate::template::substitute;
+
+pub const PLACEHOLDERS: [&str; 6] = ["table", "index", "date", "ext", "size", "files"];
+const DEFAULT: &str = "file_{size}_{files}_{index}.{ext}";
+const DEFAULT_PER_TABLE: &str = "{table}_{index}.{ext}";
+const DEFAULT_BINARY: &str = "file_{size}_{files}_{index}_{table}.{ext}";
+
+
+#[derive(Error, Debug)]
+pub enum FileNameError {
+    #[error("File name template {template} has an unknown placeholder {placeholder}.")]
+    UnknownPlaceholder { template: String, placeholder: String },
+    #[error("File name template {template} has an unclosed placeholder.")]
+    UnclosedPlaceholder { template: String },
+    #[error("File name template {template} needs {{index}} so files do not overwrite each other.")]
+    MissingIndex { template: String },
+    #[error("File name template {template} needs {{table}} to write a file per table.")]
+    MissingTable { template: String },
+}
+
+
+/// File names with `{table}`, `{index}`, `{date}` (UTC, `YYYYMMDD`), `{ext}`, `{size}` and `{files}` placeholders.
+/// Files holding every table render `{table}` as `all`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileNameTemplate {
+    template: String,
+}
+
+impl FileNameTemplate {
+    pub fn new(template: impl Into<String>) -> Result<Self> {
+        let template = template.into();
+        let mut rest = template.as_str();
+        let mut has_index = false;
+
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..].find('}')
+                .ok_or_else(|| Error::from(FileNameError::UnclosedPlaceholder { template: template.clone() }))?;
+            let placeholder = &rest[start + 1..start + end];
+
+            if !PLACEHOLDERS.contains(&placeholder) {
+                return Err(Error::from(FileNameError::UnknownPlaceholder {
+                    template: template.clone(),
+                    placeholder: placeholder.into(),
+                }));
+            }
+            has_index |= placeholder == "index";
+            rest = &rest[start + end + 1..];
+        }
+
+        if !has_index {
+            return Err(Error::from(FileNameError::MissingIndex { template }));
+        }
+
+        Ok(FileNameTemplate { template })
+    }
+
+    pub fn has_table(&self) -> bool {
+        self.template.contains("{table}")
+    }
+
+    pub fn render(&self, table: &str, index: u64, ext: &str, size: u64, files: u64) -> String {
+        let parameters: HashMap<String, String> = HashMap::from([
+            ("table".to_string(), table.to_string()),
+            ("index".to_string(), index.to_string()),
+            ("date".to_string(), today()),
+            ("ext".to_string(), ext.to_string()),
+            ("size".to_string(), size.to_string()),
+            ("files".to_string(), files.to_string()),
+        ]);
+
+        // Every placeholder was checked in new.
+        substitute(&self.template, &parameters).unwrap_or_else(|_| self.template.clone())
+    }
+}
+
+impl FromStr for FileNameTemplate {
+    type Err = Error;
+
+    fn from_str(template: &str) -> Result<Self> {
+        FileNameTemplate::new(template)
+    }
+}
+
+
+fn today() -> String {
+    let days = SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_secs() / 86400).unwrap_or(0) as i64;
+    let (year, month, day) = civil_from_days(days);
+
+    format!("{:04}{:02}{:02}", year, month, day)
+}
+
+
+// Year, month and day from days since the epoch, Howard Hinnant's algorithm.
+pub(crate) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+
+impl ExportFile {
+    pub fn with_file_name_template(mut self, file_name: FileNameTemplate) -> Self {
+        self.file_name = Some(file_name);
+        self
+    }
+
+
+    fn render_file_name(&self, default: &str, table: &str, file_index: u64, ext: &str) -> String {
+        match &self.file_name {
+            Some(x) => x.render(table, file_index, ext, self.file_size_bytes, self.number_of_files),
+            None => FileNameTemplate { template: default.into() }
+                .render(table, file_index, ext, self.file_size_bytes, self.number_of_files),
+        }
+    }
+
+
+    pub fn file_path(&self, folder_path: &Path, file_index: u64) -> PathBuf {
+        folder_path.join(self.render_file_name(DEFAULT, "all", file_index, self.compression.extension()))
+    }
+
+
+    pub fn table_file_path(&self, folder_path: &Path, table: &Table, file_index: u64) -> PathBuf {
+        folder_path.join(self.render_file_name(DEFAULT_PER_TABLE, &table.id_value, file_index, self.compression.extension()))
+    }
+
+
+    pub(crate) fn binary_file_path(&self, folder_path: &Path, table: &Table, file_index: u64, ext: &str) -> PathBuf {
+        folder_path.join(self.render_file_name(DEFAULT_BINARY, &table.id_value, file_index, ext))
+    }
+
+
+    // Template of the files written per table, see `table_file_path`.
+    pub(crate) fn table_file_template(&self) -> &str {
+        self.file_name.as_ref().map(|x| x.template.as_str()).unwrap_or(DEFAULT_PER_TABLE)
+    }
+
+
+    // Names of every file of `table` written per table, the index and date are `*`, e.g. `customers_*.txt`.
+    pub(crate) fn table_file_pattern(&self, table: &Table) -> String {
+        let parameters: HashMap<String, String> = HashMap::from([
+            ("table".to_string(), table.id_value.clone()),
+            ("index".to_string(), "*".to_string()),
+            ("date".to_string(), "*".to_string()),
+            ("ext".to_string(), self.compression.extension().to_string()),
+            ("size".to_string(), self.file_size_bytes.to_string()),
+            ("files".to_string(), self.number_of_files.to_string()),
+        ]);
+
+        let template = self.table_file_template();
+        substitute(template, &parameters).unwrap_or_else(|_| template.into())
+    }
+
+
+    /// Writing a file per table needs `{table}` in a custom template.
+    pub(crate) fn check_table_file_names(&self) -> Result<()> {
+        match &self.file_name {
+            Some(x) if !x.has_table() => Err(Error::from(FileNameError::MissingTable { template: x.template.clone() })),
+            _ => Ok(()),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::Column;
+    use crate::types::ColumnType;
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    #[test]
+    fn file_name_template_test() {
+        let t = Table::new(
+            "orders".into(),
+            vec![Column::new("column".into(), 3, ColumnType::Char(3), simple_generator)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+        let ef = ExportFile::new(vec![t], 60, 2).unwrap();
+        let folder = Path::new("out");
+
+        assert_eq!(ef.file_path(folder, 1), folder.join("file_30_2_1.txt"));
+
+        let ef = ef.with_file_name_template("extract_{table}_{index}.{ext}".parse().unwrap());
+        assert_eq!(ef.file_path(folder, 1), folder.join("extract_all_1.txt"));
+        assert_eq!(ef.table_file_path(folder, &ef.tables[0], 0), folder.join("extract_orders_0.txt"));
+        assert_eq!(FileNameTemplate::new("{date}_{index}").unwrap().render("", 0, "", 0, 0).len(), 10);
+
+        assert!(FileNameTemplate::new("{table}_{idx}").is_err());
+        assert!(FileNameTemplate::new("{table}.txt").is_err());
+        assert!(FileNameTemplate::new("{index").is_err());
+        assert!(ef.with_file_name_template("{index}.{ext}".parse().unwrap()).check_table_file_names().is_err());
+    }
+}
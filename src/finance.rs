@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+use thiserror::Error;
+
+use crate::{generators, Column, Table};
+use crate::generators::finance::{self as accounts, DoubleEntry, ACCOUNTS};
+use crate::types::{ColumnSize, ColumnType};
+
+pub const PRESETS: [&str; 2] = ["chart_of_accounts", "journal_entry"];
+/// Lines of every journal entry transaction of the preset.
+pub const LINES_PER_TRANSACTION: u64 = 4;
+
+
+#[derive(Error, Debug)]
+pub enum LedgerError {
+    #[error("Unknown preset {preset}, expected chart_of_accounts or journal_entry.")]
+    UnknownPreset { preset: String },
+    #[error("Table {table} has no column {column} to check balances with.")]
+    MissingColumn { table: String, column: String },
+    #[error("Amount {value} of table {table} is not a number.")]
+    InvalidAmount { table: String, value: String },
+    #[error("Transaction {transaction} has debits of {debits} and credits of {credits}.")]
+    Unbalanced { transaction: String, debits: Decimal, credits: Decimal },
+}
+
+
+/// Columns of a preset, journal entries reference the `id` of the `chart_of_accounts` table.
+pub fn preset_columns(preset: &str) -> Result<Vec<Column>> {
+    let key = || {
+        Column::new("id".into(), ColumnSize::range(1, 10), ColumnType::Int, generators::sequence(1, 1)).as_key()
+    };
+
+    Ok(match preset {
+        "chart_of_accounts" => vec![
+            key(),
+            Column::new("code".into(), ColumnSize::range(4, 7), ColumnType::Varchar(10), accounts::account_code()),
+            Column::new("name".into(), ColumnSize::range(4, 22), ColumnType::Varchar(40), accounts::account_name()),
+            Column::new("type".into(), ColumnSize::range(5, 9), ColumnType::Varchar(9), accounts::account_type()),
+        ],
+        "journal_entry" => {
+            let entry = DoubleEntry::new(LINES_PER_TRANSACTION)?;
+            vec![
+                key(),
+                Column::new("transaction_id".into(), ColumnSize::range(1, 10), ColumnType::Int, entry.transaction_id()),
+                Column::new("account_id".into(), ColumnSize::range(1, 3), ColumnType::Int,
+                            generators::random_int(1, ACCOUNTS.len() as i64))
+                    .references("chart_of_accounts", "id"),
+                Column::new("side".into(), 1, ColumnType::Char(1), entry.side()),
+                Column::new("amount".into(), ColumnSize::range(4, 8), ColumnType::Decimal(12, 2), entry.amount()),
+                Column::new("posted_date".into(), 10, ColumnType::Date,
+                            entry.posting_date("2020-01-01", "2025-12-31")?),
+            ]
+        }
+        _ => return Err(Error::from(LedgerError::UnknownPreset { preset: preset.into() })),
+    })
+}
+
+
+/// Rows of a preset that belong together, every file holds whole journal entry transactions.
+pub fn preset_row_group(preset: &str) -> u64 {
+    match preset {
+        "journal_entry" => LINES_PER_TRANSACTION,
+        _ => 1,
+    }
+}
+
+
+/// A chart of accounts with the standard accounts once and journal entries taking the rest of the export.
+pub fn tables(percent_size_accounts: Decimal) -> Result<Vec<Table>> {
+    PRESETS.iter()
+        .zip([percent_size_accounts, Decimal::ONE - percent_size_accounts])
+        .map(|(preset, percent_size)| {
+            Ok(Table::new(preset.to_string(), preset_columns(preset)?, "|".into(), percent_size)
+                .with_row_group(preset_row_group(preset)))
+        })
+        .collect()
+}
+
+
+/// Checks that every transaction in `rows` of a journal entry table has equal debits and credits, so a
+/// reconciliation test can assert the data it starts from is consistent. Null amounts count as 0.
+pub fn check_balance(table: &Table, rows: &[Vec<String>]) -> Result<()> {
+    let position = |name: &str| {
+        table.columns.iter()
+            .position(|x| x.name == name)
+            .map(|x| x + 1)
+            .ok_or_else(|| {
+                Error::from(LedgerError::MissingColumn { table: table.id_value.clone(), column: name.into() })
+            })
+    };
+    let (transaction, side, amount) = (position("transaction_id")?, position("side")?, position("amount")?);
+
+    let mut balances: BTreeMap<&str, (Decimal, Decimal)> = BTreeMap::new();
+    for row in rows {
+        let value = match table.is_null(&table.columns[amount - 1], &row[amount]) {
+            true => Decimal::ZERO,
+            false => Decimal::from_str(&row[amount]).map_err(|_| {
+                Error::from(LedgerError::InvalidAmount { table: table.id_value.clone(), value: row[amount].clone() })
+            })?,
+        };
+        let balance = balances.entry(row[transaction].as_str()).or_default();
+        match row[side].as_str() {
+            "D" => balance.0 += value,
+            _ => balance.1 += value,
+        }
+    }
+
+    match balances.into_iter().find(|(_, (debits, credits))| debits != credits) {
+        Some((transaction, (debits, credits))) => {
+            Err(Error::from(LedgerError::Unbalanced { transaction: transaction.into(), debits, credits }))
+        }
+        None => Ok(()),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{ExportFile, SizeSpec};
+
+    #[test]
+    fn journal_entry_test() {
+        // Accounts are listed once, journal entries are sized by bytes.
+        let accounts_once = || SizeSpec::Rows(HashMap::from([("chart_of_accounts".to_string(), ACCOUNTS.len() as u64)]));
+        let ef = ExportFile::new(tables(Decimal::new(1, 1)).unwrap(), 20_000, 2)
+            .unwrap()
+            .with_size_spec(accounts_once())
+            .unwrap();
+        let files = (0..2).map(|x| ef.generate_file_rows(x).unwrap()).collect::<Vec<_>>();
+        let accounts = files.iter().flat_map(|x| x[0].iter().map(|y| y[1].clone())).collect::<Vec<String>>();
+        let entries = files.iter().flat_map(|x| x[1].clone()).collect::<Vec<Vec<String>>>();
+
+        assert!(files.iter().all(|x| (x[1].len() as u64).is_multiple_of(LINES_PER_TRANSACTION)));
+        assert!(files[0][0].iter().all(|x| ACCOUNTS.iter().any(|y| y.0 == x[2] && y.1 == x[3] && y.2 == x[4])));
+        for file in files.iter() {
+            check_balance(&ef.tables[1], &file[1]).unwrap();
+        }
+        assert!(entries.iter().all(|x| accounts.contains(&x[3])));
+        // Lines of a transaction are posted on the same day.
+        assert!(entries.chunks(4).all(|x| x.iter().all(|y| y[6] == x[0][6])));
+
+        // Sizes that do not divide into whole transactions are rounded down to them.
+        for size in [20_100, 20_300] {
+            let ef = ExportFile::new(tables(Decimal::new(1, 1)).unwrap(), size, 2)
+                .unwrap()
+                .with_size_spec(accounts_once())
+                .unwrap();
+            let rows = ef.generate_file_rows(1).unwrap().swap_remove(1);
+            check_balance(&ef.tables[1], &rows).unwrap();
+        }
+        let ef = ef.with_size_spec(SizeSpec::Rows(HashMap::from([("journal_entry".to_string(), 42)]))).unwrap();
+        assert_eq!(ef.table_total_rows(&ef.tables[1]).unwrap(), 40);
+
+        let mut unbalanced = entries.clone();
+        unbalanced[0][5] = "0.00".into();
+        assert!(check_balance(&ef.tables[1], &unbalanced).is_err());
+        assert!(preset_columns("ledger").is_err());
+
+        let config = r#"{"size": "20KB", "tables": [
+            {"preset": "chart_of_accounts", "percent_size": 0.1},
+            {"preset": "journal_entry", "percent_size": 0.9}
+        ]}"#;
+        let ef = crate::builder::ExportFileBuilder::from_json(config).unwrap().build().unwrap();
+        assert_eq!(ef.tables[1].columns[4].name, "amount");
+        assert_eq!(ef.tables[1].row_group(), LINES_PER_TRANSACTION);
+    }
+}
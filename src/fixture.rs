@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::{Column, ExportFile, SizeSpec, Table};
+use crate::config::generator_from_spec;
+use crate::ddl::Dialect;
+use crate::types::ColumnType;
+
+pub const DEFAULT_FIXTURE_ROWS: u64 = 100;
+
+
+#[derive(Error, Debug)]
+pub enum FixtureError {
+    #[error("Invalid column {column} of fixture table {table}: {reason}.")]
+    InvalidColumn { table: String, column: String, reason: String },
+}
+
+
+/// Fixture from `table => columns` pairs, e.g. `fixture!("customers" => "id INT sequence key").rows(10)`, see
+/// [`Fixture::table`] for the column syntax.
+#[macro_export]
+macro_rules! fixture {
+    ($($table:expr => $columns:expr),+ $(,)?) => {
+        $crate::fixture::Fixture::new()$(.table($table, $columns))+
+    };
+}
+
+
+/// Compact spec of a small seeded dataset for the tests of downstream crates.
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    tables: Vec<(String, String)>,
+    rows: u64,
+    seed: u64,
+}
+
+impl Default for Fixture {
+    fn default() -> Self {
+        Fixture::new()
+    }
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        Fixture { tables: vec![], rows: DEFAULT_FIXTURE_ROWS, seed: 0 }
+    }
+
+    /// Adds a table from comma separated `name TYPE generator` columns, followed by `key` or `-> table.column`
+    /// when needed. Generator arguments are JSON values, e.g. `random_int(0, 10)` or `one_of("A", "B")`.
+    pub fn table(mut self, id: &str, columns: &str) -> Self {
+        self.tables.push((id.into(), columns.into()));
+        self
+    }
+
+    pub fn rows(mut self, rows: u64) -> Self {
+        self.rows = rows;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn build(self) -> Result<ExportFile> {
+        let count = self.tables.len().max(1) as u64;
+        let share = Decimal::ONE / Decimal::from(count);
+
+        let mut tables = vec![];
+        for (i, (id, columns)) in self.tables.iter().enumerate() {
+            let columns = split_top_level(columns, |x| x == ',')
+                .into_iter()
+                .filter(|x| !x.trim().is_empty())
+                .map(|x| fixture_column(id, x))
+                .collect::<Result<Vec<Column>>>()?;
+            let percent_size = match i as u64 + 1 == count {
+                true => Decimal::ONE - share * Decimal::from(count - 1),
+                false => share,
+            };
+            tables.push(columns.into_iter()
+                .fold(Table::builder(id.as_str()).percent_size(percent_size), |x, y| x.add_column(y))
+                .build()?);
+        }
+
+        // Byte sizes only have to let every row fit, the row counts are fixed.
+        let widest = tables.iter().map(|x| x.columns.iter().map(|y| y.size.max()).sum::<u64>()).max().unwrap_or(1);
+        let rows = tables.iter().map(|x| (x.id_value.clone(), self.rows)).collect();
+
+        ExportFile::new(tables, (widest + 1) * count * 2, 1)?
+            .with_seed(self.seed)
+            .with_size_spec(SizeSpec::Rows(rows))
+    }
+
+    /// Rows of every table by table id, without the table id in front.
+    pub fn generate(self) -> Result<HashMap<String, Vec<Vec<String>>>> {
+        let export_file = self.build()?;
+        let rows = export_file.generate_file_rows(0)?;
+
+        Ok(export_file.tables.iter()
+            .zip(rows)
+            .map(|(table, rows)| (table.id_value.clone(), rows.into_iter().map(|x| x[1..].to_vec()).collect()))
+            .collect())
+    }
+
+    /// Writes the fixture like `generate_fixture` into a new temporary folder that is removed on drop.
+    pub fn generate_temp_dir(self) -> Result<FixtureDir> {
+        let rows = self.rows;
+        let export_file = self.build()?;
+        let dir = FixtureDir {
+            path: std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>())),
+        };
+        export_file.generate_fixture(&dir.path, rows)?;
+
+        Ok(dir)
+    }
+}
+
+
+pub struct FixtureDir {
+    path: PathBuf,
+}
+
+impl FixtureDir {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for FixtureDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+
+// Splits on separators outside of parentheses and double quotes.
+fn split_top_level(value: &str, separator: impl Fn(char) -> bool) -> Vec<&str> {
+    let mut parts = vec![];
+    let (mut depth, mut quoted, mut start) = (0, false, 0);
+
+    for (i, x) in value.char_indices() {
+        match x {
+            '"' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth -= 1,
+            x if depth == 0 && !quoted && separator(x) => {
+                parts.push(&value[start..i]);
+                start = i + x.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+
+    parts
+}
+
+
+// Bytes a value of the type takes in the text output at most, used as the column size.
+fn type_size(column_type: &ColumnType) -> u64 {
+    match column_type {
+        ColumnType::Boolean => 5,
+        ColumnType::SmallInt => 6,
+        ColumnType::Int => 11,
+        ColumnType::BigInt => 20,
+        ColumnType::Real | ColumnType::Double => 24,
+        ColumnType::Decimal(precision, _) => *precision as u64 + 2,
+        ColumnType::Char(length) | ColumnType::Varchar(length) => *length as u64,
+        ColumnType::Date => 10,
+        ColumnType::Timestamp => 19,
+        ColumnType::Text | ColumnType::Custom(_) => 32,
+    }
+}
+
+
+fn fixture_column(table: &str, spec: &str) -> Result<Column> {
+    let invalid = |reason: &str| Error::from(FixtureError::InvalidColumn {
+        table: table.into(),
+        column: spec.trim().into(),
+        reason: reason.into(),
+    });
+    let tokens: Vec<&str> = split_top_level(spec, char::is_whitespace).into_iter().filter(|x| !x.is_empty()).collect();
+    let [name, column_type, generator, options @ ..] = tokens.as_slice() else {
+        return Err(invalid("expected name, type and generator"));
+    };
+
+    let generator = match generator.split_once('(') {
+        Some((name, args)) => {
+            let args: Value = serde_json::from_str(&format!("[{}]", args.strip_suffix(')').unwrap_or(args)))
+                .map_err(|_| invalid("generator arguments are not JSON values"))?;
+            json!({"name": name, "args": args})
+        }
+        None => json!(generator),
+    };
+    let column_type = ColumnType::from(*column_type);
+    let mut column = Column::new((*name).into(), type_size(&column_type), column_type, generator_from_spec(&generator)?);
+
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match *option {
+            "key" => column = column.as_key(),
+            "->" => {
+                let (table, referenced) = options.next()
+                    .and_then(|x| x.split_once('.'))
+                    .ok_or_else(|| invalid("expected table.column after ->"))?;
+                column = column.references(table, referenced);
+            }
+            _ => return Err(invalid(&format!("unknown option {}", option))),
+        }
+    }
+
+    Ok(column)
+}
+
+
+impl ExportFile {
+    /// The same tables, seeds and namespace with exactly `rows_per_table` rows of every table in one file, so
+    /// references stay valid while the data is small enough for a unit test or a bug report.
+    pub fn fixture(&self, rows_per_table: u64) -> Result<ExportFile> {
+        let rows = self.tables.iter()
+            .map(|x| (x.id_value.clone(), rows_per_table))
+            .collect();
+
+        let mut fixture = ExportFile::new(self.tables.clone(), self.file_size_bytes.max(2), 1)?
+            .with_namespace(self.namespace.clone())?
+            .with_interleave(self.interleave)
+            .with_size_spec(SizeSpec::Rows(rows))?;
+        fixture.seed = self.seed;
+
+        Ok(fixture)
+    }
+
+
+    /// Writes the fixture as csv, every enabled binary format, a schema and Postgres DDL into `folder_path`.
+    pub fn generate_fixture(&self, folder_path: &Path, rows_per_table: u64) -> Result<()> {
+        let fixture = self.fixture(rows_per_table)?.with_manifest(true);
+
+        fixture.generate_all_files(folder_path)?;
+        #[cfg(feature = "parquet")]
+        fixture.generate_parquet(folder_path)?;
+        #[cfg(feature = "avro")]
+        fixture.generate_avro(folder_path)?;
+        fixture.schema_json(&folder_path.join("schema.json"))?;
+        fs::write(folder_path.join("schema.sql"), fixture.get_schema_ddl(Dialect::Postgres)?)?;
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column, Table};
+    use crate::types::ColumnType;
+
+    #[test]
+    fn fixture_test() {
+        let customers = Table::new(
+            "A".into(),
+            vec![Column::new("id".into(), 4, ColumnType::Int, generators::random_int(0, 999_999_999)).as_key()],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+        let orders = Table::new(
+            "B".into(),
+            vec![Column::new("customer_id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).references("A", "id")],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+        let ef = ExportFile::new(vec![customers, orders], 1 << 24, 64).unwrap().with_seed(4);
+
+        let rows = ef.fixture(DEFAULT_FIXTURE_ROWS).unwrap().generate_file_rows(0).unwrap();
+        let keys: HashSet<&String> = rows[0].iter().map(|x| &x[1]).collect();
+        assert_eq!(rows[0].len(), 100);
+        assert_eq!(rows[1].len(), 100);
+        assert!(rows[1].iter().all(|x| keys.contains(&x[1])));
+        assert_eq!(rows[0][..10], ef.generate_file_rows(0).unwrap()[0][..10]);
+
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_fixture(&folder, 5).unwrap();
+        let mut names: Vec<String> = fs::read_dir(&folder).unwrap()
+            .map(|x| x.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|x| !x.ends_with(".parquet") && !x.ends_with(".avro"))
+            .collect();
+        names.sort();
+        let content = fs::read_to_string(ef.fixture(5).unwrap().file_path(&folder, 0)).unwrap();
+        fs::remove_dir_all(&folder).unwrap();
+
+        assert_eq!(names[1..], ["manifest.json", "schema.json", "schema.sql"]);
+        assert_eq!(content.lines().count(), 10);
+    }
+
+    #[test]
+    fn fixture_macro_test() {
+        let spec = || crate::fixture!(
+            "customers" => "id INT sequence(5, 1) key, segment CHAR(1) one_of(\"A\", \"B\")",
+            "orders" => "customer_id INT sequence -> customers.id, amount DECIMAL(8, 2) uniform(0, 100)",
+        ).rows(20).seed(3);
+
+        let rows = spec().generate().unwrap();
+        let keys: HashSet<&String> = rows["customers"].iter().map(|x| &x[0]).collect();
+        assert_eq!(rows["customers"][0], ["5", rows["customers"][0][1].as_str()]);
+        assert!(["A", "B"].contains(&rows["customers"][0][1].as_str()));
+        assert_eq!(rows["orders"].len(), 20);
+        assert!(rows["orders"].iter().all(|x| keys.contains(&x[0])));
+        assert_eq!(spec().generate().unwrap(), rows);
+
+        let dir = spec().generate_temp_dir().unwrap();
+        let path = dir.path().to_path_buf();
+        assert!(path.join("schema.sql").exists());
+        drop(dir);
+        assert!(!path.exists());
+
+        assert!(Fixture::new().table("A", "id INT").build().is_err());
+        assert!(Fixture::new().table("A", "id INT sequence unique").build().is_err());
+    }
+}
@@ -0,0 +1,731 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::Column;
+use crate::random::RandomSource;
+use crate::types::{ColumnSize, ColumnType};
+use crate::values::{format_date, format_timestamp, parse_date, parse_timestamp_micros};
+
+#[cfg(feature = "fake")]
+mod fake;
+#[cfg(feature = "fake")]
+pub use fake::{address, first_name, last_name, person_name, phone, Locale, LocaleError};
+#[cfg(feature = "healthcare")]
+pub mod healthcare;
+pub mod adtech;
+pub mod finance;
+pub mod iot;
+pub mod retail;
+pub mod survey;
+pub mod telecom;
+
+
+#[derive(Error, Debug)]
+pub enum GeneratorError {
+    #[error("Sequence overflowed at row {row_index}.")]
+    SequenceOverflow { row_index: u64 },
+    #[error("Invalid {distribution} distribution: {reason}.")]
+    InvalidDistribution { distribution: String, reason: String },
+    #[error("Generator depends on the values of earlier columns and can only be used in a row.")]
+    NeedsRow,
+    #[error("Column {column} is not generated before this one, only earlier columns can be read.")]
+    UnknownRowValue { column: String },
+    #[error("Value {value} of column {column} is not a number.")]
+    NotANumber { column: String, value: String },
+}
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct RowContext<'a> {
+    pub row_index: u64,
+    pub random: &'a dyn RandomSource,
+}
+
+
+/// Values of the columns generated before the current one in its row.
+#[derive(Debug, Clone, Copy)]
+pub struct RowValues<'a> {
+    columns: &'a [Column],
+    values: &'a [String],
+}
+
+impl<'a> RowValues<'a> {
+    /// `values` are those of the first `values.len()` of `columns`.
+    pub fn new(columns: &'a [Column], values: &'a [String]) -> Self {
+        RowValues { columns, values }
+    }
+
+    pub fn get(&self, column: &str) -> Result<&'a str> {
+        self.columns.iter()
+            .take(self.values.len())
+            .position(|x| x.name == column)
+            .map(|x| self.values[x].as_str())
+            .ok_or_else(|| Error::from(GeneratorError::UnknownRowValue { column: column.into() }))
+    }
+
+    pub fn parse<T: FromStr>(&self, column: &str) -> Result<T> {
+        let value = self.get(column)?;
+        value.parse()
+            .map_err(|_| Error::from(GeneratorError::NotANumber { column: column.into(), value: value.into() }))
+    }
+}
+
+
+type GeneratorFn = dyn Fn(&RowContext) -> Result<String> + Send + Sync;
+type RowGeneratorFn = dyn Fn(&RowContext, &RowValues) -> Result<String> + Send + Sync;
+type InverseFn = dyn Fn(&str) -> Option<u64> + Send + Sync;
+type NullRowsFn = dyn Fn(u64) -> bool + Send + Sync;
+
+#[derive(Clone)]
+pub struct Generator {
+    generator: Arc<GeneratorFn>,
+    row_generator: Option<Arc<RowGeneratorFn>>,
+    inverse: Option<Arc<InverseFn>>,
+    by_row_index: bool,
+    null_rows: Option<Arc<NullRowsFn>>,
+    spec: Option<Arc<Value>>,
+}
+
+impl Generator {
+    pub fn new(generator: impl Fn(&RowContext) -> Result<String> + Send + Sync + 'static) -> Self {
+        Generator {
+            generator: Arc::new(generator),
+            row_generator: None,
+            inverse: None,
+            by_row_index: false,
+            null_rows: None,
+            spec: None,
+        }
+    }
+
+    /// A generator that reads the values of earlier columns of its row, e.g. `net = gross - tax`.
+    pub fn from_row(generator: impl Fn(&RowContext, &RowValues) -> Result<String> + Send + Sync + 'static) -> Self {
+        Generator {
+            generator: Arc::new(|_| Err(Error::from(GeneratorError::NeedsRow))),
+            row_generator: Some(Arc::new(generator)),
+            inverse: None,
+            by_row_index: false,
+            null_rows: None,
+            spec: None,
+        }
+    }
+
+    pub fn depends_on_row(&self) -> bool {
+        self.row_generator.is_some()
+    }
+
+    /// Maps a generated value back to the row index that produced it, so the row can be located without a scan.
+    pub fn with_inverse(mut self, inverse: impl Fn(&str) -> Option<u64> + Send + Sync + 'static) -> Self {
+        self.inverse = Some(Arc::new(inverse));
+        self
+    }
+
+    /// Marks values as a function of the row index, e.g. the side of a journal line, so a seeded sample of the
+    /// generator is never taken for all of its values even when it has few of them.
+    pub fn by_row_index(mut self) -> Self {
+        self.by_row_index = true;
+        self
+    }
+
+    pub fn is_by_row_index(&self) -> bool {
+        self.by_row_index
+    }
+
+    /// Rows whose value is null whatever the null rate of the column, e.g. the questions a survey skips.
+    pub fn with_null_rows(mut self, is_null: impl Fn(u64) -> bool + Send + Sync + 'static) -> Self {
+        self.null_rows = Some(Arc::new(is_null));
+        self
+    }
+
+    pub fn has_null_rows(&self) -> bool {
+        self.null_rows.is_some()
+    }
+
+    pub fn is_null_row(&self, row_index: u64) -> bool {
+        self.null_rows.as_ref().is_some_and(|x| x(row_index))
+    }
+
+    /// Names the built in generator and arguments this one was made with, e.g. `{"name": "sequence", "args": [1, 1]}`,
+    /// so definitions using it can be saved and loaded again, see [`crate::config::generator_from_spec`].
+    pub fn with_spec(mut self, spec: Value) -> Self {
+        self.spec = Some(Arc::new(spec));
+        self
+    }
+
+    pub fn spec(&self) -> Option<&Value> {
+        self.spec.as_deref()
+    }
+
+    /// Number of distinct values of a built in generator whose spec bounds them, e.g. 10 for `random_int(1, 10)`.
+    pub fn value_space(&self) -> Option<u64> {
+        let spec = self.spec()?;
+        let args = &spec["args"];
+        let span = |from: i64, to: i64| (from <= to).then(|| (to as i128 - from as i128 + 1).min(u64::MAX as i128) as u64);
+        let seconds = |x: &Value| parse_timestamp_micros(x.as_str()?).map(|y| y.div_euclid(1_000_000));
+
+        match spec["name"].as_str()? {
+            "constant" => Some(1),
+            "one_of" => Some(args.as_array()?.iter().map(|x| x.to_string()).collect::<HashSet<String>>().len() as u64),
+            "random_int" => span(args[0].as_i64()?, args[1].as_i64()?),
+            "random_decimal" => {
+                let (precision, scale) = (args[0].as_u64()? as u32, args[1].as_u64()? as u32);
+                10u64.checked_pow(precision.saturating_sub(scale).min(18) + scale.min(18)).or(Some(u64::MAX))
+            }
+            "random_date" => span(parse_date(args[0].as_str()?)?, parse_date(args[1].as_str()?)?),
+            "random_timestamp" => span(seconds(&args[0])?, seconds(&args[1])?),
+            "zipf" => args[0].as_u64(),
+            _ => None,
+        }
+    }
+
+    /// Value `index` of the ones counted by [`Generator::value_space`], for the built in generators that pick each of
+    /// them with the same probability. `None` for other generators and for an index past the value space.
+    pub fn value_at(&self, index: u64) -> Option<String> {
+        let spec = self.spec()?;
+        let args = &spec["args"];
+        let offset = |from: i64, to: i64| (index as i128 <= to as i128 - from as i128).then(|| from as i128 + index as i128);
+        let seconds = |x: &Value| parse_timestamp_micros(x.as_str()?).map(|y| y.div_euclid(1_000_000));
+
+        match spec["name"].as_str()? {
+            "constant" => (index == 0).then(|| args[0].as_str().map(String::from)).flatten(),
+            "one_of" => {
+                let mut seen: HashSet<String> = HashSet::new();
+                args.as_array()?.iter()
+                    .filter(|x| seen.insert(x.to_string()))
+                    .nth(index as usize)
+                    .and_then(|x| x.as_str().map(String::from))
+            }
+            "random_int" => offset(args[0].as_i64()?, args[1].as_i64()?).map(|x| x.to_string()),
+            "random_decimal" => {
+                let (precision, scale) = (args[0].as_u64()? as u32, args[1].as_u64()? as u32);
+                let (integer_digits, scale) = (precision.saturating_sub(scale).min(18), scale.min(18));
+                if index >= 10u64.checked_pow(integer_digits + scale)? {
+                    return None;
+                }
+                let (integer, fraction) = (index / 10u64.pow(scale), index % 10u64.pow(scale));
+                Some(match scale {
+                    0 => integer.to_string(),
+                    _ => format!("{}.{:0width$}", integer, fraction, width = scale as usize),
+                })
+            }
+            "random_date" => offset(parse_date(args[0].as_str()?)?, parse_date(args[1].as_str()?)?)
+                .map(|x| format_date(x as i64)),
+            "random_timestamp" => offset(seconds(&args[0])?, seconds(&args[1])?).map(|x| format_timestamp(x as i64)),
+            _ => None,
+        }
+    }
+
+    pub fn generate(&self, context: &RowContext) -> Result<String> {
+        (self.generator)(context)
+    }
+
+    pub fn generate_in_row(&self, context: &RowContext, row: &RowValues) -> Result<String> {
+        match &self.row_generator {
+            Some(x) => x(context, row),
+            None => (self.generator)(context),
+        }
+    }
+
+    /// Row index of `value`, `None` when the generator has no inverse or could not produce the value.
+    pub fn invert(&self, value: &str) -> Option<u64> {
+        self.inverse.as_ref().and_then(|x| x(value))
+    }
+}
+
+impl fmt::Debug for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Generator")
+    }
+}
+
+impl<F> From<F> for Generator
+where
+    F: Fn() -> Result<String> + Send + Sync + 'static,
+{
+    fn from(generator: F) -> Self {
+        Generator::new(move |_| generator())
+    }
+}
+
+
+/// Position of the row in its table across all files starting at `start`, file `n` continues where file `n - 1`
+/// ended and every value is computed from the row's own index, so no counter is shared between threads.
+pub fn row_number(start: u64) -> Generator {
+    Generator::new(move |context| {
+        context.row_index.checked_add(start)
+            .map(|x| x.to_string())
+            .ok_or_else(|| Error::from(GeneratorError::SequenceOverflow { row_index: context.row_index }))
+    })
+        .with_inverse(move |value| value.parse::<u64>().ok()?.checked_sub(start))
+        .with_spec(json!({"name": "row_number", "args": [start]}))
+}
+
+
+/// Values `start, start + step, ...` derived from the row index, so rows generated in parallel stay distinct and ordered.
+pub fn sequence(start: i64, step: i64) -> Generator {
+    Generator::new(move |context| {
+        (context.row_index as i128)
+            .checked_mul(step as i128)
+            .and_then(|x| x.checked_add(start as i128))
+            .filter(|x| i64::try_from(*x).is_ok())
+            .map(|x| x.to_string())
+            .ok_or_else(|| Error::from(GeneratorError::SequenceOverflow { row_index: context.row_index }))
+    })
+        .with_inverse(move |value| {
+            let offset = value.parse::<i128>().ok()? - start as i128;
+            match step {
+                0 => None,
+                _ if offset % step as i128 != 0 => None,
+                _ => u64::try_from(offset / step as i128).ok(),
+            }
+        })
+        .with_spec(json!({"name": "sequence", "args": [start, step]}))
+}
+
+
+
+pub fn constant(value: impl Into<String>) -> Generator {
+    let value = value.into();
+    let spec = json!({"name": "constant", "args": [value]});
+    Generator::new(move |_| Ok(value.clone())).with_spec(spec)
+}
+
+
+/// One of the values, each equally likely.
+pub fn one_of(values: Vec<String>) -> Generator {
+    let spec = json!({"name": "one_of", "args": values});
+    Generator::new(move |context| {
+        if values.is_empty() {
+            return Err(invalid("one_of", "values must not be empty"));
+        }
+        Ok(values[context.random.below(values.len() as u64) as usize].clone())
+    })
+        .with_spec(spec)
+}
+
+
+/// Integers in `low..=high`.
+pub fn random_int(low: i64, high: i64) -> Generator {
+    Generator::new(move |context| {
+        if low > high {
+            return Err(invalid("random_int", "low must not be greater than high"));
+        }
+        let span = (high as i128 - low as i128 + 1) as u128;
+        let offset = if span > u64::MAX as u128 { context.random.next_u64() } else { context.random.below(span as u64) };
+        Ok((low as i128 + offset as i128).to_string())
+    })
+        .with_spec(json!({"name": "random_int", "args": [low, high]}))
+}
+
+
+/// `minuend - subtrahend` of two earlier numeric columns.
+pub fn difference(minuend: impl Into<String>, subtrahend: impl Into<String>) -> Generator {
+    let (minuend, subtrahend) = (minuend.into(), subtrahend.into());
+    let spec = json!({"name": "difference", "args": [minuend, subtrahend]});
+    Generator::from_row(move |_, row| {
+        Ok((row.parse::<f64>(&minuend)? - row.parse::<f64>(&subtrahend)?).to_string())
+    })
+        .with_spec(spec)
+}
+
+
+/// One of the values listed for the value of an earlier column, e.g. a city of the row's country.
+pub fn one_of_for(column: impl Into<String>, values: HashMap<String, Vec<String>>) -> Generator {
+    let column = column.into();
+    let spec = json!({"name": "one_of_for", "args": [column, values]});
+    Generator::from_row(move |context, row| {
+        let key = row.get(&column)?;
+        let choices = values.get(key)
+            .filter(|x| !x.is_empty())
+            .ok_or_else(|| invalid("one_of_for", &format!("no values for {} {}", column, key)))?;
+        Ok(choices[context.random.below(choices.len() as u64) as usize].clone())
+    })
+        .with_spec(spec)
+}
+
+
+/// Non negative decimals with up to `precision - scale` integer digits and exactly `scale` fraction digits.
+pub fn random_decimal(precision: u32, scale: u32) -> Generator {
+    let integer_digits = precision.saturating_sub(scale).min(18);
+    let scale = scale.min(18);
+
+    Generator::new(move |context| {
+        let integer = context.random.below(10u64.pow(integer_digits));
+        Ok(match scale {
+            0 => integer.to_string(),
+            _ => format!("{}.{:0width$}", integer, context.random.below(10u64.pow(scale)), width = scale as usize),
+        })
+    })
+        .with_spec(json!({"name": "random_decimal", "args": [precision, scale]}))
+}
+
+
+/// Dates in `from..=to`, both `YYYY-MM-DD`.
+pub fn random_date(from: &str, to: &str) -> Generator {
+    let range = parse_date(from).zip(parse_date(to)).filter(|(x, y)| x <= y);
+
+    Generator::new(move |context| {
+        let (from, to) = range.ok_or_else(|| invalid("random_date", "expected YYYY-MM-DD bounds, from before to"))?;
+        Ok(format_date(from + context.random.below((to - from) as u64 + 1) as i64))
+    })
+        .with_spec(json!({"name": "random_date", "args": [from, to]}))
+}
+
+
+/// Timestamps with whole seconds in `from..=to`, both `YYYY-MM-DD HH:MM:SS`.
+pub fn random_timestamp(from: &str, to: &str) -> Generator {
+    let seconds = |x: &str| parse_timestamp_micros(x).map(|y| y.div_euclid(1_000_000));
+    let range = seconds(from).zip(seconds(to)).filter(|(x, y)| x <= y);
+
+    Generator::new(move |context| {
+        let (from, to) = range.ok_or_else(|| invalid("random_timestamp", "expected timestamp bounds, from before to"))?;
+        Ok(format_timestamp(from + context.random.below((to - from) as u64 + 1) as i64))
+    })
+        .with_spec(json!({"name": "random_timestamp", "args": [from, to]}))
+}
+
+
+const DEFAULT_FROM: &str = "2000-01-01";
+const DEFAULT_TO: &str = "2030-12-31";
+const DEFAULT_TEXT_LENGTH: u64 = 100;
+const DEFAULT_CUSTOM_LENGTH: u64 = 16;
+
+
+/// Generator and size for a column declared with only a type: random numbers, dates between 2000 and 2030 and
+/// strings filling `CHAR(n)` or up to `VARCHAR(n)`.
+pub fn for_type(column_type: &ColumnType) -> (Generator, ColumnSize) {
+    // Uniform numbers mostly have all their digits.
+    let digits = |x: u64| ColumnSize::Range { min: 1, max: x, avg: x };
+
+    match column_type {
+        ColumnType::Boolean => (one_of(vec!["true".into(), "false".into()]), ColumnSize::range(4, 5)),
+        ColumnType::SmallInt => (random_int(0, i16::MAX as i64), digits(5)),
+        ColumnType::Int => (random_int(0, i32::MAX as i64), digits(10)),
+        ColumnType::BigInt => (random_int(0, i64::MAX), digits(19)),
+        ColumnType::Real => (random_decimal(9, 4), digits(10)),
+        ColumnType::Double => (random_decimal(15, 6), digits(16)),
+        ColumnType::Decimal(precision, scale) => {
+            let integer_digits = precision.saturating_sub(*scale).min(18) as u64;
+            let fraction = if *scale > 0 { *scale.min(&18) as u64 + 1 } else { 0 };
+            (random_decimal(*precision, *scale), digits(integer_digits.max(1) + fraction))
+        }
+        ColumnType::Char(length) => (random_string(*length as usize), ColumnSize::Fixed(*length as u64)),
+        ColumnType::Varchar(length) => {
+            (random_string_between(1, *length as usize), ColumnSize::range(1, *length as u64))
+        }
+        ColumnType::Text => {
+            (random_string_between(1, DEFAULT_TEXT_LENGTH as usize), ColumnSize::range(1, DEFAULT_TEXT_LENGTH))
+        }
+        ColumnType::Date => (random_date(DEFAULT_FROM, DEFAULT_TO), ColumnSize::Fixed(10)),
+        ColumnType::Timestamp => (
+            random_timestamp(&format!("{} 00:00:00", DEFAULT_FROM), &format!("{} 23:59:59", DEFAULT_TO)),
+            ColumnSize::Fixed(19),
+        ),
+        ColumnType::Custom(_) => {
+            (random_string(DEFAULT_CUSTOM_LENGTH as usize), ColumnSize::Fixed(DEFAULT_CUSTOM_LENGTH))
+        }
+    }
+}
+
+
+const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+
+/// Alphanumeric strings of exactly `length` characters.
+pub fn random_string(length: usize) -> Generator {
+    random_string_between(length, length)
+}
+
+
+/// Alphanumeric strings of `min` to `max` characters, uniformly distributed, for `ColumnSize::range(min, max)`.
+pub fn random_string_between(min: usize, max: usize) -> Generator {
+    let (min, max) = (min.min(max), min.max(max));
+
+    Generator::new(move |context| {
+        let length = if max > min { min + context.random.below((max - min) as u64 + 1) as usize } else { min };
+        Ok((0..length)
+            .map(|_| ALPHABET[context.random.below(ALPHABET.len() as u64) as usize] as char)
+            .collect())
+    })
+        .with_spec(json!({"name": "random_string", "args": [min, max]}))
+}
+
+
+fn invalid(distribution: &str, reason: &str) -> Error {
+    Error::from(GeneratorError::InvalidDistribution { distribution: distribution.into(), reason: reason.into() })
+}
+
+
+fn standard_normal(random: &dyn RandomSource) -> f64 {
+    // Box-Muller, 1 - u keeps the logarithm finite.
+    let u = 1.0 - random.next_f64();
+    let v = random.next_f64();
+    (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+}
+
+
+pub fn uniform(low: f64, high: f64) -> Generator {
+    Generator::new(move |context| {
+        if low.is_nan() || high.is_nan() || low > high {
+            return Err(invalid("uniform", "low must not be greater than high"));
+        }
+        Ok((low + (high - low) * context.random.next_f64()).to_string())
+    })
+        .with_spec(json!({"name": "uniform", "args": [low, high]}))
+}
+
+
+pub fn normal(mean: f64, stddev: f64) -> Generator {
+    Generator::new(move |context| {
+        if stddev.is_nan() || stddev < 0.0 {
+            return Err(invalid("normal", "stddev must not be negative"));
+        }
+        Ok((mean + stddev * standard_normal(context.random)).to_string())
+    })
+        .with_spec(json!({"name": "normal", "args": [mean, stddev]}))
+}
+
+
+/// `exp(X)` where `X` is normal with the given mean and stddev.
+pub fn lognormal(mean: f64, stddev: f64) -> Generator {
+    Generator::new(move |context| {
+        if stddev.is_nan() || stddev < 0.0 {
+            return Err(invalid("lognormal", "stddev must not be negative"));
+        }
+        Ok((mean + stddev * standard_normal(context.random)).exp().to_string())
+    })
+        .with_spec(json!({"name": "lognormal", "args": [mean, stddev]}))
+}
+
+
+pub fn exponential(rate: f64) -> Generator {
+    Generator::new(move |context| {
+        if rate.is_nan() || rate <= 0.0 {
+            return Err(invalid("exponential", "rate must be positive"));
+        }
+        Ok((-(1.0 - context.random.next_f64()).ln() / rate).to_string())
+    })
+        .with_spec(json!({"name": "exponential", "args": [rate]}))
+}
+
+
+/// Ranks `1..=n` where rank `k` is drawn with probability proportional to `1 / k^exponent`.
+pub fn zipf(n: u64, exponent: f64) -> Generator {
+    let cumulative: Arc<Vec<f64>> = Arc::new(
+        (1..=n)
+            .scan(0.0, |sum, k| {
+                *sum += 1.0 / (k as f64).powf(exponent);
+                Some(*sum)
+            })
+            .collect()
+    );
+
+    Generator::new(move |context| {
+        let total = match cumulative.last() {
+            Some(x) if x.is_finite() && exponent >= 0.0 => *x,
+            _ => return Err(invalid("zipf", "n must be positive and exponent not negative")),
+        };
+        let point = context.random.next_f64() * total;
+        let rank = cumulative.partition_point(|x| *x <= point).min(cumulative.len() - 1) + 1;
+        Ok(rank.to_string())
+    })
+        .with_spec(json!({"name": "zipf", "args": [n, exponent]}))
+}
+
+
+/// Values from bucket `i` spanning `boundaries[i]..boundaries[i + 1]` with probability proportional to
+/// `frequencies[i]`, uniform within the bucket.
+pub fn histogram(boundaries: Vec<f64>, frequencies: Vec<f64>) -> Generator {
+    let spec = json!({"name": "histogram", "args": [boundaries, frequencies]});
+    let cumulative: Arc<Vec<f64>> = Arc::new(
+        frequencies.iter()
+            .scan(0.0, |sum, x| {
+                *sum += x;
+                Some(*sum)
+            })
+            .collect()
+    );
+    let valid = boundaries.len() == frequencies.len() + 1
+        && boundaries.windows(2).all(|x| x[0] <= x[1])
+        && frequencies.iter().all(|x| *x >= 0.0)
+        && cumulative.last().is_some_and(|x| x.is_finite() && *x > 0.0);
+
+    Generator::new(move |context| {
+        if !valid {
+            return Err(invalid(
+                "histogram",
+                "needs one more ascending boundary than frequencies and a positive total frequency",
+            ));
+        }
+        let point = context.random.next_f64() * cumulative[cumulative.len() - 1];
+        let bucket = cumulative.partition_point(|x| *x <= point).min(cumulative.len() - 1);
+        let (low, high) = (boundaries[bucket], boundaries[bucket + 1]);
+        Ok((low + (high - low) * context.random.next_f64()).to_string())
+    })
+        .with_spec(spec)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{SeededRandom, ThreadRandom};
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    #[test]
+    fn from_fn_test() {
+        let generator = Generator::from(simple_generator);
+
+        assert_eq!(generator.generate(&RowContext { row_index: 5, random: &ThreadRandom }).unwrap(), "ABC");
+    }
+
+    #[test]
+    fn sequence_test() {
+        let generator = sequence(100, 5);
+
+        assert_eq!(generator.generate(&RowContext { row_index: 0, random: &ThreadRandom }).unwrap(), "100");
+        assert_eq!(generator.generate(&RowContext { row_index: 3, random: &ThreadRandom }).unwrap(), "115");
+        assert!(sequence(i64::MAX, 1).generate(&RowContext { row_index: 1, random: &ThreadRandom }).is_err());
+        assert_eq!(generator.invert("115"), Some(3));
+        assert_eq!(generator.invert("116"), None);
+        assert_eq!(generator.invert("95"), None);
+
+        let generator = row_number(1);
+        let last = RowContext { row_index: u64::MAX - 1, random: &ThreadRandom };
+        assert_eq!(generator.generate(&last).unwrap(), u64::MAX.to_string());
+        assert!(generator.generate(&RowContext { row_index: u64::MAX, random: &ThreadRandom }).is_err());
+        assert_eq!(generator.invert("1"), Some(0));
+        assert_eq!(generator.invert("0"), None);
+    }
+
+    #[test]
+    fn simple_generators_test() {
+        let context = RowContext { row_index: 0, random: &ThreadRandom };
+
+        assert_eq!(constant("x").generate(&context).unwrap(), "x");
+        assert!(["a", "b"].contains(&one_of(vec!["a".into(), "b".into()]).generate(&context).unwrap().as_str()));
+        assert!(one_of(vec![]).generate(&context).is_err());
+        assert_eq!(random_int(-2, -2).generate(&context).unwrap(), "-2");
+        assert!(random_int(i64::MIN, i64::MAX).generate(&context).is_ok());
+        assert_eq!(random_string(12).generate(&context).unwrap().len(), 12);
+    }
+
+    #[test]
+    fn value_at_test() {
+        let one_of = one_of(vec!["a".into(), "b".into(), "a".into(), "c".into()]);
+        assert_eq!((0..3).filter_map(|x| one_of.value_at(x)).collect::<Vec<String>>(), ["a", "b", "c"]);
+        assert_eq!(one_of.value_at(3), None);
+        assert_eq!(one_of.value_space(), Some(3));
+
+        assert_eq!(constant("x").value_at(0).as_deref(), Some("x"));
+        assert_eq!(random_int(-2, 2).value_at(4).as_deref(), Some("2"));
+        assert_eq!(random_int(-2, 2).value_at(5), None);
+        assert_eq!(random_decimal(4, 2).value_at(1234).as_deref(), Some("12.34"));
+        assert_eq!(random_decimal(4, 2).value_at(5).as_deref(), Some("0.05"));
+        assert_eq!(random_decimal(40, 2).value_at(0), None);
+        assert_eq!(random_date("2024-02-28", "2024-03-01").value_at(1).as_deref(), Some("2024-02-29"));
+        assert_eq!(
+            random_timestamp("2024-01-01 00:00:00", "2024-01-02 00:00:00").value_at(61).as_deref(),
+            Some("2024-01-01 00:01:01")
+        );
+        assert_eq!(zipf(10, 1.0).value_at(0), None);
+    }
+
+    #[test]
+    fn row_generators_test() {
+        let columns = vec![
+            crate::Column::new("gross".into(), 4, crate::types::ColumnType::Double, constant("10")),
+            crate::Column::new("tax".into(), 4, crate::types::ColumnType::Double, constant("2.5")),
+            crate::Column::new("country".into(), 2, crate::types::ColumnType::Char(2), constant("HR")),
+        ];
+        let values: Vec<String> = vec!["10".into(), "2.5".into(), "HR".into()];
+        let row = RowValues::new(&columns, &values);
+        let context = RowContext { row_index: 0, random: &ThreadRandom };
+
+        assert_eq!(difference("gross", "tax").generate_in_row(&context, &row).unwrap(), "7.5");
+        let cities = HashMap::from([("HR".to_string(), vec!["Zagreb".to_string()])]);
+        assert_eq!(one_of_for("country", cities).generate_in_row(&context, &row).unwrap(), "Zagreb");
+
+        assert!(difference("gross", "tax").generate(&context).is_err());
+        assert!(difference("gross", "tax").generate_in_row(&context, &RowValues::new(&columns, &values[..1])).is_err());
+        assert!(difference("gross", "country").generate_in_row(&context, &row).is_err());
+    }
+
+    #[test]
+    fn for_type_test() {
+        let random = SeededRandom::new(3);
+        let context = RowContext { row_index: 0, random: &random };
+
+        let sql_types = [
+            "BOOLEAN", "SMALLINT", "INT", "BIGINT", "REAL", "DOUBLE", "DECIMAL(10,2)", "DECIMAL(2,2)", "CHAR(4)",
+            "VARCHAR(8)", "TEXT", "DATE", "TIMESTAMP", "UUID",
+        ];
+        for sql_type in sql_types {
+            let (generator, size) = for_type(&ColumnType::from(sql_type));
+            for _ in 0..50 {
+                let value = generator.generate(&context).unwrap();
+                assert!(value.len() as u64 >= size.min() && value.len() as u64 <= size.max(), "{} {}", sql_type, value);
+            }
+        }
+
+        let (generator, _) = for_type(&ColumnType::Decimal(5, 2));
+        assert!(generator.generate(&context).unwrap().split_once('.').unwrap().1.len() == 2);
+        assert!(random_date("2024-01-01", "2023-01-01").generate(&context).is_err());
+        assert_eq!(random_date("2024-02-29", "2024-02-29").generate(&context).unwrap(), "2024-02-29");
+        assert_eq!(
+            random_timestamp("2024-01-01 10:00:00", "2024-01-01 10:00:00").generate(&context).unwrap(),
+            "2024-01-01 10:00:00"
+        );
+    }
+
+    fn sample(generator: &Generator, count: u64) -> Vec<f64> {
+        let random = SeededRandom::new(42);
+        (0..count)
+            .map(|row_index| generator.generate(&RowContext { row_index, random: &random }).unwrap().parse().unwrap())
+            .collect()
+    }
+
+    fn mean(values: &[f64]) -> f64 {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+
+    #[test]
+    fn distributions_test() {
+        assert!(sample(&uniform(2.0, 4.0), 1000).iter().all(|x| (2.0..4.0).contains(x)));
+        assert!((mean(&sample(&normal(10.0, 2.0), 10000)) - 10.0).abs() < 0.2);
+        assert!((mean(&sample(&exponential(0.5), 10000)) - 2.0).abs() < 0.2);
+        assert!(sample(&lognormal(0.0, 1.0), 1000).iter().all(|x| *x > 0.0));
+
+        let ranks = sample(&zipf(100, 1.2), 10000);
+        let ones = ranks.iter().filter(|x| **x == 1.0).count();
+        let tens = ranks.iter().filter(|x| **x == 10.0).count();
+        assert!(ranks.iter().all(|x| (1.0..=100.0).contains(x)));
+        assert!(ones > 5 * tens);
+
+        assert!(normal(0.0, -1.0).generate(&RowContext { row_index: 0, random: &ThreadRandom }).is_err());
+        assert!(zipf(0, 1.0).generate(&RowContext { row_index: 0, random: &ThreadRandom }).is_err());
+    }
+
+    #[test]
+    fn histogram_test() {
+        let values = sample(&histogram(vec![0.0, 10.0, 100.0, 1000.0], vec![8.0, 2.0, 0.0]), 10000);
+        let low = values.iter().filter(|x| **x < 10.0).count();
+
+        assert!(values.iter().all(|x| (0.0..100.0).contains(x)));
+        assert!((low as f64 / 10000.0 - 0.8).abs() < 0.03);
+
+        let context = RowContext { row_index: 0, random: &ThreadRandom };
+        assert!(histogram(vec![0.0, 1.0], vec![1.0, 1.0]).generate(&context).is_err());
+        assert!(histogram(vec![1.0, 0.0], vec![1.0]).generate(&context).is_err());
+        assert!(histogram(vec![0.0, 1.0], vec![0.0]).generate(&context).is_err());
+    }
+}
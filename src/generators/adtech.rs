@@ -0,0 +1,312 @@
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::events::EventClock;
+use crate::generators::Generator;
+use crate::random::{mix, RandomSource, SeededRandom};
+use crate::values::format_timestamp_micros;
+
+
+#[derive(Error, Debug)]
+pub enum FunnelError {
+    #[error("A funnel needs at least 1 {what}.")]
+    Empty { what: String },
+    #[error("Every {what} needs at least 1 {of} before it.")]
+    InvalidRate { what: String, of: String },
+    #[error("Attribution windows have to be at least 1 second.")]
+    ZeroWindow,
+}
+
+
+pub const PLACEMENTS: [&str; 4] = ["feed", "sidebar", "search", "video"];
+pub const CONVERSION_TYPES: [&str; 3] = ["purchase", "signup", "add_to_cart"];
+// Streams of the seeded draws of clicks and conversions, impressions use the row index alone.
+const CLICK_STREAM: u64 = u64::MAX / 41;
+const CONVERSION_STREAM: u64 = u64::MAX / 43;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Impression {
+    pub campaign: u64,
+    pub ad: u64,
+    pub user: u64,
+    pub placement: &'static str,
+    pub micros: i64,
+    /// Price of the impression in millionths of a currency unit.
+    pub cost_micros: u64,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Click {
+    pub impression: u64,
+    pub micros: i64,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conversion {
+    pub click: u64,
+    pub conversion_type: &'static str,
+    pub micros: i64,
+    pub revenue_cents: u64,
+}
+
+
+/// Impressions of ads shown to `users` users, one click for every `impressions_per_click` impressions and one
+/// conversion for every `clicks_per_conversion` clicks. Click `j` is one of the impressions `j * impressions_per_click`
+/// onwards and happens within the click window after it, conversions follow their click the same way within the
+/// attribution window. Every row is derived from its index, so the funnel holds across files at any generation rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdFunnel {
+    users: u64,
+    campaigns: u64,
+    ads_per_campaign: u64,
+    impressions_per_click: u64,
+    clicks_per_conversion: u64,
+    clock: EventClock,
+    click_window: u64,
+    conversion_window: u64,
+    seed: u64,
+}
+
+impl AdFunnel {
+    /// A funnel of 2% click through and 5% conversion rates, 10 impressions a second from `start`.
+    pub fn new(users: u64, start: &str) -> Result<Self> {
+        if users == 0 {
+            return Err(Error::from(FunnelError::Empty { what: "user".into() }));
+        }
+
+        Ok(AdFunnel {
+            users,
+            campaigns: 20,
+            ads_per_campaign: 5,
+            impressions_per_click: 50,
+            clicks_per_conversion: 20,
+            clock: EventClock::new(start, 10.0)?.with_jitter(0.9),
+            click_window: 30 * 60,
+            conversion_window: 7 * 86_400,
+            seed: 0,
+        })
+    }
+
+    pub fn with_campaigns(mut self, campaigns: u64, ads_per_campaign: u64) -> Result<Self> {
+        for (count, what) in [(campaigns, "campaign"), (ads_per_campaign, "ad per campaign")] {
+            if count == 0 {
+                return Err(Error::from(FunnelError::Empty { what: what.into() }));
+            }
+        }
+        self.campaigns = campaigns;
+        self.ads_per_campaign = ads_per_campaign;
+        Ok(self)
+    }
+
+    /// Impressions for every click and clicks for every conversion, 50 and 20 by default.
+    pub fn with_rates(mut self, impressions_per_click: u64, clicks_per_conversion: u64) -> Result<Self> {
+        if impressions_per_click == 0 {
+            return Err(Error::from(FunnelError::InvalidRate { what: "click".into(), of: "impression".into() }));
+        }
+        if clicks_per_conversion == 0 {
+            return Err(Error::from(FunnelError::InvalidRate { what: "conversion".into(), of: "click".into() }));
+        }
+        self.impressions_per_click = impressions_per_click;
+        self.clicks_per_conversion = clicks_per_conversion;
+        Ok(self)
+    }
+
+    /// Seconds a click follows its impression and a conversion its click within, 30 minutes and 7 days by default.
+    pub fn with_windows(mut self, click_seconds: u64, conversion_seconds: u64) -> Result<Self> {
+        if click_seconds == 0 || conversion_seconds == 0 {
+            return Err(Error::from(FunnelError::ZeroWindow));
+        }
+        self.click_window = click_seconds;
+        self.conversion_window = conversion_seconds;
+        Ok(self)
+    }
+
+    /// Impressions are shown `rate` a second from `start` on.
+    pub fn with_clock(mut self, start: &str, rate: f64) -> Result<Self> {
+        self.clock = EventClock::new(start, rate)?.with_jitter(0.9);
+        Ok(self)
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+
+    /// Row ratio of clicks to impressions.
+    pub fn clicks_per_impression(&self) -> f64 {
+        1.0 / self.impressions_per_click as f64
+    }
+
+    /// Row ratio of conversions to clicks.
+    pub fn conversions_per_click(&self) -> f64 {
+        1.0 / self.clicks_per_conversion as f64
+    }
+
+    pub fn click_window(&self) -> u64 {
+        self.click_window
+    }
+
+    pub fn conversion_window(&self) -> u64 {
+        self.conversion_window
+    }
+
+
+    pub fn impression(&self, row_index: u64) -> Impression {
+        let random = SeededRandom::new(mix(self.seed, row_index));
+        let campaign = random.below(self.campaigns);
+
+        Impression {
+            campaign,
+            ad: campaign * self.ads_per_campaign + random.below(self.ads_per_campaign),
+            user: random.below(self.users),
+            placement: PLACEMENTS[random.below(PLACEMENTS.len() as u64) as usize],
+            micros: self.clock.micros_at(row_index, random.next_f64()),
+            // A CPM of 0.50 to 4.50.
+            cost_micros: 500 + random.below(4000),
+        }
+    }
+
+
+    pub fn click(&self, row_index: u64) -> Click {
+        let random = SeededRandom::new(mix(mix(self.seed, row_index), CLICK_STREAM));
+        let impression = parent(row_index, self.impressions_per_click, &random);
+        // Most clicks come within seconds, a few near the end of the window.
+        let delay = 1 + (random.next_f64().powi(4) * (self.click_window - 1) as f64) as i64;
+
+        Click { impression, micros: self.impression(impression).micros + delay * 1_000_000 }
+    }
+
+
+    pub fn conversion(&self, row_index: u64) -> Conversion {
+        let random = SeededRandom::new(mix(mix(self.seed, row_index), CONVERSION_STREAM));
+        let click = parent(row_index, self.clicks_per_conversion, &random);
+        let delay = 1 + random.below(self.conversion_window) as i64;
+        let conversion_type = CONVERSION_TYPES[random.below(CONVERSION_TYPES.len() as u64) as usize];
+
+        Conversion {
+            click,
+            conversion_type,
+            micros: self.click(click).micros + delay * 1_000_000,
+            revenue_cents: match conversion_type {
+                "purchase" => 500 + random.below(20_000),
+                _ => 0,
+            },
+        }
+    }
+
+
+    fn generator(&self, value: impl Fn(AdFunnel, u64) -> String + Send + Sync + 'static) -> Generator {
+        let funnel = *self;
+        Generator::new(move |context| Ok(value(funnel, context.row_index))).by_row_index()
+    }
+
+    pub fn impression_campaign_id(&self) -> Generator {
+        self.generator(|funnel, x| (funnel.impression(x).campaign + 1).to_string())
+    }
+
+    pub fn impression_ad_id(&self) -> Generator {
+        self.generator(|funnel, x| (funnel.impression(x).ad + 1).to_string())
+    }
+
+    pub fn impression_user_id(&self) -> Generator {
+        self.generator(|funnel, x| (funnel.impression(x).user + 1).to_string())
+    }
+
+    pub fn impression_placement(&self) -> Generator {
+        self.generator(|funnel, x| funnel.impression(x).placement.to_string())
+    }
+
+    pub fn impression_time(&self) -> Generator {
+        self.generator(|funnel, x| format_timestamp_micros(funnel.impression(x).micros))
+    }
+
+    /// Cost with six decimals.
+    pub fn impression_cost(&self) -> Generator {
+        self.generator(|funnel, x| format!("0.{:06}", funnel.impression(x).cost_micros))
+    }
+
+    /// 1 based id of the impression clicked.
+    pub fn click_impression_id(&self) -> Generator {
+        self.generator(|funnel, x| (funnel.click(x).impression + 1).to_string())
+    }
+
+    pub fn click_ad_id(&self) -> Generator {
+        self.generator(|funnel, x| (funnel.impression(funnel.click(x).impression).ad + 1).to_string())
+    }
+
+    pub fn click_user_id(&self) -> Generator {
+        self.generator(|funnel, x| (funnel.impression(funnel.click(x).impression).user + 1).to_string())
+    }
+
+    pub fn click_time(&self) -> Generator {
+        self.generator(|funnel, x| format_timestamp_micros(funnel.click(x).micros))
+    }
+
+    /// 1 based id of the click converted.
+    pub fn conversion_click_id(&self) -> Generator {
+        self.generator(|funnel, x| (funnel.conversion(x).click + 1).to_string())
+    }
+
+    pub fn conversion_user_id(&self) -> Generator {
+        self.generator(|funnel, x| {
+            (funnel.impression(funnel.click(funnel.conversion(x).click).impression).user + 1).to_string()
+        })
+    }
+
+    /// `purchase`, `signup` or `add_to_cart`.
+    pub fn conversion_type(&self) -> Generator {
+        self.generator(|funnel, x| funnel.conversion(x).conversion_type.to_string())
+    }
+
+    pub fn conversion_time(&self) -> Generator {
+        self.generator(|funnel, x| format_timestamp_micros(funnel.conversion(x).micros))
+    }
+
+    /// Revenue with two decimals, zero for anything but a purchase.
+    pub fn conversion_revenue(&self) -> Generator {
+        self.generator(|funnel, x| {
+            let cents = funnel.conversion(x).revenue_cents;
+            format!("{}.{:02}", cents / 100, cents % 100)
+        })
+    }
+}
+
+
+// Row `index` of a table with a row for every `per` rows of its parent is one of the first half of its `per` parent
+// rows. Row counts of ratios round to the nearest row, which keeps the last row on a parent row that exists.
+fn parent(index: u64, per: u64, random: &SeededRandom) -> u64 {
+    index * per + random.below(per.div_ceil(2))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ad_funnel_test() {
+        let funnel = AdFunnel::new(1000, "2025-03-01 00:00:00").unwrap().with_seed(4);
+        let clicks: Vec<Click> = (0..2000).map(|x| funnel.click(x)).collect();
+
+        // Clicks keep the order of the impressions they click, each a different one.
+        assert!(clicks.windows(2).all(|x| x[0].impression < x[1].impression));
+        for click in clicks.iter() {
+            let shown = funnel.impression(click.impression).micros;
+            assert!(click.micros > shown && click.micros <= shown + 30 * 60 * 1_000_000);
+        }
+        for conversion in (0..100).map(|x| funnel.conversion(x)) {
+            let clicked = funnel.click(conversion.click).micros;
+            assert!(conversion.micros > clicked && conversion.micros <= clicked + 7 * 86_400 * 1_000_000);
+            assert_eq!(conversion.revenue_cents > 0, conversion.conversion_type == "purchase");
+        }
+        // 5 impressions at 3 per click round to 2 clicks, the second still clicks one of the 5.
+        assert!(funnel.with_rates(3, 1).unwrap().click(1).impression < 5);
+        assert!(AdFunnel::new(0, "2025-03-01 00:00:00").is_err());
+        assert!(funnel.with_windows(0, 1).is_err());
+        assert!(funnel.with_rates(0, 1).is_err());
+    }
+}
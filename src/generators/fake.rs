@@ -0,0 +1,174 @@
+use std::str::FromStr;
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::generators::Generator;
+use crate::random::RandomSource;
+
+
+#[derive(Error, Debug)]
+pub enum LocaleError {
+    #[error("Unknown locale {locale}.")]
+    UnknownLocale { locale: String },
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Locale {
+    Hr,
+    En,
+    De,
+}
+
+impl FromStr for Locale {
+    type Err = Error;
+
+    fn from_str(locale: &str) -> Result<Self> {
+        match locale.to_ascii_lowercase().as_str() {
+            "hr" | "hr_hr" | "hr-hr" => Ok(Locale::Hr),
+            "en" | "en_us" | "en-us" => Ok(Locale::En),
+            "de" | "de_de" | "de-de" => Ok(Locale::De),
+            _ => Err(Error::from(LocaleError::UnknownLocale { locale: locale.into() })),
+        }
+    }
+}
+
+
+struct LocaleData {
+    first_names: &'static [&'static str],
+    last_names: &'static [&'static str],
+    streets: &'static [&'static str],
+    // (city, postal code, region)
+    cities: &'static [(&'static str, &'static str, &'static str)],
+    // `#` is replaced by a random digit.
+    phone_formats: &'static [&'static str],
+}
+
+const HR: LocaleData = LocaleData {
+    first_names: &["Ivan", "Marko", "Luka", "Josip", "Tomislav", "Ana", "Ivana", "Petra", "Maja", "Katarina"],
+    last_names: &["Horvat", "Kovačević", "Babić", "Marić", "Jurić", "Novak", "Kovačić", "Knežević", "Vuković", "Marković"],
+    streets: &["Ilica", "Vukovarska", "Savska cesta", "Ulica kralja Zvonimira", "Frankopanska", "Ulica grada Vukovara"],
+    cities: &[
+        ("Zagreb", "10000", ""),
+        ("Split", "21000", ""),
+        ("Rijeka", "51000", ""),
+        ("Osijek", "31000", ""),
+        ("Zadar", "23000", ""),
+    ],
+    phone_formats: &["+385 91 ### ####", "+385 98 ### ####", "+385 99 ### ####", "+385 1 ### ####"],
+};
+
+const EN: LocaleData = LocaleData {
+    first_names: &["James", "John", "Robert", "Michael", "William", "Mary", "Patricia", "Jennifer", "Linda", "Elizabeth"],
+    last_names: &["Smith", "Johnson", "Williams", "Brown", "Jones", "Garcia", "Miller", "Davis", "Wilson", "Moore"],
+    streets: &["Main St", "Oak Ave", "Maple St", "Cedar Ln", "Elm St", "Washington Blvd"],
+    cities: &[
+        ("Springfield", "62701", "IL"),
+        ("Austin", "73301", "TX"),
+        ("Portland", "97201", "OR"),
+        ("Denver", "80201", "CO"),
+        ("Columbus", "43085", "OH"),
+    ],
+    phone_formats: &["+1 (###) ###-####"],
+};
+
+const DE: LocaleData = LocaleData {
+    first_names: &["Lukas", "Leon", "Finn", "Paul", "Jonas", "Anna", "Mia", "Emma", "Lena", "Hannah"],
+    last_names: &["Müller", "Schmidt", "Schneider", "Fischer", "Weber", "Meyer", "Wagner", "Becker", "Schulz", "Hoffmann"],
+    streets: &["Hauptstraße", "Bahnhofstraße", "Gartenstraße", "Schulstraße", "Dorfstraße", "Lindenstraße"],
+    cities: &[
+        ("Berlin", "10115", ""),
+        ("Hamburg", "20095", ""),
+        ("München", "80331", ""),
+        ("Köln", "50667", ""),
+        ("Frankfurt am Main", "60311", ""),
+    ],
+    phone_formats: &["+49 151 ########", "+49 160 ########", "+49 30 #######"],
+};
+
+impl Locale {
+    fn data(&self) -> &'static LocaleData {
+        match self {
+            Locale::Hr => &HR,
+            Locale::En => &EN,
+            Locale::De => &DE,
+        }
+    }
+}
+
+
+fn pick<T: Copy>(values: &[T], random: &dyn RandomSource) -> T {
+    values[random.below(values.len() as u64) as usize]
+}
+
+
+pub fn first_name(locale: Locale) -> Generator {
+    Generator::new(move |context| Ok(pick(locale.data().first_names, context.random).to_string()))
+}
+
+
+pub fn last_name(locale: Locale) -> Generator {
+    Generator::new(move |context| Ok(pick(locale.data().last_names, context.random).to_string()))
+}
+
+
+pub fn person_name(locale: Locale) -> Generator {
+    Generator::new(move |context| {
+        let data = locale.data();
+        Ok(format!("{} {}", pick(data.first_names, context.random), pick(data.last_names, context.random)))
+    })
+}
+
+
+pub fn address(locale: Locale) -> Generator {
+    Generator::new(move |context| {
+        let data = locale.data();
+        let street = pick(data.streets, context.random);
+        let number = context.random.below(200) + 1;
+        let (city, postal_code, region) = pick(data.cities, context.random);
+
+        Ok(match locale {
+            Locale::En => format!("{} {}, {}, {} {}", number, street, city, region, postal_code),
+            Locale::Hr | Locale::De => format!("{} {}, {} {}", street, number, postal_code, city),
+        })
+    })
+}
+
+
+pub fn phone(locale: Locale) -> Generator {
+    Generator::new(move |context| {
+        Ok(pick(locale.data().phone_formats, context.random)
+            .chars()
+            .map(|x| match x {
+                '#' => char::from(b'0' + context.random.below(10) as u8),
+                x => x,
+            })
+            .collect())
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::RowContext;
+    use crate::random::SeededRandom;
+
+    #[test]
+    fn fake_test() {
+        let random = SeededRandom::new(1);
+        let context = RowContext { row_index: 0, random: &random };
+
+        let name = person_name(Locale::Hr).generate(&context).unwrap();
+        assert!(HR.first_names.contains(&name.split(' ').next().unwrap()));
+
+        let phone = phone(Locale::En).generate(&context).unwrap();
+        assert_eq!(phone.len(), "+1 (###) ###-####".len());
+        assert!(phone.starts_with("+1 (") && !phone.contains('#'));
+
+        assert!(address(Locale::De).generate(&context).unwrap().contains(", "));
+        assert_eq!(Locale::from_str("hr-HR").unwrap(), Locale::Hr);
+        assert!(Locale::from_str("xx").is_err());
+    }
+}
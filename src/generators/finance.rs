@@ -0,0 +1,208 @@
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::generators::Generator;
+use crate::random::{mix, RandomSource, SeededRandom};
+use crate::values::{format_date, parse_date};
+
+
+#[derive(Error, Debug)]
+pub enum FinanceError {
+    #[error("A double entry transaction needs at least 2 lines, got {lines}.")]
+    TooFewLines { lines: u64 },
+    #[error("Amounts of a double entry transaction must be at least 0.01, got a maximum of {max_amount} cents.")]
+    InvalidMaxAmount { max_amount: u64 },
+    #[error("Invalid posting dates {from} to {to}, expected YYYY-MM-DD bounds, from before to.")]
+    InvalidDates { from: String, to: String },
+}
+
+
+/// (code, name, type)
+pub const ACCOUNTS: &[(&str, &str, &str)] = &[
+    ("1000", "Cash", "asset"),
+    ("1100", "Accounts receivable", "asset"),
+    ("1200", "Inventory", "asset"),
+    ("1500", "Equipment", "asset"),
+    ("2000", "Accounts payable", "liability"),
+    ("2100", "Accrued expenses", "liability"),
+    ("2500", "Loans payable", "liability"),
+    ("3000", "Owner's equity", "equity"),
+    ("4000", "Sales revenue", "revenue"),
+    ("4100", "Service revenue", "revenue"),
+    ("5000", "Cost of goods sold", "expense"),
+    ("6000", "Salaries expense", "expense"),
+    ("6100", "Rent expense", "expense"),
+    ("6200", "Utilities expense", "expense"),
+];
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Debit,
+    Credit,
+}
+
+impl Side {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Side::Debit => "D",
+            Side::Credit => "C",
+        }
+    }
+}
+
+
+/// Journal lines grouped into transactions of `lines` consecutive rows whose debits equal their credits. Every
+/// transaction is derived from its own index, so any line can be generated on its own and files generated in
+/// parallel still balance. Size the table by a multiple of `lines` rows for the last transaction to be complete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DoubleEntry {
+    lines: u64,
+    max_amount: u64,
+    seed: u64,
+}
+
+impl DoubleEntry {
+    pub fn new(lines: u64) -> Result<Self> {
+        if lines < 2 {
+            return Err(Error::from(FinanceError::TooFewLines { lines }));
+        }
+        Ok(DoubleEntry { lines, max_amount: 1_000_000, seed: 0 })
+    }
+
+    /// Largest amount of a debit line in cents, credits split the debits between them. Debits are at least a cent
+    /// per credit so every credit gets one.
+    pub fn with_max_amount(mut self, max_amount: u64) -> Result<Self> {
+        if max_amount == 0 {
+            return Err(Error::from(FinanceError::InvalidMaxAmount { max_amount }));
+        }
+        self.max_amount = max_amount;
+        Ok(self)
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+
+    /// Sides and amounts in cents of the lines of a transaction, the debits first.
+    pub fn transaction(&self, transaction: u64) -> Vec<(Side, u64)> {
+        let random = SeededRandom::new(mix(self.seed, transaction));
+        let debits = 1 + random.below(self.lines - 1);
+        let credits = self.lines - debits;
+
+        let mut entries: Vec<(Side, u64)> = (0..debits)
+            .map(|_| (Side::Debit, credits + random.below(self.max_amount.saturating_sub(credits).max(1))))
+            .collect();
+        let total: u64 = entries.iter().map(|x| x.1).sum();
+
+        // Every credit gets a cent and the rest is split by random weights, the last credit takes the remainder.
+        let weights: Vec<u64> = (0..credits).map(|_| 1 + random.below(100)).collect();
+        let weight_sum: u64 = weights.iter().sum();
+        let mut remaining = total - credits;
+        for (index, weight) in weights.iter().enumerate() {
+            let share = match index as u64 == credits - 1 {
+                true => remaining,
+                false => ((total - credits) as u128 * *weight as u128 / weight_sum as u128) as u64,
+            };
+            remaining -= share;
+            entries.push((Side::Credit, 1 + share));
+        }
+
+        entries
+    }
+
+
+    fn line(&self, row_index: u64) -> (Side, u64) {
+        self.transaction(row_index / self.lines)[(row_index % self.lines) as usize]
+    }
+
+
+    /// 1 based id of the transaction a line belongs to.
+    pub fn transaction_id(&self) -> Generator {
+        let lines = self.lines;
+        Generator::new(move |context| Ok((context.row_index / lines + 1).to_string())).by_row_index()
+    }
+
+
+    /// `D` or `C`.
+    pub fn side(&self) -> Generator {
+        let entry = *self;
+        Generator::new(move |context| Ok(entry.line(context.row_index).0.as_str().to_string())).by_row_index()
+    }
+
+
+    /// Dates in `from..=to`, the same for every line of a transaction.
+    pub fn posting_date(&self, from: &str, to: &str) -> Result<Generator> {
+        let (from_day, to_day) = parse_date(from).zip(parse_date(to))
+            .filter(|(x, y)| x <= y)
+            .ok_or_else(|| Error::from(FinanceError::InvalidDates { from: from.into(), to: to.into() }))?;
+        let entry = *self;
+
+        Ok(Generator::new(move |context| {
+            let random = SeededRandom::new(mix(mix(entry.seed, context.row_index / entry.lines), u64::MAX / 17));
+            Ok(format_date(from_day + random.below((to_day - from_day) as u64 + 1) as i64))
+        }).by_row_index())
+    }
+
+
+    /// Amount with two decimals.
+    pub fn amount(&self) -> Generator {
+        let entry = *self;
+        Generator::new(move |context| {
+            let cents = entry.line(context.row_index).1;
+            Ok(format!("{}.{:02}", cents / 100, cents % 100))
+        }).by_row_index()
+    }
+}
+
+
+fn account(row_index: u64) -> ((&'static str, &'static str, &'static str), u64) {
+    (ACCOUNTS[(row_index % ACCOUNTS.len() as u64) as usize], row_index / ACCOUNTS.len() as u64)
+}
+
+
+/// Account codes of a chart of accounts row, codes past the standard accounts get a `.n` suffix, e.g. `1000.1`.
+pub fn account_code() -> Generator {
+    Generator::new(|context| Ok(match account(context.row_index) {
+        ((code, _, _), 0) => code.to_string(),
+        ((code, _, _), cycle) => format!("{}.{}", code, cycle),
+    })).by_row_index()
+}
+
+
+pub fn account_name() -> Generator {
+    Generator::new(|context| Ok(match account(context.row_index) {
+        ((_, name, _), 0) => name.to_string(),
+        ((_, name, _), cycle) => format!("{} {}", name, cycle + 1),
+    })).by_row_index()
+}
+
+
+/// asset, liability, equity, revenue or expense.
+pub fn account_type() -> Generator {
+    Generator::new(|context| Ok(account(context.row_index).0.2.to_string())).by_row_index()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn double_entry_test() {
+        let entry = DoubleEntry::new(4).unwrap().with_max_amount(2).unwrap().with_seed(9);
+        for transaction in 0..200 {
+            let lines = entry.transaction(transaction);
+            let sum = |side: Side| lines.iter().filter(|x| x.0 == side).map(|x| x.1).sum::<u64>();
+            assert_eq!(lines.len(), 4);
+            assert_eq!(sum(Side::Debit), sum(Side::Credit));
+            assert!(lines.iter().all(|x| x.1 > 0));
+            assert_eq!(lines[0].0, Side::Debit);
+            assert_eq!(lines[3].0, Side::Credit);
+        }
+        assert!(DoubleEntry::new(1).is_err());
+        assert!(DoubleEntry::new(2).unwrap().with_max_amount(0).is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::generators::Generator;
+use crate::random::RandomSource;
+
+pub const LOINC_SYSTEM: &str = "http://loinc.org";
+pub const ICD10_SYSTEM: &str = "http://hl7.org/fhir/sid/icd-10";
+pub const ACT_CODE_SYSTEM: &str = "http://terminology.hl7.org/CodeSystem/v3-ActCode";
+pub const UCUM_SYSTEM: &str = "http://unitsofmeasure.org";
+
+
+#[derive(Error, Debug)]
+pub enum HealthcareError {
+    #[error("Code {code} of column {column} is not a known observation code.")]
+    UnknownObservation { column: String, code: String },
+}
+
+
+/// A LOINC coded measurement with its UCUM unit and the range its values are drawn from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObservationCode {
+    pub code: &'static str,
+    pub display: &'static str,
+    pub unit: &'static str,
+    pub low: f64,
+    pub high: f64,
+    pub decimals: usize,
+}
+
+const fn coded(code: &'static str, display: &'static str, unit: &'static str, low: f64, high: f64, decimals: usize)
+    -> ObservationCode {
+    ObservationCode { code, display, unit, low, high, decimals }
+}
+
+pub const OBSERVATIONS: &[ObservationCode] = &[
+    coded("8867-4", "Heart rate", "/min", 50.0, 120.0, 0),
+    coded("8480-6", "Systolic blood pressure", "mm[Hg]", 90.0, 180.0, 0),
+    coded("8462-4", "Diastolic blood pressure", "mm[Hg]", 55.0, 110.0, 0),
+    coded("8310-5", "Body temperature", "Cel", 35.5, 40.0, 1),
+    coded("29463-7", "Body weight", "kg", 3.0, 150.0, 1),
+    coded("2339-0", "Glucose", "mg/dL", 60.0, 250.0, 0),
+];
+
+// (code, display)
+pub const DIAGNOSES: &[(&str, &str)] = &[
+    ("I10", "Essential (primary) hypertension"),
+    ("E11.9", "Type 2 diabetes mellitus without complications"),
+    ("J06.9", "Acute upper respiratory infection, unspecified"),
+    ("M54.5", "Low back pain"),
+    ("R51", "Headache"),
+    ("Z00.00", "Encounter for general adult medical examination without abnormal findings"),
+];
+
+// (code, display)
+pub const ENCOUNTER_CLASSES: &[(&str, &str)] = &[
+    ("AMB", "ambulatory"),
+    ("EMER", "emergency"),
+    ("IMP", "inpatient encounter"),
+    ("HH", "home health"),
+    ("VR", "virtual"),
+];
+
+pub const GENDERS: &[&str] = &["male", "female", "other", "unknown"];
+
+
+/// Display text of a LOINC, ICD-10 or encounter class code.
+pub fn display(code: &str) -> Option<&'static str> {
+    OBSERVATIONS.iter()
+        .find(|x| x.code == code)
+        .map(|x| x.display)
+        .or_else(|| DIAGNOSES.iter().chain(ENCOUNTER_CLASSES).find(|x| x.0 == code).map(|x| x.1))
+}
+
+
+pub fn observation(code: &str) -> Option<&'static ObservationCode> {
+    OBSERVATIONS.iter().find(|x| x.code == code)
+}
+
+
+fn pick<T: Copy>(values: &[T], random: &dyn RandomSource) -> T {
+    values[random.below(values.len() as u64) as usize]
+}
+
+
+/// FHIR administrative gender, mostly male and female.
+pub fn administrative_gender() -> Generator {
+    Generator::new(|context| {
+        let roll = context.random.below(100);
+        Ok(GENDERS[if roll < 49 { 0 } else if roll < 98 { 1 } else { 2 + (roll - 98) as usize }].to_string())
+    })
+}
+
+
+/// Medical record numbers like `MRN00000042` derived from the row index, so they are unique across files.
+pub fn medical_record_number() -> Generator {
+    Generator::new(|context| Ok(format!("MRN{:08}", context.row_index + 1))).by_row_index()
+}
+
+
+pub fn loinc_code() -> Generator {
+    Generator::new(|context| Ok(pick(OBSERVATIONS, context.random).code.to_string()))
+}
+
+
+pub fn icd10_code() -> Generator {
+    Generator::new(|context| Ok(pick(DIAGNOSES, context.random).0.to_string()))
+}
+
+
+pub fn encounter_class() -> Generator {
+    Generator::new(|context| Ok(pick(ENCOUNTER_CLASSES, context.random).0.to_string()))
+}
+
+
+fn observation_for(column: &str, code: &str) -> Result<&'static ObservationCode> {
+    observation(code)
+        .ok_or_else(|| Error::from(HealthcareError::UnknownObservation { column: column.into(), code: code.into() }))
+}
+
+
+/// A value in the range of the LOINC code in an earlier column.
+pub fn observation_value(code_column: impl Into<String>) -> Generator {
+    let code_column = code_column.into();
+    Generator::from_row(move |context, row| {
+        let observation = observation_for(&code_column, row.get(&code_column)?)?;
+        let value = observation.low + context.random.next_f64() * (observation.high - observation.low);
+        Ok(format!("{:.*}", observation.decimals, value))
+    })
+}
+
+
+/// The UCUM unit of the LOINC code in an earlier column.
+pub fn observation_unit(code_column: impl Into<String>) -> Generator {
+    let code_column = code_column.into();
+    Generator::from_row(move |_, row| Ok(observation_for(&code_column, row.get(&code_column)?)?.unit.to_string()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Column, Table};
+    use crate::generators::RowContext;
+    use crate::random::SeededRandom;
+    use crate::types::ColumnType;
+
+    #[test]
+    fn healthcare_generators_test() {
+        let random = SeededRandom::new(3);
+        let context = RowContext { row_index: 41, random: &random };
+        assert_eq!(medical_record_number().generate(&context).unwrap(), "MRN00000042");
+        assert!(GENDERS.contains(&administrative_gender().generate(&context).unwrap().as_str()));
+        assert_eq!(display("I10"), Some("Essential (primary) hypertension"));
+
+        let t = Table::new(
+            "observation".into(),
+            vec![
+                Column::new("code".into(), 7, ColumnType::Varchar(7), loinc_code()),
+                Column::new("value".into(), 5, ColumnType::Decimal(5, 1), observation_value("code")),
+                Column::new("unit".into(), 6, ColumnType::Varchar(6), observation_unit("code")),
+            ],
+            "|".into(),
+            rust_decimal::Decimal::ONE,
+        );
+        for row in t.generate_rows_vec(50).unwrap() {
+            let observation = observation(&row[1]).unwrap();
+            let value = row[2].parse::<f64>().unwrap();
+            assert!(value >= observation.low && value <= observation.high);
+            assert_eq!(row[3], observation.unit);
+        }
+    }
+}
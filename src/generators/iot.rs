@@ -0,0 +1,241 @@
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::events::EventClock;
+use crate::generators::Generator;
+use crate::random::{mix, RandomSource, SeededRandom};
+
+
+#[derive(Error, Debug)]
+pub enum IotError {
+    #[error("A sensor fleet needs at least 1 device.")]
+    NoDevices,
+    #[error("Devices have to report at an interval of at least 1 second.")]
+    ZeroInterval,
+}
+
+
+/// A sensor model and the range its readings start in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SensorModel {
+    pub model: &'static str,
+    pub sensor_type: &'static str,
+    pub unit: &'static str,
+    pub low: f64,
+    pub high: f64,
+    pub noise: f64,
+}
+
+const fn model(model: &'static str, sensor_type: &'static str, unit: &'static str, low: f64, high: f64, noise: f64)
+    -> SensorModel {
+    SensorModel { model, sensor_type, unit, low, high, noise }
+}
+
+pub const MODELS: &[SensorModel] = &[
+    model("TH-100", "temperature", "Cel", 15.0, 30.0, 0.3),
+    model("HM-20", "humidity", "%", 30.0, 70.0, 1.5),
+    model("PR-7", "pressure", "hPa", 990.0, 1030.0, 0.8),
+    model("CO2-4", "co2", "ppm", 400.0, 900.0, 15.0),
+];
+pub const SITES: [&str; 4] = ["plant-a", "plant-b", "warehouse", "office"];
+/// Value of a faulty reading that reports an error code instead of a measurement.
+pub const ERROR_CODE: f64 = -999.9;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadingStatus {
+    Ok,
+    /// No value arrived, the value is empty.
+    Dropout,
+    /// A spike far outside the model's range or the error code.
+    Fault,
+}
+
+impl ReadingStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReadingStatus::Ok => "ok",
+            ReadingStatus::Dropout => "dropout",
+            ReadingStatus::Fault => "fault",
+        }
+    }
+}
+
+
+/// A device of the fleet, readings start at `baseline` and move by `drift_per_hour`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Device {
+    pub model: SensorModel,
+    pub site: &'static str,
+    pub baseline: f64,
+    pub drift_per_hour: f64,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reading {
+    pub device: u64,
+    pub tick: u64,
+    pub status: ReadingStatus,
+    pub value: Option<f64>,
+}
+
+
+/// Readings of `devices` devices that all report every `interval` seconds, row `i` is device `i % devices` at tick
+/// `i / devices`. Devices and readings are derived from their index, so readings join the device rows and drift
+/// steadily from file to file at any generation rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SensorFleet {
+    devices: u64,
+    interval: u64,
+    dropout_rate: f64,
+    fault_rate: f64,
+    seed: u64,
+}
+
+impl SensorFleet {
+    pub fn new(devices: u64) -> Result<Self> {
+        if devices == 0 {
+            return Err(Error::from(IotError::NoDevices));
+        }
+        Ok(SensorFleet { devices, interval: 10, dropout_rate: 0.02, fault_rate: 0.005, seed: 0 })
+    }
+
+    /// Seconds between the readings of a device.
+    pub fn with_interval(mut self, seconds: u64) -> Result<Self> {
+        if seconds == 0 {
+            return Err(Error::from(IotError::ZeroInterval));
+        }
+        self.interval = seconds;
+        Ok(self)
+    }
+
+    /// Fractions of readings that drop out or are faulty, clamped to `0.0..=1.0`.
+    pub fn with_failure_rates(mut self, dropout_rate: f64, fault_rate: f64) -> Self {
+        let clamp = |x: f64| if x.is_nan() { 0.0 } else { x.clamp(0.0, 1.0) };
+        self.dropout_rate = clamp(dropout_rate);
+        self.fault_rate = clamp(fault_rate);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+
+    /// Event times of the readings, every device once per interval from `start`.
+    pub fn clock(&self, start: &str) -> Result<EventClock> {
+        EventClock::new(start, self.devices as f64 / self.interval as f64)
+    }
+
+
+    pub fn device(&self, device: u64) -> Device {
+        let random = SeededRandom::new(mix(mix(self.seed, device), u64::MAX / 37));
+        let model = MODELS[random.below(MODELS.len() as u64) as usize];
+
+        Device {
+            model,
+            site: SITES[random.below(SITES.len() as u64) as usize],
+            baseline: model.low + (model.high - model.low) * random.next_f64(),
+            // Up to a percent of the model's range per hour either way.
+            drift_per_hour: (model.high - model.low) * 0.01 * (2.0 * random.next_f64() - 1.0),
+        }
+    }
+
+
+    /// Value a device reads at a tick without noise.
+    pub fn expected(&self, device: u64, tick: u64) -> f64 {
+        let device = self.device(device);
+        device.baseline + device.drift_per_hour * (tick * self.interval) as f64 / 3600.0
+    }
+
+
+    pub fn reading(&self, row_index: u64) -> Reading {
+        let (device, tick) = (row_index % self.devices, row_index / self.devices);
+        let random = SeededRandom::new(mix(self.seed, row_index));
+        let model = self.device(device).model;
+
+        let (status, value) = if random.chance(self.dropout_rate) {
+            (ReadingStatus::Dropout, None)
+        } else if random.chance(self.fault_rate) {
+            let spike = model.high + (model.high - model.low) * (5.0 + 5.0 * random.next_f64());
+            (ReadingStatus::Fault, Some(if random.chance(0.5) { ERROR_CODE } else { spike }))
+        } else {
+            // Roughly normal noise from the sum of three uniform draws.
+            let noise = (random.next_f64() + random.next_f64() + random.next_f64() - 1.5) * model.noise;
+            (ReadingStatus::Ok, Some(self.expected(device, tick) + noise))
+        };
+
+        Reading { device, tick, status, value }
+    }
+
+
+    fn generator(&self, value: impl Fn(SensorFleet, u64) -> String + Send + Sync + 'static) -> Generator {
+        let fleet = *self;
+        Generator::new(move |context| Ok(value(fleet, context.row_index))).by_row_index()
+    }
+
+    /// 1 based id of the device of a reading.
+    pub fn reading_device_id(&self) -> Generator {
+        self.generator(|fleet, x| (fleet.reading(x).device + 1).to_string())
+    }
+
+    /// Value with two decimals, empty when the reading dropped out.
+    pub fn reading_value(&self) -> Generator {
+        self.generator(|fleet, x| fleet.reading(x).value.map(|y| format!("{:.2}", y)).unwrap_or_default())
+    }
+
+    /// `ok`, `dropout` or `fault`.
+    pub fn reading_status(&self) -> Generator {
+        self.generator(|fleet, x| fleet.reading(x).status.as_str().to_string())
+    }
+
+    pub fn device_model(&self) -> Generator {
+        self.generator(|fleet, x| fleet.device(x).model.model.to_string())
+    }
+
+    pub fn device_sensor_type(&self) -> Generator {
+        self.generator(|fleet, x| fleet.device(x).model.sensor_type.to_string())
+    }
+
+    pub fn device_unit(&self) -> Generator {
+        self.generator(|fleet, x| fleet.device(x).model.unit.to_string())
+    }
+
+    pub fn device_site(&self) -> Generator {
+        self.generator(|fleet, x| fleet.device(x).site.to_string())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sensor_fleet_test() {
+        let fleet = SensorFleet::new(5).unwrap().with_failure_rates(0.1, 0.05).with_seed(8);
+        let readings: Vec<Reading> = (0..5000).map(|x| fleet.reading(x)).collect();
+        let share = |status| readings.iter().filter(|x| x.status == status).count() as f64 / 5000.0;
+
+        assert!((share(ReadingStatus::Dropout) - 0.1).abs() < 0.03);
+        assert!(share(ReadingStatus::Fault) > 0.02 && share(ReadingStatus::Fault) < 0.08);
+        for reading in readings.iter() {
+            let model = fleet.device(reading.device).model;
+            let expected = fleet.expected(reading.device, reading.tick);
+            match reading.status {
+                ReadingStatus::Ok => assert!((reading.value.unwrap() - expected).abs() <= 1.5 * model.noise),
+                ReadingStatus::Dropout => assert!(reading.value.is_none()),
+                ReadingStatus::Fault => {
+                    assert!(reading.value.is_some_and(|x| x == ERROR_CODE || x > model.high))
+                }
+            }
+        }
+        // 1000 ticks of 10 seconds drift a device by its hourly drift times 2.78.
+        let drifted = fleet.expected(0, 1000) - fleet.expected(0, 0);
+        assert!((drifted - fleet.device(0).drift_per_hour * 10_000.0 / 3600.0).abs() < 1e-9);
+        assert!(SensorFleet::new(0).is_err());
+        assert!(fleet.with_interval(0).is_err());
+    }
+}
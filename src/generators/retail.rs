@@ -0,0 +1,214 @@
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::generators::Generator;
+use crate::random::{mix, RandomSource, SeededRandom};
+
+
+#[derive(Error, Debug)]
+pub enum RetailError {
+    #[error("Orders need a catalogue of at least 1 product.")]
+    NoProducts,
+    #[error("Orders need at least 1 line.")]
+    NoLines,
+}
+
+
+/// (name, category)
+pub const CATALOGUE: &[(&str, &str)] = &[
+    ("Coffee mug", "kitchen"),
+    ("Chef's knife", "kitchen"),
+    ("Cutting board", "kitchen"),
+    ("Desk lamp", "home"),
+    ("Throw pillow", "home"),
+    ("Wall clock", "home"),
+    ("Notebook", "office"),
+    ("Ballpoint pens", "office"),
+    ("Running shoes", "sports"),
+    ("Yoga mat", "sports"),
+    ("Water bottle", "sports"),
+    ("Headphones", "electronics"),
+    ("Phone charger", "electronics"),
+    ("Board game", "toys"),
+];
+
+pub const PAYMENT_METHODS: [&str; 4] = ["card", "paypal", "bank_transfer", "gift_card"];
+// Most baskets hold one or two of a product.
+const QUANTITIES: [u64; 8] = [1, 1, 1, 1, 2, 2, 3, 5];
+
+
+/// A line of an order, its amount is `quantity * unit_price` in cents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderLine {
+    pub order: u64,
+    pub product: u64,
+    pub quantity: u64,
+    pub unit_price: u64,
+}
+
+impl OrderLine {
+    pub fn amount(&self) -> u64 {
+        self.quantity * self.unit_price
+    }
+}
+
+
+/// Orders of `lines` consecutive order line rows over a catalogue of `products`, with one payment per order. Lines
+/// and payments are derived from their row index and the unit price from the product, so the total of order `i`,
+/// the amount of payment `i` and the sum of the lines of order `i` always agree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Basket {
+    products: u64,
+    lines: u64,
+    seed: u64,
+}
+
+impl Basket {
+    pub fn new(products: u64, lines: u64) -> Result<Self> {
+        if products == 0 {
+            return Err(Error::from(RetailError::NoProducts));
+        }
+        if lines == 0 {
+            return Err(Error::from(RetailError::NoLines));
+        }
+        Ok(Basket { products, lines, seed: 0 })
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+
+    /// Price of a product in cents, from 0.99 to 199.99.
+    pub fn price(&self, product: u64) -> u64 {
+        let random = SeededRandom::new(mix(mix(self.seed, product), u64::MAX / 31));
+        99 + 100 * random.below(200)
+    }
+
+
+    pub fn line(&self, row_index: u64) -> OrderLine {
+        let random = SeededRandom::new(mix(self.seed, row_index));
+        let product = random.below(self.products);
+
+        OrderLine {
+            order: row_index / self.lines,
+            product,
+            quantity: QUANTITIES[random.below(QUANTITIES.len() as u64) as usize],
+            unit_price: self.price(product),
+        }
+    }
+
+
+    /// Sum of the lines of an order in cents.
+    pub fn order_total(&self, order: u64) -> u64 {
+        (order * self.lines..(order + 1) * self.lines).map(|x| self.line(x).amount()).sum()
+    }
+
+
+    fn generator(value: impl Fn(u64) -> String + Send + Sync + 'static) -> Generator {
+        Generator::new(move |context| Ok(value(context.row_index))).by_row_index()
+    }
+
+    /// Price of product row `i` with two decimals.
+    pub fn product_price(&self) -> Generator {
+        let basket = *self;
+        Basket::generator(move |x| cents(basket.price(x)))
+    }
+
+    pub fn line_order_id(&self) -> Generator {
+        let basket = *self;
+        Basket::generator(move |x| (basket.line(x).order + 1).to_string())
+    }
+
+    pub fn line_product_id(&self) -> Generator {
+        let basket = *self;
+        Basket::generator(move |x| (basket.line(x).product + 1).to_string())
+    }
+
+    pub fn line_quantity(&self) -> Generator {
+        let basket = *self;
+        Basket::generator(move |x| basket.line(x).quantity.to_string())
+    }
+
+    pub fn line_unit_price(&self) -> Generator {
+        let basket = *self;
+        Basket::generator(move |x| cents(basket.line(x).unit_price))
+    }
+
+    pub fn line_amount(&self) -> Generator {
+        let basket = *self;
+        Basket::generator(move |x| cents(basket.line(x).amount()))
+    }
+
+    /// Total of order row `i`.
+    pub fn order_total_amount(&self) -> Generator {
+        let basket = *self;
+        Basket::generator(move |x| cents(basket.order_total(x)))
+    }
+
+    /// Payment row `i` pays order `i` in full.
+    pub fn payment_order_id(&self) -> Generator {
+        Basket::generator(|x| (x + 1).to_string())
+    }
+
+    pub fn payment_amount(&self) -> Generator {
+        self.order_total_amount()
+    }
+}
+
+
+pub fn cents(cents: u64) -> String {
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+
+fn product(row_index: u64) -> ((&'static str, &'static str), u64) {
+    (CATALOGUE[(row_index % CATALOGUE.len() as u64) as usize], row_index / CATALOGUE.len() as u64)
+}
+
+
+/// Names of the catalogue, products past it get a number, e.g. `Coffee mug 2`.
+pub fn product_name() -> Generator {
+    Generator::new(|context| Ok(match product(context.row_index) {
+        ((name, _), 0) => name.to_string(),
+        ((name, _), cycle) => format!("{} {}", name, cycle + 1),
+    })).by_row_index()
+}
+
+
+pub fn product_category() -> Generator {
+    Generator::new(|context| Ok(product(context.row_index).0.1.to_string())).by_row_index()
+}
+
+
+/// `customer1@example.com` for the first customer.
+pub fn customer_email() -> Generator {
+    Generator::new(|context| Ok(format!("customer{}@example.com", context.row_index + 1))).by_row_index()
+}
+
+
+/// `SKU-000001` for the first product.
+pub fn product_sku() -> Generator {
+    Generator::new(|context| Ok(format!("SKU-{:06}", context.row_index + 1))).by_row_index()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basket_test() {
+        let basket = Basket::new(20, 3).unwrap().with_seed(2);
+        for order in 0..100 {
+            let lines: Vec<OrderLine> = (order * 3..order * 3 + 3).map(|x| basket.line(x)).collect();
+            assert!(lines.iter().all(|x| x.order == order && x.product < 20));
+            assert!(lines.iter().all(|x| x.unit_price == basket.price(x.product)));
+            assert_eq!(basket.order_total(order), lines.iter().map(|x| x.amount()).sum::<u64>());
+        }
+        assert_eq!(cents(1205), "12.05");
+        assert!(Basket::new(0, 3).is_err());
+        assert!(Basket::new(20, 0).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::generators::Generator;
+use crate::random::{mix, RandomSource, SeededRandom};
+
+
+#[derive(Error, Debug)]
+pub enum SurveyError {
+    #[error("A questionnaire needs at least 1 question and 1 section.")]
+    Empty,
+    #[error("{sections} sections need at least as many questions, there are {questions}.")]
+    TooManySections { questions: u64, sections: u64 },
+    #[error("The {what} rate has to be between 0 and 1, it is {rate}.")]
+    InvalidRate { what: String, rate: f64 },
+}
+
+
+/// Answer of a question, a section's gate is answered yes or no and every other question on a 1 to 5 scale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Answer {
+    Gate(bool),
+    Scale(u64),
+}
+
+// Streams of the seeded draws of screening, section gates and answers.
+const SCREEN_STREAM: u64 = u64::MAX / 47;
+const GATE_STREAM: u64 = u64::MAX / 53;
+const ANSWER_STREAM: u64 = u64::MAX / 59;
+
+
+/// A questionnaire of `questions` questions in up to `sections` sections of equal length, the last may be shorter.
+/// Respondents screened out answer nothing, the first question of every section is a gate and respondents who answer
+/// it no skip the rest of the section, a few questions are left unanswered. Every answer is derived from the row index
+/// and question, so skip logic holds across files.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Questionnaire {
+    questions: u64,
+    sections: u64,
+    screened_in: f64,
+    gate_yes: f64,
+    nonresponse: f64,
+    seed: u64,
+}
+
+impl Questionnaire {
+    /// 70% of respondents screened in, 30% answering yes to a gate and 5% of other questions left unanswered.
+    pub fn new(questions: u64, sections: u64) -> Result<Self> {
+        if questions == 0 || sections == 0 {
+            return Err(Error::from(SurveyError::Empty));
+        }
+        if sections > questions {
+            return Err(Error::from(SurveyError::TooManySections { questions, sections }));
+        }
+
+        Ok(Questionnaire { questions, sections, screened_in: 0.7, gate_yes: 0.3, nonresponse: 0.05, seed: 0 })
+    }
+
+    pub fn with_rates(mut self, screened_in: f64, gate_yes: f64, nonresponse: f64) -> Result<Self> {
+        for (what, rate) in [("screened in", screened_in), ("gate yes", gate_yes), ("nonresponse", nonresponse)] {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(Error::from(SurveyError::InvalidRate { what: what.into(), rate }));
+            }
+        }
+        self.screened_in = screened_in;
+        self.gate_yes = gate_yes;
+        self.nonresponse = nonresponse;
+        Ok(self)
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+
+    pub fn questions(&self) -> u64 {
+        self.questions
+    }
+
+    fn per_section(&self) -> u64 {
+        self.questions.div_ceil(self.sections)
+    }
+
+    /// 0 based section of a question.
+    pub fn section(&self, question: u64) -> u64 {
+        question / self.per_section()
+    }
+
+    pub fn is_gate(&self, question: u64) -> bool {
+        question.is_multiple_of(self.per_section())
+    }
+
+    /// Column of a question, e.g. `s01_q01` for the gate of the first section.
+    pub fn column_name(&self, question: u64) -> String {
+        format!("s{:02}_q{:02}", self.section(question) + 1, question % self.per_section() + 1)
+    }
+
+
+    pub fn is_screened_in(&self, row_index: u64) -> bool {
+        SeededRandom::new(mix(mix(self.seed, row_index), SCREEN_STREAM)).chance(self.screened_in)
+    }
+
+    // Gates are answered by everyone screened in, their answer decides the rest of the section.
+    fn gate(&self, row_index: u64, section: u64) -> bool {
+        SeededRandom::new(mix(mix(mix(self.seed, row_index), GATE_STREAM), section)).chance(self.gate_yes)
+    }
+
+    /// Answer of respondent `row_index`, `None` for questions skipped or left unanswered.
+    pub fn answer(&self, row_index: u64, question: u64) -> Option<Answer> {
+        if question >= self.questions || !self.is_screened_in(row_index) {
+            return None;
+        }
+        let section = self.section(question);
+        if self.is_gate(question) {
+            return Some(Answer::Gate(self.gate(row_index, section)));
+        }
+        if !self.gate(row_index, section) {
+            return None;
+        }
+
+        let random = SeededRandom::new(mix(mix(mix(self.seed, row_index), ANSWER_STREAM), question));
+        match random.chance(self.nonresponse) {
+            true => None,
+            false => Some(Answer::Scale(1 + random.below(5))),
+        }
+    }
+
+
+    /// `true` or `false`.
+    pub fn screened_in(&self) -> Generator {
+        let questionnaire = *self;
+        Generator::new(move |context| Ok(questionnaire.is_screened_in(context.row_index).to_string()))
+            .by_row_index()
+    }
+
+    /// `Y` or `N` for a gate, `1` to `5` otherwise, null where the respondent did not answer.
+    pub fn question(&self, question: u64) -> Generator {
+        let questionnaire = *self;
+        Generator::new(move |context| {
+            Ok(match questionnaire.answer(context.row_index, question) {
+                Some(Answer::Gate(x)) => if x { "Y" } else { "N" }.to_string(),
+                Some(Answer::Scale(x)) => x.to_string(),
+                None => String::new(),
+            })
+        })
+            .by_row_index()
+            .with_null_rows(move |x| questionnaire.answer(x, question).is_none())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn questionnaire_test() {
+        let questionnaire = Questionnaire::new(100, 8).unwrap().with_seed(6);
+
+        assert_eq!(questionnaire.column_name(0), "s01_q01");
+        assert_eq!(questionnaire.column_name(99), "s08_q09");
+        for row in 0..500 {
+            let screened_in = questionnaire.is_screened_in(row);
+            for question in 0..100 {
+                let answer = questionnaire.answer(row, question);
+                match questionnaire.is_gate(question) {
+                    true => assert_eq!(answer.is_some(), screened_in),
+                    // Questions are only answered in sections whose gate was answered yes.
+                    false if answer.is_some() => {
+                        assert_eq!(questionnaire.answer(row, question - question % 13), Some(Answer::Gate(true)));
+                    }
+                    false => (),
+                }
+            }
+        }
+        let answered = (0..500).flat_map(|x| (0..100).map(move |y| (x, y)))
+            .filter(|(x, y)| questionnaire.answer(*x, *y).is_some())
+            .count();
+        assert!(answered < 500 * 100 / 3);
+        assert!(Questionnaire::new(3, 4).is_err());
+        assert!(questionnaire.with_rates(1.5, 0.5, 0.0).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::generators::Generator;
+use crate::random::{mix, RandomSource, SeededRandom};
+use crate::values::{format_timestamp, parse_timestamp_micros};
+
+
+#[derive(Error, Debug)]
+pub enum TelecomError {
+    #[error("Calls need at least 2 subscribers, got {subscribers}.")]
+    TooFewSubscribers { subscribers: u64 },
+    #[error("Calls need at least 1 cell tower.")]
+    NoTowers,
+    #[error("Invalid call period {from} to {to}, expected YYYY-MM-DD HH:MM:SS bounds, from before to.")]
+    InvalidPeriod { from: String, to: String },
+}
+
+
+/// Cell ids of tower 0, 1, ...
+pub const FIRST_CELL_ID: u64 = 10_001;
+/// Towers sharing a location area code.
+pub const TOWERS_PER_AREA: u64 = 10;
+// Calls longer than this hand over to the neighbouring tower.
+const HANDOVER_SECONDS: u64 = 300;
+// Bounding box of the towers, (south, west) and (north, east).
+const AREA: ((f64, f64), (f64, f64)) = ((45.70, 15.80), (45.90, 16.10));
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallType {
+    Voice,
+    Sms,
+}
+
+impl CallType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CallType::Voice => "voice",
+            CallType::Sms => "sms",
+        }
+    }
+}
+
+
+/// One call detail record, `end = start + duration` and an sms takes no time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Call {
+    pub caller: u64,
+    pub callee: u64,
+    pub call_type: CallType,
+    pub start: i64,
+    pub duration: u64,
+    pub start_tower: u64,
+    pub end_tower: u64,
+}
+
+impl Call {
+    pub fn end(&self) -> i64 {
+        self.start + self.duration as i64
+    }
+}
+
+
+/// Call detail records between `subscribers` phone numbers over `towers` cell towers. A subscriber calls someone else
+/// from their home tower and long calls hand over to the next tower. Every record is derived from its own row index,
+/// so rows are generated independently at any rate and the fields of a record always agree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallDetail {
+    subscribers: u64,
+    towers: u64,
+    from: i64,
+    seconds: u64,
+    seed: u64,
+}
+
+impl CallDetail {
+    pub fn new(subscribers: u64, towers: u64) -> Result<Self> {
+        if subscribers < 2 {
+            return Err(Error::from(TelecomError::TooFewSubscribers { subscribers }));
+        }
+        if towers == 0 {
+            return Err(Error::from(TelecomError::NoTowers));
+        }
+        CallDetail { subscribers, towers, from: 0, seconds: 1, seed: 0 }
+            .with_period("2024-01-01 00:00:00", "2024-12-31 23:59:59")
+    }
+
+    /// Calls start in `from..=to`, both `YYYY-MM-DD HH:MM:SS`.
+    pub fn with_period(mut self, from: &str, to: &str) -> Result<Self> {
+        let seconds = |x: &str| parse_timestamp_micros(x).map(|y| y.div_euclid(1_000_000));
+        let (from_second, to_second) = seconds(from).zip(seconds(to))
+            .filter(|(x, y)| x <= y)
+            .ok_or_else(|| Error::from(TelecomError::InvalidPeriod { from: from.into(), to: to.into() }))?;
+
+        self.from = from_second;
+        self.seconds = (to_second - from_second) as u64 + 1;
+        Ok(self)
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+
+    pub fn call(&self, row_index: u64) -> Call {
+        let random = SeededRandom::new(mix(self.seed, row_index));
+        let caller = random.below(self.subscribers);
+        let callee = (caller + 1 + random.below(self.subscribers - 1)) % self.subscribers;
+        let (call_type, duration) = match random.chance(0.2) {
+            true => (CallType::Sms, 0),
+            // Mostly short calls with a long tail up to an hour.
+            false => (CallType::Voice, 1 + (random.next_f64().powi(3) * 3600.0) as u64),
+        };
+        let start_tower = home_tower(caller, self.towers);
+
+        Call {
+            caller,
+            callee,
+            call_type,
+            start: self.from + random.below(self.seconds) as i64,
+            duration,
+            start_tower,
+            end_tower: match duration > HANDOVER_SECONDS {
+                true => (start_tower + 1) % self.towers,
+                false => start_tower,
+            },
+        }
+    }
+
+
+    fn generator(&self, value: impl Fn(Call) -> String + Send + Sync + 'static) -> Generator {
+        let detail = *self;
+        Generator::new(move |context| Ok(value(detail.call(context.row_index)))).by_row_index()
+    }
+
+    pub fn caller(&self) -> Generator {
+        self.generator(|x| msisdn(x.caller))
+    }
+
+    pub fn callee(&self) -> Generator {
+        self.generator(|x| msisdn(x.callee))
+    }
+
+    /// `voice` or `sms`.
+    pub fn call_type(&self) -> Generator {
+        self.generator(|x| x.call_type.as_str().to_string())
+    }
+
+    pub fn start_time(&self) -> Generator {
+        self.generator(|x| format_timestamp(x.start))
+    }
+
+    pub fn end_time(&self) -> Generator {
+        self.generator(|x| format_timestamp(x.end()))
+    }
+
+    /// Seconds.
+    pub fn duration(&self) -> Generator {
+        self.generator(|x| x.duration.to_string())
+    }
+
+    /// Cell id of the tower the call started on.
+    pub fn cell_id(&self) -> Generator {
+        self.generator(|x| cell_id(x.start_tower).to_string())
+    }
+
+    /// Location area code of the tower the call started on.
+    pub fn lac(&self) -> Generator {
+        self.generator(|x| lac(x.start_tower).to_string())
+    }
+
+    pub fn end_cell_id(&self) -> Generator {
+        self.generator(|x| cell_id(x.end_tower).to_string())
+    }
+}
+
+
+/// Croatian mobile number of a subscriber, e.g. `385910000042`.
+pub fn msisdn(subscriber: u64) -> String {
+    format!("3859{:08}", subscriber % 100_000_000)
+}
+
+
+pub fn home_tower(subscriber: u64, towers: u64) -> u64 {
+    mix(subscriber, u64::MAX / 19) % towers
+}
+
+
+pub fn cell_id(tower: u64) -> u64 {
+    FIRST_CELL_ID + tower
+}
+
+
+pub fn lac(tower: u64) -> u64 {
+    1000 + tower / TOWERS_PER_AREA
+}
+
+
+/// (latitude, longitude) of a tower, towers of a location area are close to each other.
+pub fn location(tower: u64) -> (f64, f64) {
+    let area = SeededRandom::new(mix(lac(tower), u64::MAX / 23));
+    let site = SeededRandom::new(mix(tower, u64::MAX / 29));
+    let ((south, west), (north, east)) = AREA;
+    let at = |low: f64, high: f64, x: &SeededRandom| low + (high - low) * (0.9 * area.next_f64() + 0.1 * x.next_f64());
+
+    (at(south, north, &site), at(west, east, &site))
+}
+
+
+// Row `i` of a cell tower table is tower `i`.
+fn tower(value: fn(u64) -> String) -> Generator {
+    Generator::new(move |context| Ok(value(context.row_index))).by_row_index()
+}
+
+pub fn tower_cell_id() -> Generator {
+    tower(|x| cell_id(x).to_string())
+}
+
+pub fn tower_lac() -> Generator {
+    tower(|x| lac(x).to_string())
+}
+
+pub fn tower_latitude() -> Generator {
+    tower(|x| format!("{:.6}", location(x).0))
+}
+
+pub fn tower_longitude() -> Generator {
+    tower(|x| format!("{:.6}", location(x).1))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn call_detail_test() {
+        let detail = CallDetail::new(50, 20).unwrap().with_seed(4);
+        for row in 0..500 {
+            let call = detail.call(row);
+            assert_ne!(call.caller, call.callee);
+            assert!(call.callee < 50);
+            assert_eq!(call.start_tower, home_tower(call.caller, 20));
+            assert_eq!(call.call_type == CallType::Sms, call.duration == 0);
+            assert!(call.duration <= 3600);
+            assert_eq!(call.end_tower == call.start_tower, call.duration <= HANDOVER_SECONDS);
+        }
+        assert!(location(3).0 >= AREA.0.0 && location(3).1 <= AREA.1.1);
+        assert!(CallDetail::new(1, 20).is_err());
+        assert!(CallDetail::new(2, 0).is_err());
+        assert!(detail.with_period("2024-02-01 00:00:00", "2024-01-01 00:00:00").is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::ExportFile;
+use crate::metadata::FileMetadata;
+
+pub const HEALTH_PORT_ENV: &str = "DIPLOMSKI_HEALTH_PORT";
+pub const HEARTBEAT_FILE_ENV: &str = "DIPLOMSKI_HEARTBEAT_FILE";
+/// The health endpoint reports 503 once no file was finished for this long.
+pub const STALE_AFTER: Duration = Duration::from_secs(600);
+
+
+/// Answers every request on a background thread with the handler's status, content type and body.
+pub(crate) fn serve_http(
+    address: impl ToSocketAddrs,
+    handler: impl Fn() -> (&'static str, &'static str, String) + Send + 'static,
+) -> Result<SocketAddr> {
+    let listener = TcpListener::bind(address)?;
+    let local_address = listener.local_addr()?;
+
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            // Only the request line matters, the rest of the request is not read.
+            let mut request_line = String::new();
+            if BufReader::new(&stream).read_line(&mut request_line).is_err() {
+                continue;
+            }
+
+            let (status, content_type, body) = handler();
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                content_type,
+                body.len(),
+                body
+            );
+        }
+    });
+
+    Ok(local_address)
+}
+
+
+/// Progress of a running generation, reported on the health endpoint and written to the heartbeat file.
+#[derive(Debug)]
+pub struct Heartbeat {
+    started: Instant,
+    files_total: AtomicU64,
+    files_completed: AtomicU64,
+    rows: AtomicU64,
+    bytes: AtomicU64,
+    last_progress_millis: AtomicU64,
+    file: Option<PathBuf>,
+}
+
+impl Heartbeat {
+    pub fn new(files_total: u64) -> Self {
+        Heartbeat {
+            started: Instant::now(),
+            files_total: AtomicU64::new(files_total),
+            files_completed: AtomicU64::new(0),
+            rows: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            last_progress_millis: AtomicU64::new(0),
+            file: None,
+        }
+    }
+
+    /// Rewrites the file with the current status on every beat, orchestrators can watch its contents or mtime.
+    pub fn with_file(mut self, path: PathBuf) -> Self {
+        self.file = Some(path);
+        self
+    }
+
+    /// Records progress made without finishing a file.
+    pub fn beat(&self) -> Result<()> {
+        self.last_progress_millis.store(self.started.elapsed().as_millis() as u64, Ordering::Relaxed);
+
+        match &self.file {
+            Some(path) => Ok(fs::write(path, self.status().to_string())?),
+            None => Ok(()),
+        }
+    }
+
+    pub fn add_rows(&self, rows: u64, bytes: u64) -> Result<()> {
+        self.rows.fetch_add(rows, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.beat()
+    }
+
+    pub fn file_written(&self, metadata: &FileMetadata) -> Result<()> {
+        self.files_completed.fetch_add(1, Ordering::Relaxed);
+        self.add_rows(metadata.tables.iter().map(|x| x.rows).sum(), metadata.bytes)
+    }
+
+    pub fn since_progress(&self) -> Duration {
+        self.started.elapsed().saturating_sub(Duration::from_millis(self.last_progress_millis.load(Ordering::Relaxed)))
+    }
+
+    pub fn status(&self) -> serde_json::Value {
+        json!({
+            "files_completed": self.files_completed.load(Ordering::Relaxed),
+            "files_total": self.files_total.load(Ordering::Relaxed),
+            "rows": self.rows.load(Ordering::Relaxed),
+            "bytes": self.bytes.load(Ordering::Relaxed),
+            "seconds_since_progress": self.since_progress().as_secs_f64(),
+            "timestamp": SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_secs()).unwrap_or(0),
+        })
+    }
+
+    /// Serves the status as JSON on every path from a background thread, with status 503 once no progress
+    /// was made for `stale_after`. Returns the bound address, which matters when binding port 0.
+    pub fn serve(self: &Arc<Self>, address: impl ToSocketAddrs, stale_after: Duration) -> Result<SocketAddr> {
+        let heartbeat = self.clone();
+
+        serve_http(address, move || {
+            let status = if heartbeat.since_progress() > stale_after { "503 Service Unavailable" } else { "200 OK" };
+            (status, "application/json", heartbeat.status().to_string())
+        })
+    }
+
+    /// Heartbeat serving on `0.0.0.0:port` and/or writing `file`, `None` when neither is configured.
+    pub fn start(files_total: u64, port: Option<u16>, file: Option<PathBuf>) -> Result<Option<Arc<Heartbeat>>> {
+        if port.is_none() && file.is_none() {
+            return Ok(None);
+        }
+
+        let mut heartbeat = Heartbeat::new(files_total);
+        if let Some(file) = file {
+            heartbeat = heartbeat.with_file(file);
+        }
+        let heartbeat = Arc::new(heartbeat);
+        heartbeat.beat()?;
+
+        if let Some(port) = port {
+            heartbeat.serve(("0.0.0.0", port), STALE_AFTER)?;
+        }
+
+        Ok(Some(heartbeat))
+    }
+
+    /// Reads `DIPLOMSKI_HEALTH_PORT` and `DIPLOMSKI_HEARTBEAT_FILE`.
+    pub fn from_env(files_total: u64) -> Result<Option<Arc<Heartbeat>>> {
+        let port = match env::var(HEALTH_PORT_ENV) {
+            Ok(x) => Some(x.trim().parse()?),
+            Err(_) => None,
+        };
+
+        Heartbeat::start(files_total, port, env::var_os(HEARTBEAT_FILE_ENV).map(PathBuf::from))
+    }
+}
+
+
+impl ExportFile {
+    /// Reports every written file to the heartbeat before calling the existing file callback.
+    pub fn with_heartbeat(mut self, heartbeat: Arc<Heartbeat>) -> Self {
+        let callback = self.file_callback.take();
+
+        self.with_file_callback(move |x| {
+            heartbeat.file_written(x)?;
+            match &callback {
+                Some(callback) => callback(x),
+                None => Ok(()),
+            }
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::net::TcpStream;
+
+    use super::*;
+    use crate::metadata::TableMetadata;
+
+    #[test]
+    fn heartbeat_test() {
+        let heartbeat = Arc::new(Heartbeat::new(2));
+        heartbeat.file_written(&FileMetadata {
+            path: PathBuf::from("file_0.txt"),
+            file_index: 0,
+            tables: vec![TableMetadata { table: "A".into(), rows: 10, bytes: 60 }],
+            bytes: 60,
+            checksum: 0,
+            sha256: String::new(),
+            duration: Duration::from_millis(5),
+        }).unwrap();
+
+        let address = heartbeat.serve("127.0.0.1:0", Duration::from_secs(60)).unwrap();
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(b"GET /healthz HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        let status: serde_json::Value = serde_json::from_str(response.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(status["files_completed"], 1);
+        assert_eq!(status["rows"], 10);
+    }
+}
@@ -0,0 +1,242 @@
+use std::path::Path;
+
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use crate::{generators, Column, ExportFile, Table};
+use crate::generators::healthcare::{self as codes, ACT_CODE_SYSTEM, ICD10_SYSTEM, LOINC_SYSTEM, UCUM_SYSTEM};
+use crate::generators::Locale;
+use crate::types::{ColumnSize, ColumnType};
+
+pub const PRESETS: [&str; 3] = ["patient", "encounter", "observation"];
+
+
+#[derive(Error, Debug)]
+pub enum PresetError {
+    #[error("Unknown preset {preset}, expected one of patient, encounter or observation.")]
+    UnknownPreset { preset: String },
+    #[error("Table {table} is not a FHIR resource, name it patient, encounter or observation.")]
+    NotFhir { table: String },
+}
+
+
+fn key(name: &str) -> Column {
+    Column::new(name.into(), ColumnSize::range(1, 10), ColumnType::Int, generators::sequence(1, 1)).as_key()
+}
+
+
+fn reference(name: &str, table: &str) -> Column {
+    Column::new(name.into(), ColumnSize::range(1, 10), ColumnType::Int, generators::random_int(1, 1000))
+        .references(table, "id")
+}
+
+
+fn one_of(values: &[&str]) -> generators::Generator {
+    generators::one_of(values.iter().map(|x| x.to_string()).collect())
+}
+
+
+/// Columns of a preset, named after the FHIR elements they are written to.
+pub fn preset_columns(preset: &str) -> Result<Vec<Column>> {
+    let timestamp = || generators::random_timestamp("2020-01-01 00:00:00", "2025-12-31 23:59:59");
+
+    Ok(match preset {
+        "patient" => vec![
+            key("id"),
+            Column::new("mrn".into(), 11, ColumnType::Char(11), codes::medical_record_number()),
+            Column::new("family".into(), ColumnSize::range(4, 8), ColumnType::Varchar(20),
+                        generators::last_name(Locale::En)),
+            Column::new("given".into(), ColumnSize::range(4, 9), ColumnType::Varchar(20),
+                        generators::first_name(Locale::En)),
+            Column::new("gender".into(), ColumnSize::range(4, 7), ColumnType::Varchar(7),
+                        codes::administrative_gender()),
+            Column::new("birth_date".into(), 10, ColumnType::Date, generators::random_date("1930-01-01", "2024-12-31")),
+        ],
+        "encounter" => vec![
+            key("id"),
+            reference("patient_id", "patient"),
+            Column::new("status".into(), ColumnSize::range(8, 11), ColumnType::Varchar(11),
+                        one_of(&["finished", "in-progress", "cancelled"])),
+            Column::new("class".into(), ColumnSize::range(2, 4), ColumnType::Varchar(4), codes::encounter_class()),
+            Column::new("period_start".into(), 19, ColumnType::Timestamp, timestamp()),
+            Column::new("reason_code".into(), ColumnSize::range(3, 6), ColumnType::Varchar(6), codes::icd10_code()),
+        ],
+        "observation" => vec![
+            key("id"),
+            reference("patient_id", "patient"),
+            reference("encounter_id", "encounter"),
+            Column::new("status".into(), ColumnSize::range(5, 11), ColumnType::Varchar(11),
+                        one_of(&["final", "amended", "preliminary"])),
+            Column::new("code".into(), ColumnSize::range(6, 7), ColumnType::Varchar(7), codes::loinc_code()),
+            Column::new("value".into(), ColumnSize::range(2, 5), ColumnType::Decimal(6, 1),
+                        codes::observation_value("code")),
+            Column::new("unit".into(), ColumnSize::range(2, 6), ColumnType::Varchar(6),
+                        codes::observation_unit("code")),
+            Column::new("effective".into(), 19, ColumnType::Timestamp, timestamp()),
+        ],
+        _ => return Err(Error::from(PresetError::UnknownPreset { preset: preset.into() })),
+    })
+}
+
+
+/// Patients, their encounters and coded observations, a fifth, three tenths and half of the export.
+pub fn tables() -> Result<Vec<Table>> {
+    PRESETS.iter()
+        .zip(["0.2", "0.3", "0.5"])
+        .map(|(preset, percent_size)| {
+            Ok(Table::new(preset.to_string(), preset_columns(preset)?, "|".into(), Decimal::from_str(percent_size)?))
+        })
+        .collect()
+}
+
+
+fn coding(system: &str, code: &str) -> Value {
+    let mut coding = json!({ "system": system, "code": code });
+    if let Some(display) = codes::display(code) {
+        coding["display"] = json!(display);
+    }
+    coding
+}
+
+
+// Timestamps are written as FHIR dateTime, `YYYY-MM-DDTHH:MM:SS`.
+fn date_time(value: &str) -> String {
+    value.replacen(' ', "T", 1)
+}
+
+
+impl Table {
+    /// A FHIR resource of the row, the resource type is the table id and elements are read from the preset column
+    /// names, columns a preset does not have and null values are left out.
+    pub fn row_to_fhir(&self, row: &[String]) -> Result<Value> {
+        let values: Map<String, Value> = self.columns.iter()
+            .zip(row.iter().skip(1))
+            .filter(|(column, value)| !self.is_null(column, value))
+            .map(|(column, value)| (column.name.clone(), json!(value)))
+            .collect();
+        let get = |name: &str| values.get(name).and_then(|x| x.as_str());
+
+        let mut resource = Map::new();
+        let mut set = |name: &str, value: Option<Value>| {
+            if let Some(value) = value {
+                resource.insert(name.into(), value);
+            }
+        };
+        set("resourceType", Some(json!(match self.id_value.to_ascii_lowercase().as_str() {
+            "patient" => "Patient",
+            "encounter" => "Encounter",
+            "observation" => "Observation",
+            _ => return Err(Error::from(PresetError::NotFhir { table: self.id_value.clone() })),
+        })));
+        set("id", get("id").map(|x| json!(x)));
+
+        match self.id_value.to_ascii_lowercase().as_str() {
+            "patient" => {
+                set("identifier", get("mrn").map(|x| json!([{ "system": "urn:mrn", "value": x }])));
+                let mut name = Map::new();
+                if let Some(family) = get("family") {
+                    name.insert("family".into(), json!(family));
+                }
+                if let Some(given) = get("given") {
+                    name.insert("given".into(), json!([given]));
+                }
+                set("name", (!name.is_empty()).then(|| json!([name])));
+                set("gender", get("gender").map(|x| json!(x)));
+                set("birthDate", get("birth_date").map(|x| json!(x)));
+            }
+            "encounter" => {
+                set("status", get("status").map(|x| json!(x)));
+                set("class", get("class").map(|x| coding(ACT_CODE_SYSTEM, x)));
+                set("subject", get("patient_id").map(|x| json!({ "reference": format!("Patient/{}", x) })));
+                set("period", get("period_start").map(|x| json!({ "start": date_time(x) })));
+                set("reasonCode", get("reason_code").map(|x| json!([{ "coding": [coding(ICD10_SYSTEM, x)] }])));
+            }
+            _ => {
+                set("status", get("status").map(|x| json!(x)));
+                set("code", get("code").map(|x| json!({ "coding": [coding(LOINC_SYSTEM, x)] })));
+                set("subject", get("patient_id").map(|x| json!({ "reference": format!("Patient/{}", x) })));
+                set("encounter", get("encounter_id").map(|x| json!({ "reference": format!("Encounter/{}", x) })));
+                set("effectiveDateTime", get("effective").map(|x| json!(date_time(x))));
+                set("valueQuantity", get("value").map(|x| {
+                    let mut quantity = json!({ "value": x.parse::<f64>().map(|y| json!(y)).unwrap_or(json!(x)) });
+                    if let Some(unit) = get("unit") {
+                        quantity["unit"] = json!(unit);
+                        quantity["system"] = json!(UCUM_SYSTEM);
+                        quantity["code"] = json!(unit);
+                    }
+                    quantity
+                }));
+            }
+        }
+
+        Ok(Value::Object(resource))
+    }
+
+
+    /// A FHIR resource per line.
+    pub fn rows_to_fhir_ndjson(&self, rows: &[Vec<String>]) -> Result<String> {
+        let mut ndjson = String::new();
+        for row in rows {
+            ndjson.push_str(&self.row_to_fhir(row)?.to_string());
+            ndjson.push('\n');
+        }
+        Ok(ndjson)
+    }
+}
+
+
+impl ExportFile {
+    /// Writes every table as FHIR NDJSON files of its resource, the way bulk data exports are laid out.
+    pub fn generate_fhir_ndjson(&self, folder_path: &Path) -> Result<()> {
+        self.write_table_files(folder_path, "ndjson", |table, rows| Ok(table.rows_to_fhir_ndjson(rows)?.into_bytes()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn healthcare_preset_test() {
+        let ef = ExportFile::new(tables().unwrap(), 20_000, 2).unwrap().with_seed(3);
+        let rows = ef.generate_file_rows(1).unwrap();
+        let patient_ids = rows[0].iter().map(|x| x[1].clone()).collect::<Vec<String>>();
+
+        let patient = ef.tables[0].row_to_fhir(&rows[0][0]).unwrap();
+        assert_eq!(patient["resourceType"], "Patient");
+        assert!(patient["identifier"][0]["value"].as_str().unwrap().starts_with("MRN"));
+        assert!(codes::GENDERS.contains(&patient["gender"].as_str().unwrap()));
+
+        let observation = ef.tables[2].row_to_fhir(&rows[2][0]).unwrap();
+        assert_eq!(observation["code"]["coding"][0]["system"], LOINC_SYSTEM);
+        assert!(observation["valueQuantity"]["value"].is_f64());
+        assert!(observation["effectiveDateTime"].as_str().unwrap().contains('T'));
+        let subject = observation["subject"]["reference"].as_str().unwrap().strip_prefix("Patient/").unwrap();
+        let file_0_ids = ef.generate_file_rows(0).unwrap()[0].iter().map(|x| x[1].clone()).collect::<Vec<String>>();
+        assert!(patient_ids.iter().chain(file_0_ids.iter()).any(|x| x == subject));
+
+        let ndjson = ef.tables[1].rows_to_fhir_ndjson(&rows[1]).unwrap();
+        assert_eq!(ndjson.lines().count(), rows[1].len());
+        assert!(ndjson.lines().all(|x| serde_json::from_str::<Value>(x).unwrap()["resourceType"] == "Encounter"));
+        assert!(preset_columns("claim").is_err());
+    }
+
+    #[test]
+    fn healthcare_config_test() {
+        let config = r#"{
+            "size": "20KB",
+            "tables": [
+                {"preset": "patient", "percent_size": 0.4},
+                {"preset": "encounter", "id": "encounter", "percent_size": 0.6, "delimiter": ";",
+                 "columns": [{"name": "ward", "type": "VARCHAR(4)", "generator": {"name": "constant", "args": ["W1"]}}]}
+            ]
+        }"#;
+        let ef = crate::builder::ExportFileBuilder::from_json(config).unwrap().build().unwrap();
+        assert_eq!(ef.tables[0].columns.len(), 6);
+        assert_eq!(ef.tables[1].columns.last().unwrap().name, "ward");
+        assert_eq!(ef.tables[1].delimiter, ";");
+    }
+}
@@ -0,0 +1,146 @@
+use std::str::FromStr;
+
+use anyhow::{Error, Result};
+use rayon::prelude::*;
+use thiserror::Error;
+
+use crate::ExportFile;
+use crate::random::{RandomSource, SeededRandom, ThreadRandom};
+
+
+#[derive(Error, Debug)]
+pub enum InterleaveError {
+    #[error("Unknown interleave mode {mode}, expected blocked, round_robin or shuffled.")]
+    UnknownMode { mode: String },
+}
+
+
+/// How rows of different tables are ordered within one file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InterleaveMode {
+    /// Every table's rows in one block, tables in export order.
+    #[default]
+    Blocked,
+    /// One row of each table in turn until all tables run out.
+    RoundRobin,
+    /// Rows of all tables in random order, reproducible when the export is seeded.
+    Shuffled,
+}
+
+impl FromStr for InterleaveMode {
+    type Err = Error;
+
+    fn from_str(mode: &str) -> Result<Self> {
+        match mode.to_ascii_lowercase().replace('-', "_").as_str() {
+            "blocked" => Ok(InterleaveMode::Blocked),
+            "round_robin" => Ok(InterleaveMode::RoundRobin),
+            "shuffled" => Ok(InterleaveMode::Shuffled),
+            _ => Err(Error::from(InterleaveError::UnknownMode { mode: mode.into() })),
+        }
+    }
+}
+
+
+fn blocked_order(counts: &[usize]) -> Vec<usize> {
+    counts.iter()
+        .enumerate()
+        .flat_map(|(table, count)| std::iter::repeat_n(table, *count))
+        .collect()
+}
+
+
+impl ExportFile {
+    pub fn with_interleave(mut self, interleave: InterleaveMode) -> Self {
+        self.interleave = interleave;
+        self
+    }
+
+
+    /// Table index of every row in the order the rows are written to a file.
+    pub(crate) fn interleave_order(&self, file_index: u64, counts: &[usize]) -> Vec<usize> {
+        match self.interleave {
+            InterleaveMode::Blocked => blocked_order(counts),
+            InterleaveMode::RoundRobin => (0..counts.iter().copied().max().unwrap_or(0))
+                .flat_map(|row| counts.iter().enumerate().filter(move |x| *x.1 > row).map(|x| x.0))
+                .collect(),
+            InterleaveMode::Shuffled => {
+                let seeded = self.file_seed(file_index).map(SeededRandom::new);
+                let random: &dyn RandomSource = match &seeded {
+                    Some(x) => x,
+                    None => &ThreadRandom,
+                };
+
+                let mut order = blocked_order(counts);
+                for i in (1..order.len()).rev() {
+                    order.swap(i, random.below(i as u64 + 1) as usize);
+                }
+                order
+            }
+        }
+    }
+
+
+    /// Contents of one file from every table's rows, with the uncompressed bytes of each table.
+    pub(crate) fn interleave_file(&self, file_index: u64, rows: &[Vec<Vec<String>>]) -> (String, Vec<u64>) {
+        if self.interleave == InterleaveMode::Blocked {
+            let data: Vec<String> = self.tables.iter()
+                .zip(rows)
+                .map(|(table, rows)| table.rows_to_string(rows))
+                .collect();
+            let bytes = data.iter().map(|x| self.encoding.encoded_len(x)).collect();
+            return (data.concat(), bytes);
+        }
+
+        let lines: Vec<Vec<String>> = self.tables.iter()
+            .zip(rows)
+            .map(|(table, rows)| rows.par_iter().map(|x| table.row_to_string(x)).collect())
+            .collect();
+        let bytes = lines.iter().map(|x| x.iter().map(|y| self.encoding.encoded_len(y)).sum()).collect();
+
+        let mut cursors = vec![0; lines.len()];
+        let mut data = String::new();
+        for table in self.interleave_order(file_index, &lines.iter().map(|x| x.len()).collect::<Vec<usize>>()) {
+            data.push_str(&lines[table][cursors[table]]);
+            cursors[table] += 1;
+        }
+
+        (data, bytes)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column, Table};
+    use crate::types::ColumnType;
+
+    fn table(id: &str) -> Table {
+        Table::new(
+            id.into(),
+            vec![Column::new("id".into(), 2, ColumnType::Int, generators::sequence(1, 1))],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        )
+    }
+
+    #[test]
+    fn interleave_test() {
+        let ef = ExportFile::new(vec![table("A"), table("B")], 30, 1).unwrap();
+        let first_column = |x: &str| x.lines().map(|y| y.split('|').next().unwrap().to_string()).collect::<Vec<String>>();
+
+        let round_robin = ef.with_interleave(InterleaveMode::RoundRobin);
+        assert_eq!(first_column(&round_robin.generate_export().unwrap()), vec!["A", "B", "A", "B", "A", "B"]);
+
+        let shuffled = round_robin.with_interleave(InterleaveMode::Shuffled).with_seed(3);
+        let export = shuffled.generate_export().unwrap();
+        let mut lines: Vec<&str> = export.lines().collect();
+        lines.sort();
+        assert_eq!(lines, vec!["A|1", "A|2", "A|3", "B|1", "B|2", "B|3"]);
+        assert_eq!(export, shuffled.generate_export().unwrap());
+
+        assert!("diagonal".parse::<InterleaveMode>().is_err());
+    }
+}
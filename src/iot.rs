@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+use thiserror::Error;
+
+use crate::{generators, Column, ExportFile, SizeSpec, Table};
+use crate::generators::iot::SensorFleet;
+use crate::types::{ColumnSize, ColumnType};
+
+pub const PRESETS: [&str; 2] = ["device", "sensor_reading"];
+/// Devices of the fleet, the `device` table needs this many rows.
+pub const DEVICES: u64 = 50;
+/// Event time of the first reading.
+pub const START: &str = "2025-01-01 00:00:00";
+
+
+#[derive(Error, Debug)]
+pub enum SensorPresetError {
+    #[error("Unknown preset {preset}, expected device or sensor_reading.")]
+    UnknownPreset { preset: String },
+}
+
+
+/// Columns of a preset. Readings hold the id of their device and are timed by the fleet's event clock, every
+/// device once per interval, so files continue the stream where the previous one ended.
+pub fn preset_columns(preset: &str) -> Result<Vec<Column>> {
+    let fleet = SensorFleet::new(DEVICES)?;
+    let text = |name: &str, size: ColumnSize, length, generator| {
+        Column::new(name.into(), size, ColumnType::Varchar(length), generator)
+    };
+
+    Ok(match preset {
+        "device" => vec![
+            Column::new("id".into(), ColumnSize::range(1, 10), ColumnType::Int, generators::sequence(1, 1)).as_key(),
+            text("model", ColumnSize::range(4, 6), 10, fleet.device_model()),
+            text("sensor_type", ColumnSize::range(3, 11), 11, fleet.device_sensor_type()),
+            text("unit", ColumnSize::range(1, 3), 5, fleet.device_unit()),
+            text("site", ColumnSize::range(6, 9), 20, fleet.device_site()),
+            Column::new("installed".into(), 10, ColumnType::Date, generators::random_date("2020-01-01", "2024-12-31")),
+        ],
+        "sensor_reading" => vec![
+            Column::new("id".into(), ColumnSize::range(1, 10), ColumnType::Int, generators::sequence(1, 1)).as_key(),
+            Column::new("device_id".into(), ColumnSize::range(1, 2), ColumnType::Int, fleet.reading_device_id()),
+            Column::new("reading_time".into(), 26, ColumnType::Timestamp,
+                        fleet.clock(START)?.generator(&ColumnType::Timestamp)),
+            Column::new("value".into(), ColumnSize::range(4, 7), ColumnType::Decimal(10, 2), fleet.reading_value()),
+            text("status", ColumnSize::range(2, 7), 7, fleet.reading_status()),
+        ],
+        _ => return Err(Error::from(SensorPresetError::UnknownPreset { preset: preset.into() })),
+    })
+}
+
+
+/// Every preset table, the devices once and readings taking the rest of the export.
+pub fn tables(percent_size_devices: Decimal) -> Result<Vec<Table>> {
+    PRESETS.iter()
+        .zip([percent_size_devices, Decimal::ONE - percent_size_devices])
+        .map(|(preset, percent_size)| {
+            Ok(Table::new(preset.to_string(), preset_columns(preset)?, "|".into(), percent_size))
+        })
+        .collect()
+}
+
+
+/// The sensor fleet export, `DEVICES` device rows and readings filling the rest.
+pub fn export_file(file_size_bytes: u64, number_of_files: u64) -> Result<ExportFile> {
+    ExportFile::new(tables(Decimal::new(1, 2))?, file_size_bytes, number_of_files)?
+        .with_size_spec(SizeSpec::Rows(HashMap::from([("device".to_string(), DEVICES)])))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn sensor_reading_test() {
+        let ef = export_file(200_000, 2).unwrap().with_seed(6);
+        let files = (0..2).map(|x| ef.generate_file_rows(x).unwrap()).collect::<Vec<_>>();
+        let devices: HashSet<String> = files.iter().flat_map(|x| x[0].iter().map(|y| y[1].clone())).collect();
+        let readings = files.iter().flat_map(|x| x[1].clone()).collect::<Vec<Vec<String>>>();
+
+        assert_eq!(devices.len() as u64, DEVICES);
+        assert!(readings.len() > 2000);
+        assert!(readings.iter().all(|x| devices.contains(&x[2])));
+        // Every device reports once per 10 second tick, in order across files.
+        assert!(readings.windows(2).all(|x| x[0][3] <= x[1][3]));
+        assert!(readings[DEVICES as usize][3].starts_with("2025-01-01 00:00:10"));
+        assert!(readings.iter().all(|x| (x[5] == "dropout") == x[4].is_empty()));
+        assert!(readings.iter().any(|x| x[5] == "fault"));
+        assert!(preset_columns("gateway").is_err());
+    }
+}
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
-use std::io::{Write};
-use std::path::{Path, PathBuf};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
 
 use anyhow::{Error, Result};
 use rayon::prelude::*;
@@ -10,37 +12,297 @@ use rust_decimal::Error::ConversionTo;
 use rust_decimal::prelude::*;
 use thiserror::Error;
 
+use crate::compression::Compression;
+use crate::corruption::{corruptions_name, CorruptionProfile};
+use crate::ddl::{Dialect, IdentifierQuoting, TypeMapping, View};
+use crate::dictionary::ColumnData;
+use crate::encoding::Encoding;
+use crate::file_name::FileNameTemplate;
+use crate::generators::{Generator, RowContext, RowValues};
+use crate::interleave::InterleaveMode;
+use crate::archive::ArchiveFormat;
+use crate::checkpoint::Checkpoint;
+use crate::chunked::DEFAULT_CHUNK_BYTES;
+use crate::control::control_name;
+use crate::manifest::manifest_name;
+use crate::output_format::{fit, OutputFormat};
+use crate::metadata::{ChecksumWriter, FileCallback, FileMetadata, TableMetadata};
+use crate::profile::TableProfile;
+use crate::progress::ProgressSink;
+use crate::random::{mix, RandomSource, SeededRandom, ThreadRandom};
+use crate::relationships::Reference;
+use crate::report::{report_name, GenerationReport};
+use crate::stress::NumericStress;
+use crate::throttle::Pacer;
+use crate::transform::Transform;
+use crate::types::{Annotations, ColumnSize, ColumnType, NullRepresentation, RecordTerminator};
+use crate::unique::{unique_columns, UniqueFiles, UniqueIndex};
+
+pub use rust_decimal::Decimal;
+#[cfg(feature = "derive")]
+pub use diplomski_projekt_derive::SyntheticTable;
+
+#[cfg(all(test, feature = "derive"))]
+extern crate self as diplomski_projekt;
+
+pub mod ablation;
+pub mod adtech;
+pub mod affinity;
+pub mod archive;
+#[cfg(feature = "async")]
+pub mod async_io;
+pub mod builder;
+pub mod checkpoint;
+pub mod chunked;
+pub mod cli;
+pub mod compose;
+pub mod compression;
+pub mod config;
+pub mod control;
+pub mod corruption;
+pub mod ddl;
+pub mod definition;
+pub mod delta;
+pub mod dictionary;
+pub mod encoding;
+pub mod events;
+pub mod external;
+pub mod external_table;
+pub mod file_name;
+pub mod finance;
+pub mod fixture;
+pub mod generators;
+pub mod health;
+#[cfg(feature = "healthcare")]
+pub mod healthcare;
+pub mod interleave;
+pub mod iot;
+pub mod locate;
+pub mod manifest;
+pub mod metadata;
+#[cfg(feature = "prometheus")]
+pub mod metrics;
+pub mod output_format;
+pub mod partition;
+pub mod progress;
+pub mod random;
+pub mod per_table;
+pub mod plan;
+pub mod profile;
+pub mod relationships;
+pub mod report;
+pub mod retail;
+pub mod rows;
+pub mod sample;
+pub mod scale;
+pub mod scd;
+pub mod schema;
+pub mod shard;
+pub mod sizing;
+pub mod sinks;
+pub mod spill;
+pub mod statement;
+pub mod stress;
+pub mod survey;
+pub mod telecom;
+pub mod template;
+pub mod throttle;
+pub mod tlv;
+pub mod transform;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod types;
+pub mod unique;
+pub mod validation;
+pub mod warnings;
+pub mod worker;
+mod values;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+#[cfg(feature = "avro")]
+pub mod avro;
+
+/// Generated values sampled to find how many bytes a column's characters take.
+const SIZE_PROBE_ROWS: u64 = 16;
+/// Generated rows averaged for measured sizing.
+const SIZE_SAMPLE_ROWS: u64 = 256;
+
 #[derive(Debug, Clone)]
 pub struct Column {
     name: String,
-    size: u64,
-    sql_type: String,
-    generator: fn() -> Result<String>,
+    size: ColumnSize,
+    column_type: ColumnType,
+    generator: Generator,
+    key: bool,
+    unique: bool,
+    not_null: bool,
+    dictionary: bool,
+    reference: Option<Reference>,
+    null_rate: f64,
+    default: Option<String>,
+    identity: bool,
+    check: Option<String>,
+    transforms: Vec<Transform>,
+    annotations: Annotations,
 }
 
 impl Column {
     pub fn new(
         name: String,
-        size: u64,
-        sql_type: String,
-        generator: fn() -> Result<String>,
+        size: impl Into<ColumnSize>,
+        column_type: ColumnType,
+        generator: impl Into<Generator>,
     ) -> Self {
         Column {
             name,
-            size,
-            sql_type,
-            generator,
+            size: size.into(),
+            column_type,
+            generator: generator.into(),
+            key: false,
+            unique: false,
+            not_null: false,
+            dictionary: true,
+            reference: None,
+            null_rate: 0.0,
+            default: None,
+            identity: false,
+            check: None,
+            transforms: vec![],
+            annotations: Annotations::default(),
         }
     }
+
+    /// A column sized and generated by the defaults of its type, see [`generators::for_type`].
+    pub fn from_type(name: String, column_type: ColumnType) -> Self {
+        let (generator, size) = generators::for_type(&column_type);
+        Column::new(name, size, column_type, generator)
+    }
+
+    /// A BIGINT column with the row's 0 based position in its table across all files, see [`generators::row_number`].
+    pub fn row_number(name: String) -> Self {
+        Column::new(name, ColumnSize::range(1, 20), ColumnType::BigInt, generators::row_number(0))
+    }
+
+    /// Part of the table's primary key, keys are never null and are widened by the namespace if one is set.
+    pub fn as_key(mut self) -> Self {
+        self.key = true;
+        self
+    }
+
+    pub fn is_key(&self) -> bool {
+        self.key
+    }
+
+    /// No two rows of the table share a value of this column, across all files of an export, see [`Table::with_unique`].
+    pub fn unique(mut self, unique: bool) -> Self {
+        self.unique = unique;
+        self
+    }
+
+    pub fn is_unique(&self) -> bool {
+        self.unique
+    }
+
+    /// Declares the column never null, `build_schema` rejects it if it can still generate nulls.
+    pub fn as_not_null(mut self) -> Self {
+        self.not_null = true;
+        self
+    }
+
+    pub fn is_not_null(&self) -> bool {
+        self.not_null || self.key
+    }
+
+    /// Whether no two rows share a value, by the unique flag, an identity or a sequence of the row index.
+    pub fn is_generated_uniquely(&self) -> bool {
+        self.unique || self.identity || self.generator.spec().is_some_and(|x| {
+            x["name"] == "row_number" || (x["name"] == "sequence" && x["args"][1] != 0)
+        })
+    }
+
+    /// Generates the column plain in [`Table::generate_table_columns`], which otherwise dictionary encodes columns
+    /// whose generator has few distinct values.
+    pub fn without_dictionary(mut self) -> Self {
+        self.dictionary = false;
+        self
+    }
+
+    /// Fraction of generated values that are null, clamped to `0.0..=1.0`.
+    pub fn with_null_rate(mut self, null_rate: f64) -> Self {
+        self.null_rate = if null_rate.is_nan() { 0.0 } else { null_rate.clamp(0.0, 1.0) };
+        self
+    }
+
+    pub fn is_nullable(&self) -> bool {
+        self.null_rate > 0.0 || self.generator.has_null_rows()
+    }
+
+    /// SQL expression declared as the column's default in DDL, e.g. `0` or `CURRENT_TIMESTAMP`.
+    pub fn with_default(mut self, default: impl Into<String>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    /// Values are generated by the database, DDL declares an identity column and database sinks leave it out.
+    pub fn as_identity(mut self) -> Self {
+        self.identity = true;
+        self
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.identity
+    }
+
+    /// SQL boolean expression emitted as a check constraint, e.g. `amount >= 0`.
+    pub fn with_check(mut self, check: impl Into<String>) -> Self {
+        self.check = Some(check.into());
+        self
+    }
+
+    pub fn with_annotations(mut self, annotations: Annotations) -> Self {
+        self.annotations = annotations;
+        self
+    }
+
+    // Sizes count characters, so a column of multi-byte values is widened by the most bytes per character seen in
+    // a seeded sample of its generator.
+    fn bytes_per_char(&self) -> u64 {
+        let bytes_per_char = |x: &str| x.len().div_ceil(x.chars().count().max(1)) as u64;
+        let widest = (0..SIZE_PROBE_ROWS)
+            .filter_map(|x| self.generator.generate(&RowContext { row_index: x, random: &SeededRandom::new(x) }).ok())
+            .map(|x| bytes_per_char(&x))
+            .max();
+
+        widest.unwrap_or(1).max(1)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Table {
     id_value: String,
+    qualifiers: Vec<String>,
     columns: Vec<Column>,
     delimiter: String,
+    terminator: RecordTerminator,
+    output_format: OutputFormat,
+    encoding: Encoding,
     percent_size: Decimal,
+    bytes_per_char: Vec<u64>,
     row_size_bytes: u64,
+    measured_row_size: OnceLock<Decimal>,
+    namespace: Option<String>,
+    null_representation: NullRepresentation,
+    random: Arc<dyn RandomSource>,
+    seed: Option<u64>,
+    row_group: u64,
+    unique: Vec<Vec<usize>>,
+    value_spaces: Vec<Option<u64>>,
+    unique_index: Vec<UniqueIndex>,
+    unique_key: Arc<OnceLock<u64>>,
+    profile: Option<Arc<TableProfile>>,
+    corruption: CorruptionProfile,
+    numeric_stress: NumericStress,
+    annotations: Annotations,
 }
 
 impl Table {
@@ -50,68 +312,463 @@ impl Table {
         delimiter: String,
         percent_size: Decimal,
     ) -> Table {
-        let row_size_bytes: u64 = columns
+        let bytes_per_char = columns
             .iter()
-            .map(|x| x.size)
-            .sum();
-        Table { id_value, columns, delimiter, percent_size, row_size_bytes }
+            .map(|x| x.bytes_per_char())
+            .collect();
+        let unique = unique_columns(&columns);
+        let mut table = Table {
+            id_value,
+            qualifiers: vec![],
+            columns,
+            delimiter,
+            terminator: RecordTerminator::Lf,
+            output_format: OutputFormat::Delimited,
+            encoding: Encoding::Utf8,
+            percent_size,
+            bytes_per_char,
+            row_size_bytes: 0,
+            measured_row_size: OnceLock::new(),
+            namespace: None,
+            null_representation: NullRepresentation::Empty,
+            random: Arc::new(ThreadRandom),
+            seed: None,
+            row_group: 1,
+            unique,
+            value_spaces: vec![],
+            unique_index: vec![],
+            unique_key: Arc::new(OnceLock::new()),
+            profile: None,
+            corruption: CorruptionProfile::new(),
+            numeric_stress: NumericStress::new(),
+            annotations: Annotations::default(),
+        };
+        table.update_row_size();
+        table.update_value_spaces();
+        table
+    }
+
+
+    /// Draws every value from a source seeded by the seed, row and column so output does not depend on threading.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self.measured_row_size = OnceLock::new();
+        self
+    }
+
+
+    pub fn with_random_source(mut self, random: Arc<dyn RandomSource>) -> Self {
+        self.random = random;
+        self.unique_key = Arc::new(OnceLock::new());
+        self
+    }
+
+
+    /// Rows belong together in groups of `rows`, e.g. the lines of a transaction, so every file of an export holds
+    /// whole groups and row counts are rounded down to them.
+    pub fn with_row_group(mut self, rows: u64) -> Self {
+        self.row_group = rows.max(1);
+        self
+    }
+
+
+    pub fn row_group(&self) -> u64 {
+        self.row_group
+    }
+
+
+    pub fn with_null_representation(mut self, null_representation: NullRepresentation) -> Self {
+        self.null_representation = null_representation;
+        self
+    }
+
+
+    pub fn with_delimiter(mut self, delimiter: impl Into<String>) -> Self {
+        self.delimiter = delimiter.into();
+        self.update_row_size();
+        self
+    }
+
+
+    pub fn with_annotations(mut self, annotations: Annotations) -> Self {
+        self.annotations = annotations;
+        self
+    }
+
+
+    pub fn with_terminator(mut self, terminator: RecordTerminator) -> Self {
+        self.terminator = terminator;
+        self.update_row_size();
+        self
+    }
+
+
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self.update_row_size();
+        self
+    }
+
+
+    /// Places the table in a catalog, schema or database, e.g. `analytics.staging`, for DDL, manifests and sinks.
+    pub fn with_qualifier(mut self, qualifier: &str) -> Self {
+        self.qualifiers = qualifier.split('.').filter(|x| !x.is_empty()).map(|x| x.to_string()).collect();
+        self
+    }
+
+
+    /// The id with its qualifiers, e.g. `analytics.staging.orders`.
+    pub fn qualified_name(&self) -> String {
+        self.qualifiers.iter()
+            .chain(std::iter::once(&self.id_value))
+            .map(|x| x.as_str())
+            .collect::<Vec<&str>>()
+            .join(".")
+    }
+
+
+    pub(crate) fn is_null(&self, column: &Column, value: &str) -> bool {
+        column.is_nullable() && value == self.null_representation.as_str()
+    }
+
+
+    fn set_namespace(&mut self, namespace: Option<String>) {
+        self.namespace = namespace;
+        self.update_row_size();
+    }
+
+
+    fn namespace_size(&self) -> u64 {
+        self.namespace.as_ref().map_or(0, |x| x.len() as u64 + 1)
+    }
+
+
+    // Bytes of a row as written: the id, a delimiter before every value, the values, namespaced keys and the
+    // terminator. Fixed width rows have no delimiters and pad values to their largest size, delimited values count
+    // their average size and key value pairs also their names. Single byte encodings write a byte per character.
+    fn update_row_size(&mut self) {
+        let key_columns = self.columns.iter().filter(|x| x.key).count() as u64;
+        let namespace_size = self.namespace_size();
+        let (delimiter_size, fixed_width) = match self.output_format {
+            OutputFormat::Delimited | OutputFormat::KeyValue { .. } => {
+                (self.encoding.encoded_len(&self.delimiter), false)
+            }
+            OutputFormat::FixedWidth { .. } => (0, true),
+        };
+        let names_size = match self.output_format {
+            OutputFormat::KeyValue { separator } => self.columns.iter()
+                .map(|x| self.encoding.encoded_len(&format!("{}{}", x.name, separator)))
+                .fold(0, u64::saturating_add),
+            _ => 0,
+        };
+        let value_size = self.columns.iter()
+            .zip(self.bytes_per_char.iter())
+            .map(|(x, y)| {
+                let chars = if fixed_width { x.size.max() } else { x.size.average() };
+                chars.saturating_mul(if self.encoding.is_single_byte() { 1 } else { *y })
+            })
+            .fold(0, u64::saturating_add);
+
+        self.measured_row_size = OnceLock::new();
+        self.row_size_bytes = [
+            self.encoding.encoded_len(&self.id_value),
+            delimiter_size.saturating_mul(self.columns.len() as u64),
+            value_size,
+            names_size,
+            key_columns.saturating_mul(namespace_size),
+            self.encoding.encoded_len(self.terminator.as_str()),
+        ]
+            .into_iter()
+            .fold(0, u64::saturating_add);
+    }
+
+
+    fn value_random(&self, row_index: u64, column_index: usize) -> Option<SeededRandom> {
+        self.stream_seed().map(|x| SeededRandom::new(mix(mix(x, row_index), column_index as u64)))
+    }
+
+
+    fn generate_value(&self, column_index: usize, row_index: u64) -> Result<String> {
+        if let Some(digit) = self.permuted_digits(row_index).get(column_index).copied().flatten() {
+            return self.permuted_value(column_index, row_index, digit);
+        }
+        if !self.columns[column_index].generator.depends_on_row() {
+            return self.generate_value_with(column_index, row_index, self.value_random(row_index, column_index), &[]);
+        }
+
+        // Earlier columns are generated again, a seeded table gives them the same values as in the full row.
+        let mut row = self.generate_values_at(row_index)?;
+        Ok(row.swap_remove(column_index + 1))
+    }
+
+
+    /// Generates a value of the column, `row` holds the values of the earlier columns for generators that read them.
+    pub(crate) fn generate_value_with(
+        &self,
+        column_index: usize,
+        row_index: u64,
+        value_random: Option<SeededRandom>,
+        row: &[String],
+    ) -> Result<String> {
+        let column = &self.columns[column_index];
+        let random: &dyn RandomSource = match &value_random {
+            Some(x) => x,
+            None => self.random.as_ref(),
+        };
+
+        if column.generator.is_null_row(row_index) || (column.null_rate > 0.0 && random.chance(column.null_rate)) {
+            return Ok(self.null_representation.as_str().to_string());
+        }
+
+        let started = self.profile.as_ref().map(|_| Instant::now());
+        let value = column.generator.generate_in_row(&RowContext { row_index, random }, &RowValues::new(&self.columns, row))?;
+        let value = column.transform(value);
+        if let (Some(profile), Some(started)) = (&self.profile, started) {
+            profile.record(column_index, started.elapsed());
+        }
+
+        Ok(self.namespaced(column_index, value))
+    }
+
+
+    // Keys of a namespaced table are prefixed by the namespace, unless they reference another table.
+    pub(crate) fn namespaced(&self, column_index: usize, value: String) -> String {
+        let column = &self.columns[column_index];
+        match &self.namespace {
+            Some(namespace) if column.key && column.reference.is_none() => format!("{}-{}", namespace, value),
+            _ => value,
+        }
     }
 
 
     pub fn generate_table_row(&self) -> Result<String> {
-        let mut buffer: Vec<String> = vec![self.id_value.clone()];
-        buffer.append(
-            &mut self.columns.iter()
-                .map(|x| (x.generator)())
-                .collect::<Result<Vec<String>>>()?
-        );
+        self.generate_table_row_at(0)
+    }
 
-        Ok(buffer.join(&self.delimiter) + "\n")
+    pub fn generate_table_row_at(&self, row_index: u64) -> Result<String> {
+        Ok(self.row_to_string(&self.generate_table_row_vec_at(row_index)?))
     }
 
     pub fn generate_table_row_vec(&self) -> Result<Vec<String>> {
+        self.generate_table_row_vec_at(0)
+    }
+
+    pub fn generate_table_row_vec_at(&self, row_index: u64) -> Result<Vec<String>> {
+        let mut row = self.generate_values_at(row_index)?;
+        self.corrupt_row(row_index, row_index, &mut row)?;
+        Ok(row)
+    }
+
+    // Values of a row before corruption, unique constraints are enforced on these.
+    pub(crate) fn generate_values_at(&self, row_index: u64) -> Result<Vec<String>> {
+        let digits = self.permuted_digits(row_index);
         let mut buffer: Vec<String> = vec![self.id_value.clone()];
-        buffer.append(
-            &mut self.columns.iter()
-                .map(|x| (x.generator)())
-                .collect::<Result<Vec<String>>>()?
-        );
+        for column_index in 0..self.columns.len() {
+            let value = match digits.get(column_index).copied().flatten() {
+                Some(digit) => self.permuted_value(column_index, row_index, digit)?,
+                None => self.generate_value_with(
+                    column_index,
+                    row_index,
+                    self.value_random(row_index, column_index),
+                    &buffer[1..],
+                )?,
+            };
+            buffer.push(value);
+        }
 
         Ok(buffer)
     }
 
-    pub fn generate_table(&self, file_size_bytes: u64) -> Result<String> {
+    // Swaps the column's generator after construction, dropping what was probed from the old one.
+    pub(crate) fn replace_generator(&mut self, column_index: usize, generator: Generator) {
+        self.columns[column_index].generator = generator;
+        self.update_value_spaces();
+        self.measured_row_size = OnceLock::new();
+    }
+
+    /// Generates again the columns that read earlier values after the first of the `changed` columns.
+    pub(crate) fn regenerate_dependents(&self, changed: &[usize], row_index: u64, row: &mut [String]) -> Result<()> {
+        let first = match changed.iter().min() {
+            Some(x) => *x,
+            None => return Ok(()),
+        };
+
+        for x in first + 1..self.columns.len() {
+            if self.columns[x].generator.depends_on_row() && !changed.contains(&x) {
+                row[x + 1] = self.generate_value_with(x, row_index, self.value_random(row_index, x), &row[1..x + 1])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn row_to_string(&self, row: &[String]) -> String {
+        let (padding, alignment) = match self.output_format {
+            OutputFormat::Delimited => return row.join(&self.delimiter) + self.terminator.as_str(),
+            OutputFormat::KeyValue { separator } => return self.row_to_pairs(row, separator),
+            OutputFormat::FixedWidth { padding, alignment } => (padding, alignment),
+        };
+
+        let mut line = row[0].clone();
+        for (column, value) in self.columns.iter().zip(row[1..].iter()) {
+            let width = column.size.max() + if column.key { self.namespace_size() } else { 0 };
+            fit(value, width as usize, padding, alignment, &mut line);
+        }
+
+        line + self.terminator.as_str()
+    }
+
+    // Values a corrupted row has beyond its columns are written without a name.
+    fn row_to_pairs(&self, row: &[String], separator: char) -> String {
+        let mut line = row[0].clone();
+        for (index, value) in row[1..].iter().enumerate() {
+            line.push_str(&self.delimiter);
+            if let Some(column) = self.columns.get(index) {
+                line.push_str(&column.name);
+                line.push(separator);
+            }
+            line.push_str(value);
+        }
+
+        line + self.terminator.as_str()
+    }
+
+    fn rows_to_string(&self, rows: &[Vec<String>]) -> String {
+        rows.par_iter()
+            .map(|x| self.row_to_string(x))
+            .collect()
+    }
+
+    pub fn row_count(&self, file_size_bytes: u64) -> Result<u64> {
         let table_size_bytes = (
             Decimal::from(file_size_bytes)
                 * self.percent_size
         )
             .to_u64()
             .ok_or(ConversionTo("Failed to convert to u64".into()))?;
-        let row_count = table_size_bytes / self.row_size_bytes;
 
-        (0..row_count)
+        table_size_bytes.checked_div(self.row_size_bytes)
+            .ok_or_else(|| Error::from(ExportFileError::EmptyTable { table: self.id_value.clone() }))
+    }
+
+    /// Rows that fit into the table's share of a file by the average bytes of its first generated rows.
+    pub fn measured_row_count(&self, file_size_bytes: u64) -> Result<u64> {
+        let row_size = match self.measured_row_size.get() {
+            Some(x) => *x,
+            None => {
+                let rows = self.generate_rows_vec(SIZE_SAMPLE_ROWS)?;
+                let bytes = rows.iter()
+                    .map(|x| self.encoding.encoded_len(&self.row_to_string(x)))
+                    .fold(0, u64::saturating_add);
+                let row_size = (Decimal::from(bytes) / Decimal::from(SIZE_SAMPLE_ROWS)).max(Decimal::ONE);
+                *self.measured_row_size.get_or_init(|| row_size)
+            }
+        };
+
+        (Decimal::from(file_size_bytes) * self.percent_size / row_size)
+            .floor()
+            .to_u64()
+            .ok_or_else(|| Error::from(ConversionTo("Failed to convert to u64".into())))
+    }
+
+    pub fn generate_rows(&self, row_count: u64) -> Result<String> {
+        self.generate_rows_from(0, row_count)
+    }
+
+    pub fn generate_rows_from(&self, first_row_index: u64, row_count: u64) -> Result<String> {
+        self.check_value_space(row_count)?;
+        if self.is_sequential() {
+            return Ok(self.rows_to_string(&self.generate_rows_vec_from(first_row_index, row_count)?));
+        }
+
+        (first_row_index..row_range_end(first_row_index, row_count)?)
+            .into_par_iter()
+            .map(|x| self.generate_table_row_at(x))
+            .collect()
+    }
+
+    pub fn generate_rows_vec(&self, row_count: u64) -> Result<Vec<Vec<String>>> {
+        self.generate_rows_vec_from(0, row_count)
+    }
+
+    pub fn generate_rows_vec_from(&self, first_row_index: u64, row_count: u64) -> Result<Vec<Vec<String>>> {
+        self.check_value_space(row_count)?;
+        let mut rows = self.generate_values_from(first_row_index, row_count)?;
+        self.enforce_unique(first_row_index, &mut rows, &mut self.unique_seen())?;
+        self.corrupt_rows(first_row_index, rows)
+    }
+
+    // Values of rows before unique constraints are enforced.
+    pub(crate) fn generate_values_from(&self, first_row_index: u64, row_count: u64) -> Result<Vec<Vec<String>>> {
+        (first_row_index..row_range_end(first_row_index, row_count)?)
             .into_par_iter()
-            .map(|_| self.generate_table_row())
-            .try_reduce(|| "".to_string(), |x, y| Ok(x + &y))
+            .map(|x| self.generate_values_at(x))
+            .collect()
+    }
+
+    pub(crate) fn corrupt_rows(&self, first_row_index: u64, mut rows: Vec<Vec<String>>) -> Result<Vec<Vec<String>>> {
+        self.duplicate_keys(first_row_index, &mut rows);
+        rows.par_iter_mut()
+            .enumerate()
+            .try_for_each(|(i, x)| self.corrupt_row(first_row_index, first_row_index + i as u64, x))?;
+        Ok(rows)
+    }
+
+    pub fn generate_table(&self, file_size_bytes: u64) -> Result<String> {
+        self.generate_rows(self.row_count(file_size_bytes)?)
     }
 
     pub fn generate_table_vec(&self, file_size_bytes: u64) -> Result<Vec<Vec<String>>> {
-        let table_size_bytes = (
-            Decimal::from(file_size_bytes)
-                * self.percent_size
-        )
-            .to_u64()
-            .ok_or(ConversionTo("Failed to convert to u64".into()))?;
-        let row_count = table_size_bytes / self.row_size_bytes;
+        self.generate_rows_vec(self.row_count(file_size_bytes)?)
+    }
+
+    pub fn generate_table_typed<T: SyntheticTable + Send>(&self, file_size_bytes: u64) -> Result<Vec<T>> {
+        let row_count = self.row_count(file_size_bytes)?;
 
         (0..row_count)
             .into_par_iter()
-            .map(|_| self.generate_table_row_vec())
+            .map(|x| {
+                let row = self.generate_table_row_vec_at(x)?;
+                Ok(T::from_row(&row[1..])?)
+            })
+            .collect()
+    }
+
+    pub fn generate_table_columns(&self, file_size_bytes: u64) -> Result<Vec<ColumnData>> {
+        let row_count = self.row_count(file_size_bytes)?;
+
+        (0..self.columns.len())
+            .map(|x| self.generate_column_data(x, row_count))
             .collect()
     }
 }
 
 
+pub trait SyntheticTable: Sized {
+    fn synthetic_table() -> Table;
+
+    fn from_row(row: &[String]) -> std::result::Result<Self, RowError>;
+}
+
+
+#[derive(Error, Debug)]
+pub enum RowError {
+    #[error("Row is missing a value for column {column}.")]
+    MissingValue { column: String },
+    #[error("Value {value} of column {column} can not be parsed.")]
+    InvalidValue { column: String, value: String },
+}
+
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Table {table} needs {needed} bytes for a row but only {available} bytes of each file are available to it.")]
+pub struct TableDoesNotFit {
+    pub table: String,
+    pub needed: u64,
+    pub available: u64,
+}
+
+
 #[derive(Error, Debug)]
 pub enum ExportFileError {
     #[error("Sum of table percentage sizes must be equal 1. It was {sum_percent_size}.")]
@@ -120,10 +777,47 @@ pub enum ExportFileError {
     DuplicateColumns { table: String, column: String },
     #[error("Export File contains duplicate table {table}.")]
     DuplicateTables { table: String },
-    #[error("Too many files to generate {files}")]
-    TooManyFiles { files: u64 },
+    #[error("{files} files do not fit into {data_size_bytes} bytes, every file needs at least one byte.")]
+    TooManyFiles { files: u64, data_size_bytes: u64 },
+    #[error("{quantity} does not fit into 64 bits, split the export into several smaller ones.")]
+    SizeOverflow { quantity: String },
+    #[error("Export needs at least one file.")]
+    NoFiles,
+    #[error("Export size is 0 bytes, set a size that fits at least one row of every table.")]
+    ZeroSize,
+    #[error("Table {table} has no columns, so no rows can be sized for it.")]
+    EmptyTable { table: String },
+    #[error("Table {table} has percent size {percent_size}, it must be above 0 to hold any rows.")]
+    InvalidPercentSize { table: String, percent_size: Decimal },
     #[error("ReduceFailed")]
     ReduceFailed,
+    #[error("Table {table} has no percent size.")]
+    MissingPercentSize { table: String },
+    #[error("Export File has no target size in bytes.")]
+    MissingTargetBytes,
+    #[error("Export File does not contain table {table}.")]
+    UnknownTable { table: String },
+    #[error("Column {column} of table {table} is {column_type}, only textual keys can be namespaced.")]
+    NamespacedNonTextKey { table: String, column: String, column_type: String },
+    #[error("Column {column} of table {table} is a key or not null, but its null rate or generator makes nulls.")]
+    NullableNotNull { table: String, column: String },
+    #[error(
+        "{} table(s) do not fit into a file: {} Raise the size to at least {} bytes, use fewer files or size the \
+         tables by rows.",
+        .tables.len(),
+        .tables.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(" "),
+        .minimum_bytes
+    )]
+    TablesDoNotFit { tables: Vec<TableDoesNotFit>, minimum_bytes: u64 },
+}
+
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SizeSpec {
+    #[default]
+    Bytes,
+    /// Total rows per table id across all files, tables that are not listed are sized by bytes.
+    Rows(HashMap<String, u64>),
 }
 
 
@@ -131,29 +825,112 @@ pub struct ExportFile {
     tables: Vec<Table>,
     number_of_files: u64,
     file_size_bytes: u64,
+    namespace: Option<String>,
+    compression: Compression,
+    size_spec: SizeSpec,
+    file_callback: Option<FileCallback>,
+    progress: Vec<Arc<dyn ProgressSink>>,
+    interleave: InterleaveMode,
+    seed: Option<u64>,
+    file_name: Option<FileNameTemplate>,
+    manifest: bool,
+    max_rows_per_file: Option<u64>,
+    scale_factor: f64,
+    row_ratios: HashMap<String, (String, f64)>,
+    affinity: HashMap<String, Vec<u64>>,
+    checkpoint: bool,
+    archive: Option<ArchiveFormat>,
+    encoding: Encoding,
+    measured_sizing: bool,
+    identifier_quoting: HashMap<Dialect, IdentifierQuoting>,
+    type_mapping: TypeMapping,
+    constraints_ddl: bool,
+    views: Vec<View>,
+    chunk_bytes: u64,
+    throttle: Option<Pacer>,
+    report: bool,
+    control_batch: Option<String>,
+    generator_errors: Mutex<HashMap<String, u64>>,
+    unique_files: Vec<Mutex<UniqueFiles>>,
 }
 
+const FILE_STREAM: u64 = u64::MAX - 1;
+
+// Sizes are summed or multiplied as u128, which can not overflow for u64 operands, and narrowed here.
+pub(crate) fn checked_u64(value: u128, quantity: &str) -> Result<u64> {
+    u64::try_from(value).map_err(|_| Error::from(ExportFileError::SizeOverflow { quantity: quantity.into() }))
+}
+
+
+// End of the rows starting at `first_row_index`, an error instead of wrapping past the last row index.
+pub(crate) fn row_range_end(first_row_index: u64, row_count: u64) -> Result<u64> {
+    checked_u64(first_row_index as u128 + row_count as u128, "Row index")
+}
+
+
+// Every table needs room for a row in every file, the error names the smallest size where all of them fit.
+pub(crate) fn does_not_fit(tables: &[Table], file_size_bytes: u64, number_of_files: u64) -> Result<()> {
+    let does_not_fit: Vec<TableDoesNotFit> = tables.iter()
+        .filter(|x| Decimal::from(file_size_bytes) * x.percent_size < Decimal::from(x.row_size_bytes))
+        .map(|x| TableDoesNotFit {
+            table: x.id_value.clone(),
+            needed: x.row_size_bytes,
+            available: (Decimal::from(file_size_bytes) * x.percent_size).floor().to_u64().unwrap_or(0),
+        })
+        .collect();
+
+    if does_not_fit.is_empty() {
+        return Ok(());
+    }
+
+    let minimum_file_bytes = tables.iter()
+        .filter_map(|x| (Decimal::from(x.row_size_bytes) / x.percent_size).ceil().to_u64())
+        .max()
+        .unwrap_or(u64::MAX);
+
+    Err(Error::from(ExportFileError::TablesDoNotFit {
+        tables: does_not_fit,
+        minimum_bytes: minimum_file_bytes.saturating_mul(number_of_files),
+    }))
+}
+
+
 impl ExportFile {
     pub fn new(
         tables: Vec<Table>,
         data_size_bytes: u64,
         number_of_files: u64,
     ) -> Result<ExportFile> {
+        if number_of_files == 0 {
+            return Err(Error::from(ExportFileError::NoFiles));
+        }
+        if data_size_bytes == 0 {
+            return Err(Error::from(ExportFileError::ZeroSize));
+        }
         if number_of_files >= data_size_bytes {
-            return Err(Error::from(ExportFileError::TooManyFiles { files: number_of_files }));
+            return Err(Error::from(ExportFileError::TooManyFiles { files: number_of_files, data_size_bytes }));
         }
 
         let file_size_bytes = data_size_bytes / number_of_files;
 
-        let is_possible = tables.iter()
-            .map(|x| Decimal::from(file_size_bytes) * x.percent_size >= Decimal::from(x.row_size_bytes))
-            .reduce(|x, y| x && y)
-            .ok_or(Error::from(ExportFileError::ReduceFailed))?;
+        if tables.is_empty() {
+            return Err(Error::from(ExportFileError::ReduceFailed));
+        }
 
-        if !is_possible {
-            return Err(Error::from(ExportFileError::TooManyFiles { files: number_of_files }));
+        for table in tables.iter() {
+            if table.columns.is_empty() {
+                return Err(Error::from(ExportFileError::EmptyTable { table: table.id_value.clone() }));
+            }
+            if table.percent_size <= Decimal::ZERO {
+                return Err(Error::from(ExportFileError::InvalidPercentSize {
+                    table: table.id_value.clone(),
+                    percent_size: table.percent_size,
+                }));
+            }
         }
 
+        does_not_fit(&tables, file_size_bytes, number_of_files)?;
+
         let sum_percent_size: Decimal = tables.iter()
             .map(|x| x.percent_size)
             .sum();
@@ -162,82 +939,503 @@ impl ExportFile {
             return Err(Error::from(ExportFileError::SumPercentSizeIncorrect { sum_percent_size }));
         }
 
-        Ok(ExportFile { tables, number_of_files, file_size_bytes })
+        let unique_files = tables.iter().map(|_| Mutex::new(UniqueFiles::default())).collect();
+
+        let export_file = ExportFile {
+            tables,
+            number_of_files,
+            file_size_bytes,
+            namespace: None,
+            compression: Compression::None,
+            size_spec: SizeSpec::Bytes,
+            file_callback: None,
+            progress: vec![],
+            interleave: InterleaveMode::Blocked,
+            seed: None,
+            file_name: None,
+            manifest: false,
+            max_rows_per_file: None,
+            scale_factor: 1.0,
+            row_ratios: HashMap::new(),
+            affinity: HashMap::new(),
+            checkpoint: false,
+            archive: None,
+            encoding: Encoding::Utf8,
+            measured_sizing: false,
+            identifier_quoting: HashMap::new(),
+            type_mapping: TypeMapping::new(),
+            constraints_ddl: false,
+            views: vec![],
+            chunk_bytes: DEFAULT_CHUNK_BYTES,
+            throttle: None,
+            report: false,
+            control_batch: None,
+            generator_errors: Mutex::new(HashMap::new()),
+            unique_files,
+        };
+        export_file.generation_order()?;
+
+        Ok(export_file)
     }
 
 
-    pub fn generate_export(&self) -> Result<String> {
-        self.tables.par_iter()
-            .map(|x| x.generate_table(self.file_size_bytes))
-            .try_reduce(|| "".to_string(), |x, y| Ok(x + &y))
+    /// Like `new`, but rescales every table's `percent_size` by their sum so any positive weights can be used.
+    pub fn new_normalized(
+        mut tables: Vec<Table>,
+        data_size_bytes: u64,
+        number_of_files: u64,
+    ) -> Result<ExportFile> {
+        let sum_percent_size: Decimal = tables.iter().map(|x| x.percent_size).sum();
+
+        if tables.iter().all(|x| x.percent_size > Decimal::ZERO) {
+            // The last table takes what rounding left, so the shares sum to exactly 1.
+            let mut remaining = Decimal::ONE;
+            let last = tables.len().saturating_sub(1);
+            for (index, table) in tables.iter_mut().enumerate() {
+                table.percent_size = match index == last {
+                    true => remaining,
+                    false => table.percent_size / sum_percent_size,
+                };
+                remaining -= table.percent_size;
+            }
+        }
+
+        ExportFile::new(tables, data_size_bytes, number_of_files)
     }
 
 
-    pub fn generate_raw_tables(&self) -> HashMap<String, Result<Vec<Vec<String>>>> {
-        self.tables.par_iter()
-            .map(|x| {
-                let mut m: HashMap<String, Result<Vec<Vec<String>>>> = HashMap::new();
-                m.insert(
-                    x.id_value.clone(),
-                    x.generate_table_vec(self.file_size_bytes),
-                );
-                m
-            })
-            .reduce(|| HashMap::new(), |a, b| {
-                a.into_iter().chain(b).collect()
-            })
+    /// Prefixes key column values with a run namespace, e.g. `run1-42`, `None` leaves them bare. Namespaced values
+    /// are text, so every key column and every column referencing one must have a textual type.
+    pub fn with_namespace(mut self, namespace: Option<String>) -> Result<Self> {
+        if namespace.is_some() {
+            for table in self.tables.iter() {
+                for column in table.columns.iter() {
+                    let namespaced = column.key || column.reference.as_ref().is_some_and(|x| {
+                        self.tables.iter()
+                            .filter(|y| y.id_value == x.table)
+                            .flat_map(|y| y.columns.iter())
+                            .any(|y| y.name == x.column && y.key)
+                    });
+                    if namespaced && !column.column_type.is_textual() {
+                        return Err(Error::from(ExportFileError::NamespacedNonTextKey {
+                            table: table.id_value.clone(),
+                            column: column.name.clone(),
+                            column_type: column.column_type.to_string(),
+                        }));
+                    }
+                }
+            }
+        }
+
+        for table in self.tables.iter_mut() {
+            table.set_namespace(namespace.clone());
+        }
+        self.namespace = namespace;
+        Ok(self)
     }
 
 
-    pub fn raw_tables_to_string(tables: HashMap<String, Result<Vec<Vec<String>>>>, delimiter: &str) -> Result<String> {
-        Ok(tables.par_iter()
-            .map(|x|
-                {
-                    x.1.as_ref()
-                        .par_iter()
-                        .map(|y| y.par_iter()
-                            .map(|z| z.join(delimiter))
-                            .reduce(|| "".to_string(), |a, b| a + &b)
-                        )
-                        .reduce(|| "".to_string(), |a, b| a + &b)
-                })
-            .reduce(|| "".to_string(), |a, b| {
-                a + &b
-            }))
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
     }
 
 
-    pub fn generate_export_to_file(&self, path: &Path) -> Result<()> {
-        let exported = self.generate_export()?;
-        let mut file = File::create(path)?;
-        file.write_all(exported.as_ref())?;
-        Ok(())
+    pub fn with_null_representation(mut self, null_representation: NullRepresentation) -> Self {
+        self.tables = self.tables.into_iter()
+            .map(|x| x.with_null_representation(null_representation.clone()))
+            .collect();
+        self
     }
 
 
-    pub fn generate_all_files(&self, folder_path: &Path) -> Result<()> {
-        fs::create_dir_all(folder_path)?;
+    /// Sets the delimiter of every table.
+    pub fn with_delimiter(mut self, delimiter: &str) -> Self {
+        self.tables = self.tables.into_iter()
+            .map(|x| x.with_delimiter(delimiter))
+            .collect();
+        self
+    }
+
+
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.tables = self.tables.into_iter()
+            .map(|x| x.with_output_format(output_format))
+            .collect();
+        self
+    }
+
+
+    /// Encodes written text files, tables are sized for it.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        for table in self.tables.iter_mut() {
+            table.encoding = encoding;
+            table.update_row_size();
+        }
+        self.encoding = encoding;
+        self
+    }
+
+
+    /// Sets the record terminator of every table.
+    pub fn with_terminator(mut self, terminator: RecordTerminator) -> Self {
+        self.tables = self.tables.into_iter()
+            .map(|x| x.with_terminator(terminator.clone()))
+            .collect();
+        self
+    }
+
+
+    /// Qualifies every table.
+    pub fn with_qualifier(mut self, qualifier: &str) -> Self {
+        self.tables = self.tables.into_iter()
+            .map(|x| x.with_qualifier(qualifier))
+            .collect();
+        self
+    }
+
+
+    pub fn with_table_qualifier(mut self, table: &str, qualifier: &str) -> Result<Self> {
+        let index = self.table_index(table)?;
+        self.tables[index] = self.tables[index].clone().with_qualifier(qualifier);
+        Ok(self)
+    }
+
+
+    pub fn with_table_delimiter(mut self, table: &str, delimiter: &str) -> Result<Self> {
+        let index = self.table_index(table)?;
+        self.tables[index].delimiter = delimiter.into();
+        self.tables[index].update_row_size();
+        Ok(self)
+    }
+
+
+    pub fn with_table_terminator(mut self, table: &str, terminator: RecordTerminator) -> Result<Self> {
+        let index = self.table_index(table)?;
+        self.tables[index].terminator = terminator;
+        self.tables[index].update_row_size();
+        Ok(self)
+    }
+
+
+    fn table_index(&self, table: &str) -> Result<usize> {
+        self.tables.iter()
+            .position(|x| x.id_value == table)
+            .ok_or_else(|| Error::from(ExportFileError::UnknownTable { table: table.into() }))
+    }
+
+
+    pub fn with_random_source(mut self, random: Arc<dyn RandomSource>) -> Self {
+        self.tables = self.tables.into_iter()
+            .map(|x| x.with_random_source(random.clone()))
+            .collect();
+        self
+    }
+
+
+    /// Seeds every table so the same seed always generates the same files.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self.tables = self.tables.into_iter()
+            .enumerate()
+            .map(|(i, x)| x.with_seed(mix(seed, i as u64)))
+            .collect();
+        self
+    }
+
+
+    /// Seed of the randomness of file `file_index` as a whole, e.g. the order of shuffled rows. Values are seeded by
+    /// their row index, which continues from file to file, so every file of a seeded export is different and the set
+    /// is the same on every run.
+    pub fn file_seed(&self, file_index: u64) -> Option<u64> {
+        self.seed.map(|x| mix(mix(x, FILE_STREAM), file_index))
+    }
+
+
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+
+    /// Called with the metadata of every file after it is closed.
+    pub fn with_file_callback(
+        mut self,
+        callback: impl Fn(&FileMetadata) -> Result<()> + Send + Sync + 'static,
+    ) -> Self {
+        self.file_callback = Some(Arc::new(callback));
+        self
+    }
+
+
+    pub(crate) fn file_written(&self, metadata: FileMetadata) -> Result<()> {
+        for progress in &self.progress {
+            progress.file_completed(&metadata);
+        }
+
+        match &self.file_callback {
+            Some(callback) => callback(&metadata),
+            None => Ok(()),
+        }
+    }
+
+
+    pub fn with_size_spec(mut self, size_spec: SizeSpec) -> Result<Self> {
+        if let SizeSpec::Rows(rows) = &size_spec {
+            for table in rows.keys() {
+                if !self.tables.iter().any(|x| &x.id_value == table) {
+                    return Err(Error::from(ExportFileError::UnknownTable { table: table.clone() }));
+                }
+            }
+        }
+
+        self.size_spec = size_spec;
+        Ok(self)
+    }
+
+
+    pub fn table_row_count(&self, table: &Table, file_index: u64) -> Result<u64> {
+        let (files, before, in_file) = self.table_files(table, file_index);
+        if !in_file {
+            return Ok(0);
+        }
+
+        match self.table_rows_total(table)? {
+            Some(total) => {
+                let groups = total / table.row_group;
+                Ok((groups / files + u64::from(before < groups % files)) * table.row_group)
+            }
+            None => self.table_row_count_by_bytes(table),
+        }
+    }
+
+
+    // A namespace set after `new` widens key columns, so a table can stop fitting and would silently be empty.
+    fn table_row_count_by_bytes(&self, table: &Table) -> Result<u64> {
+        let rows = if self.measured_sizing {
+            table.measured_row_count(self.file_size_bytes)?
+        } else {
+            table.row_count(self.file_size_bytes)?
+        };
+        if rows == 0 {
+            does_not_fit(&self.tables, self.file_size_bytes, self.number_of_files)?;
+        }
+
+        Ok(rows - rows % table.row_group)
+    }
+
+
+    /// Index of the first row of `table` in file `file_index`, so row indices continue across files.
+    pub fn table_row_offset(&self, table: &Table, file_index: u64) -> Result<u64> {
+        // Files a table is not pinned to are skipped, its rows continue in the next file it is pinned to.
+        let (files, before, _) = self.table_files(table, file_index);
+        let offset = match self.table_rows_total(table)? {
+            Some(total) => {
+                let groups = total / table.row_group;
+                ((groups / files) as u128 * before as u128 + before.min(groups % files) as u128)
+                    * table.row_group as u128
+            }
+            None => self.table_row_count_by_bytes(table)? as u128 * before as u128,
+        };
+
+        checked_u64(offset, &format!("Row offset of table {} in file {}", table.id_value, file_index))
+    }
+
+
+    /// Rows of `table` across all files.
+    pub fn table_total_rows(&self, table: &Table) -> Result<u64> {
+        self.table_row_offset(table, self.number_of_files)
+    }
+
+
+    pub fn generate_export(&self) -> Result<String> {
+        Ok(self.interleave_file(0, &self.generate_file_rows(0)?).0)
+    }
+
+
+    pub fn generate_raw_tables(&self) -> HashMap<String, Result<Vec<Vec<String>>>> {
+        self.tables.par_iter()
+            .map(|x| {
+                let mut m: HashMap<String, Result<Vec<Vec<String>>>> = HashMap::new();
+                m.insert(
+                    x.id_value.clone(),
+                    self.table_row_count(x, 0).and_then(|y| x.generate_rows_vec(y)),
+                );
+                m
+            })
+            .reduce(HashMap::new, |a, b| {
+                a.into_iter().chain(b).collect()
+            })
+    }
+
+
+    pub fn raw_tables_to_string(tables: HashMap<String, Result<Vec<Vec<String>>>>, delimiter: &str) -> Result<String> {
+        Ok(tables.par_iter()
+            .map(|x|
+                {
+                    x.1.as_ref()
+                        .par_iter()
+                        .map(|y| y.par_iter()
+                            .map(|z| z.join(delimiter))
+                            .reduce(|| "".to_string(), |a, b| a + &b)
+                        )
+                        .reduce(|| "".to_string(), |a, b| a + &b)
+                })
+            .reduce(|| "".to_string(), |a, b| {
+                a + &b
+            }))
+    }
 
-        (0..self.number_of_files.to_owned()).into_par_iter()
-            .try_for_each(|x| -> Result<()> {
-                let file_path = PathBuf::new()
-                    .join(folder_path)
-                    .join(format!(
-                        "file_{}_{}_{}.txt",
-                        &self.file_size_bytes,
-                        &self.number_of_files,
-                        &x
-                    ));
 
-                self.generate_export_to_file(file_path.as_path())?;
+    /// Renders raw tables in the export's table order with each table's own delimiter and terminator, unlike
+    /// `raw_tables_to_string` which joins them with one delimiter and no terminators.
+    pub fn render_raw_tables(&self, mut tables: HashMap<String, Result<Vec<Vec<String>>>>) -> Result<String> {
+        let mut rendered = String::new();
 
-                Ok(())
-            })?;
+        for table in self.tables.iter() {
+            if let Some(rows) = tables.remove(&table.id_value) {
+                rendered.push_str(&table.rows_to_string(&rows?));
+            }
+        }
+
+        Ok(rendered)
+    }
+
+
+    pub fn generate_export_to_file(&self, path: &Path) -> Result<()> {
+        self.generate_export_file(path, 0)
+    }
+
+
+    // Encodes and compresses data into a new file, returning the bytes written to disk and their checksums.
+    pub(crate) fn write_encoded(&self, path: &Path, data: &str) -> Result<(u64, u32, String)> {
+        let data = self.encoding.encode(data)?;
+        let file = ChecksumWriter::new(BufWriter::new(File::create(path)?));
+        let mut encoder = self.compression.encoder(file)?;
+        encoder.write_all(&data)?;
+        Ok(encoder.finish()?.finish()?)
+    }
+
+
+    pub fn generate_export_file(&self, path: &Path, file_index: u64) -> Result<()> {
+        for metadata in self.write_export_file(path, file_index)? {
+            self.file_written(metadata)?;
+        }
 
         Ok(())
     }
 
 
+    pub(crate) fn write_export_file(&self, path: &Path, file_index: u64) -> Result<Vec<FileMetadata>> {
+        // Spilled and interleaved files order rows across tables, so they are generated whole.
+        if self.interleave == InterleaveMode::Blocked && self.max_rows_per_file.is_none() {
+            return self.write_chunked(path, file_index);
+        }
+
+        let started = Instant::now();
+        let mut tables: Vec<TableMetadata> = vec![];
+
+        let rows = self.generate_file_rows(file_index)?;
+        let counts: Vec<usize> = rows.iter().map(|x| x.len()).collect();
+        if self.max_rows_per_file.is_some_and(|x| counts.iter().sum::<usize>() as u64 > x) {
+            let tables: Vec<&Table> = self.tables.iter().collect();
+            let order = self.interleave_order(file_index, &counts);
+            return self.write_spilled(path, file_index, &tables, &rows, &order, started);
+        }
+
+        let (data, table_bytes) = self.interleave_file(file_index, &rows);
+        let (bytes, checksum, sha256) = self.write_encoded(path, &data)?;
+
+        for ((table, rows), bytes) in self.tables.iter().zip(rows.iter()).zip(table_bytes) {
+            self.progress_rows(&table.id_value, rows.len() as u64, bytes);
+            tables.push(TableMetadata {
+                table: table.id_value.clone(),
+                rows: rows.len() as u64,
+                bytes,
+            });
+        }
+
+        Ok(vec![FileMetadata {
+            path: path.to_path_buf(),
+            file_index,
+            tables,
+            bytes,
+            checksum,
+            sha256,
+            duration: started.elapsed(),
+        }])
+    }
+
+
+    pub fn generate_all_files(&self, folder_path: &Path) -> Result<()> {
+        self.generate_all_files_with_report(folder_path).map(|_| ())
+    }
+
+
+    /// Like `generate_all_files`, returning the files written, their rows and bytes and the generation errors.
+    pub fn generate_all_files_with_report(&self, folder_path: &Path) -> Result<GenerationReport> {
+        let report = self.generate_files(folder_path, (0..self.number_of_files).collect(), "", None)?;
+        self.package_export(folder_path)?;
+        Ok(report)
+    }
+
+
+    // Outputs of a shard carry its suffix, resumed files are only added to the manifest.
+    pub(crate) fn generate_files(
+        &self,
+        folder_path: &Path,
+        file_indices: Vec<u64>,
+        suffix: &str,
+        resumed: Option<Vec<FileMetadata>>,
+    ) -> Result<GenerationReport> {
+        let started = Instant::now();
+        fs::create_dir_all(folder_path)?;
+        self.progress_start(file_indices.len() as u64);
+        self.reset_errors();
+        let checkpoint = Checkpoint::open(self, folder_path, suffix, resumed.is_some())?;
+
+        let corrupted = self.tables.iter().any(|x| !x.corruption.is_empty()).then(|| file_indices.clone());
+        let completed = Mutex::new(vec![]);
+        let written = file_indices.into_par_iter()
+            .map(|x| -> Result<Vec<FileMetadata>> {
+                let metadata = self.write_export_file(self.file_path(folder_path, x).as_path(), x)
+                    .inspect_err(|x| self.progress_error(x))?;
+                for file in metadata.iter() {
+                    self.file_written(file.clone())?;
+                }
+                if let Some(checkpoint) = &checkpoint {
+                    checkpoint.record(self, x, &metadata)?;
+                }
+
+                completed.lock().unwrap_or_else(|x| x.into_inner()).extend(metadata.iter().cloned());
+                Ok(metadata)
+            })
+            .collect::<Result<Vec<Vec<FileMetadata>>>>();
+
+        // A failed run reports the files it completed and its errors, the error itself is returned after.
+        let completed = completed.into_inner().unwrap_or_else(|x| x.into_inner());
+        let report = self.generation_report(completed, started.elapsed());
+        let reported = match self.report {
+            true => self.write_report(folder_path, &report_name(suffix), &report),
+            false => Ok(()),
+        };
+        let written = written?;
+        reported?;
+        // Control rows mark a complete batch, so a failed run writes none.
+        self.write_control(folder_path, &control_name(suffix), &report)?;
+
+        if self.manifest {
+            let files = resumed.into_iter().flatten().chain(written.into_iter().flatten()).collect();
+            self.write_manifest(folder_path, &manifest_name(suffix), files)?;
+        }
+        if let Some(file_indices) = corrupted {
+            self.write_corruptions(folder_path, &corruptions_name(suffix), &file_indices)?;
+        }
+
+        self.progress_finish();
+        Ok(report)
+    }
+
+
     pub fn build_schema(&self) -> Result<HashMap<String, HashMap<String, String>>> {
         let mut schema: HashMap<String, HashMap<String, String>> = HashMap::new();
 
@@ -251,7 +1449,13 @@ impl ExportFile {
                         column: column.name.clone(),
                     }));
                 }
-                columns.insert(column.name.clone(), column.sql_type.clone());
+                if column.is_not_null() && column.is_nullable() {
+                    return Err(Error::from(ExportFileError::NullableNotNull {
+                        table: table.id_value.clone(),
+                        column: column.name.clone(),
+                    }));
+                }
+                columns.insert(column.name.clone(), column.column_type.to_string());
             }
 
             if schema.contains_key(&table.id_value) {
@@ -267,8 +1471,47 @@ impl ExportFile {
     }
 
 
+    /// Maps tables to their column types. Annotated, nullable and not null columns become objects with a `type`, and
+    /// tables with annotations, a key or unique constraints get a `$table` entry with `primary_key` and `unique`.
     pub fn get_schema_json_str(&self) -> Result<String> {
-        Ok(serde_json::to_string(&self.build_schema()?)?)
+        let schema = self.build_schema()?;
+        let mut json = serde_json::Map::new();
+
+        for table in self.tables.iter() {
+            let mut columns: serde_json::Map<String, serde_json::Value> = table.columns.iter()
+                .map(|x| {
+                    let column_type = serde_json::Value::from(schema[&table.id_value][&x.name].clone());
+                    let mut column = x.annotations.to_json();
+                    if x.is_nullable() {
+                        column.insert("nullable".into(), true.into());
+                    }
+                    if x.is_not_null() {
+                        column.insert("not_null".into(), true.into());
+                    }
+                    if column.is_empty() {
+                        return (x.name.clone(), column_type);
+                    }
+                    column.insert("type".into(), column_type);
+                    (x.name.clone(), serde_json::Value::Object(column))
+                })
+                .collect();
+
+            let mut constraints = table.annotations.to_json();
+            let key: Vec<&str> = table.columns.iter().filter(|x| x.key).map(|x| x.name.as_str()).collect();
+            if !key.is_empty() {
+                constraints.insert("primary_key".into(), key.into());
+            }
+            let unique = table.unique_constraints();
+            if !unique.is_empty() {
+                constraints.insert("unique".into(), unique.into());
+            }
+            if !constraints.is_empty() {
+                columns.insert("$table".into(), serde_json::Value::Object(constraints));
+            }
+            json.insert(table.id_value.clone(), serde_json::Value::Object(columns));
+        }
+
+        Ok(serde_json::to_string(&json)?)
     }
 
 
@@ -285,9 +1528,11 @@ impl ExportFile {
 
 
 #[cfg(test)]
+#[allow(clippy::identity_op, clippy::single_match)]
 mod tests {
     use std::result::Result::Ok;
     use super::*;
+    use crate::random::TapeRandom;
 
     fn simple_generator() -> Result<String> {
         Ok("ABC".into())
@@ -406,4 +1651,611 @@ mod tests {
             Err(_) => {}
         }
     }
+
+
+    #[test]
+    fn get_schema_json_column_type_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("code".into(), 3, "char(3)".into(), simple_generator),
+                Column::new("amount".into(), 3, "numeric(10, 2)".into(), simple_generator),
+                Column::new("legacy".into(), 3, "char[3]".into(), simple_generator),
+            ],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+        let ef = ExportFile::new(vec![t], 1024 * 1024, 1).unwrap();
+
+        assert_eq!(
+            ef.get_schema_json_str().unwrap(),
+            r#"{"A":{"amount":"DECIMAL(10,2)","code":"CHAR(3)","legacy":"char[3]"}}"#
+        );
+    }
+
+
+    #[test]
+    fn get_schema_json_annotations_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("column".into(), 3, "CHAR[3]".into(), simple_generator)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        ).with_annotations(Annotations::new().with_owner("sales"));
+        let ef = ExportFile::new(vec![t], 1024 * 1024, 1).unwrap();
+
+        assert_eq!(ef.get_schema_json_str().unwrap(), r#"{"A":{"$table":{"owner":"sales"},"column":"CHAR[3]"}}"#);
+    }
+
+
+    #[test]
+    fn get_schema_json_pii_test() {
+        let c = Column::new("column".into(), 3, "CHAR[3]".into(), simple_generator).with_annotations(
+            Annotations::new().with_description("Email").with_tag("contact").with_pii(crate::types::Pii::Direct)
+        );
+        let t = Table::new("A".into(), vec![c], "|".into(), Decimal::from_str("1.0").unwrap());
+        let ef = ExportFile::new(vec![t], 1024 * 1024, 1).unwrap();
+
+        assert_eq!(
+            ef.get_schema_json_str().unwrap(),
+            r#"{"A":{"column":{"description":"Email","pii":"direct","tags":["contact"],"type":"CHAR[3]"}}}"#
+        );
+        assert_eq!(ef.tables[0].columns[0].annotations.comment().unwrap(), "Email; tags: contact; pii: direct");
+    }
+
+
+    #[test]
+    fn namespace_test() {
+        let c = Column::new(
+            "column".into(),
+            3,
+            "CHAR[3]".into(),
+            simple_generator,
+        );
+
+        let t1 = Table::new(
+            "A".into(),
+            vec![c.clone().as_key(), c.clone()],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+
+        let ef = ExportFile::new(
+            vec![t1.clone()],
+            1024 * 1024,
+            1,
+        ).unwrap();
+
+        assert_eq!(ef.namespace(), None);
+        assert_eq!(ef.tables[0].generate_table_row().unwrap(), "A|ABC|ABC\n");
+
+        let ef = ef.with_namespace(Some("run1".into())).unwrap();
+        assert_eq!(ef.tables[0].generate_table_row().unwrap(), "A|run1-ABC|ABC\n");
+
+        let ef = ef.with_namespace(None).unwrap();
+        assert_eq!(ef.tables[0].generate_table_row().unwrap(), "A|ABC|ABC\n");
+
+        let int_key = Table::new(
+            "B".into(),
+            vec![Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).as_key()],
+            "|".into(),
+            Decimal::ONE,
+        );
+        let ef = ExportFile::new(vec![int_key], 1024, 1).unwrap();
+        assert_eq!(ef.tables[0].generate_table_row().unwrap(), "B|1\n");
+        assert!(matches!(
+            ef.with_namespace(Some("run1".into())).err().and_then(|x| x.downcast::<ExportFileError>().ok()),
+            Some(ExportFileError::NamespacedNonTextKey { .. })
+        ));
+    }
+
+
+    #[test]
+    fn new_normalized_test() {
+        let t = |id: &str, percent_size: &str| Table::new(
+            id.into(),
+            vec![Column::new("column".into(), 3, ColumnType::Char(3), simple_generator)],
+            "|".into(),
+            Decimal::from_str(percent_size).unwrap(),
+        );
+        let tables = || vec![t("A", "1"), t("B", "1"), t("C", "1")];
+
+        assert!(ExportFile::new(tables(), 300, 1).is_err());
+        let ef = ExportFile::new_normalized(tables(), 300, 1).unwrap();
+        assert_eq!(ef.tables.iter().map(|x| x.percent_size).sum::<Decimal>(), Decimal::ONE);
+        let rows: Vec<u64> = ef.tables.iter().map(|x| x.row_count(ef.file_size_bytes).unwrap()).collect();
+        assert_eq!(rows[0], rows[2]);
+
+        let ef = ExportFile::new_normalized(vec![t("A", "30"), t("B", "10")], 400, 1).unwrap();
+        assert_eq!(ef.tables[0].percent_size, Decimal::from_str("0.75").unwrap());
+        assert!(ExportFile::new_normalized(vec![t("A", "1"), t("B", "0")], 400, 1).is_err());
+    }
+
+    #[test]
+    fn tables_do_not_fit_test() {
+        let c = Column::new(
+            "column".into(),
+            3,
+            "CHAR[3]".into(),
+            simple_generator,
+        );
+
+        let t1 = Table::new(
+            "A".into(),
+            vec![c.clone(), c.clone()],
+            "|".into(),
+            Decimal::from_str("0.2").unwrap(),
+        );
+        let t2 = Table::new(
+            "B".into(),
+            vec![c.clone()],
+            "|".into(),
+            Decimal::from_str("0.55").unwrap(),
+        );
+        let t3 = Table::new(
+            "C".into(),
+            vec![c.clone(), c.clone()],
+            "|".into(),
+            Decimal::from_str("0.25").unwrap(),
+        );
+
+        let ef = ExportFile::new(vec![t1, t2, t3], 20, 1);
+
+        match ef.err().and_then(|x| x.downcast::<ExportFileError>().ok()) {
+            Some(ExportFileError::TablesDoNotFit { tables, minimum_bytes }) => {
+                assert_eq!(
+                    tables,
+                    vec![
+                        TableDoesNotFit { table: "A".into(), needed: 10, available: 4 },
+                        TableDoesNotFit { table: "C".into(), needed: 10, available: 5 },
+                    ]
+                );
+                assert_eq!(minimum_bytes, 50);
+            }
+            _ => panic!("expected TablesDoNotFit"),
+        }
+    }
+
+
+    #[test]
+    fn petabyte_sizing_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("column".into(), 3, "CHAR[3]".into(), simple_generator)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+        let files = 1 << 20;
+        let ef = ExportFile::new(vec![t.clone()], 1 << 50, files).unwrap();
+        let per_file = (1 << 30) / 6;
+
+        assert_eq!(ef.table_row_offset(&ef.tables[0], files - 1).unwrap(), per_file * (files - 1));
+        assert_eq!(ef.table_total_rows(&ef.tables[0]).unwrap(), per_file * files);
+
+        let ef = ExportFile::new(vec![t.clone()], u64::MAX, 1).unwrap();
+        assert!(ef.table_row_offset(&ef.tables[0], 7).is_err());
+        assert!(t.generate_rows_from(u64::MAX, 2).is_err());
+        assert!(t.rows_from(u64::MAX, 2).next().unwrap().is_err());
+    }
+
+
+    #[test]
+    fn tiny_budget_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("column".into(), 3, "CHAR[3]".into(), simple_generator).as_key()],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+        let error = |x: Result<ExportFile>| x.err().and_then(|y| y.downcast::<ExportFileError>().ok());
+
+        assert!(matches!(error(ExportFile::new(vec![t.clone()], 0, 1)), Some(ExportFileError::ZeroSize)));
+        assert!(matches!(error(ExportFile::new(vec![t.clone()], 30, 0)), Some(ExportFileError::NoFiles)));
+        assert!(matches!(
+            error(ExportFile::new(vec![Table::new("B".into(), vec![], "|".into(), Decimal::ONE)], 30, 1)),
+            Some(ExportFileError::EmptyTable { .. })
+        ));
+
+        // A namespace widens the key past the 20 bytes the file has room for.
+        let ef = ExportFile::new(vec![t], 20, 1).unwrap();
+        assert_eq!(ef.table_row_count(&ef.tables[0], 0).unwrap(), 3);
+        let ef = ef.with_namespace(Some("longer-namespace".into())).unwrap();
+        match ef.table_row_count(&ef.tables[0], 0).err().and_then(|x| x.downcast::<ExportFileError>().ok()) {
+            Some(ExportFileError::TablesDoNotFit { minimum_bytes, .. }) => assert_eq!(minimum_bytes, 23),
+            _ => panic!("expected TablesDoNotFit"),
+        }
+    }
+
+
+    #[test]
+    fn size_spec_rows_test() {
+        let c = Column::new(
+            "column".into(),
+            3,
+            "CHAR[3]".into(),
+            simple_generator,
+        );
+
+        let t1 = Table::new(
+            "A".into(),
+            vec![c.clone()],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+        let t2 = Table::new(
+            "B".into(),
+            vec![c.clone()],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+
+        assert_eq!(t1.generate_rows(3).unwrap(), "A|ABC\n".repeat(3));
+
+        let ef = ExportFile::new(vec![t1.clone(), t2.clone()], 108, 3)
+            .unwrap()
+            .with_size_spec(SizeSpec::Rows(HashMap::from([("A".to_string(), 7)])))
+            .unwrap();
+
+        let rows: Vec<u64> = (0..3).map(|x| ef.table_row_count(&ef.tables[0], x).unwrap()).collect();
+        assert_eq!(rows, vec![3, 2, 2]);
+        assert_eq!(ef.table_row_count(&ef.tables[1], 0).unwrap(), 3);
+
+        let unknown = ExportFile::new(vec![t1, t2], 60, 3)
+            .unwrap()
+            .with_size_spec(SizeSpec::Rows(HashMap::from([("C".to_string(), 7)])));
+        assert!(unknown.is_err());
+    }
+
+
+    #[test]
+    fn row_number_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::row_number("row_number".into()),
+                Column::new("value".into(), 5, ColumnType::Int, generators::random_int(10000, 99999)),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        );
+        let ef = ExportFile::new(vec![t], 2000, 4).unwrap().with_seed(3);
+
+        let numbers: Vec<u64> = (0..4u64).into_par_iter()
+            .map(|x| ef.generate_file_rows(x).unwrap().remove(0))
+            .collect::<Vec<Vec<Vec<String>>>>()
+            .into_iter()
+            .flatten()
+            .map(|x| x[1].parse().unwrap())
+            .collect();
+        assert_eq!(numbers, (0..ef.table_total_rows(&ef.tables[0]).unwrap()).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn sequence_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1))],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+
+        assert_eq!(t.generate_rows(3).unwrap(), "A|1\nA|2\nA|3\n");
+
+        let ef = ExportFile::new(vec![t], 60, 3)
+            .unwrap()
+            .with_size_spec(SizeSpec::Rows(HashMap::from([("A".to_string(), 7)])))
+            .unwrap();
+
+        let ids: Vec<String> = (0..3)
+            .flat_map(|x| {
+                let t = &ef.tables[0];
+                t.generate_rows_vec_from(ef.table_row_offset(t, x).unwrap(), ef.table_row_count(t, x).unwrap())
+                    .unwrap()
+            })
+            .map(|x| x[1].clone())
+            .collect();
+
+        assert_eq!(ids, (1..=7).map(|x| x.to_string()).collect::<Vec<String>>());
+    }
+
+
+    #[test]
+    fn terminator_test() {
+        let c = Column::new("column".into(), 3, "CHAR[3]".into(), simple_generator);
+        let t1 = Table::new("A".into(), vec![c.clone()], "|".into(), Decimal::from_str("0.5").unwrap());
+        let t2 = Table::new("B".into(), vec![c], "|".into(), Decimal::from_str("0.5").unwrap());
+
+        let ef = ExportFile::new(vec![t1, t2], 60, 1)
+            .unwrap()
+            .with_terminator(RecordTerminator::CrLf)
+            .with_table_delimiter("B", ",")
+            .unwrap()
+            .with_table_terminator("B", RecordTerminator::from(";"))
+            .unwrap()
+            .with_size_spec(SizeSpec::Rows(HashMap::from([("A".to_string(), 2), ("B".to_string(), 2)])))
+            .unwrap();
+
+        let expected = "A|ABC\r\nA|ABC\r\nB,ABC;B,ABC;";
+        assert_eq!(ef.generate_export().unwrap(), expected);
+        assert_eq!(ef.render_raw_tables(ef.generate_raw_tables()).unwrap(), expected);
+        let raw = HashMap::from([("A".to_string(), Ok(vec![vec!["A".to_string(), "ABC".to_string()]]))]);
+        assert_eq!(ExportFile::raw_tables_to_string(raw, "|").unwrap(), "A|ABC");
+        assert!(ef.with_table_terminator("C", RecordTerminator::Lf).is_err());
+    }
+
+
+    #[test]
+    fn row_size_test() {
+        fn accented_generator() -> Result<String> {
+            Ok("čšž".into())
+        }
+
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("column".into(), 3, "CHAR[3]".into(), accented_generator)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+        assert_eq!(t.row_size_bytes, 9);
+        assert_eq!(t.generate_table_row().unwrap().len() as u64, t.row_size_bytes);
+
+        let t = t.with_terminator(RecordTerminator::CrLf).with_delimiter("||");
+        assert_eq!(t.row_size_bytes, 11);
+        assert_eq!(t.generate_table_row().unwrap().len() as u64, t.row_size_bytes);
+    }
+
+
+    #[test]
+    fn variable_size_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new(
+                    "note".into(),
+                    ColumnSize::range(2, 10),
+                    ColumnType::Varchar(10),
+                    generators::random_string_between(2, 10),
+                ),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        );
+        assert_eq!(t.row_size_bytes, 9);
+        assert_eq!(t.row_count(900).unwrap(), 100);
+
+        let rows = t.generate_table_vec(900).unwrap();
+        assert!(rows.iter().all(|x| (2..=10).contains(&x[1].len())));
+        assert!(rows.iter().any(|x| x[1].len() != rows[0][1].len()));
+        assert_eq!(t.with_output_format(OutputFormat::fixed_width()).row_size_bytes, 12);
+    }
+
+
+    #[test]
+    fn key_value_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("id".into(), 2, ColumnType::Int, generators::sequence(10, 1)),
+                Column::new("currency".into(), 3, ColumnType::Char(3), generators::constant("EUR")),
+            ],
+            ";".into(),
+            Decimal::ONE,
+        )
+            .with_output_format(OutputFormat::KeyValue { separator: ':' });
+
+        assert_eq!(t.generate_rows(2).unwrap(), "A;id:10;currency:EUR\nA;id:11;currency:EUR\n");
+        assert_eq!(t.row_size_bytes, 21);
+    }
+
+    #[test]
+    fn fixed_width_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("id".into(), 4, ColumnType::Varchar(4), generators::sequence(1, 1)).as_key(),
+                Column::new("code".into(), 2, "CHAR[2]".into(), simple_generator),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        );
+        let ef = ExportFile::new(vec![t], 100, 1)
+            .unwrap()
+            .with_namespace(Some("ns".into()))
+            .unwrap()
+            .with_output_format(OutputFormat::fixed_width())
+            .with_size_spec(SizeSpec::Rows(HashMap::from([("A".to_string(), 2)])))
+            .unwrap();
+
+        let export = ef.generate_export().unwrap();
+        assert_eq!(export, "Ans-1   AB\nAns-2   AB\n");
+        assert_eq!(export.lines().next().unwrap().len() as u64 + 1, ef.tables[0].row_size_bytes);
+    }
+
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derive_synthetic_table_test() {
+        #[allow(dead_code)]
+        #[derive(SyntheticTable)]
+        #[synthetic(id = "A", delimiter = ";", terminator = "crlf", percent_size = 1.0)]
+        struct Row {
+            #[synthetic(sql_type = "CHAR[3]", size = 3, generator = simple_generator, key)]
+            code: String,
+            #[synthetic(name = "other", sql_type = "CHAR[3]", size = 3, generator = simple_generator)]
+            value: String,
+        }
+
+        let t = <Row as SyntheticTable>::synthetic_table();
+
+        assert_eq!(t.id_value, "A");
+        assert_eq!(t.row_size_bytes, 11);
+        assert!(t.columns[0].key);
+        assert_eq!(t.columns[1].name, "other");
+        assert_eq!(t.generate_table_row().unwrap(), "A;ABC;ABC\r\n");
+    }
+
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn generate_table_typed_test() {
+        fn number_generator() -> Result<String> {
+            Ok("42".into())
+        }
+
+        #[derive(SyntheticTable)]
+        struct Row {
+            #[synthetic(sql_type = "INT", size = 2, generator = number_generator)]
+            id: i32,
+            #[synthetic(sql_type = "CHAR[3]", size = 3, generator = simple_generator)]
+            code: String,
+            #[synthetic(sql_type = "INT", size = 2, generator = number_generator, null_rate = 1.0)]
+            parent_id: Option<i32>,
+        }
+
+        #[allow(dead_code)]
+        #[derive(SyntheticTable)]
+        struct Invalid(
+            #[synthetic(name = "id", sql_type = "INT", size = 3, generator = simple_generator)] i32,
+        );
+
+        let rows = Row::synthetic_table().generate_table_typed::<Row>(100).unwrap();
+
+        assert_eq!(rows.len(), 7);
+        assert_eq!(rows[0].id, 42);
+        assert_eq!(rows[0].code, "ABC");
+        assert_eq!(rows[0].parent_id, None);
+        assert!(Invalid::synthetic_table().generate_table_typed::<Invalid>(30).is_err());
+        assert!(matches!(Invalid::from_row(&[]), Err(RowError::MissingValue { .. })));
+    }
+
+
+    #[test]
+    fn dictionary_test() {
+        let c = Column::new(
+            "column".into(),
+            3,
+            "CHAR[3]".into(),
+            simple_generator,
+        );
+
+        let t1 = Table::new(
+            "A".into(),
+            vec![c.clone(), c.clone().without_dictionary()],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+
+        let columns = t1.generate_table_columns(100).unwrap();
+
+        assert!(matches!(columns[0], ColumnData::Dictionary { .. }));
+        assert!(matches!(columns[1], ColumnData::Plain(_)));
+        assert_eq!(columns[0].len(), 10);
+        assert_eq!(columns[0].get(9), "ABC");
+        assert_eq!(t1.generate_table_row().unwrap(), "A|ABC|ABC\n");
+    }
+
+
+    #[test]
+    fn null_rate_test() {
+        let c = Column::new(
+            "column".into(),
+            3,
+            "CHAR[3]".into(),
+            simple_generator,
+        );
+
+        let t1 = Table::new(
+            "A".into(),
+            vec![
+                c.clone().with_null_rate(1.0),
+                Column::new("other".into(), 3, "CHAR[3]".into(), simple_generator),
+            ],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+
+        assert_eq!(t1.generate_table_row().unwrap(), "A||ABC\n");
+
+        let ef = ExportFile::new(vec![t1], 60, 1)
+            .unwrap()
+            .with_null_representation(NullRepresentation::BackslashN);
+
+        assert_eq!(ef.tables[0].generate_table_row().unwrap(), "A|\\N|ABC\n");
+        assert_eq!(ef.build_schema().unwrap()["A"]["column"], "CHAR[3]");
+        assert_eq!(
+            ef.get_schema_json_str().unwrap(),
+            r#"{"A":{"column":{"nullable":true,"type":"CHAR[3]"},"other":"CHAR[3]"}}"#
+        );
+    }
+
+
+    #[test]
+    fn random_source_test() {
+        let c = Column::new(
+            "column".into(),
+            3,
+            "CHAR[3]".into(),
+            simple_generator,
+        ).with_null_rate(0.5);
+
+        let t1 = Table::new(
+            "A".into(),
+            vec![c],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+
+        let always_null = t1.clone().with_random_source(Arc::new(TapeRandom::new(vec![0])));
+        let never_null = t1.with_random_source(Arc::new(TapeRandom::new(vec![u64::MAX])));
+
+        assert_eq!(always_null.generate_rows(3).unwrap(), "A|\n".repeat(3));
+        assert_eq!(never_null.generate_rows(3).unwrap(), "A|ABC\n".repeat(3));
+    }
+
+
+    #[test]
+    fn file_seed_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("column".into(), 6, ColumnType::Int, generators::random_int(0, 999_999))],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+        let export = |seed: u64| {
+            let ef = ExportFile::new(vec![t.clone()], 300, 3).unwrap().with_seed(seed);
+            (0..3).map(|x| ef.interleave_file(x, &ef.generate_file_rows(x).unwrap()).0).collect::<Vec<String>>()
+        };
+
+        let files = export(7);
+        assert!(files[0] != files[1] && files[1] != files[2] && files[0] != files[2]);
+        assert_eq!(files, export(7));
+        assert_ne!(files, export(8));
+
+        let ef = ExportFile::new(vec![t], 300, 3).unwrap();
+        assert_eq!(ef.file_seed(0), None);
+        let ef = ef.with_seed(7);
+        assert_ne!(ef.file_seed(0), ef.file_seed(1));
+    }
+
+
+    #[test]
+    fn row_dependency_test() {
+        let t1 = Table::new(
+            "A".into(),
+            vec![
+                Column::new("gross".into(), 3, ColumnType::Int, generators::random_int(100, 999)).unique(true),
+                Column::new("tax".into(), 2, ColumnType::Int, generators::random_int(10, 99)),
+                Column::new("net".into(), 3, ColumnType::Int, generators::difference("gross", "tax")),
+            ],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        ).with_seed(5);
+
+        let rows = t1.generate_rows_vec(200).unwrap();
+        for row in rows.iter() {
+            let values: Vec<i64> = row[1..].iter().map(|x| x.parse().unwrap()).collect();
+            assert_eq!(values[2], values[0] - values[1]);
+        }
+        assert_eq!(t1.generate_value(2, 7).unwrap(), rows[7][3]);
+    }
 }
@@ -0,0 +1,174 @@
+use anyhow::{Error, Result};
+use rayon::prelude::*;
+use thiserror::Error;
+
+use crate::{ExportFile, ExportFileError};
+use crate::sample::SampleError;
+
+
+#[derive(Error, Debug)]
+pub enum LocateError {
+    #[error("Table {table} has no column {column}.")]
+    UnknownColumn { table: String, column: String },
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowLocation {
+    pub file_index: u64,
+    /// Row of the table across the whole export.
+    pub row_index: u64,
+    /// Row of the table within the file.
+    pub table_row: u64,
+    /// Line of the row within the file holding every table, following the interleave mode.
+    pub line: u64,
+}
+
+
+impl ExportFile {
+    /// Where the first row of `table` with `value` in `column` is written. Generators with an inverse, like
+    /// `sequence`, are located directly, any other column is regenerated without writing, which needs a seed.
+    pub fn locate(&self, table: &str, column: &str, value: &str) -> Result<Option<RowLocation>> {
+        let table_index = self.tables.iter()
+            .position(|x| x.id_value == table)
+            .ok_or_else(|| Error::from(ExportFileError::UnknownTable { table: table.into() }))?;
+        let target = &self.tables[table_index];
+        let column_index = target.columns.iter()
+            .position(|x| x.name == column)
+            .ok_or_else(|| Error::from(LocateError::UnknownColumn { table: table.into(), column: column.into() }))?;
+        let column = &target.columns[column_index];
+
+        let counts = (0..self.number_of_files)
+            .map(|x| self.table_row_count(target, x))
+            .collect::<Result<Vec<u64>>>()?;
+        let total = self.table_total_rows(target)?;
+
+        let unique = target.unique.iter().any(|x| x.contains(&column_index));
+        let raw = match &target.namespace {
+            Some(namespace) if column.key && column.reference.is_none() => match value.strip_prefix(&format!("{}-", namespace)) {
+                Some(x) => x,
+                None => return Ok(None),
+            },
+            _ => value,
+        };
+
+        let row_index = match column.generator.invert(raw).filter(|_| column.reference.is_none() && !unique) {
+            Some(row_index) => {
+                // A null may have been drawn instead of the generated one.
+                if row_index < total && target.generate_value(column_index, row_index)? == value {
+                    Some(row_index)
+                } else {
+                    None
+                }
+            }
+            None => self.scan(table_index, column_index, value, &counts)?,
+        };
+
+        row_index.map(|x| self.row_location(table_index, x, &counts)).transpose()
+    }
+
+
+    fn scan(&self, table_index: usize, column_index: usize, value: &str, counts: &[u64]) -> Result<Option<u64>> {
+        if self.tables.iter().any(|x| x.seed.is_none()) {
+            return Err(Error::from(SampleError::Unseeded));
+        }
+
+        let target = &self.tables[table_index];
+        let independent = target.columns.iter().all(|x| x.reference.is_none()) && target.unique.is_empty();
+        let offsets: Vec<u64> = counts.iter()
+            .scan(0, |offset, x| {
+                *offset += x;
+                Some(*offset - x)
+            })
+            .collect();
+
+        let found = (0..self.number_of_files).into_par_iter()
+            .map(|file_index| -> Result<Option<u64>> {
+                let offset = offsets[file_index as usize];
+
+                if independent {
+                    return (offset..offset + counts[file_index as usize]).into_par_iter()
+                        .find_map_first(|x| match target.generate_value(column_index, x) {
+                            Ok(y) if y == value => Some(Ok(x)),
+                            Ok(_) => None,
+                            Err(e) => Some(Err(e)),
+                        })
+                        .transpose();
+                }
+
+                Ok(self.generate_file_rows(file_index)?[table_index].iter()
+                    .position(|x| x.get(column_index + 1).is_some_and(|y| y == value))
+                    .map(|x| offset + x as u64))
+            })
+            .collect::<Result<Vec<Option<u64>>>>()?;
+
+        Ok(found.into_iter().flatten().next())
+    }
+
+
+    fn row_location(&self, table_index: usize, row_index: u64, counts: &[u64]) -> Result<RowLocation> {
+        let mut file_index = 0;
+        let mut table_row = row_index;
+        while table_row >= counts[file_index as usize] {
+            table_row -= counts[file_index as usize];
+            file_index += 1;
+        }
+
+        let file_counts = self.tables.iter()
+            .map(|x| Ok(self.table_row_count(x, file_index)? as usize))
+            .collect::<Result<Vec<usize>>>()?;
+        let line = self.interleave_order(file_index, &file_counts).into_iter()
+            .enumerate()
+            .filter(|x| x.1 == table_index)
+            .nth(table_row as usize)
+            .map_or(0, |x| x.0 as u64);
+
+        Ok(RowLocation { file_index, row_index, table_row, line })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column, Table};
+    use crate::types::ColumnType;
+
+    fn table(id: &str, columns: Vec<Column>) -> Table {
+        Table::new(id.into(), columns, "|".into(), Decimal::from_str("0.5").unwrap())
+    }
+
+    #[test]
+    fn locate_test() {
+        let customers = table("A", vec![
+            Column::new("id".into(), 4, ColumnType::Varchar(4), generators::sequence(1, 1)).as_key(),
+            Column::new("code".into(), 8, ColumnType::Varchar(8), generators::random_string(8)),
+        ]);
+        let orders = table("B", vec![Column::new("amount".into(), 4, ColumnType::Int, generators::sequence(1, 1))]);
+        let ef = ExportFile::new(vec![customers, orders], 400, 2)
+            .unwrap()
+            .with_seed(5)
+            .with_namespace(Some("run1".into()))
+            .unwrap();
+
+        let files: Vec<String> = (0..2)
+            .map(|x| ef.interleave_file(x, &ef.generate_file_rows(x).unwrap()).0)
+            .collect();
+        let row = |location: &RowLocation| files[location.file_index as usize].lines().nth(location.line as usize).unwrap().to_string();
+
+        let key = format!("{}-5", ef.namespace().unwrap());
+        let location = ef.locate("A", "id", &key).unwrap().unwrap();
+        assert_eq!(location.row_index, 4);
+        assert!(row(&location).starts_with(&format!("A|{}|", key)));
+
+        let code = ef.generate_file_rows(1).unwrap()[0][1][2].clone();
+        let location = ef.locate("A", "code", &code).unwrap().unwrap();
+        assert_eq!(location.file_index, 1);
+        assert!(row(&location).ends_with(&code));
+
+        assert_eq!(ef.locate("B", "amount", "1000").unwrap(), None);
+        assert!(ef.locate("A", "missing", "1").is_err());
+    }
+}
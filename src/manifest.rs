@@ -0,0 +1,164 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use hmac::{Hmac, KeyInit, Mac};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::ExportFile;
+use crate::metadata::FileMetadata;
+use crate::output_format::OutputFormat;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+pub(crate) fn manifest_name(suffix: &str) -> String {
+    format!("manifest{}.json", suffix)
+}
+
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+
+impl ExportFile {
+    /// Also writes a manifest with every file's name, size, rows per table and SHA-256 next to the files.
+    pub fn with_manifest(mut self, manifest: bool) -> Self {
+        self.manifest = manifest;
+        self
+    }
+
+
+    pub(crate) fn write_manifest(&self, folder_path: &Path, name: &str, mut files: Vec<FileMetadata>) -> Result<()> {
+        files.sort_by_key(|x| x.file_index);
+
+        let files: Vec<Value> = files.iter()
+            .map(|x| {
+                let rows: Map<String, Value> = x.tables.iter()
+                    .map(|y| (y.table.clone(), json!(y.rows)))
+                    .collect();
+                json!({
+                    "name": x.path.file_name().map(|y| y.to_string_lossy().to_string()),
+                    "file_index": x.file_index,
+                    "bytes": x.bytes,
+                    "rows": rows,
+                    "sha256": x.sha256,
+                })
+            })
+            .collect();
+
+        let tables: Map<String, Value> = self.tables.iter()
+            .map(|x| (x.id_value.clone(), json!(x.qualified_name())))
+            .collect();
+        // Columns and delimiters let a later run read the files back, see `ExternalSource::Export`.
+        let columns: Map<String, Value> = self.tables.iter()
+            .map(|x| (x.id_value.clone(), json!(x.columns.iter().map(|y| y.name.as_str()).collect::<Vec<&str>>())))
+            .collect();
+        let delimiters: Map<String, Value> = self.tables.iter()
+            .filter(|x| x.output_format == OutputFormat::Delimited)
+            .map(|x| (x.id_value.clone(), json!(x.delimiter)))
+            .collect();
+        let manifest = json!({
+            "tables": tables,
+            "columns": columns,
+            "delimiters": delimiters,
+            "files": files,
+            "total_bytes": files.iter().filter_map(|x| x["bytes"].as_u64()).sum::<u64>(),
+        });
+
+        Ok(fs::write(folder_path.join(name), serde_json::to_string_pretty(&manifest)?)?)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{Column, Table};
+    use crate::types::ColumnType;
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    #[test]
+    fn sha256_test() {
+        // NIST FIPS 180-2 examples plus the lengths around the padding boundary.
+        assert_eq!(sha256_hex(b""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(sha256_hex(b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(
+            sha256_hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert_eq!(
+            sha256_hex(b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu"),
+            "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1"
+        );
+        assert_eq!(sha256_hex(&vec![b'a'; 1_000_000]), "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0");
+        assert_eq!(sha256_hex(&[b'a'; 55]), "9f4390f8d30c2dd92ec9f095b65e2b9ae9b0a925a5258e241c9f1e910f734318");
+        assert_eq!(sha256_hex(&[b'a'; 56]), "b35439a4ac6f0948b6d6f9e3c6af0f5f590ce20f1bde7090ef7970686ec6738a");
+        assert_eq!(sha256_hex(&[b'a'; 64]), "ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb");
+    }
+
+    #[test]
+    fn hmac_sha256_test() {
+        // RFC 4231 test cases 1, 2, 3 and 6.
+        assert_eq!(
+            to_hex(&hmac_sha256(&[0x0b; 20], b"Hi There")),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+        assert_eq!(
+            to_hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            to_hex(&hmac_sha256(&[0xaa; 20], &[0xdd; 50])),
+            "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe"
+        );
+        assert_eq!(
+            to_hex(&hmac_sha256(&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First")),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn manifest_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("column".into(), 3, ColumnType::Char(3), simple_generator)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+        let ef = ExportFile::new(vec![t], 60, 2).unwrap().with_manifest(true);
+
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_all_files(&folder).unwrap();
+        let manifest: Value = serde_json::from_str(&fs::read_to_string(folder.join(MANIFEST_FILE)).unwrap()).unwrap();
+        let content = fs::read(ef.file_path(&folder, 1)).unwrap();
+        fs::remove_dir_all(&folder).unwrap();
+
+        assert_eq!(manifest["files"][1]["name"], "file_30_2_1.txt");
+        assert_eq!(manifest["files"][1]["rows"]["A"], 5);
+        assert_eq!(manifest["files"][1]["bytes"], 30);
+        assert_eq!(manifest["files"][1]["sha256"], sha256_hex(&content));
+        assert_eq!(manifest["total_bytes"], 60);
+        assert_eq!(manifest["tables"]["A"], "A");
+        assert_eq!(manifest["columns"]["A"], json!(["column"]));
+        assert_eq!(manifest["delimiters"]["A"], "|");
+    }
+}
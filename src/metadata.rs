@@ -0,0 +1,173 @@
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+
+use crate::{ExportFile, Table};
+use crate::compression::crc32_update;
+use crate::manifest::{sha256_hex, to_hex};
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableMetadata {
+    pub table: String,
+    pub rows: u64,
+    /// Bytes of the table's rows before compression.
+    pub bytes: u64,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMetadata {
+    pub path: PathBuf,
+    pub file_index: u64,
+    pub tables: Vec<TableMetadata>,
+    /// Bytes written to disk.
+    pub bytes: u64,
+    /// CRC32 of the bytes written to disk.
+    pub checksum: u32,
+    /// Hex SHA-256 of the bytes written to disk.
+    pub sha256: String,
+    /// Time spent generating and writing the file.
+    pub duration: Duration,
+}
+
+
+pub type FileCallback = Arc<dyn Fn(&FileMetadata) -> Result<()> + Send + Sync>;
+
+
+// Counts and checksums everything written through it.
+pub(crate) struct ChecksumWriter<W: Write> {
+    writer: W,
+    bytes: u64,
+    crc: u32,
+    sha: Sha256,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+    pub(crate) fn new(writer: W) -> Self {
+        ChecksumWriter { writer, bytes: 0, crc: 0, sha: Sha256::new() }
+    }
+
+    pub(crate) fn finish(mut self) -> io::Result<(u64, u32, String)> {
+        self.writer.flush()?;
+        Ok((self.bytes, self.crc, to_hex(&self.sha.finalize())))
+    }
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.bytes += written as u64;
+        self.crc = crc32_update(self.crc, &buf[..written]);
+        self.sha.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+
+
+impl ExportFile {
+    // Writes a file per table and file index with the table's rows encoded by `encode`, the layout of the binary
+    // formats such as Parquet and Avro.
+    pub(crate) fn write_table_files<F>(&self, folder_path: &Path, ext: &str, encode: F) -> Result<()>
+    where
+        F: Fn(&Table, &[Vec<String>]) -> Result<Vec<u8>> + Sync,
+    {
+        self.check_table_file_names()?;
+        fs::create_dir_all(folder_path)?;
+        self.progress_start(self.number_of_files.saturating_mul(self.tables.len() as u64));
+
+        (0..self.number_of_files).into_par_iter()
+            .try_for_each(|x| -> Result<()> {
+                let rows = self.generate_file_rows(x)?;
+
+                self.tables.par_iter()
+                    .zip(rows.par_iter())
+                    .try_for_each(|(table, rows)| {
+                        let started = Instant::now();
+                        let file_path = self.binary_file_path(folder_path, table, x, ext);
+
+                        let data = encode(table, rows)?;
+                        fs::write(&file_path, &data)?;
+                        self.progress_rows(&table.id_value, rows.len() as u64, data.len() as u64);
+
+                        self.file_written(FileMetadata {
+                            path: file_path,
+                            file_index: x,
+                            tables: vec![TableMetadata {
+                                table: table.id_value.clone(),
+                                rows: rows.len() as u64,
+                                bytes: data.len() as u64,
+                            }],
+                            bytes: data.len() as u64,
+                            checksum: crc32_update(0, &data),
+                            sha256: sha256_hex(&data),
+                            duration: started.elapsed(),
+                        })
+                    })
+            })
+            .inspect_err(|x| self.progress_error(x))?;
+
+        self.progress_finish();
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Mutex;
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{Column, ExportFile, Table};
+    use crate::types::ColumnType;
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    #[test]
+    fn file_callback_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("column".into(), 3, ColumnType::Char(3), simple_generator)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+
+        let written: Arc<Mutex<Vec<FileMetadata>>> = Arc::new(Mutex::new(vec![]));
+        let sink = written.clone();
+        let ef = ExportFile::new(vec![t], 60, 2)
+            .unwrap()
+            .with_file_callback(move |x| {
+                sink.lock().unwrap().push(x.clone());
+                Ok(())
+            });
+
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_all_files(&folder).unwrap();
+
+        let mut written = written.lock().unwrap().clone();
+        written.sort_by_key(|x| x.file_index);
+        fs::remove_dir_all(&folder).unwrap();
+
+        assert_eq!(written.len(), 2);
+        assert_eq!(written[1].tables, vec![TableMetadata { table: "A".into(), rows: 5, bytes: 30 }]);
+        assert_eq!(written[1].bytes, 30);
+        assert_eq!(written[1].checksum, crc32_update(0, "A|ABC\n".repeat(5).as_bytes()));
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Write;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Error, Result};
+
+use crate::health::serve_http;
+use crate::metadata::FileMetadata;
+use crate::progress::ProgressSink;
+
+pub const METRICS_PORT_ENV: &str = "DIPLOMSKI_METRICS_PORT";
+/// Upper bounds in seconds of the file duration histogram buckets.
+pub const DURATION_BUCKETS: [f64; 10] = [0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+
+#[derive(Debug, Default, Clone)]
+struct Histogram {
+    buckets: [u64; DURATION_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(DURATION_BUCKETS) {
+            if value <= bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+}
+
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+
+/// Prometheus metrics of a running export, attach with `ExportFile::with_progress` and expose with `serve`.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    files_total: AtomicU64,
+    files_completed: AtomicU64,
+    bytes: AtomicU64,
+    errors: AtomicU64,
+    rows: Mutex<BTreeMap<String, u64>>,
+    durations: Mutex<BTreeMap<String, Histogram>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics::default()
+    }
+
+    /// Metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(out, "# HELP diplomski_rows_generated_total Rows generated per table.");
+        let _ = writeln!(out, "# TYPE diplomski_rows_generated_total counter");
+        for (table, rows) in self.rows.lock().unwrap_or_else(|x| x.into_inner()).iter() {
+            let _ = writeln!(out, "diplomski_rows_generated_total{{table=\"{}\"}} {}", escape_label(table), rows);
+        }
+
+        let counters = [
+            ("diplomski_bytes_written_total", "Uncompressed bytes of rows written.", &self.bytes),
+            ("diplomski_files_completed_total", "Files written and closed.", &self.files_completed),
+            ("diplomski_errors_total", "Errors that stopped generation.", &self.errors),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {} {}\n# TYPE {} counter\n{} {}", name, help, name, name, value.load(Ordering::Relaxed));
+        }
+
+        let _ = writeln!(out, "# HELP diplomski_files_total Files the export will write.");
+        let _ = writeln!(out, "# TYPE diplomski_files_total gauge");
+        let _ = writeln!(out, "diplomski_files_total {}", self.files_total.load(Ordering::Relaxed));
+
+        let _ = writeln!(out, "# HELP diplomski_file_duration_seconds Time to generate and write a file per sink.");
+        let _ = writeln!(out, "# TYPE diplomski_file_duration_seconds histogram");
+        for (sink, histogram) in self.durations.lock().unwrap_or_else(|x| x.into_inner()).iter() {
+            let sink = escape_label(sink);
+            for (bound, count) in DURATION_BUCKETS.iter().zip(histogram.buckets) {
+                let _ = writeln!(out, "diplomski_file_duration_seconds_bucket{{sink=\"{}\",le=\"{}\"}} {}", sink, bound, count);
+            }
+            let _ = writeln!(out, "diplomski_file_duration_seconds_bucket{{sink=\"{}\",le=\"+Inf\"}} {}", sink, histogram.count);
+            let _ = writeln!(out, "diplomski_file_duration_seconds_sum{{sink=\"{}\"}} {}", sink, histogram.sum);
+            let _ = writeln!(out, "diplomski_file_duration_seconds_count{{sink=\"{}\"}} {}", sink, histogram.count);
+        }
+
+        out
+    }
+
+    /// Serves `render` on every path from a background thread and returns the bound address.
+    pub fn serve(self: &Arc<Self>, address: impl ToSocketAddrs) -> Result<SocketAddr> {
+        let metrics = self.clone();
+        serve_http(address, move || ("200 OK", "text/plain; version=0.0.4", metrics.render()))
+    }
+
+    /// Metrics served on `0.0.0.0` at the port from `DIPLOMSKI_METRICS_PORT`, `None` when it is not set.
+    pub fn from_env() -> Result<Option<Arc<Metrics>>> {
+        match env::var(METRICS_PORT_ENV) {
+            Ok(port) => {
+                let metrics = Arc::new(Metrics::new());
+                metrics.serve(("0.0.0.0", port.trim().parse::<u16>()?))?;
+                Ok(Some(metrics))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+impl ProgressSink for Metrics {
+    fn start(&self, files: u64, _bytes: u64) {
+        self.files_total.fetch_add(files, Ordering::Relaxed);
+    }
+
+    fn rows_generated(&self, table: &str, rows: u64) {
+        *self.rows.lock().unwrap_or_else(|x| x.into_inner()).entry(table.to_string()).or_default() += rows;
+    }
+
+    fn bytes_written(&self, bytes: u64) {
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn file_completed(&self, metadata: &FileMetadata) {
+        self.files_completed.fetch_add(1, Ordering::Relaxed);
+
+        // The sink is the output format, told apart by the file extension.
+        let sink = metadata.path.extension().and_then(|x| x.to_str()).unwrap_or("file").to_string();
+        self.durations.lock().unwrap_or_else(|x| x.into_inner())
+            .entry(sink)
+            .or_default()
+            .observe(metadata.duration.as_secs_f64());
+    }
+
+    fn error(&self, _error: &Error) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{Read, Write as _};
+    use std::net::TcpStream;
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{Column, ExportFile, Table};
+    use crate::types::ColumnType;
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    #[test]
+    fn metrics_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("column".into(), 3, ColumnType::Char(3), simple_generator)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+
+        let metrics = Arc::new(Metrics::new());
+        let ef = ExportFile::new(vec![t], 60, 2)
+            .unwrap()
+            .with_progress(metrics.clone());
+
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_all_files(&folder).unwrap();
+        fs::remove_dir_all(&folder).unwrap();
+
+        let address = metrics.serve("127.0.0.1:0").unwrap();
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("diplomski_rows_generated_total{table=\"A\"} 10\n"));
+        assert!(response.contains("diplomski_bytes_written_total 60\n"));
+        assert!(response.contains("diplomski_file_duration_seconds_count{sink=\"txt\"} 2\n"));
+        assert!(response.contains("diplomski_errors_total 0\n"));
+    }
+}
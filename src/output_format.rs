@@ -0,0 +1,113 @@
+use std::str::FromStr;
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+
+#[derive(Error, Debug)]
+pub enum OutputFormatError {
+    #[error("Unknown output format {format}, expected delimited, fixed_width or key_value.")]
+    UnknownFormat { format: String },
+    #[error("Unknown alignment {alignment}, expected left or right.")]
+    UnknownAlignment { alignment: String },
+}
+
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Alignment {
+    #[default]
+    Left,
+    Right,
+}
+
+impl FromStr for Alignment {
+    type Err = Error;
+
+    fn from_str(alignment: &str) -> Result<Self> {
+        match alignment.to_ascii_lowercase().as_str() {
+            "left" => Ok(Alignment::Left),
+            "right" => Ok(Alignment::Right),
+            _ => Err(Error::from(OutputFormatError::UnknownAlignment { alignment: alignment.into() })),
+        }
+    }
+}
+
+
+/// How a text row is laid out, values joined by the table delimiter, positional fields or named pairs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Delimited,
+    /// Every value padded or truncated to its column size, key columns are widened by the namespace.
+    FixedWidth { padding: char, alignment: Alignment },
+    /// Every value preceded by its column name and the separator, e.g. `A;amount=10;currency=EUR` for `;`.
+    KeyValue { separator: char },
+}
+
+impl OutputFormat {
+    pub fn fixed_width() -> Self {
+        OutputFormat::FixedWidth { padding: ' ', alignment: Alignment::Left }
+    }
+
+    pub fn key_value() -> Self {
+        OutputFormat::KeyValue { separator: '=' }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        match format.to_ascii_lowercase().replace('-', "_").as_str() {
+            "delimited" => Ok(OutputFormat::Delimited),
+            "fixed_width" => Ok(OutputFormat::fixed_width()),
+            "key_value" => Ok(OutputFormat::key_value()),
+            _ => Err(Error::from(OutputFormatError::UnknownFormat { format: format.into() })),
+        }
+    }
+}
+
+
+// Widths count characters like column sizes do, so multi-byte values are never cut inside a character.
+pub(crate) fn fit(value: &str, width: usize, padding: char, alignment: Alignment, field: &mut String) {
+    let length = value.chars().count();
+
+    if length >= width {
+        field.extend(value.chars().take(width));
+        return;
+    }
+
+    let pad = std::iter::repeat_n(padding, width - length);
+    match alignment {
+        Alignment::Left => {
+            field.push_str(value);
+            field.extend(pad);
+        }
+        Alignment::Right => {
+            field.extend(pad);
+            field.push_str(value);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_test() {
+        let fitted = |value: &str, width: usize, padding: char, alignment: Alignment| {
+            let mut field = String::new();
+            fit(value, width, padding, alignment, &mut field);
+            field
+        };
+
+        assert_eq!(fitted("ab", 4, ' ', Alignment::Left), "ab  ");
+        assert_eq!(fitted("42", 4, '0', Alignment::Right), "0042");
+        assert_eq!(fitted("čšžđ", 3, ' ', Alignment::Left), "čšž");
+        assert_eq!("fixed-width".parse::<OutputFormat>().unwrap(), OutputFormat::fixed_width());
+        assert_eq!("key_value".parse::<OutputFormat>().unwrap(), OutputFormat::KeyValue { separator: '=' });
+        assert!("columnar".parse::<OutputFormat>().is_err());
+    }
+}
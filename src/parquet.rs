@@ -0,0 +1,316 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use ::parquet::basic::{LogicalType, Repetition, TimeUnit, Type as PhysicalType};
+use ::parquet::column::writer::ColumnWriter;
+use ::parquet::data_type::{ByteArray, FixedLenByteArray};
+use ::parquet::file::properties::WriterProperties;
+use ::parquet::file::writer::SerializedFileWriter;
+use ::parquet::format::MicroSeconds;
+use ::parquet::schema::types::Type;
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+use thiserror::Error;
+
+use crate::{Column, ExportFile, Table};
+use crate::types::ColumnType;
+use crate::values::{parse_bool, parse_date, parse_timestamp_micros};
+
+
+#[derive(Error, Debug)]
+pub enum ParquetError {
+    #[error("Value {value} of column {column} can not be stored as {column_type}.")]
+    InvalidValue { column: String, value: String, column_type: ColumnType },
+}
+
+
+// Precision and scale a DECIMAL column is stored with, Parquet decimals hold at most 38 digits.
+fn decimal_precision(precision: u32, scale: u32) -> (u32, u32) {
+    let precision = precision.clamp(1, 38);
+    (precision, scale.min(precision))
+}
+
+
+fn decimal_unscaled(value: &str, precision: u32, scale: u32) -> Option<i128> {
+    let (precision, scale) = decimal_precision(precision, scale);
+    let mut decimal = Decimal::from_str(value.trim()).ok()?;
+    decimal.rescale(scale);
+    let unscaled = decimal.mantissa();
+    (unscaled.unsigned_abs() < 10u128.pow(precision)).then_some(unscaled)
+}
+
+
+impl Column {
+    /// Parquet field the column is written as, everything without a matching Parquet type is stored as a string.
+    pub fn parquet_field(&self) -> Result<Type> {
+        let (physical_type, logical_type) = match &self.column_type {
+            ColumnType::Boolean => (PhysicalType::BOOLEAN, None),
+            ColumnType::SmallInt | ColumnType::Int => (PhysicalType::INT32, None),
+            ColumnType::BigInt => (PhysicalType::INT64, None),
+            ColumnType::Real | ColumnType::Double => (PhysicalType::DOUBLE, None),
+            ColumnType::Decimal(precision, scale) => {
+                let (precision, scale) = decimal_precision(*precision, *scale);
+                let physical_type = match precision {
+                    ..=9 => PhysicalType::INT32,
+                    10..=18 => PhysicalType::INT64,
+                    _ => PhysicalType::FIXED_LEN_BYTE_ARRAY,
+                };
+                (physical_type, Some(LogicalType::Decimal { scale: scale as i32, precision: precision as i32 }))
+            }
+            ColumnType::Date => (PhysicalType::INT32, Some(LogicalType::Date)),
+            ColumnType::Timestamp => (PhysicalType::INT64, Some(LogicalType::Timestamp {
+                is_adjusted_to_u_t_c: false,
+                unit: TimeUnit::MICROS(MicroSeconds {}),
+            })),
+            _ => (PhysicalType::BYTE_ARRAY, Some(LogicalType::String)),
+        };
+
+        let mut field = Type::primitive_type_builder(&self.name, physical_type)
+            .with_repetition(if self.is_nullable() { Repetition::OPTIONAL } else { Repetition::REQUIRED })
+            .with_logical_type(logical_type.clone());
+        if physical_type == PhysicalType::FIXED_LEN_BYTE_ARRAY {
+            field = field.with_length(16);
+        }
+        if let Some(LogicalType::Decimal { scale, precision }) = logical_type {
+            field = field.with_precision(precision).with_scale(scale);
+        }
+
+        Ok(field.build()?)
+    }
+
+
+    fn parquet_values<T>(&self, values: &[&str], parse: impl Fn(&str) -> Option<T>) -> Result<Vec<T>> {
+        values.iter()
+            .map(|x| parse(x).ok_or_else(|| Error::from(ParquetError::InvalidValue {
+                column: self.name.clone(),
+                value: x.to_string(),
+                column_type: self.column_type.clone(),
+            })))
+            .collect()
+    }
+
+
+    fn parquet_int32(&self, value: &str) -> Option<i32> {
+        match self.column_type {
+            ColumnType::Date => parse_date(value.trim())?.try_into().ok(),
+            ColumnType::Decimal(precision, scale) => decimal_unscaled(value, precision, scale)?.try_into().ok(),
+            _ => value.trim().parse().ok(),
+        }
+    }
+
+
+    fn parquet_int64(&self, value: &str) -> Option<i64> {
+        match self.column_type {
+            ColumnType::Timestamp => parse_timestamp_micros(value.trim()),
+            ColumnType::Decimal(precision, scale) => decimal_unscaled(value, precision, scale)?.try_into().ok(),
+            _ => value.trim().parse().ok(),
+        }
+    }
+
+
+    fn parquet_fixed_len(&self, value: &str) -> Option<FixedLenByteArray> {
+        match self.column_type {
+            ColumnType::Decimal(precision, scale) => {
+                let unscaled = decimal_unscaled(value, precision, scale)?;
+                Some(FixedLenByteArray::from(unscaled.to_be_bytes().to_vec()))
+            }
+            _ => None,
+        }
+    }
+}
+
+
+impl Table {
+    pub fn parquet_schema(&self) -> Result<Type> {
+        let fields = self.columns.iter()
+            .map(|x| x.parquet_field().map(Arc::new))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Type::group_type_builder("schema").with_fields(fields).build()?)
+    }
+
+
+    pub fn generate_parquet(&self, file_size_bytes: u64) -> Result<Vec<u8>> {
+        self.rows_to_parquet(&self.generate_table_vec(file_size_bytes)?)
+    }
+
+
+    fn rows_to_parquet(&self, rows: &[Vec<String>]) -> Result<Vec<u8>> {
+        for (i, row) in rows.iter().enumerate() {
+            self.check_row_length(i, row)?;
+        }
+
+        let properties = WriterProperties::builder()
+            .set_created_by(format!("diplomski_projekt version {}", env!("CARGO_PKG_VERSION")))
+            .build();
+        let mut writer = SerializedFileWriter::new(Vec::new(), Arc::new(self.parquet_schema()?), Arc::new(properties))?;
+        let mut row_group = writer.next_row_group()?;
+
+        for (i, column) in self.columns.iter().enumerate() {
+            let Some(mut column_writer) = row_group.next_column()? else { break };
+
+            // Column 0 of a generated row is the table id, which the file name already carries.
+            let present = rows.iter()
+                .map(|x| !self.is_null(column, &x[i + 1]))
+                .collect::<Vec<bool>>();
+            let values = rows.iter()
+                .zip(present.iter())
+                .filter(|x| *x.1)
+                .map(|x| x.0[i + 1].as_str())
+                .collect::<Vec<&str>>();
+            let levels = present.iter().map(|x| *x as i16).collect::<Vec<i16>>();
+            let levels = column.is_nullable().then_some(levels.as_slice());
+
+            match column_writer.untyped() {
+                ColumnWriter::BoolColumnWriter(x) =>
+                    x.write_batch(&column.parquet_values(&values, parse_bool)?, levels, None)?,
+                ColumnWriter::Int32ColumnWriter(x) =>
+                    x.write_batch(&column.parquet_values(&values, |x| column.parquet_int32(x))?, levels, None)?,
+                ColumnWriter::Int64ColumnWriter(x) =>
+                    x.write_batch(&column.parquet_values(&values, |x| column.parquet_int64(x))?, levels, None)?,
+                ColumnWriter::DoubleColumnWriter(x) =>
+                    x.write_batch(&column.parquet_values(&values, |x| x.trim().parse().ok())?, levels, None)?,
+                ColumnWriter::FixedLenByteArrayColumnWriter(x) =>
+                    x.write_batch(&column.parquet_values(&values, |x| column.parquet_fixed_len(x))?, levels, None)?,
+                ColumnWriter::ByteArrayColumnWriter(x) =>
+                    x.write_batch(&column.parquet_values(&values, |x| Some(ByteArray::from(x)))?, levels, None)?,
+                _ => unreachable!("parquet_field only declares the physical types written above"),
+            };
+
+            column_writer.close()?;
+        }
+
+        row_group.close()?;
+        Ok(writer.into_inner()?)
+    }
+
+
+    pub fn generate_parquet_to_file(&self, file_size_bytes: u64, path: &Path) -> Result<()> {
+        let parquet = self.generate_parquet(file_size_bytes)?;
+        let mut file = File::create(path)?;
+        file.write_all(parquet.as_ref())?;
+        Ok(())
+    }
+}
+
+
+impl ExportFile {
+    pub fn generate_parquet(&self, folder_path: &Path) -> Result<()> {
+        self.write_table_files(folder_path, "parquet", |table, rows| table.rows_to_parquet(rows))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use ::parquet::file::reader::{FileReader, SerializedFileReader};
+    use ::parquet::record::{Row, RowAccessor};
+
+    use super::*;
+
+    fn int_generator() -> Result<String> {
+        Ok("42".into())
+    }
+
+    fn decimal_generator() -> Result<String> {
+        Ok("12.5".into())
+    }
+
+    fn date_generator() -> Result<String> {
+        Ok("2024-01-18".into())
+    }
+
+    fn read_back(parquet: Vec<u8>) -> Vec<Row> {
+        let path = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}.parquet", rand::random::<u32>()));
+        fs::write(&path, parquet).unwrap();
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let rows = reader.get_row_iter(None).unwrap().collect::<std::result::Result<Vec<Row>, _>>().unwrap();
+        fs::remove_file(&path).unwrap();
+        rows
+    }
+
+    #[test]
+    fn parquet_field_test() {
+        let field = Column::new("amount".into(), 4, "DECIMAL(10,2)".into(), decimal_generator).parquet_field().unwrap();
+        assert_eq!(field.get_physical_type(), PhysicalType::INT64);
+        assert_eq!(field.get_precision(), 10);
+        assert_eq!(field.get_scale(), 2);
+
+        let field = Column::new("id".into(), 3, "CHAR(3)".into(), int_generator).parquet_field().unwrap();
+        assert_eq!(field.get_physical_type(), PhysicalType::BYTE_ARRAY);
+        assert_eq!(field.get_basic_info().logical_type(), Some(LogicalType::String));
+    }
+
+    #[test]
+    fn generate_parquet_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("id".into(), 2, "INT".into(), int_generator),
+                Column::new("amount".into(), 4, "DECIMAL(10,2)".into(), decimal_generator),
+                Column::new("day".into(), 10, "DATE".into(), date_generator),
+            ],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+
+        let rows = read_back(t.generate_parquet(160).unwrap());
+
+        assert!(!rows.is_empty());
+        for row in rows {
+            let values = row.get_column_iter().map(|x| x.1.to_string()).collect::<Vec<String>>();
+            assert_eq!(values, vec!["42", "12.50", "2024-01-18"]);
+        }
+    }
+
+    #[test]
+    fn generate_parquet_invalid_value_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("id".into(), 10, "INT".into(), date_generator)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+
+        assert!(t.generate_parquet(100).is_err());
+    }
+
+    #[test]
+    fn generate_parquet_nullable_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("id".into(), 10, "INT".into(), date_generator).with_null_rate(1.0)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+
+        let rows = read_back(t.generate_parquet(100).unwrap());
+
+        assert!(!rows.is_empty());
+        assert!(rows.iter().all(|x| x.get_int(0).is_err()));
+    }
+
+    #[test]
+    fn generate_parquet_truncated_row_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("id".into(), 2, "INT".into(), int_generator),
+                Column::new("day".into(), 10, "DATE".into(), date_generator),
+            ],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        )
+            .with_corruption(crate::corruption::CorruptionProfile::new().with_truncated_rows(1.0));
+
+        let error = t.generate_parquet(100).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<crate::corruption::CorruptionError>(),
+            Some(crate::corruption::CorruptionError::TruncatedRow { row: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+use std::fs;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::{Error, Result};
+use rayon::prelude::*;
+use thiserror::Error;
+
+use crate::ExportFile;
+use crate::compression::Encoder;
+use crate::metadata::{ChecksumWriter, FileMetadata, TableMetadata};
+
+
+#[derive(Error, Debug)]
+pub enum PartitionError {
+    #[error("Can not partition by {column}, table {table} has no such column.")]
+    MissingColumn { table: String, column: String },
+}
+
+
+struct Partition {
+    path: PathBuf,
+    file_index: u64,
+    encoder: Encoder<ChecksumWriter<BufWriter<File>>>,
+    tables: Vec<TableMetadata>,
+    started: Instant,
+}
+
+
+// Values become part of a file name, so anything but letters, digits, `-`, `_` and `.` is replaced.
+fn partition_name(column: &str, value: &str, ext: &str) -> String {
+    let value: String = match value {
+        "" => "null".into(),
+        x => x.chars()
+            .map(|y| if y.is_ascii_alphanumeric() || "-_.".contains(y) { y } else { '_' })
+            .collect(),
+    };
+
+    format!("{}={}.{}", column, value, ext)
+}
+
+
+// Hive escapes partition values byte by byte and has its own directory for nulls.
+fn hive_partition_dir(column: &str, value: Option<&str>) -> String {
+    let value = match value {
+        None => "__HIVE_DEFAULT_PARTITION__".into(),
+        Some(x) => x.bytes()
+            .map(|y| match y.is_ascii_alphanumeric() || b"-_. ".contains(&y) {
+                true => (y as char).to_string(),
+                false => format!("%{:02X}", y),
+            })
+            .collect::<String>(),
+    };
+
+    format!("{}={}", column, value)
+}
+
+
+impl ExportFile {
+    /// Writes every row to the file of its `column` value, e.g. `country=HR.txt`, instead of equal-size files.
+    /// Every table needs the column, a file stays open per distinct value until the export is done.
+    pub fn generate_partitioned_files(&self, folder_path: &Path, column: &str) -> Result<()> {
+        let columns = self.tables.iter()
+            .map(|x| {
+                x.columns.iter()
+                    .position(|y| y.name == column)
+                    .ok_or_else(|| Error::from(PartitionError::MissingColumn { table: x.id_value.clone(), column: column.into() }))
+            })
+            .collect::<Result<Vec<usize>>>()?;
+
+        fs::create_dir_all(folder_path)?;
+        self.progress_start(self.number_of_files);
+
+        let mut partitions: BTreeMap<String, Partition> = BTreeMap::new();
+        for file_index in 0..self.number_of_files {
+            let rows = self.generate_file_rows(file_index).inspect_err(|x| self.progress_error(x))?;
+
+            for (table_index, (table, rows)) in self.tables.iter().zip(rows).enumerate() {
+                let mut by_value: BTreeMap<String, Vec<Vec<String>>> = BTreeMap::new();
+                for row in rows {
+                    by_value.entry(partition_name(column, &row[columns[table_index] + 1], self.compression.extension()))
+                        .or_default()
+                        .push(row);
+                }
+
+                for (name, rows) in by_value {
+                    let next_index = partitions.len() as u64;
+                    let partition = match partitions.entry(name) {
+                        Entry::Occupied(x) => x.into_mut(),
+                        Entry::Vacant(x) => {
+                            let path = folder_path.join(x.key());
+                            let file = ChecksumWriter::new(BufWriter::new(File::create(&path)?));
+                            x.insert(Partition {
+                                path,
+                                file_index: next_index,
+                                encoder: self.compression.encoder(file)?,
+                                tables: self.tables.iter()
+                                    .map(|y| TableMetadata { table: y.id_value.clone(), rows: 0, bytes: 0 })
+                                    .collect(),
+                                started: Instant::now(),
+                            })
+                        }
+                    };
+
+                    let data = table.rows_to_string(&rows);
+                    let data = self.encoding.encode(&data)?;
+                    partition.encoder.write_all(&data)?;
+                    partition.tables[table_index].rows += rows.len() as u64;
+                    partition.tables[table_index].bytes += data.len() as u64;
+                    self.progress_rows(&table.id_value, rows.len() as u64, data.len() as u64);
+                }
+            }
+        }
+
+        for partition in partitions.into_values() {
+            let (bytes, checksum, sha256) = partition.encoder.finish()?.finish()?;
+            self.file_written(FileMetadata {
+                path: partition.path,
+                file_index: partition.file_index,
+                tables: partition.tables.into_iter().filter(|x| x.rows > 0).collect(),
+                bytes,
+                checksum,
+                sha256,
+                duration: partition.started.elapsed(),
+            })?;
+        }
+
+        self.progress_finish();
+        Ok(())
+    }
+
+
+    /// Writes a Hive style layout, `<table>/<column>=<value>/part-<n>.txt` with the rows of file `n` that have the
+    /// value, leaving the column out of the rows as partitioned external tables expect. Tables without the column are
+    /// written to `<table>/part-<n>.txt`.
+    pub fn generate_hive_partitioned_files(&self, folder_path: &Path, column: &str) -> Result<()> {
+        let ext = self.compression.extension();
+        self.progress_start(self.number_of_files.saturating_mul(self.tables.len() as u64));
+
+        (0..self.number_of_files).into_par_iter()
+            .try_for_each(|x| -> Result<()> {
+                let rows = self.generate_file_rows(x)?;
+
+                for (table, rows) in self.tables.iter().zip(rows) {
+                    let position = table.columns.iter().position(|y| y.name == column);
+                    let mut layout = table.clone();
+                    let mut by_dir: BTreeMap<PathBuf, Vec<Vec<String>>> = BTreeMap::new();
+                    match position {
+                        Some(position) => {
+                            layout.columns.remove(position);
+                            for mut row in rows {
+                                let value = row.remove(position + 1);
+                                let null = table.is_null(&table.columns[position], &value);
+                                let dir = hive_partition_dir(column, (!null).then_some(value.as_str()));
+                                by_dir.entry(folder_path.join(&table.id_value).join(dir))
+                                    .or_default()
+                                    .push(row);
+                            }
+                        }
+                        None => {
+                            by_dir.insert(folder_path.join(&table.id_value), rows);
+                        }
+                    }
+
+                    for (dir, rows) in by_dir {
+                        let started = Instant::now();
+                        fs::create_dir_all(&dir)?;
+                        let path = dir.join(format!("part-{:04}.{}", x, ext));
+                        let data = layout.rows_to_string(&rows);
+                        let (bytes, checksum, sha256) = self.write_encoded(&path, &data)?;
+                        self.progress_rows(&table.id_value, rows.len() as u64, data.len() as u64);
+
+                        self.file_written(FileMetadata {
+                            path,
+                            file_index: x,
+                            tables: vec![TableMetadata {
+                                table: table.id_value.clone(),
+                                rows: rows.len() as u64,
+                                bytes: data.len() as u64,
+                            }],
+                            bytes,
+                            checksum,
+                            sha256,
+                            duration: started.elapsed(),
+                        })?;
+                    }
+                }
+
+                Ok(())
+            })
+            .inspect_err(|x| self.progress_error(x))?;
+
+        self.progress_finish();
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column, Table};
+    use crate::types::ColumnType;
+
+    #[test]
+    fn generate_partitioned_files_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)),
+                Column::new("country".into(), 2, ColumnType::Char(2), generators::one_of(vec!["HR".into(), "DE/AT".into()])),
+            ],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+        let ef = ExportFile::new(vec![t], 600, 3).unwrap().with_seed(2);
+
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_partitioned_files(&folder, "country").unwrap();
+
+        let mut names: Vec<String> = fs::read_dir(&folder).unwrap()
+            .map(|x| x.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        let croatia = fs::read_to_string(folder.join("country=HR.txt")).unwrap();
+        let total = croatia.lines().count() + fs::read_to_string(folder.join("country=DE_AT.txt")).unwrap().lines().count();
+        fs::remove_dir_all(&folder).unwrap();
+
+        assert_eq!(names, vec!["country=DE_AT.txt", "country=HR.txt"]);
+        assert!(croatia.lines().all(|x| x.ends_with("|HR")));
+        assert_eq!(total as u64, (0..3).map(|x| ef.generate_file_rows(x).unwrap()[0].len() as u64).sum::<u64>());
+        assert!(ef.generate_partitioned_files(&folder, "missing").is_err());
+    }
+
+    #[test]
+    fn generate_hive_partitioned_files_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)),
+                Column::new("country".into(), 2, ColumnType::Char(2), generators::one_of(vec!["HR".into(), "DE/AT".into()]))
+                    .with_null_rate(0.2),
+            ],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+        let d = Table::new(
+            "D".into(),
+            vec![Column::new("name".into(), 3, ColumnType::Char(3), generators::constant("ABC"))],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+        let ef = ExportFile::new(vec![t, d], 600, 2).unwrap().with_seed(2);
+
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_hive_partitioned_files(&folder, "country").unwrap();
+
+        let mut dirs: Vec<String> = fs::read_dir(folder.join("A")).unwrap()
+            .map(|x| x.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        dirs.sort();
+        let croatia = fs::read_to_string(folder.join("A").join("country=HR").join("part-0001.txt")).unwrap();
+        let unpartitioned = fs::read_to_string(folder.join("D").join("part-0000.txt")).unwrap();
+        fs::remove_dir_all(&folder).unwrap();
+
+        assert_eq!(dirs, vec!["country=DE%2FAT", "country=HR", "country=__HIVE_DEFAULT_PARTITION__"]);
+        assert!(croatia.lines().all(|x| x.split('|').count() == 2 && !x.contains("HR")));
+        assert_eq!(unpartitioned.lines().count(), ef.generate_file_rows(0).unwrap()[1].len());
+    }
+}
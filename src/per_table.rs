@@ -0,0 +1,107 @@
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::Result;
+use rayon::prelude::*;
+
+use crate::ExportFile;
+use crate::metadata::{FileMetadata, TableMetadata};
+
+
+impl ExportFile {
+    /// Writes every table to its own files, part `n` of a table holds the rows it would have in file `n`.
+    pub fn generate_files_per_table(&self, folder_path: &Path) -> Result<()> {
+        self.check_table_file_names()?;
+        fs::create_dir_all(folder_path)?;
+        self.progress_start(self.number_of_files.saturating_mul(self.tables.len() as u64));
+
+        (0..self.number_of_files).into_par_iter()
+            .try_for_each(|x| -> Result<()> {
+                let rows = self.generate_file_rows(x)?;
+
+                self.tables.par_iter()
+                    .zip(rows.par_iter())
+                    .try_for_each(|(table, rows)| {
+                        let started = Instant::now();
+                        let path = self.table_file_path(folder_path, table, x);
+                        // Templates like `{table}/part_{index}.{ext}` keep every table in a folder of its own.
+                        if let Some(folder) = path.parent() {
+                            fs::create_dir_all(folder)?;
+                        }
+
+                        if self.max_rows_per_file.is_some_and(|x| rows.len() as u64 > x) {
+                            let rows = std::slice::from_ref(rows);
+                            let order = vec![0; rows[0].len()];
+                            for metadata in self.write_spilled(&path, x, &[table], rows, &order, started)? {
+                                self.file_written(metadata)?;
+                            }
+                            return Ok(());
+                        }
+
+                        let data = table.rows_to_string(rows);
+                        let (bytes, checksum, sha256) = self.write_encoded(&path, &data)?;
+                        self.progress_rows(&table.id_value, rows.len() as u64, data.len() as u64);
+
+                        self.file_written(FileMetadata {
+                            path,
+                            file_index: x,
+                            tables: vec![TableMetadata {
+                                table: table.id_value.clone(),
+                                rows: rows.len() as u64,
+                                bytes: data.len() as u64,
+                            }],
+                            bytes,
+                            checksum,
+                            sha256,
+                            duration: started.elapsed(),
+                        })
+                    })
+            })
+            .inspect_err(|x| self.progress_error(x))?;
+
+        self.progress_finish();
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{Column, Table};
+    use crate::types::ColumnType;
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    fn table(id: &str) -> Table {
+        Table::new(
+            id.into(),
+            vec![Column::new("column".into(), 3, ColumnType::Char(3), simple_generator)],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        )
+    }
+
+    #[test]
+    fn generate_files_per_table_test() {
+        let ef = ExportFile::new(vec![table("A"), table("B")], 120, 2).unwrap();
+
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_files_per_table(&folder).unwrap();
+
+        let mut names: Vec<String> = fs::read_dir(&folder).unwrap()
+            .map(|x| x.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        let content = fs::read_to_string(folder.join("B_1.txt")).unwrap();
+        fs::remove_dir_all(&folder).unwrap();
+
+        assert_eq!(names, vec!["A_0.txt", "A_1.txt", "B_0.txt", "B_1.txt"]);
+        assert_eq!(content, "B|ABC\n".repeat(5));
+    }
+}
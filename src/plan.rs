@@ -0,0 +1,144 @@
+use std::fmt;
+use std::mem::size_of;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::ExportFile;
+
+// Rows of every table generated to time it, few enough that planning a large export takes well under a second.
+const SAMPLE_ROWS: u64 = 200;
+
+
+/// What an export would generate, sized the same way as generating it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TablePlan {
+    pub table: String,
+    pub rows: u64,
+    /// Rows in the first file, files after it have the same or one row less.
+    pub rows_per_file: u64,
+    pub row_size_bytes: u64,
+    pub bytes: u64,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportPlan {
+    pub tables: Vec<TablePlan>,
+    pub files: u64,
+    /// Uncompressed bytes of the largest file.
+    pub bytes_per_file: u64,
+    pub total_bytes: u64,
+    /// Rows and text of the files generated at the same time, one per thread.
+    pub memory_bytes: u64,
+    /// Extrapolated from generating a sample of every table's rows on this machine.
+    pub estimated_duration: Duration,
+}
+
+impl fmt::Display for ExportPlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} files of up to {} bytes, {} bytes in total, about {} bytes of memory and {}s.",
+            self.files,
+            self.bytes_per_file,
+            self.total_bytes,
+            self.memory_bytes,
+            self.estimated_duration.as_secs(),
+        )?;
+
+        for table in self.tables.iter() {
+            writeln!(
+                f,
+                "Table {} has {} rows, {} per file, of {} bytes, {} bytes in total.",
+                table.table, table.rows, table.rows_per_file, table.row_size_bytes, table.bytes,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+
+impl ExportFile {
+    /// Reports row counts, sizes, memory and time of the export without writing any files.
+    pub fn plan(&self) -> Result<ExportPlan> {
+        let threads = (rayon::current_num_threads() as u64).clamp(1, self.number_of_files.max(1));
+        let mut tables = vec![];
+        let mut bytes_per_file: u64 = 0;
+        let mut memory_per_file: u64 = 0;
+        let mut sample_time = Duration::ZERO;
+
+        for table in self.tables.iter() {
+            let rows = self.table_total_rows(table)?;
+            let rows_per_file = self.table_row_count(table, 0)?;
+
+            // Generated rows are held as a string per value before they are joined into the file's text.
+            let row_memory = table.row_size_bytes * 2 + (table.columns.len() as u64 + 2) * size_of::<String>() as u64;
+            bytes_per_file = bytes_per_file.saturating_add(rows_per_file.saturating_mul(table.row_size_bytes));
+            memory_per_file = memory_per_file.saturating_add(rows_per_file.saturating_mul(row_memory));
+
+            let sample_rows = rows.min(SAMPLE_ROWS);
+            if sample_rows > 0 {
+                let started = Instant::now();
+                table.generate_rows_vec(sample_rows)?;
+                sample_time += started.elapsed().mul_f64(rows as f64 / sample_rows as f64);
+            }
+
+            tables.push(TablePlan {
+                table: table.id_value.clone(),
+                rows,
+                rows_per_file,
+                row_size_bytes: table.row_size_bytes,
+                bytes: rows.saturating_mul(table.row_size_bytes),
+            });
+        }
+
+        Ok(ExportPlan {
+            total_bytes: tables.iter().map(|x| x.bytes).fold(0, u64::saturating_add),
+            tables,
+            files: self.number_of_files,
+            bytes_per_file,
+            memory_bytes: memory_per_file.saturating_mul(threads),
+            estimated_duration: sample_time.div_f64(threads as f64),
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column, SizeSpec, Table};
+    use crate::types::ColumnType;
+
+    #[test]
+    fn plan_test() {
+        let table = |id: &str| Table::new(
+            id.into(),
+            vec![Column::new("code".into(), 3, ColumnType::Char(3), generators::constant("ABC"))],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+        let ef = ExportFile::new(vec![table("A"), table("B")], 1000, 4)
+            .unwrap()
+            .with_size_spec(SizeSpec::Rows(HashMap::from([("B".to_string(), 10)])))
+            .unwrap();
+        let plan = ef.plan().unwrap();
+
+        // Rows are 6 bytes: the id, a delimiter, the value and the terminator.
+        let expected = |table: &str, rows, rows_per_file, bytes| {
+            TablePlan { table: table.into(), rows, rows_per_file, row_size_bytes: 6, bytes }
+        };
+        assert_eq!(plan.tables, vec![expected("A", 80, 20, 480), expected("B", 10, 3, 60)]);
+        assert_eq!(plan.files, 4);
+        assert_eq!(plan.bytes_per_file, 138);
+        assert_eq!(plan.total_bytes, 540);
+        assert!(plan.memory_bytes > plan.bytes_per_file);
+        assert!(plan.to_string().contains("Table B has 10 rows, 3 per file"));
+    }
+}
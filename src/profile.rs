@@ -0,0 +1,154 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::{ExportFile, Table};
+
+
+/// Time and values of every column generator of one table, recorded without locking.
+#[derive(Debug)]
+pub struct TableProfile {
+    table: String,
+    columns: Vec<String>,
+    nanos: Vec<AtomicU64>,
+    values: Vec<AtomicU64>,
+}
+
+impl TableProfile {
+    pub(crate) fn record(&self, column_index: usize, elapsed: Duration) {
+        self.nanos[column_index].fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+        self.values[column_index].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratorCost {
+    pub table: String,
+    pub column: String,
+    pub values: u64,
+    pub nanos: u64,
+}
+
+
+/// Time spent in each column's generator across a run, see [`ExportFile::with_profile`].
+#[derive(Debug, Default)]
+pub struct GeneratorProfile {
+    tables: Mutex<Vec<Arc<TableProfile>>>,
+}
+
+impl GeneratorProfile {
+    pub fn new() -> Self {
+        GeneratorProfile::default()
+    }
+
+
+    fn register(&self, table: &Table) -> Arc<TableProfile> {
+        let profile = Arc::new(TableProfile {
+            table: table.id_value.clone(),
+            columns: table.columns.iter().map(|x| x.name.clone()).collect(),
+            nanos: table.columns.iter().map(|_| AtomicU64::new(0)).collect(),
+            values: table.columns.iter().map(|_| AtomicU64::new(0)).collect(),
+        });
+        self.tables.lock().unwrap().push(profile.clone());
+        profile
+    }
+
+
+    /// Costs of every generator that produced a value, the most expensive first.
+    pub fn costs(&self) -> Vec<GeneratorCost> {
+        let mut costs: Vec<GeneratorCost> = self.tables.lock().unwrap().iter()
+            .flat_map(|x| (0..x.columns.len()).map(move |y| GeneratorCost {
+                table: x.table.clone(),
+                column: x.columns[y].clone(),
+                values: x.values[y].load(Ordering::Relaxed),
+                nanos: x.nanos[y].load(Ordering::Relaxed),
+            }))
+            .filter(|x| x.values > 0)
+            .collect();
+
+        costs.sort_by(|x, y| y.nanos.cmp(&x.nanos).then_with(|| (&x.table, &x.column).cmp(&(&y.table, &y.column))));
+        costs
+    }
+}
+
+impl fmt::Display for GeneratorProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let costs = self.costs();
+        let total = costs.iter().map(|x| x.nanos).fold(0, u64::saturating_add).max(1);
+
+        writeln!(f, "Generator time by column:")?;
+        for (rank, cost) in costs.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>3}. {}.{} {:.1} ms ({:.1}%), {} values, {} ns per value",
+                rank + 1,
+                cost.table,
+                cost.column,
+                cost.nanos as f64 / 1e6,
+                cost.nanos as f64 * 100.0 / total as f64,
+                cost.values,
+                cost.nanos / cost.values.max(1),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+
+impl ExportFile {
+    /// Records the time spent in every column generator into `profile`, values that come out null are not timed.
+    pub fn with_profile(mut self, profile: Arc<GeneratorProfile>) -> Self {
+        for table in self.tables.iter_mut() {
+            table.profile = Some(profile.register(table));
+        }
+        self
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use anyhow::Result;
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::Column;
+    use crate::types::ColumnType;
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    fn slow_generator() -> Result<String> {
+        thread::sleep(Duration::from_micros(200));
+        Ok("XYZ".into())
+    }
+
+    #[test]
+    fn profile_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("fast".into(), 3, ColumnType::Char(3), simple_generator),
+                Column::new("slow".into(), 3, ColumnType::Char(3), slow_generator),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        );
+        let profile = Arc::new(GeneratorProfile::new());
+        let ef = ExportFile::new(vec![t], 200, 1).unwrap().with_profile(profile.clone());
+
+        let rows = ef.tables[0].generate_rows_vec(20).unwrap();
+        let costs = profile.costs();
+
+        assert_eq!(rows.len(), 20);
+        assert_eq!(costs.iter().map(|x| x.column.as_str()).collect::<Vec<&str>>(), vec!["slow", "fast"]);
+        assert!(costs.iter().all(|x| x.values == 20));
+        assert!(profile.to_string().contains("  1. A.slow "));
+    }
+}
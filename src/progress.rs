@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use anyhow::Error;
+
+use crate::ExportFile;
+use crate::metadata::FileMetadata;
+
+#[cfg(feature = "indicatif")]
+mod bar;
+#[cfg(feature = "indicatif")]
+pub use bar::ProgressBar;
+
+
+/// Receives progress while an export is generated, every method may be called from several threads at once.
+pub trait ProgressSink: Send + Sync {
+    /// Called once before generation with the number of files and the expected uncompressed size.
+    fn start(&self, _files: u64, _bytes: u64) {}
+
+    fn rows_generated(&self, _table: &str, _rows: u64) {}
+
+    /// Uncompressed bytes of rows written to a file.
+    fn bytes_written(&self, _bytes: u64) {}
+
+    fn file_completed(&self, _metadata: &FileMetadata) {}
+
+    /// Called for an error that stops generation.
+    fn error(&self, _error: &Error) {}
+
+    fn finish(&self) {}
+}
+
+
+impl<T: ProgressSink + ?Sized> ProgressSink for Arc<T> {
+    fn start(&self, files: u64, bytes: u64) {
+        (**self).start(files, bytes)
+    }
+
+    fn rows_generated(&self, table: &str, rows: u64) {
+        (**self).rows_generated(table, rows)
+    }
+
+    fn bytes_written(&self, bytes: u64) {
+        (**self).bytes_written(bytes)
+    }
+
+    fn file_completed(&self, metadata: &FileMetadata) {
+        (**self).file_completed(metadata)
+    }
+
+    fn error(&self, error: &Error) {
+        (**self).error(error)
+    }
+
+    fn finish(&self) {
+        (**self).finish()
+    }
+}
+
+
+impl ExportFile {
+    /// Adds a sink, every added sink receives all progress.
+    pub fn with_progress(mut self, progress: impl ProgressSink + 'static) -> Self {
+        self.progress.push(Arc::new(progress));
+        self
+    }
+
+
+    pub(crate) fn progress_start(&self, files: u64) {
+        self.restart_pace();
+        self.reset_unique();
+        for progress in &self.progress {
+            progress.start(files, files.saturating_mul(self.file_size_bytes));
+        }
+    }
+
+
+    pub(crate) fn progress_rows(&self, table: &str, rows: u64, bytes: u64) {
+        for progress in &self.progress {
+            progress.rows_generated(table, rows);
+            progress.bytes_written(bytes);
+        }
+        self.pace(rows, bytes);
+    }
+
+
+    pub(crate) fn progress_error(&self, error: &Error) {
+        for progress in &self.progress {
+            progress.error(error);
+        }
+    }
+
+
+    pub(crate) fn progress_finish(&self) {
+        for progress in &self.progress {
+            progress.finish();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use anyhow::Result;
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{Column, Table};
+    use crate::types::ColumnType;
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        files: AtomicU64,
+        rows: AtomicU64,
+        bytes: AtomicU64,
+        completed: AtomicU64,
+    }
+
+    impl ProgressSink for Counter {
+        fn start(&self, files: u64, _bytes: u64) {
+            self.files.store(files, Ordering::Relaxed);
+        }
+
+        fn rows_generated(&self, _table: &str, rows: u64) {
+            self.rows.fetch_add(rows, Ordering::Relaxed);
+        }
+
+        fn bytes_written(&self, bytes: u64) {
+            self.bytes.fetch_add(bytes, Ordering::Relaxed);
+        }
+
+        fn file_completed(&self, _metadata: &FileMetadata) {
+            self.completed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn progress_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("column".into(), 3, ColumnType::Char(3), simple_generator)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+
+        let counter = Arc::new(Counter::default());
+        let ef = ExportFile::new(vec![t], 60, 2)
+            .unwrap()
+            .with_progress(counter.clone());
+
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_all_files(&folder).unwrap();
+        fs::remove_dir_all(&folder).unwrap();
+
+        assert_eq!(counter.files.load(Ordering::Relaxed), 2);
+        assert_eq!(counter.rows.load(Ordering::Relaxed), 10);
+        assert_eq!(counter.bytes.load(Ordering::Relaxed), 60);
+        assert_eq!(counter.completed.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use indicatif::{ProgressDrawTarget, ProgressStyle};
+
+use crate::metadata::FileMetadata;
+use crate::progress::ProgressSink;
+
+const TEMPLATE: &str = "[{bar:30}] {msg}, {bytes} ({bytes_per_sec})";
+
+
+/// Progress bar on stderr drawn by indicatif, which redraws at most 10 times a second and hides itself when
+/// stderr is not a terminal.
+#[derive(Debug)]
+pub struct ProgressBar {
+    bar: indicatif::ProgressBar,
+    files: AtomicU64,
+    files_completed: AtomicU64,
+    rows: AtomicU64,
+}
+
+impl Default for ProgressBar {
+    fn default() -> Self {
+        ProgressBar::new()
+    }
+}
+
+impl ProgressBar {
+    pub fn new() -> Self {
+        let bar = indicatif::ProgressBar::with_draw_target(Some(0), ProgressDrawTarget::stderr());
+        bar.set_style(ProgressStyle::with_template(TEMPLATE).expect("valid template").progress_chars("#-"));
+
+        ProgressBar {
+            bar,
+            files: AtomicU64::new(0),
+            files_completed: AtomicU64::new(0),
+            rows: AtomicU64::new(0),
+        }
+    }
+
+    fn update_message(&self) {
+        self.bar.set_message(format!(
+            "{}/{} files, {} rows",
+            self.files_completed.load(Ordering::Relaxed),
+            self.files.load(Ordering::Relaxed),
+            self.rows.load(Ordering::Relaxed),
+        ));
+    }
+}
+
+impl ProgressSink for ProgressBar {
+    fn start(&self, files: u64, bytes: u64) {
+        self.files.store(files, Ordering::Relaxed);
+        self.bar.set_length(bytes);
+        self.bar.reset();
+        self.update_message();
+    }
+
+    fn rows_generated(&self, _table: &str, rows: u64) {
+        self.rows.fetch_add(rows, Ordering::Relaxed);
+        self.update_message();
+    }
+
+    fn bytes_written(&self, bytes: u64) {
+        self.bar.inc(bytes);
+    }
+
+    fn file_completed(&self, _metadata: &FileMetadata) {
+        self.files_completed.fetch_add(1, Ordering::Relaxed);
+        self.update_message();
+    }
+
+    fn finish(&self) {
+        self.update_message();
+        self.bar.finish();
+    }
+}
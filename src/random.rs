@@ -0,0 +1,152 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use rand::{Rng, RngCore};
+
+
+/// Source of the randomness the generator draws itself, e.g. null rates, dictionary and reference sampling.
+pub trait RandomSource: Send + Sync + fmt::Debug {
+    fn next_u64(&self) -> u64;
+
+    /// Uniform in `0.0..1.0`.
+    fn next_f64(&self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `0..bound`, `bound` must not be 0.
+    fn below(&self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+
+    fn chance(&self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreadRandom;
+
+impl RandomSource for ThreadRandom {
+    fn next_u64(&self) -> u64 {
+        rand::thread_rng().gen()
+    }
+}
+
+
+/// Any `rand` generator, e.g. a ChaCha or OS backed one, shared between threads behind a mutex.
+impl<R: RngCore + Send + fmt::Debug> RandomSource for Mutex<R> {
+    fn next_u64(&self) -> u64 {
+        self.lock().unwrap_or_else(|x| x.into_inner()).next_u64()
+    }
+}
+
+
+/// Splitmix64 of `stream` under `seed`, used to derive independent seeds for tables and rows.
+pub fn mix(seed: u64, stream: u64) -> u64 {
+    let mut z = seed.wrapping_add(stream.wrapping_add(1).wrapping_mul(0x9E3779B97F4A7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+
+/// Counter based splitmix64 stream, draws are reproducible for a seed as long as they happen in the same order.
+#[derive(Debug)]
+pub struct SeededRandom {
+    seed: u64,
+    counter: AtomicU64,
+}
+
+impl SeededRandom {
+    pub fn new(seed: u64) -> Self {
+        SeededRandom { seed, counter: AtomicU64::new(0) }
+    }
+}
+
+impl RandomSource for SeededRandom {
+    fn next_u64(&self) -> u64 {
+        mix(self.seed, self.counter.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+
+/// Records every draw of another source so a run can be replayed with `TapeRandom`.
+#[derive(Debug)]
+pub struct RecordingRandom<R: RandomSource> {
+    source: R,
+    tape: Mutex<Vec<u64>>,
+}
+
+impl<R: RandomSource> RecordingRandom<R> {
+    pub fn new(source: R) -> Self {
+        RecordingRandom { source, tape: Mutex::new(vec![]) }
+    }
+
+    pub fn tape(&self) -> Vec<u64> {
+        self.tape.lock().unwrap_or_else(|x| x.into_inner()).clone()
+    }
+}
+
+impl<R: RandomSource> RandomSource for RecordingRandom<R> {
+    fn next_u64(&self) -> u64 {
+        let mut tape = self.tape.lock().unwrap_or_else(|x| x.into_inner());
+        let value = self.source.next_u64();
+        tape.push(value);
+        value
+    }
+}
+
+
+/// Replays recorded draws in order, starting over once the tape runs out.
+#[derive(Debug)]
+pub struct TapeRandom {
+    tape: Vec<u64>,
+    position: AtomicUsize,
+}
+
+impl TapeRandom {
+    pub fn new(tape: Vec<u64>) -> Self {
+        TapeRandom { tape, position: AtomicUsize::new(0) }
+    }
+}
+
+impl RandomSource for TapeRandom {
+    fn next_u64(&self) -> u64 {
+        if self.tape.is_empty() {
+            return 0;
+        }
+        self.tape[self.position.fetch_add(1, Ordering::Relaxed) % self.tape.len()]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    #[test]
+    fn seeded_random_test() {
+        let a = SeededRandom::new(7);
+        let b = SeededRandom::new(7);
+
+        assert_eq!((0..4).map(|_| a.next_u64()).collect::<Vec<u64>>(), (0..4).map(|_| b.next_u64()).collect::<Vec<u64>>());
+        assert!((0..100).all(|_| a.below(3) < 3));
+        assert!((0..100).all(|_| (0.0..1.0).contains(&a.next_f64())));
+    }
+
+    #[test]
+    fn tape_random_test() {
+        let recording = RecordingRandom::new(Mutex::new(StdRng::seed_from_u64(1)));
+        let drawn: Vec<u64> = (0..3).map(|_| recording.next_u64()).collect();
+
+        let tape = TapeRandom::new(recording.tape());
+
+        assert_eq!((0..3).map(|_| tape.next_u64()).collect::<Vec<u64>>(), drawn);
+        assert_eq!(tape.next_u64(), drawn[0]);
+    }
+}
@@ -0,0 +1,236 @@
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::{Column, ExportFile, Table};
+use crate::generators::Generator;
+
+
+#[derive(Error, Debug)]
+pub enum RelationshipError {
+    #[error("Column {column} of table {table} references unknown column {referenced_table}.{referenced_column}.")]
+    UnknownReference { table: String, column: String, referenced_table: String, referenced_column: String },
+    #[error("Table {table} is part of a reference cycle.")]
+    ReferenceCycle { table: String },
+    #[error("Column {column} of table {table} references table {referenced_table} which has no rows in file {file_index}.")]
+    EmptyReference { table: String, column: String, referenced_table: String, file_index: u64 },
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub table: String,
+    pub column: String,
+}
+
+
+impl Column {
+    /// Samples values from the keys generated for `table.column` when generated as part of an export,
+    /// the column's own generator is only used when its table is generated on its own.
+    pub fn references(mut self, table: impl Into<String>, column: impl Into<String>) -> Self {
+        self.reference = Some(Reference { table: table.into(), column: column.into() });
+        self
+    }
+
+    pub fn reference(&self) -> Option<&Reference> {
+        self.reference.as_ref()
+    }
+}
+
+
+impl ExportFile {
+    // Returns (table index, column position) of the referenced column.
+    fn resolve_reference(&self, table: &str, column: &Column, reference: &Reference) -> Result<(usize, usize)> {
+        self.tables.iter()
+            .position(|x| x.id_value == reference.table)
+            .and_then(|x| {
+                self.tables[x].columns.iter()
+                    .position(|y| y.name == reference.column)
+                    .map(|y| (x, y))
+            })
+            .ok_or_else(|| Error::from(RelationshipError::UnknownReference {
+                table: table.into(),
+                column: column.name.clone(),
+                referenced_table: reference.table.clone(),
+                referenced_column: reference.column.clone(),
+            }))
+    }
+
+
+    /// Table indices ordered so every table comes after the tables it references.
+    pub fn generation_order(&self) -> Result<Vec<usize>> {
+        let mut parents: Vec<Vec<usize>> = vec![vec![]; self.tables.len()];
+
+        for (index, table) in self.tables.iter().enumerate() {
+            for column in table.columns.iter() {
+                if let Some(reference) = &column.reference {
+                    parents[index].push(self.resolve_reference(&table.id_value, column, reference)?.0);
+                }
+            }
+        }
+
+        let mut order: Vec<usize> = vec![];
+        let mut done = vec![false; self.tables.len()];
+
+        while order.len() < self.tables.len() {
+            let ready: Vec<usize> = (0..self.tables.len())
+                .filter(|x| !done[*x] && parents[*x].iter().all(|y| done[*y] && y != x))
+                .collect();
+
+            if ready.is_empty() {
+                let table = (0..self.tables.len()).find(|x| !done[*x]).unwrap_or(0);
+                return Err(Error::from(RelationshipError::ReferenceCycle {
+                    table: self.tables[table].id_value.clone(),
+                }));
+            }
+
+            for x in ready {
+                done[x] = true;
+                order.push(x);
+            }
+        }
+
+        Ok(order)
+    }
+
+
+    // Copy of the table whose referencing columns sample from the rows already generated for their parents.
+    pub(crate) fn with_reference_pools(&self, table: &Table, rows: &[Option<Vec<Vec<String>>>], file_index: u64) -> Result<Table> {
+        let mut table = table.clone();
+        let id_value = table.id_value.clone();
+
+        for column in table.columns.iter_mut() {
+            let reference = match &column.reference {
+                Some(x) => x.clone(),
+                None => continue,
+            };
+            let (parent, position) = self.resolve_reference(&id_value, column, &reference)?;
+
+            // A parent pinned to other files is not in this one, its keys come from the files it is pinned to.
+            let pinned = match self.table_files(&self.tables[parent], file_index).2 {
+                true => None,
+                false => Some(self.pinned_rows(parent)?),
+            };
+            // Truncated rows of a corrupted parent may not have the key, they are left out of the pool.
+            let pool: Arc<Vec<String>> = Arc::new(
+                pinned.as_ref().or(rows[parent].as_ref()).iter()
+                    .flat_map(|x| x.iter())
+                    .filter_map(|x| x.get(position + 1).cloned())
+                    .collect()
+            );
+
+            if pool.is_empty() {
+                return Err(Error::from(RelationshipError::EmptyReference {
+                    table: id_value,
+                    column: column.name.clone(),
+                    referenced_table: reference.table,
+                    file_index,
+                }));
+            }
+
+            column.generator = Generator::new(move |context| {
+                Ok(pool[context.random.below(pool.len() as u64) as usize].clone())
+            });
+        }
+
+        Ok(table)
+    }
+
+
+    /// Rows of every table for one file, parents are generated before the tables referencing them.
+    pub fn generate_file_rows(&self, file_index: u64) -> Result<Vec<Vec<Vec<String>>>> {
+        Ok(self.generate_tables(file_index, &vec![true; self.tables.len()])?.into_iter().flatten().collect())
+    }
+
+
+    // Rows of the `wanted` tables for one file, `None` for the others.
+    pub(crate) fn generate_tables(&self, file_index: u64, wanted: &[bool]) -> Result<Vec<Option<Vec<Vec<String>>>>> {
+        let mut rows: Vec<Option<Vec<Vec<String>>>> = vec![None; self.tables.len()];
+
+        for index in self.generation_order()?.into_iter().filter(|x| wanted[*x]) {
+            let table = self.with_reference_pools(&self.tables[index], &rows, file_index)?;
+            rows[index] = Some(self.generate_table_rows(index, &table, file_index)?);
+        }
+
+        Ok(rows)
+    }
+
+
+    // Rows of table `index` in a file, `table` being the table with the reference pools of the file.
+    pub(crate) fn generate_table_rows(&self, index: usize, table: &Table, file_index: u64) -> Result<Vec<Vec<String>>> {
+        let generated = match table.unique.is_empty() {
+            true => table.generate_rows_vec_from(
+                self.table_row_offset(table, file_index)?,
+                self.table_row_count(table, file_index)?,
+            ),
+            false => self.generate_unique_rows(index, table, file_index)
+                .and_then(|x| table.corrupt_rows(self.table_row_offset(table, file_index)?, x)),
+        };
+
+        generated.inspect_err(|_| self.count_error(&table.id_value))
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::generators;
+    use crate::types::ColumnType;
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    fn table(id: &str, columns: Vec<Column>) -> Table {
+        Table::new(id.into(), columns, "|".into(), Decimal::from_str("0.5").unwrap())
+    }
+
+    #[test]
+    fn references_test() {
+        let orders = table(
+            "B",
+            vec![Column::new("customer_id".into(), 20, ColumnType::Varchar(20), simple_generator).references("A", "id")],
+        );
+        let customers = table(
+            "A",
+            vec![Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).as_key()],
+        );
+
+        let ef = ExportFile::new(vec![orders.clone(), customers.clone()], 1000, 1).unwrap();
+        assert_eq!(ef.generation_order().unwrap(), vec![1, 0]);
+
+        let rows = ef.generate_file_rows(0).unwrap();
+        let keys: HashSet<&String> = rows[1].iter().map(|x| &x[1]).collect();
+
+        assert!(!rows[0].is_empty());
+        assert!(rows[0].iter().all(|x| keys.contains(&x[1])));
+
+        let unknown = table(
+            "B",
+            vec![Column::new("customer_id".into(), 4, ColumnType::Int, simple_generator).references("A", "missing")],
+        );
+        assert!(ExportFile::new(vec![unknown, customers], 1000, 1).is_err());
+
+        let cycle = table(
+            "A",
+            vec![
+                Column::new("id".into(), 4, ColumnType::Int, simple_generator),
+                Column::new("order_id".into(), 4, ColumnType::Int, simple_generator).references("B", "customer_id"),
+            ],
+        );
+        match ExportFile::new(vec![orders, cycle], 1000, 1) {
+            Err(x) => assert!(matches!(
+                x.downcast_ref::<RelationshipError>(),
+                Some(RelationshipError::ReferenceCycle { .. })
+            )),
+            Ok(_) => panic!("reference cycles must be rejected"),
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+
+use crate::ExportFile;
+use crate::control::utc_timestamp;
+use crate::metadata::FileMetadata;
+
+pub const REPORT_FILE: &str = "report.json";
+
+pub(crate) fn report_name(suffix: &str) -> String {
+    format!("report{}.json", suffix)
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableReport {
+    pub table: String,
+    pub rows: u64,
+    /// Bytes of the table's rows before compression.
+    pub bytes: u64,
+    /// Errors of the table's generators, any of them stops generation.
+    pub errors: u64,
+}
+
+
+/// What a run generated and how fast, so a benchmark run documents itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerationReport {
+    pub tables: Vec<TableReport>,
+    /// Files written by the run, files a resumed run kept are not in it.
+    pub files: Vec<FileMetadata>,
+    /// Wall time of the run.
+    pub duration: Duration,
+    /// When the run finished.
+    pub generated_at: SystemTime,
+}
+
+impl GenerationReport {
+    pub fn rows(&self) -> u64 {
+        self.tables.iter().map(|x| x.rows).sum()
+    }
+
+    /// Bytes written to disk.
+    pub fn bytes(&self) -> u64 {
+        self.files.iter().map(|x| x.bytes).sum()
+    }
+
+    pub fn uncompressed_bytes(&self) -> u64 {
+        self.tables.iter().map(|x| x.bytes).sum()
+    }
+
+    pub fn errors(&self) -> u64 {
+        self.tables.iter().map(|x| x.errors).sum()
+    }
+
+    /// Uncompressed MB generated per second of wall time.
+    pub fn throughput_mb_per_second(&self) -> f64 {
+        let seconds = self.duration.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        self.uncompressed_bytes() as f64 / (1 << 20) as f64 / seconds
+    }
+
+    pub fn to_json(&self) -> Value {
+        let tables: Map<String, Value> = self.tables.iter()
+            .map(|x| (x.table.clone(), json!({"rows": x.rows, "bytes": x.bytes, "errors": x.errors})))
+            .collect();
+        let files: Vec<Value> = self.files.iter()
+            .map(|x| {
+                json!({
+                    "name": x.path.file_name().map(|y| y.to_string_lossy().to_string()),
+                    "file_index": x.file_index,
+                    "bytes": x.bytes,
+                    "seconds": x.duration.as_secs_f64(),
+                })
+            })
+            .collect();
+
+        json!({
+            "generated_at": utc_timestamp(self.generated_at),
+            "seconds": self.duration.as_secs_f64(),
+            "rows": self.rows(),
+            "bytes": self.bytes(),
+            "uncompressed_bytes": self.uncompressed_bytes(),
+            "throughput_mb_per_second": self.throughput_mb_per_second(),
+            "errors": self.errors(),
+            "tables": tables,
+            "files": files,
+        })
+    }
+}
+
+
+impl ExportFile {
+    /// Also writes a report with rows per table, bytes per file, wall time, throughput and generator errors next to
+    /// the files, a run that fails writes it too.
+    pub fn with_report(mut self, report: bool) -> Self {
+        self.report = report;
+        self
+    }
+
+
+    pub(crate) fn count_error(&self, table: &str) {
+        *self.generator_errors.lock().unwrap_or_else(|x| x.into_inner()).entry(table.into()).or_default() += 1;
+    }
+
+
+    pub(crate) fn reset_errors(&self) {
+        self.generator_errors.lock().unwrap_or_else(|x| x.into_inner()).clear();
+    }
+
+
+    pub(crate) fn generation_report(&self, mut files: Vec<FileMetadata>, duration: Duration) -> GenerationReport {
+        files.sort_by_key(|x| x.file_index);
+        let errors = self.generator_errors.lock().unwrap_or_else(|x| x.into_inner()).clone();
+
+        let mut totals: HashMap<&str, (u64, u64)> = HashMap::new();
+        for table in files.iter().flat_map(|x| x.tables.iter()) {
+            let total = totals.entry(table.table.as_str()).or_default();
+            total.0 += table.rows;
+            total.1 += table.bytes;
+        }
+        let tables = self.tables.iter()
+            .map(|x| {
+                let (rows, bytes) = totals.get(x.id_value.as_str()).copied().unwrap_or_default();
+                let errors = errors.get(&x.id_value).copied().unwrap_or_default();
+                TableReport { table: x.id_value.clone(), rows, bytes, errors }
+            })
+            .collect();
+
+        GenerationReport { tables, files, duration, generated_at: SystemTime::now() }
+    }
+
+
+    pub(crate) fn write_report(&self, folder_path: &Path, name: &str, report: &GenerationReport) -> Result<()> {
+        Ok(fs::write(folder_path.join(name), serde_json::to_string_pretty(&report.to_json())?)?)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Error;
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column, Table};
+    use crate::generators::Generator;
+    use crate::types::ColumnType;
+
+    fn table(id: &str, generator: Generator) -> Table {
+        Table::new(
+            id.into(),
+            vec![Column::new("id".into(), 4, ColumnType::Int, generator)],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        )
+    }
+
+    #[test]
+    fn report_test() {
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        let ef = ExportFile::new(vec![table("A", generators::sequence(1, 1)), table("B", generators::sequence(1, 1))],
+                                 1_000, 2).unwrap()
+            .with_report(true);
+        let report = ef.generate_all_files_with_report(&folder).unwrap();
+        let json: Value = serde_json::from_str(&fs::read_to_string(folder.join(REPORT_FILE)).unwrap()).unwrap();
+
+        assert_eq!(report.files.len(), 2);
+        let rows: u64 = (0..2).flat_map(|x| ef.generate_file_rows(x).unwrap()).map(|x| x.len() as u64).sum();
+        assert_eq!(report.rows(), rows);
+        assert_eq!(report.uncompressed_bytes(), report.bytes());
+        assert_eq!(json["rows"], json!(report.rows()));
+        assert_eq!(json["tables"]["B"]["errors"], json!(0));
+
+        // A failing generator stops the run, its report still counts the error.
+        let failing = Generator::new(|_| Err(Error::msg("broken")));
+        let ef = ExportFile::new(vec![table("A", generators::sequence(1, 1)), table("B", failing)], 1_000, 2).unwrap()
+            .with_report(true);
+        assert!(ef.generate_all_files(&folder).is_err());
+        let json: Value = serde_json::from_str(&fs::read_to_string(folder.join(REPORT_FILE)).unwrap()).unwrap();
+        fs::remove_dir_all(&folder).unwrap();
+
+        assert!(json["tables"]["B"]["errors"].as_u64().unwrap() >= 1);
+        assert_eq!(json["tables"]["A"]["errors"], json!(0));
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+use thiserror::Error;
+
+use crate::{generators, Column, ExportFile, SizeSpec, Table};
+use crate::generators::retail::{self as shop, Basket, PAYMENT_METHODS};
+use crate::types::{ColumnSize, ColumnType};
+
+pub const PRESETS: [&str; 5] = ["customers", "products", "orders", "order_lines", "payments"];
+/// Products of the catalogue order lines are drawn from, the `products` table needs this many rows.
+pub const PRODUCTS: u64 = 100;
+pub const LINES_PER_ORDER: u64 = 3;
+
+
+#[derive(Error, Debug)]
+pub enum OrderError {
+    #[error("Unknown preset {preset}, expected customers, products, orders, order_lines or payments.")]
+    UnknownPreset { preset: String },
+    #[error("Table {table} has no column {column} to check orders with.")]
+    MissingColumn { table: String, column: String },
+    #[error("Amount {value} of table {table} is not a number.")]
+    InvalidAmount { table: String, value: String },
+    #[error("Order {order} has {what} {actual}, expected {expected}.")]
+    Mismatch { order: String, what: String, expected: Decimal, actual: Decimal },
+    #[error("Value {value} of {table}.{column} is not the id of a row it references.")]
+    DanglingKey { table: String, column: String, value: String },
+}
+
+
+fn key() -> Column {
+    Column::new("id".into(), ColumnSize::range(1, 10), ColumnType::Int, generators::sequence(1, 1)).as_key()
+}
+
+
+fn one_of(values: &[&str]) -> generators::Generator {
+    generators::one_of(values.iter().map(|x| x.to_string()).collect())
+}
+
+
+/// Columns of a preset. Orders reference customers, lines and payments hold the ids of their orders and products.
+pub fn preset_columns(preset: &str) -> Result<Vec<Column>> {
+    let basket = Basket::new(PRODUCTS, LINES_PER_ORDER)?;
+    let id = |name: &str, generator| Column::new(name.into(), ColumnSize::range(1, 10), ColumnType::Int, generator);
+    let amount = |name: &str, generator| {
+        Column::new(name.into(), ColumnSize::range(4, 7), ColumnType::Decimal(12, 2), generator)
+    };
+
+    Ok(match preset {
+        "customers" => vec![
+            key(),
+            Column::new("email".into(), ColumnSize::range(22, 30), ColumnType::Varchar(40), shop::customer_email()),
+            Column::new("city".into(), ColumnSize::range(5, 8), ColumnType::Varchar(20),
+                        one_of(&["Zagreb", "Split", "Rijeka", "Osijek", "Zadar"])),
+            Column::new("signed_up".into(), 10, ColumnType::Date, generators::random_date("2018-01-01", "2024-12-31")),
+        ],
+        "products" => vec![
+            key(),
+            Column::new("sku".into(), 10, ColumnType::Char(10), shop::product_sku()),
+            Column::new("name".into(), ColumnSize::range(8, 16), ColumnType::Varchar(30), shop::product_name()),
+            Column::new("category".into(), ColumnSize::range(4, 11), ColumnType::Varchar(11), shop::product_category()),
+            amount("price", basket.product_price()),
+        ],
+        "orders" => vec![
+            key(),
+            id("customer_id", generators::random_int(1, 1000)).references("customers", "id"),
+            Column::new("ordered_at".into(), 19, ColumnType::Timestamp,
+                        generators::random_timestamp("2025-01-01 00:00:00", "2025-12-31 23:59:59")),
+            Column::new("status".into(), ColumnSize::range(6, 9), ColumnType::Varchar(9),
+                        one_of(&["placed", "shipped", "delivered", "returned"])),
+            amount("total", basket.order_total_amount()),
+        ],
+        "order_lines" => vec![
+            key(),
+            id("order_id", basket.line_order_id()),
+            id("product_id", basket.line_product_id()),
+            Column::new("quantity".into(), 1, ColumnType::Int, basket.line_quantity()),
+            amount("unit_price", basket.line_unit_price()),
+            amount("amount", basket.line_amount()),
+        ],
+        "payments" => vec![
+            key(),
+            id("order_id", basket.payment_order_id()),
+            Column::new("method".into(), ColumnSize::range(4, 13), ColumnType::Varchar(13), one_of(&PAYMENT_METHODS)),
+            amount("amount", basket.payment_amount()),
+        ],
+        _ => return Err(Error::from(OrderError::UnknownPreset { preset: preset.into() })),
+    })
+}
+
+
+/// Every preset table, a tenth customers and a fifth orders with the rest following from them.
+pub fn tables() -> Result<Vec<Table>> {
+    PRESETS.iter()
+        .zip(["0.1", "0.05", "0.2", "0.45", "0.2"])
+        .map(|(preset, percent_size)| {
+            Ok(Table::new(preset.to_string(), preset_columns(preset)?, "|".into(), Decimal::from_str(percent_size)?))
+        })
+        .collect()
+}
+
+
+/// The linked retail export, the catalogue once, `LINES_PER_ORDER` lines and a payment for every order.
+pub fn export_file(file_size_bytes: u64, number_of_files: u64) -> Result<ExportFile> {
+    ExportFile::new(tables()?, file_size_bytes, number_of_files)?
+        .with_size_spec(SizeSpec::Rows(HashMap::from([("products".to_string(), PRODUCTS)])))?
+        .with_row_ratio("order_lines", "orders", LINES_PER_ORDER as f64)?
+        .with_row_ratio("payments", "orders", 1.0)
+}
+
+
+/// Checks the rows of every file of a retail export together, `rows[i]` are all rows of `tables[i]`. Keys of orders,
+/// lines and payments have to be the ids of rows they reference, lines cost their quantity times the product price
+/// and the lines and payments of an order add up to its total. Tables that are not in the export are skipped.
+pub fn check_orders(tables: &[Table], rows: &[Vec<Vec<String>>]) -> Result<()> {
+    let table = |id: &str| tables.iter().position(|x| x.id_value == id);
+    let position = |index: usize, name: &str| {
+        tables[index].columns.iter()
+            .position(|x| x.name == name)
+            .map(|x| x + 1)
+            .ok_or_else(|| {
+                Error::from(OrderError::MissingColumn { table: tables[index].id_value.clone(), column: name.into() })
+            })
+    };
+    let decimal = |index: usize, value: &str| {
+        Decimal::from_str(value).map_err(|_| {
+            Error::from(OrderError::InvalidAmount { table: tables[index].id_value.clone(), value: value.into() })
+        })
+    };
+    let dangling = |index: usize, column: &str, value: &str| {
+        let table = tables[index].id_value.clone();
+        Error::from(OrderError::DanglingKey { table, column: column.into(), value: value.into() })
+    };
+
+    let mut customers: Option<HashSet<&str>> = None;
+    if let Some(index) = table("customers") {
+        let id = position(index, "id")?;
+        customers = Some(rows[index].iter().map(|x| x[id].as_str()).collect());
+    }
+    let mut prices: Option<HashMap<&str, Decimal>> = None;
+    if let Some(index) = table("products") {
+        let (id, price) = (position(index, "id")?, position(index, "price")?);
+        prices = Some(rows[index].iter()
+            .map(|x| Ok((x[id].as_str(), decimal(index, &x[price])?)))
+            .collect::<Result<_>>()?);
+    }
+
+    let mut totals: HashMap<&str, Decimal> = HashMap::new();
+    if let Some(index) = table("orders") {
+        let (id, customer) = (position(index, "id")?, position(index, "customer_id")?);
+        let total = position(index, "total")?;
+        for row in rows[index].iter() {
+            if customers.as_ref().is_some_and(|x| !x.contains(row[customer].as_str())) {
+                return Err(dangling(index, "customer_id", &row[customer]));
+            }
+            totals.insert(row[id].as_str(), decimal(index, &row[total])?);
+        }
+    }
+
+    // Sums of the lines or payments of every order, compared to the order totals.
+    let compare = |index: usize, what: &str, amounts: HashMap<&str, Decimal>| -> Result<()> {
+        if table("orders").is_none() {
+            return Ok(());
+        }
+        for (order, total) in totals.iter() {
+            let amount = amounts.get(order).copied().unwrap_or_default();
+            if amount != *total {
+                return Err(Error::from(OrderError::Mismatch {
+                    order: order.to_string(), what: what.into(), expected: *total, actual: amount,
+                }));
+            }
+        }
+        match amounts.keys().find(|x| !totals.contains_key(*x)) {
+            Some(order) => Err(dangling(index, "order_id", order)),
+            None => Ok(()),
+        }
+    };
+
+    if let Some(index) = table("order_lines") {
+        let (order, product) = (position(index, "order_id")?, position(index, "product_id")?);
+        let (quantity, unit_price, amount) =
+            (position(index, "quantity")?, position(index, "unit_price")?, position(index, "amount")?);
+        let mut sums: HashMap<&str, Decimal> = HashMap::new();
+
+        for row in rows[index].iter() {
+            let (unit, line) = (decimal(index, &row[unit_price])?, decimal(index, &row[amount])?);
+            match prices.as_ref().map(|x| x.get(row[product].as_str())) {
+                Some(None) => return Err(dangling(index, "product_id", &row[product])),
+                Some(Some(price)) if *price != unit => {
+                    return Err(Error::from(OrderError::Mismatch {
+                        order: row[order].clone(), what: "a line priced".into(), expected: *price, actual: unit,
+                    }));
+                }
+                _ => {}
+            }
+            let expected = decimal(index, &row[quantity])? * unit;
+            if expected != line {
+                return Err(Error::from(OrderError::Mismatch {
+                    order: row[order].clone(), what: "a line amount of".into(), expected, actual: line,
+                }));
+            }
+            *sums.entry(row[order].as_str()).or_default() += line;
+        }
+        compare(index, "lines adding up to", sums)?;
+    }
+
+    if let Some(index) = table("payments") {
+        let (order, amount) = (position(index, "order_id")?, position(index, "amount")?);
+        let mut sums: HashMap<&str, Decimal> = HashMap::new();
+        for row in rows[index].iter() {
+            *sums.entry(row[order].as_str()).or_default() += decimal(index, &row[amount])?;
+        }
+        compare(index, "payments adding up to", sums)?;
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retail_preset_test() {
+        let ef = export_file(40_000, 2).unwrap();
+        let files = (0..2).map(|x| ef.generate_file_rows(x).unwrap()).collect::<Vec<_>>();
+        let rows: Vec<Vec<Vec<String>>> = (0..PRESETS.len())
+            .map(|x| files.iter().flat_map(|y| y[x].clone()).collect())
+            .collect();
+
+        assert_eq!(rows[1].len() as u64, PRODUCTS);
+        assert_eq!(rows[3].len(), rows[2].len() * LINES_PER_ORDER as usize);
+        assert_eq!(rows[4].len(), rows[2].len());
+        check_orders(&ef.tables, &rows).unwrap();
+
+        let mut unpaid = rows.clone();
+        unpaid[4][0][4] = "0.00".into();
+        assert!(check_orders(&ef.tables, &unpaid).is_err());
+        let mut dangling = rows.clone();
+        dangling[3][0][3] = "1000".into();
+        assert!(check_orders(&ef.tables, &dangling).is_err());
+        assert!(preset_columns("invoices").is_err());
+    }
+}
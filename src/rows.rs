@@ -0,0 +1,157 @@
+use anyhow::{Error, Result};
+use rayon::prelude::*;
+use thiserror::Error;
+
+use crate::{row_range_end, Table};
+use crate::unique::UniqueSeen;
+
+
+#[derive(Error, Debug)]
+pub enum RowsError {
+    #[error("Table {table} regenerates duplicates of unique values, which needs its rows in order, use rows instead of \
+             par_rows.")]
+    UniqueNotParallel { table: String },
+    #[error("Table {table} duplicates keys of previous rows, which needs its rows in order, use rows instead of par_rows.")]
+    DuplicateKeysNotParallel { table: String },
+}
+
+
+/// Rows of a table generated one at a time as they are pulled.
+pub struct Rows<'a> {
+    table: &'a Table,
+    first: u64,
+    next: u64,
+    end: u64,
+    seen: UniqueSeen<'a>,
+    previous: Option<Vec<String>>,
+    error: Option<Error>,
+}
+
+impl Iterator for Rows<'_> {
+    type Item = Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            self.next = self.end;
+            return Some(Err(error));
+        }
+        if self.next >= self.end {
+            return None;
+        }
+
+        let row_index = self.next;
+        self.next += 1;
+
+        let row = self.table.generate_values_at(row_index)
+            .and_then(|mut x| {
+                self.table.enforce_unique_row(row_index, &mut x, &mut self.seen)?;
+                if let Some(previous) = &self.previous {
+                    self.table.duplicate_key(self.first, row_index, &mut x, previous);
+                }
+                if self.table.corruption.duplicate_keys > 0.0 {
+                    self.previous = Some(x.clone());
+                }
+                self.table.corrupt_row(self.first, row_index, &mut x)?;
+                Ok(x)
+            });
+        if row.is_err() {
+            self.next = self.end;
+        }
+
+        Some(row)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.end - self.next) as usize + usize::from(self.error.is_some());
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Rows<'_> {}
+
+
+impl Table {
+    /// The rows of `generate_table_vec` generated lazily, only the unique constraints keep state between rows.
+    pub fn rows(&self, file_size_bytes: u64) -> Rows<'_> {
+        match self.row_count(file_size_bytes) {
+            Ok(row_count) => self.rows_from(0, row_count),
+            Err(error) => Rows { error: Some(error), ..self.rows_from(0, 0) },
+        }
+    }
+
+
+    pub fn rows_from(&self, first_row_index: u64, row_count: u64) -> Rows<'_> {
+        let (end, error) = match row_range_end(first_row_index, row_count) {
+            Ok(end) => (end, self.check_value_space(row_count).err()),
+            Err(error) => (first_row_index, Some(error)),
+        };
+
+        Rows {
+            table: self,
+            first: first_row_index,
+            next: first_row_index,
+            end,
+            seen: self.unique_seen(),
+            previous: None,
+            error,
+        }
+    }
+
+
+    /// Rows generated in parallel as they are pulled, `collect` keeps them in row order.
+    pub fn par_rows(&self, file_size_bytes: u64) -> Result<impl ParallelIterator<Item = Result<Vec<String>>> + '_> {
+        if self.has_regenerated_unique() {
+            return Err(Error::from(RowsError::UniqueNotParallel { table: self.id_value.clone() }));
+        }
+        if self.corruption.duplicate_keys > 0.0 {
+            return Err(Error::from(RowsError::DuplicateKeysNotParallel { table: self.id_value.clone() }));
+        }
+
+        let row_count = self.row_count(file_size_bytes)?;
+        self.check_value_space(row_count)?;
+
+        Ok((0..row_count)
+            .into_par_iter()
+            .map(|x| self.generate_table_row_vec_at(x)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column};
+    use crate::types::ColumnType;
+
+    #[test]
+    fn rows_test() {
+        let table = Table::new(
+            "A".into(),
+            vec![Column::new("day".into(), 2, ColumnType::Int, generators::random_int(1, 60))],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        )
+            .with_seed(3);
+
+        let rows: Vec<Vec<String>> = table.rows(100).collect::<Result<_>>().unwrap();
+        assert_eq!(table.rows(100).len() as u64, table.row_count(100).unwrap());
+        assert_eq!(rows, table.generate_table_vec(100).unwrap());
+        assert_eq!(rows, table.par_rows(100).unwrap().collect::<Result<Vec<Vec<String>>>>().unwrap());
+        assert_eq!(table.rows(100).take(3).count(), 3);
+
+        let table = table.with_unique(&["day"]).unwrap();
+        let rows: Vec<Vec<String>> = table.rows(100).collect::<Result<_>>().unwrap();
+        assert_eq!(rows, table.generate_table_vec(100).unwrap());
+        assert_eq!(rows, table.par_rows(100).unwrap().collect::<Result<Vec<Vec<String>>>>().unwrap());
+
+        let table = Table::new(
+            "A".into(),
+            vec![Column::new("rank".into(), 2, ColumnType::Int, generators::zipf(60, 1.0)).unique(true)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+        assert!(table.par_rows(100).is_err());
+    }
+}
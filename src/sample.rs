@@ -0,0 +1,227 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use anyhow::{Error, Result};
+use rayon::prelude::*;
+use rust_decimal::prelude::*;
+use thiserror::Error;
+
+use crate::{ExportFile, ExportFileError};
+use crate::random::{RandomSource, SeededRandom};
+use crate::types::ColumnType;
+use crate::values::{parse_bool, parse_date, parse_timestamp_micros};
+
+
+#[derive(Error, Debug)]
+pub enum SampleError {
+    #[error("Sampling needs a seeded export, otherwise regenerated rows differ from the exported ones.")]
+    Unseeded,
+    #[error("Can not sample {requested} rows of table {table}, the export has only {available}.")]
+    NotEnoughRows { table: String, requested: u64, available: u64 },
+}
+
+
+impl ExportFile {
+    /// Exactly `n` rows of `table` as they appear in the full export, chosen by `seed` and in export order.
+    /// Only the files holding the chosen rows are regenerated, single rows when the table depends on no other rows.
+    pub fn sample_rows(&self, table: &str, n: u64, seed: u64) -> Result<Vec<Vec<String>>> {
+        let table_index = self.tables.iter()
+            .position(|x| x.id_value == table)
+            .ok_or_else(|| Error::from(ExportFileError::UnknownTable { table: table.into() }))?;
+        if self.tables.iter().any(|x| x.seed.is_none()) {
+            return Err(Error::from(SampleError::Unseeded));
+        }
+
+        let counts = (0..self.number_of_files)
+            .map(|x| self.table_row_count(&self.tables[table_index], x))
+            .collect::<Result<Vec<u64>>>()?;
+        let total = self.table_total_rows(&self.tables[table_index])?;
+        if n > total {
+            return Err(Error::from(SampleError::NotEnoughRows { table: table.into(), requested: n, available: total }));
+        }
+
+        // Floyd's algorithm draws n distinct row indices without materializing all of them.
+        let random = SeededRandom::new(seed);
+        let mut chosen: BTreeSet<u64> = BTreeSet::new();
+        for upper in total - n..total {
+            let candidate = random.below(upper + 1);
+            if !chosen.insert(candidate) {
+                chosen.insert(upper);
+            }
+        }
+
+        let target = &self.tables[table_index];
+        if target.columns.iter().all(|x| x.reference().is_none()) && target.unique.is_empty() {
+            return chosen.into_par_iter()
+                .map(|x| target.generate_table_row_vec_at(x))
+                .collect();
+        }
+
+        // Row indices are global, files hold consecutive ranges of them.
+        let mut by_file: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+        let mut file_index = 0;
+        let mut file_start = 0;
+        for row_index in chosen {
+            while row_index >= file_start + counts[file_index as usize] {
+                file_start += counts[file_index as usize];
+                file_index += 1;
+            }
+            by_file.entry(file_index).or_default().push(row_index - file_start);
+        }
+
+        let rows = by_file.into_par_iter()
+            .map(|(file_index, offsets)| {
+                let mut rows = self.generate_file_rows(file_index)?.swap_remove(table_index);
+                Ok(offsets.into_iter().map(|x| std::mem::take(&mut rows[x as usize])).collect::<Vec<Vec<String>>>())
+            })
+            .collect::<Result<Vec<Vec<Vec<String>>>>>()?;
+
+        Ok(rows.into_iter().flatten().collect())
+    }
+}
+
+
+/// A generated value read as its column type, values that do not parse as it, e.g. corrupted ones, stay text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SampleValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Decimal(Decimal),
+    Date(String),
+    Timestamp(String),
+    Text(String),
+}
+
+impl SampleValue {
+    fn parse(value: &str, column_type: &ColumnType) -> SampleValue {
+        let typed = match column_type {
+            ColumnType::Boolean => parse_bool(value).map(SampleValue::Bool),
+            ColumnType::SmallInt | ColumnType::Int | ColumnType::BigInt => value.parse().ok().map(SampleValue::Int),
+            ColumnType::Real | ColumnType::Double => value.parse().ok().map(SampleValue::Float),
+            ColumnType::Decimal(..) => Decimal::from_str(value).ok().map(SampleValue::Decimal),
+            ColumnType::Date => parse_date(value).map(|_| SampleValue::Date(value.into())),
+            ColumnType::Timestamp => parse_timestamp_micros(value).map(|_| SampleValue::Timestamp(value.into())),
+            ColumnType::Char(_) | ColumnType::Varchar(_) | ColumnType::Text | ColumnType::Custom(_) => None,
+        };
+        typed.unwrap_or_else(|| SampleValue::Text(value.into()))
+    }
+}
+
+impl fmt::Display for SampleValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SampleValue::Null => write!(f, "NULL"),
+            SampleValue::Bool(x) => write!(f, "{}", x),
+            SampleValue::Int(x) => write!(f, "{}", x),
+            SampleValue::Float(x) => write!(f, "{}", x),
+            SampleValue::Decimal(x) => write!(f, "{}", x),
+            SampleValue::Date(x) | SampleValue::Timestamp(x) | SampleValue::Text(x) => write!(f, "{}", x),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSample {
+    pub table: String,
+    pub columns: Vec<(String, ColumnType)>,
+    pub rows: Vec<Vec<SampleValue>>,
+    /// The rows as they are written to text files, with the table's delimiter, output format and terminator.
+    pub text: String,
+}
+
+
+impl ExportFile {
+    /// The first `n` rows of every table, or fewer when the first file holds fewer, without generating whole files.
+    /// Rows are those the export starts with except that references are drawn from the sampled rows, so the
+    /// sample joins on its own.
+    pub fn sample(&self, n: u64) -> Result<Vec<TableSample>> {
+        let mut rows: Vec<Option<Vec<Vec<String>>>> = vec![None; self.tables.len()];
+        for index in self.generation_order()? {
+            let table = self.with_reference_pools(&self.tables[index], &rows, 0)?;
+            rows[index] = Some(table.generate_rows_vec_from(0, n.min(self.table_row_count(&table, 0)?))?);
+        }
+
+        Ok(self.tables.iter()
+            .zip(rows.into_iter().flatten())
+            .map(|(table, rows)| TableSample {
+                table: table.id_value.clone(),
+                columns: table.columns.iter().map(|x| (x.name.clone(), x.column_type.clone())).collect(),
+                rows: rows.iter()
+                    .map(|row| {
+                        table.columns.iter()
+                            .zip(row.iter().skip(1))
+                            .map(|(column, value)| match table.is_null(column, value) {
+                                true => SampleValue::Null,
+                                false => SampleValue::parse(value, &column.column_type),
+                            })
+                            .collect()
+                    })
+                    .collect(),
+                text: table.rows_to_string(&rows),
+            })
+            .collect())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column, Table};
+    use crate::types::ColumnType;
+
+    fn table(id: &str, column: Column) -> Table {
+        Table::new(id.into(), vec![column], "|".into(), Decimal::from_str("0.5").unwrap())
+    }
+
+    #[test]
+    fn sample_rows_test() {
+        let customers = table("A", Column::new("id".into(), 4, ColumnType::Int, generators::random_int(0, 9999)).as_key());
+        let orders = table("B", Column::new("customer_id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).references("A", "id"));
+        let ef = ExportFile::new(vec![customers, orders], 400, 4).unwrap().with_seed(11);
+
+        let all: Vec<Vec<Vec<Vec<String>>>> = (0..4).map(|x| ef.generate_file_rows(x).unwrap()).collect();
+
+        for (table, index) in [("A", 0), ("B", 1)] {
+            let sample = ef.sample_rows(table, 5, 3).unwrap();
+            let rows: Vec<&Vec<String>> = all.iter().flat_map(|x| x[index].iter()).collect();
+
+            assert_eq!(sample.len(), 5);
+            assert!(sample.iter().all(|x| rows.contains(&x)));
+            assert_eq!(sample, ef.sample_rows(table, 5, 3).unwrap());
+        }
+
+        assert!(ef.sample_rows("A", 1000, 3).is_err());
+        assert!(ef.sample_rows("C", 1, 3).is_err());
+    }
+
+    #[test]
+    fn sample_test() {
+        let customers = Table::new(
+            "A".into(),
+            vec![
+                Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).as_key(),
+                Column::new("since".into(), 10, ColumnType::Date, generators::constant("2024-02-29")),
+                Column::new("limit".into(), 6, ColumnType::Decimal(6, 2), generators::constant("1.5"))
+                    .with_null_rate(1.0),
+            ],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+        let customer_id = Column::new("customer_id".into(), 4, ColumnType::Int, generators::sequence(1, 1));
+        let ef = ExportFile::new(vec![customers, table("B", customer_id.references("A", "id"))], 4000, 2).unwrap();
+        let sample = ef.sample(3).unwrap();
+
+        let first = vec![SampleValue::Int(1), SampleValue::Date("2024-02-29".into()), SampleValue::Null];
+        assert_eq!(sample[0].rows[0], first);
+        assert_eq!(sample[0].text.lines().next().unwrap(), "A|1|2024-02-29|");
+        assert_eq!(sample[1].rows.len(), 3);
+        assert!(sample[1].rows.iter().all(|x| [1, 2, 3].map(SampleValue::Int).contains(&x[0])));
+        assert_eq!(sample[0].columns[1], ("since".to_string(), ColumnType::Date));
+    }
+}
@@ -0,0 +1,123 @@
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::{does_not_fit, ExportFile, ExportFileError, SizeSpec, Table};
+
+
+#[derive(Error, Debug)]
+pub enum ScaleError {
+    #[error("Scale factor must be a positive number, got {scale_factor}.")]
+    InvalidScaleFactor { scale_factor: f64 },
+    #[error("Row ratio of table {table} must be a non negative number, got {ratio}.")]
+    InvalidRatio { table: String, ratio: f64 },
+    #[error("Row ratios of table {table} form a cycle.")]
+    RatioCycle { table: String },
+}
+
+
+impl ExportFile {
+    /// Multiplies the size of every table, byte sized tables through the file size and tables with a row count
+    /// through their rows, so SF=10 of a config is ten times its SF=1 export.
+    pub fn with_scale_factor(mut self, scale_factor: f64) -> Result<Self> {
+        if !scale_factor.is_finite() || scale_factor <= 0.0 {
+            return Err(Error::from(ScaleError::InvalidScaleFactor { scale_factor }));
+        }
+
+        let file_size_bytes = (self.file_size_bytes as f64 * scale_factor).round();
+        if file_size_bytes < 1.0 || file_size_bytes >= u64::MAX as f64 {
+            return Err(Error::from(ScaleError::InvalidScaleFactor { scale_factor }));
+        }
+        self.file_size_bytes = file_size_bytes as u64;
+        does_not_fit(&self.tables, self.file_size_bytes, self.number_of_files)?;
+
+        self.scale_factor *= scale_factor;
+        Ok(self)
+    }
+
+
+    /// Sizes `table` as `ratio` rows for every row of `of`, e.g. 4 lines per order, so it follows `of` at any scale.
+    pub fn with_row_ratio(mut self, table: &str, of: &str, ratio: f64) -> Result<Self> {
+        for x in [table, of] {
+            if !self.tables.iter().any(|y| y.id_value == x) {
+                return Err(Error::from(ExportFileError::UnknownTable { table: x.into() }));
+            }
+        }
+        if !ratio.is_finite() || ratio < 0.0 {
+            return Err(Error::from(ScaleError::InvalidRatio { table: table.into(), ratio }));
+        }
+
+        let mut parent = Some(of);
+        while let Some(x) = parent {
+            if x == table {
+                return Err(Error::from(ScaleError::RatioCycle { table: table.into() }));
+            }
+            parent = self.row_ratios.get(x).map(|y| y.0.as_str());
+        }
+
+        self.row_ratios.insert(table.into(), (of.into(), ratio));
+        Ok(self)
+    }
+
+
+    /// Rows of `table` across all files when they are not derived from the file size.
+    pub(crate) fn table_rows_total(&self, table: &Table) -> Result<Option<u64>> {
+        if let Some((of, ratio)) = self.row_ratios.get(&table.id_value) {
+            let parent = self.tables.iter()
+                .find(|x| &x.id_value == of)
+                .ok_or_else(|| Error::from(ExportFileError::UnknownTable { table: of.clone() }))?;
+            return Ok(Some((self.table_total_rows(parent)? as f64 * ratio).round() as u64));
+        }
+
+        Ok(match &self.size_spec {
+            SizeSpec::Rows(rows) => rows.get(&table.id_value).map(|x| (*x as f64 * self.scale_factor).round() as u64),
+            SizeSpec::Bytes => None,
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column};
+    use crate::types::ColumnType;
+
+    fn table(id: &str) -> Table {
+        Table::new(
+            id.into(),
+            vec![Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1))],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        )
+    }
+
+    #[test]
+    fn scale_factor_test() {
+        let export = || ExportFile::new(vec![table("orders"), table("lines")], 1000, 2).unwrap();
+        let base = export().with_row_ratio("lines", "orders", 4.0).unwrap();
+        let scaled = export().with_scale_factor(10.0).unwrap().with_row_ratio("lines", "orders", 4.0).unwrap();
+
+        // Rows of byte sized tables are whole rows per file, so they scale up to the rows the rounding dropped.
+        let orders = base.table_total_rows(&base.tables[0]).unwrap();
+        let scaled_orders = scaled.table_total_rows(&scaled.tables[0]).unwrap();
+        assert!((orders * 10..(orders + 2) * 10).contains(&scaled_orders));
+        assert_eq!(base.table_total_rows(&base.tables[1]).unwrap(), orders * 4);
+        assert_eq!(scaled.table_total_rows(&scaled.tables[1]).unwrap(), scaled_orders * 4);
+
+        let rows = export()
+            .with_size_spec(SizeSpec::Rows(HashMap::from([("orders".to_string(), 15)])))
+            .unwrap()
+            .with_scale_factor(3.0)
+            .unwrap();
+        assert_eq!(rows.table_total_rows(&rows.tables[0]).unwrap(), 45);
+        assert_eq!(rows.generate_file_rows(1).unwrap()[0].len(), 22);
+
+        assert!(export().with_scale_factor(0.0).is_err());
+        assert!(base.with_row_ratio("orders", "lines", 0.25).is_err());
+        assert!(export().with_row_ratio("lines", "missing", 1.0).is_err());
+    }
+}
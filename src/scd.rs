@@ -0,0 +1,209 @@
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::Table;
+use crate::generators::{Generator, RowContext};
+use crate::random::{mix, RandomSource, SeededRandom};
+use crate::types::ColumnType;
+use crate::values::{format_date, format_timestamp, parse_date};
+
+// Version lengths and key values get their own streams so they do not depend on any column's seed.
+const LENGTH_STREAM: u64 = u64::MAX / 3;
+const KEY_STREAM: u64 = u64::MAX / 5;
+const HIGH_DATE: i64 = 2932896;
+
+
+#[derive(Error, Debug)]
+pub enum ScdError {
+    #[error("Table {table} has no column {column} for its history.")]
+    UnknownColumn { table: String, column: String },
+    #[error("Column {column} of table {table} is {column_type}, validity needs a DATE or TIMESTAMP column.")]
+    NotTemporal { table: String, column: String, column_type: String },
+    #[error("History needs at least one version per key and one day per version.")]
+    Empty,
+    #[error("Invalid history start {start}, expected YYYY-MM-DD.")]
+    InvalidStart { start: String },
+}
+
+
+/// Type 2 slowly changing dimension history: `versions` consecutive rows per business key, each valid from the end
+/// of the previous one for 1 to `max_days` days, and the last one current and valid until 9999-12-31.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scd2 {
+    pub key: String,
+    pub valid_from: String,
+    pub valid_to: String,
+    pub current: String,
+    versions: u64,
+    start_day: i64,
+    max_days: u64,
+}
+
+impl Scd2 {
+    pub fn new(
+        key: impl Into<String>,
+        valid_from: impl Into<String>,
+        valid_to: impl Into<String>,
+        current: impl Into<String>,
+    ) -> Self {
+        Scd2 {
+            key: key.into(),
+            valid_from: valid_from.into(),
+            valid_to: valid_to.into(),
+            current: current.into(),
+            versions: 3,
+            start_day: 18262,
+            max_days: 365,
+        }
+    }
+
+    pub fn with_versions(mut self, versions: u64) -> Self {
+        self.versions = versions;
+        self
+    }
+
+    /// Day the first version of every key is valid from, `YYYY-MM-DD`, 2020-01-01 by default.
+    pub fn with_start(mut self, start: &str) -> Result<Self> {
+        self.start_day = parse_date(start).ok_or_else(|| Error::from(ScdError::InvalidStart { start: start.into() }))?;
+        Ok(self)
+    }
+
+    pub fn with_max_days(mut self, max_days: u64) -> Self {
+        self.max_days = max_days;
+        self
+    }
+
+    fn entity(&self, row_index: u64) -> (u64, u64) {
+        (row_index / self.versions, row_index % self.versions)
+    }
+
+    // Day a version starts, the end of a version is the start of the next one.
+    fn day(&self, entity: u64, version: u64) -> i64 {
+        (0..version)
+            .map(|x| 1 + SeededRandom::new(mix(mix(entity, x), LENGTH_STREAM)).below(self.max_days) as i64)
+            .fold(self.start_day, i64::saturating_add)
+    }
+}
+
+
+fn format_day(day: i64, column_type: &ColumnType) -> String {
+    match column_type {
+        ColumnType::Timestamp => format_timestamp(day.saturating_mul(86400)),
+        _ => format_date(day),
+    }
+}
+
+
+impl Table {
+    /// Generates the table as SCD2 history, the key column's generator is drawn once per key and the validity and
+    /// current flag columns are replaced, other columns change freely between versions.
+    pub fn with_scd2(mut self, scd2: Scd2) -> Result<Self> {
+        if scd2.versions == 0 || scd2.max_days == 0 {
+            return Err(Error::from(ScdError::Empty));
+        }
+
+        let column = |name: &str| {
+            self.columns.iter()
+                .position(|x| x.name == name)
+                .ok_or_else(|| Error::from(ScdError::UnknownColumn {
+                    table: self.id_value.clone(),
+                    column: name.into(),
+                }))
+        };
+        let (key, valid_from, valid_to, current) =
+            (column(&scd2.key)?, column(&scd2.valid_from)?, column(&scd2.valid_to)?, column(&scd2.current)?);
+
+        for index in [valid_from, valid_to] {
+            let column = &self.columns[index];
+            if !column.column_type.is_temporal() {
+                return Err(Error::from(ScdError::NotTemporal {
+                    table: self.id_value.clone(),
+                    column: column.name.clone(),
+                    column_type: column.column_type.to_string(),
+                }));
+            }
+        }
+
+        let key_generator = self.columns[key].generator.clone();
+        let history = scd2.clone();
+        self.replace_generator(key, Generator::new(move |context| {
+            let (entity, _) = history.entity(context.row_index);
+            let random = SeededRandom::new(mix(entity, KEY_STREAM));
+            key_generator.generate(&RowContext { row_index: entity, random: &random })
+        }));
+
+        let (history, column_type) = (scd2.clone(), self.columns[valid_from].column_type.clone());
+        self.replace_generator(valid_from, Generator::new(move |context| {
+            let (entity, version) = history.entity(context.row_index);
+            Ok(format_day(history.day(entity, version), &column_type))
+        }));
+
+        let (history, column_type) = (scd2.clone(), self.columns[valid_to].column_type.clone());
+        self.replace_generator(valid_to, Generator::new(move |context| {
+            let (entity, version) = history.entity(context.row_index);
+            Ok(match version + 1 == history.versions {
+                true => format_day(HIGH_DATE, &column_type),
+                false => format_day(history.day(entity, version + 1), &column_type),
+            })
+        }));
+
+        let (yes, no) = match self.columns[current].column_type {
+            ColumnType::Boolean => ("true", "false"),
+            _ => ("Y", "N"),
+        };
+        self.replace_generator(current, Generator::new(move |context| {
+            let (_, version) = scd2.entity(context.row_index);
+            Ok(if version + 1 == scd2.versions { yes } else { no }.to_string())
+        }));
+
+        self.unique.retain(|x| !x.contains(&key));
+        self.update_value_spaces();
+        Ok(self)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column};
+
+    #[test]
+    fn scd2_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("customer_id".into(), 4, ColumnType::Int, generators::sequence(1, 1)),
+                Column::new("segment".into(), 1, ColumnType::Char(1), generators::constant("B")),
+                Column::new("valid_from".into(), 10, ColumnType::Date, generators::constant("")),
+                Column::new("valid_to".into(), 10, ColumnType::Date, generators::constant("")),
+                Column::new("is_current".into(), 1, ColumnType::Char(1), generators::constant("")),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        );
+        let scd2 = Scd2::new("customer_id", "valid_from", "valid_to", "is_current")
+            .with_versions(3)
+            .with_start("2024-01-01")
+            .unwrap()
+            .with_max_days(30);
+
+        let rows = t.clone().with_scd2(scd2.clone()).unwrap().generate_rows_vec(6).unwrap();
+        let keys: Vec<&str> = rows.iter().map(|x| x[1].as_str()).collect();
+        assert_eq!(keys, vec!["1", "1", "1", "2", "2", "2"]);
+        assert_eq!(rows[0][3], "2024-01-01");
+        for entity in rows.chunks(3) {
+            assert_eq!(entity[0][4], entity[1][3]);
+            assert_eq!(entity[1][4], entity[2][3]);
+            assert!(entity[0][3] < entity[1][3]);
+            assert_eq!(entity[2][4], "9999-12-31");
+            let current: Vec<&str> = entity.iter().map(|x| x[5].as_str()).collect();
+            assert_eq!(current, vec!["N", "N", "Y"]);
+        }
+
+        assert!(t.clone().with_scd2(scd2.clone().with_versions(0)).is_err());
+        assert!(t.with_scd2(Scd2::new("customer_id", "segment", "valid_to", "is_current")).is_err());
+    }
+}
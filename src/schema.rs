@@ -0,0 +1,272 @@
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+
+use crate::{Column, ExportFile, Table};
+use crate::types::{Annotations, ColumnType};
+
+pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+// Timestamps are written `YYYY-MM-DD HH:MM:SS` with optional micros, which the `date-time` format does not accept.
+const TIMESTAMP_PATTERN: &str = r"^\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}(\.\d{1,6})?$";
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AvroType {
+    Boolean,
+    Int,
+    Long,
+    Double,
+    Decimal { precision: u32, scale: u32 },
+    Date,
+    TimestampMicros,
+    String,
+}
+
+impl AvroType {
+    pub fn from_column_type(column_type: &ColumnType) -> AvroType {
+        match column_type {
+            ColumnType::Boolean => AvroType::Boolean,
+            ColumnType::SmallInt | ColumnType::Int => AvroType::Int,
+            ColumnType::BigInt => AvroType::Long,
+            ColumnType::Real | ColumnType::Double => AvroType::Double,
+            ColumnType::Decimal(precision, scale) => {
+                let precision = (*precision).clamp(1, 38);
+                AvroType::Decimal { precision, scale: (*scale).min(precision) }
+            }
+            ColumnType::Date => AvroType::Date,
+            ColumnType::Timestamp => AvroType::TimestampMicros,
+            _ => AvroType::String,
+        }
+    }
+
+    pub fn schema(&self) -> Value {
+        match self {
+            AvroType::Boolean => json!("boolean"),
+            AvroType::Int => json!("int"),
+            AvroType::Long => json!("long"),
+            AvroType::Double => json!("double"),
+            AvroType::Decimal { precision, scale } => json!({
+                "type": "bytes",
+                "logicalType": "decimal",
+                "precision": precision,
+                "scale": scale,
+            }),
+            AvroType::Date => json!({"type": "int", "logicalType": "date"}),
+            AvroType::TimestampMicros => json!({"type": "long", "logicalType": "timestamp-micros"}),
+            AvroType::String => json!("string"),
+        }
+    }
+}
+
+
+fn avro_name(name: &str) -> String {
+    let mut sanitized: String = name.chars()
+        .map(|x| if x.is_ascii_alphanumeric() { x } else { '_' })
+        .collect();
+
+    if !sanitized.starts_with(|x: char| x.is_ascii_alphabetic() || x == '_') {
+        sanitized.insert(0, '_');
+    }
+
+    sanitized
+}
+
+// The description becomes the Avro `doc`, the rest are kept as custom attributes that readers ignore.
+fn annotate(mut schema: Value, annotations: &Annotations) -> Value {
+    for (key, value) in annotations.to_json() {
+        let key = if key == "description" { "doc".to_string() } else { key };
+        schema[key] = value;
+    }
+
+    schema
+}
+
+
+// The description is kept, the rest become `x-` keywords validators ignore, `properties` would clash otherwise.
+fn annotate_json_schema(schema: &mut Map<String, Value>, annotations: &Annotations) {
+    for (key, value) in annotations.to_json() {
+        let key = if key == "description" { key } else { format!("x-{}", key) };
+        schema.insert(key, value);
+    }
+}
+
+
+/// JSON Schema of the values of a column type, rows hold every value as it is written to a text file.
+pub fn json_schema_type(column_type: &ColumnType) -> Map<String, Value> {
+    let integer = |x: i64| json!({"type": "integer", "minimum": -x - 1, "maximum": x});
+    let schema = match column_type {
+        ColumnType::Boolean => json!({"type": "boolean"}),
+        ColumnType::SmallInt => integer(i16::MAX as i64),
+        ColumnType::Int => integer(i32::MAX as i64),
+        ColumnType::BigInt => json!({"type": "integer"}),
+        ColumnType::Real | ColumnType::Double => json!({"type": "number"}),
+        ColumnType::Decimal(precision, scale) => {
+            let bound = 10f64.powi(precision.saturating_sub(*scale).min(38) as i32);
+            json!({"type": "number", "exclusiveMinimum": -bound, "exclusiveMaximum": bound})
+        }
+        ColumnType::Char(length) => json!({"type": "string", "minLength": length, "maxLength": length}),
+        ColumnType::Varchar(length) => json!({"type": "string", "maxLength": length}),
+        ColumnType::Date => json!({"type": "string", "format": "date"}),
+        ColumnType::Timestamp => json!({"type": "string", "pattern": TIMESTAMP_PATTERN}),
+        ColumnType::Text | ColumnType::Custom(_) => json!({"type": "string"}),
+    };
+
+    match schema {
+        Value::Object(x) => x,
+        _ => Map::new(),
+    }
+}
+
+
+impl Column {
+    pub fn json_schema(&self) -> Value {
+        let mut schema = json_schema_type(&self.column_type);
+        if self.is_nullable() {
+            let column_type = schema["type"].clone();
+            schema.insert("type".into(), json!([column_type, "null"]));
+        }
+        annotate_json_schema(&mut schema, &self.annotations);
+        Value::Object(schema)
+    }
+}
+
+
+impl Table {
+    pub fn avro_schema(&self) -> Value {
+        let mut schema = json!({
+            "type": "record",
+            "name": avro_name(&self.id_value),
+            "fields": self.columns.iter()
+                .map(|x| {
+                    let avro_type = AvroType::from_column_type(&x.column_type).schema();
+                    annotate(json!({
+                        "name": avro_name(&x.name),
+                        "type": if x.is_nullable() { json!(["null", avro_type]) } else { avro_type },
+                    }), &x.annotations)
+                })
+                .collect::<Vec<Value>>(),
+        });
+        if !self.qualifiers.is_empty() {
+            schema["namespace"] = json!(self.qualifiers.iter().map(|x| avro_name(x)).collect::<Vec<String>>().join("."));
+        }
+
+        annotate(schema, &self.annotations)
+    }
+
+
+    /// JSON Schema of a row of the table as an object of column values, nullable columns may be null.
+    pub fn json_schema(&self) -> Value {
+        let properties: Map<String, Value> = self.columns.iter().map(|x| (x.name.clone(), x.json_schema())).collect();
+        let required: Vec<&str> = self.columns.iter().filter(|x| !x.is_nullable()).map(|x| x.name.as_str()).collect();
+        let mut schema = json!({
+            "title": self.qualified_name(),
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        });
+        if let Value::Object(x) = &mut schema {
+            annotate_json_schema(x, &self.annotations);
+        }
+        schema
+    }
+}
+
+
+impl ExportFile {
+    /// Avro schema of the export, a union of a record for every table.
+    pub fn get_schema_avro(&self) -> Result<String> {
+        self.build_schema()?;
+        Ok(serde_json::to_string(&self.tables.iter().map(|x| x.avro_schema()).collect::<Vec<Value>>())?)
+    }
+
+
+    /// JSON Schema of the export, an object of row arrays for every table with the rows defined under `$defs`.
+    pub fn get_schema_jsonschema(&self) -> Result<String> {
+        self.build_schema()?;
+        // Table ids are escaped as JSON pointer tokens in references.
+        let reference = |x: &str| format!("#/$defs/{}", x.replace('~', "~0").replace('/', "~1"));
+
+        let schema = json!({
+            "$schema": JSON_SCHEMA_DIALECT,
+            "type": "object",
+            "properties": self.tables.iter()
+                .map(|x| (x.id_value.clone(), json!({"type": "array", "items": {"$ref": reference(&x.id_value)}})))
+                .collect::<Map<String, Value>>(),
+            "additionalProperties": false,
+            "$defs": self.tables.iter()
+                .map(|x| (x.id_value.clone(), x.json_schema()))
+                .collect::<Map<String, Value>>(),
+        });
+        Ok(serde_json::to_string(&schema)?)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+
+    fn int_generator() -> Result<String> {
+        Ok("42".into())
+    }
+
+    fn text_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    #[test]
+    fn avro_schema_test() {
+        let t = Table::new(
+            "A-1".into(),
+            vec![
+                Column::new("id".into(), 2, "INT".into(), int_generator),
+                Column::new("code".into(), 3, "CHAR[3]".into(), text_generator),
+            ],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+
+        assert_eq!(
+            t.avro_schema().to_string(),
+            r#"{"fields":[{"name":"id","type":"int"},{"name":"code","type":"string"}],"name":"A_1","type":"record"}"#
+        );
+
+        let t = t.with_annotations(Annotations::new().with_description("Customers").with_tag("pii"));
+        assert_eq!(t.avro_schema()["doc"], "Customers");
+        assert_eq!(t.avro_schema()["tags"], json!(["pii"]));
+        assert_eq!(t.with_qualifier("analytics.staging-1").avro_schema()["namespace"], "analytics.staging_1");
+    }
+
+    #[test]
+    fn get_schema_test() {
+        let table = |id: &str| Table::new(
+            id.into(),
+            vec![
+                Column::new("id".into(), 2, "INT".into(), int_generator)
+                    .with_annotations(Annotations::new().with_owner("crm")),
+                Column::new("code".into(), 3, "CHAR(3)".into(), text_generator).with_null_rate(0.5),
+                Column::new("amount".into(), 6, "DECIMAL(5,2)".into(), int_generator),
+            ],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+        let ef = ExportFile::new(vec![table("A/1"), table("B")], 1_000, 1).unwrap();
+
+        let avro: Value = serde_json::from_str(&ef.get_schema_avro().unwrap()).unwrap();
+        assert_eq!(avro[0]["name"], "A_1");
+        assert_eq!(avro[1]["fields"][1]["type"], json!(["null", "string"]));
+        assert_eq!(avro[1]["fields"][2]["type"]["logicalType"], "decimal");
+
+        let schema: Value = serde_json::from_str(&ef.get_schema_jsonschema().unwrap()).unwrap();
+        assert_eq!(schema["$schema"], JSON_SCHEMA_DIALECT);
+        assert_eq!(schema["properties"]["A/1"]["items"]["$ref"], "#/$defs/A~11");
+        let row = &schema["$defs"]["B"];
+        assert_eq!(row["required"], json!(["id", "amount"]));
+        assert_eq!(row["properties"]["id"], json!({"type": "integer", "minimum": -2147483648i64, "maximum": 2147483647,
+                                                   "x-owner": "crm"}));
+        assert_eq!(row["properties"]["code"]["type"], json!(["string", "null"]));
+        assert_eq!(row["properties"]["amount"]["exclusiveMaximum"], json!(1000.0));
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::ExportFile;
+use crate::report::GenerationReport;
+
+
+#[derive(Error, Debug)]
+pub enum ShardError {
+    #[error("Shard {index} is out of range for {count} shards.")]
+    OutOfRange { index: u64, count: u64 },
+    #[error("Shard {shard} is not of the form index/count.")]
+    InvalidFormat { shard: String },
+    #[error("Table {table} regenerates duplicates of unique values, which needs every earlier file and can not be \
+             sharded. Use generators with a known value space, e.g. random_int, for its unique columns.")]
+    RegeneratedUnique { table: String },
+}
+
+
+/// Shard `index` of `count`, every shard generates the files whose index leaves remainder `index` modulo `count`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    index: u64,
+    count: u64,
+}
+
+impl Shard {
+    pub fn new(index: u64, count: u64) -> Result<Self> {
+        if index >= count {
+            return Err(Error::from(ShardError::OutOfRange { index, count }));
+        }
+
+        Ok(Shard { index, count })
+    }
+
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn contains(&self, file_index: u64) -> bool {
+        file_index % self.count == self.index
+    }
+
+    // Manifest and checkpoint names of the shard.
+    pub(crate) fn suffix(&self) -> String {
+        format!("_{}_of_{}", self.index, self.count)
+    }
+
+    pub fn files(&self, number_of_files: u64) -> Vec<u64> {
+        (self.index..number_of_files)
+            .step_by(self.count as usize)
+            .collect()
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+impl FromStr for Shard {
+    type Err = Error;
+
+    fn from_str(shard: &str) -> Result<Self> {
+        let invalid = || Error::from(ShardError::InvalidFormat { shard: shard.into() });
+        let (index, count) = shard.split_once('/').ok_or_else(invalid)?;
+
+        Shard::new(
+            index.trim().parse().map_err(|_| invalid())?,
+            count.trim().parse().map_err(|_| invalid())?,
+        )
+    }
+}
+
+
+impl ExportFile {
+    /// Generates only this shard's files, seeded exports produce identical files whichever machine runs the shard.
+    /// The manifest and checkpoint are named after the shard.
+    pub fn generate_shard(&self, folder_path: &Path, shard: Shard) -> Result<GenerationReport> {
+        self.check_shard(shard)?;
+        self.generate_files(folder_path, shard.files(self.number_of_files), &shard.suffix(), None)
+    }
+
+
+    pub fn resume_shard(&self, folder_path: &Path, shard: Shard) -> Result<GenerationReport> {
+        self.check_shard(shard)?;
+        self.resume_files(folder_path, shard.files(self.number_of_files), &shard.suffix())
+    }
+
+
+    // Regenerated unique constraints are checked against the values of every earlier file, which each shard would
+    // have to generate and hold again.
+    fn check_shard(&self, shard: Shard) -> Result<()> {
+        match self.tables.iter().find(|x| x.has_regenerated_unique()).filter(|_| shard.count > 1) {
+            Some(table) => Err(Error::from(ShardError::RegeneratedUnique { table: table.id_value.clone() })),
+            None => Ok(()),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column, Table};
+    use crate::types::ColumnType;
+
+    fn export_file() -> ExportFile {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).as_key(),
+                Column::new("value".into(), 8, ColumnType::Double, generators::normal(0.0, 1.0)).with_null_rate(0.2),
+            ],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+
+        ExportFile::new(vec![t], 1000, 5).unwrap().with_seed(7)
+    }
+
+    #[test]
+    fn shard_test() {
+        assert_eq!(Shard::from_str("1/3").unwrap().files(8), vec![1, 4, 7]);
+        assert!(Shard::from_str("3/3").is_err());
+        assert!(Shard::from_str("1-3").is_err());
+    }
+
+    #[test]
+    fn generate_shard_test() {
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        let all = folder.join("all");
+        let sharded = folder.join("sharded");
+
+        export_file().generate_all_files(&all).unwrap();
+        for index in 0..2 {
+            export_file().generate_shard(&sharded, Shard::new(index, 2).unwrap()).unwrap();
+        }
+
+        let ef = export_file();
+        let files: Vec<(String, String)> = (0..5)
+            .map(|x| (
+                fs::read_to_string(ef.file_path(&all, x)).unwrap(),
+                fs::read_to_string(ef.file_path(&sharded, x)).unwrap(),
+            ))
+            .collect();
+        fs::remove_dir_all(&folder).unwrap();
+
+        assert!(files.iter().all(|(x, y)| x == y));
+        assert_ne!(files[0].0, files[1].0);
+    }
+
+    #[test]
+    fn regenerated_unique_shard_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("rank".into(), 3, ColumnType::Int, generators::zipf(500, 0.5)).unique(true)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+        let ef = ExportFile::new(vec![t], 1000, 4).unwrap();
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+
+        let error = ef.generate_shard(&folder, Shard::new(1, 2).unwrap()).unwrap_err();
+        assert!(matches!(error.downcast_ref::<ShardError>(), Some(ShardError::RegeneratedUnique { .. })));
+        assert!(ef.resume_shard(&folder, Shard::new(0, 2).unwrap()).is_err());
+        assert!(!folder.exists());
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(feature = "s3")]
+pub mod s3;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+#[cfg(feature = "mysql")]
+pub mod mysql;
+
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+use anyhow::Result;
+
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+use crate::{ExportFile, Table};
+
+
+// Tab separated rows with backslash escapes and `\N` for nulls, the text format both Postgres COPY and MySQL
+// LOAD DATA read. `rows` start with the table id like generated rows do, identity columns are left to the database.
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+fn database_text(table: &Table, rows: &[Vec<String>]) -> String {
+    let mut text = String::new();
+
+    for row in rows {
+        let values = table.columns.iter().zip(row[1..].iter()).filter(|(x, _)| !x.is_identity());
+        for (i, (column, value)) in values.enumerate() {
+            if i > 0 {
+                text.push('\t');
+            }
+            if table.is_null(column, value) {
+                text.push_str("\\N");
+                continue;
+            }
+            for x in value.chars() {
+                match x {
+                    '\\' => text.push_str("\\\\"),
+                    '\t' => text.push_str("\\t"),
+                    '\n' => text.push_str("\\n"),
+                    '\r' => text.push_str("\\r"),
+                    x => text.push(x),
+                }
+            }
+        }
+        text.push('\n');
+    }
+
+    text
+}
+
+
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+impl ExportFile {
+    // Database text of a table in a file in chunks of the configured size, generated or taken from its kept `rows`
+    // and passed to `write` in order. Returns the bytes written.
+    fn database_chunks(
+        &self,
+        table: &Table,
+        rows: Option<&[Vec<String>]>,
+        file_index: u64,
+        mut write: impl FnMut(String) -> Result<()>,
+    ) -> Result<u64> {
+        let offset = self.table_row_offset(table, file_index)?;
+        let count = self.table_row_count(table, file_index)?;
+        let chunk_rows = table.chunk_rows(self.chunk_bytes);
+        let mut bytes = 0;
+
+        for start in (0..count).step_by(chunk_rows as usize) {
+            let length = chunk_rows.min(count - start);
+            let data = match rows {
+                Some(rows) => database_text(table, &rows[start as usize..(start + length) as usize]),
+                None => database_text(table, &table.generate_rows_vec_from(offset + start, length)
+                    .inspect_err(|_| self.count_error(&table.id_value))?),
+            };
+            let length_bytes = data.len() as u64;
+
+            write(data)?;
+            self.progress_rows(&table.id_value, length, length_bytes);
+            bytes += length_bytes;
+        }
+
+        Ok(bytes)
+    }
+}
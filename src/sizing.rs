@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+use thiserror::Error;
+
+use crate::ExportFile;
+use crate::metadata::FileMetadata;
+use crate::progress::ProgressSink;
+
+
+#[derive(Error, Debug)]
+pub enum SizingError {
+    #[error("{report}")]
+    OutOfTolerance { report: String },
+}
+
+
+/// Uncompressed bytes of a written file next to the bytes it was sized for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileSize {
+    pub file_index: u64,
+    pub target_bytes: u64,
+    pub actual_bytes: u64,
+}
+
+impl FileSize {
+    /// Relative difference from the target, positive for files over it.
+    pub fn deviation(&self) -> Decimal {
+        deviation(self.actual_bytes, self.target_bytes)
+    }
+}
+
+
+fn deviation(actual: u64, target: u64) -> Decimal {
+    if target == 0 {
+        return Decimal::ZERO;
+    }
+
+    (Decimal::from(actual) - Decimal::from(target)) / Decimal::from(target)
+}
+
+
+/// Progress sink comparing the uncompressed size of every written file with its target.
+#[derive(Debug)]
+pub struct SizeReport {
+    tolerance: Decimal,
+    target_file_bytes: Mutex<u64>,
+    files: Mutex<BTreeMap<u64, u64>>,
+}
+
+impl Default for SizeReport {
+    fn default() -> Self {
+        SizeReport::new()
+    }
+}
+
+impl SizeReport {
+    /// Files within 5% of their target are on target.
+    pub fn new() -> Self {
+        SizeReport {
+            tolerance: Decimal::new(5, 2),
+            target_file_bytes: Mutex::new(0),
+            files: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Largest accepted relative difference, e.g. `0.01` for 1%.
+    pub fn with_tolerance(mut self, tolerance: Decimal) -> Self {
+        self.tolerance = tolerance.abs();
+        self
+    }
+
+    pub fn files(&self) -> Vec<FileSize> {
+        let target_bytes = *self.target_file_bytes.lock().unwrap();
+
+        self.files.lock().unwrap().iter()
+            .map(|(file_index, actual_bytes)| FileSize { file_index: *file_index, target_bytes, actual_bytes: *actual_bytes })
+            .collect()
+    }
+
+    pub fn target_bytes(&self) -> u64 {
+        self.files().iter().map(|x| x.target_bytes).fold(0, u64::saturating_add)
+    }
+
+    pub fn actual_bytes(&self) -> u64 {
+        self.files().iter().map(|x| x.actual_bytes).fold(0, u64::saturating_add)
+    }
+
+    pub fn deviation(&self) -> Decimal {
+        deviation(self.actual_bytes(), self.target_bytes())
+    }
+
+    pub fn out_of_tolerance(&self) -> Vec<FileSize> {
+        self.files().into_iter()
+            .filter(|x| x.deviation().abs() > self.tolerance)
+            .collect()
+    }
+
+    /// Fails with the summary when a file is outside the tolerance.
+    pub fn check(&self) -> Result<()> {
+        if self.out_of_tolerance().is_empty() {
+            return Ok(());
+        }
+
+        Err(Error::from(SizingError::OutOfTolerance { report: self.to_string() }))
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let percent = |x: Decimal| (x * Decimal::ONE_HUNDRED).round_dp(2);
+
+        write!(
+            f,
+            "Wrote {} of {} target bytes ({:+}%), {} of {} files outside ±{}%.",
+            self.actual_bytes(),
+            self.target_bytes(),
+            percent(self.deviation()),
+            self.out_of_tolerance().len(),
+            self.files().len(),
+            percent(self.tolerance),
+        )?;
+
+        for file in self.out_of_tolerance() {
+            write!(
+                f,
+                " File {} has {} of {} bytes ({:+}%).",
+                file.file_index,
+                file.actual_bytes,
+                file.target_bytes,
+                percent(file.deviation()),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ProgressSink for SizeReport {
+    fn start(&self, files: u64, bytes: u64) {
+        *self.target_file_bytes.lock().unwrap() = bytes.checked_div(files).unwrap_or(0);
+    }
+
+    fn file_completed(&self, metadata: &FileMetadata) {
+        let bytes = metadata.tables.iter().map(|x| x.bytes).fold(0, u64::saturating_add);
+        *self.files.lock().unwrap().entry(metadata.file_index).or_insert(0) += bytes;
+    }
+}
+
+
+impl ExportFile {
+    /// Sizes tables by the average bytes of a sample of their generated rows instead of their declared column
+    /// sizes, so files land close to their target when values are shorter than the declared sizes.
+    pub fn with_measured_sizing(mut self, measured_sizing: bool) -> Self {
+        self.measured_sizing = measured_sizing;
+        self
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{generators, Column, Table};
+    use crate::types::ColumnType;
+
+    #[test]
+    fn measured_sizing_test() {
+        // Declared as 8 digits, the values have at most 6.
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("id".into(), 8, ColumnType::Int, generators::random_int(0, 999_999))],
+            "|".into(),
+            Decimal::ONE,
+        );
+
+        let generate = |measured: bool| {
+            let report = Arc::new(SizeReport::new().with_tolerance(Decimal::new(1, 2)));
+            let ef = ExportFile::new(vec![t.clone()], 20_000, 2)
+                .unwrap()
+                .with_seed(7)
+                .with_measured_sizing(measured)
+                .with_progress(report.clone());
+
+            let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+            ef.generate_all_files(&folder).unwrap();
+            fs::remove_dir_all(&folder).unwrap();
+            report
+        };
+
+        let declared = generate(false);
+        assert_eq!(declared.target_bytes(), 20_000);
+        assert!(declared.check().is_err());
+        assert!(declared.to_string().starts_with("Wrote "));
+
+        let measured = generate(true);
+        assert_eq!(measured.files().len(), 2);
+        assert!(measured.check().is_ok(), "{}", measured);
+    }
+}
@@ -0,0 +1,135 @@
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::{ExportFile, Table};
+use crate::metadata::{FileMetadata, TableMetadata};
+
+
+#[derive(Error, Debug)]
+pub enum SpillError {
+    #[error("Max rows per file has to be at least 1.")]
+    ZeroRows,
+}
+
+
+impl ExportFile {
+    /// Caps the rows of every written csv file, rows over the cap spill into `<name>_part<n>` files next to it.
+    pub fn with_max_rows_per_file(mut self, max_rows: u64) -> Result<Self> {
+        if max_rows == 0 {
+            return Err(Error::from(SpillError::ZeroRows));
+        }
+
+        self.max_rows_per_file = Some(max_rows);
+        Ok(self)
+    }
+
+
+    /// Path of spill file `part`, part 0 is the file itself.
+    pub fn spill_path(&self, path: &Path, part: u64) -> PathBuf {
+        if part == 0 {
+            return path.to_path_buf();
+        }
+
+        let name = path.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+        let ext = format!(".{}", self.compression.extension());
+        let name = match name.strip_suffix(&ext) {
+            Some(stem) => format!("{}_part{}{}", stem, part, ext),
+            None => format!("{}_part{}", name, part),
+        };
+        path.with_file_name(name)
+    }
+
+
+    /// Rows of `tables` in `order` (a table index per line) split over the file and as many spill files as needed.
+    pub(crate) fn write_spilled(
+        &self,
+        path: &Path,
+        file_index: u64,
+        tables: &[&Table],
+        rows: &[Vec<Vec<String>>],
+        order: &[usize],
+        mut started: Instant,
+    ) -> Result<Vec<FileMetadata>> {
+        let max_rows = self.max_rows_per_file.unwrap_or(u64::MAX) as usize;
+        let mut cursors = vec![0; tables.len()];
+        let mut written = vec![];
+
+        for (part, chunk) in order.chunks(max_rows).enumerate() {
+            let mut data = String::new();
+            let mut metadata: Vec<TableMetadata> = tables.iter()
+                .map(|x| TableMetadata { table: x.id_value.clone(), rows: 0, bytes: 0 })
+                .collect();
+
+            for table in chunk {
+                let line = tables[*table].row_to_string(&rows[*table][cursors[*table]]);
+                cursors[*table] += 1;
+                metadata[*table].rows += 1;
+                metadata[*table].bytes += self.encoding.encoded_len(&line);
+                data.push_str(&line);
+            }
+
+            let path = self.spill_path(path, part as u64);
+            let (bytes, checksum, sha256) = self.write_encoded(&path, &data)?;
+            for table in metadata.iter() {
+                self.progress_rows(&table.table, table.rows, table.bytes);
+            }
+
+            written.push(FileMetadata {
+                path,
+                file_index,
+                tables: metadata,
+                bytes,
+                checksum,
+                sha256,
+                duration: started.elapsed(),
+            });
+            started = Instant::now();
+        }
+
+        Ok(written)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column};
+    use crate::types::ColumnType;
+
+    #[test]
+    fn max_rows_per_file_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("id".into(), 2, ColumnType::Int, generators::sequence(1, 1))],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+        let ef = ExportFile::new(vec![t], 250, 2).unwrap()
+            .with_max_rows_per_file(10)
+            .unwrap();
+
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_all_files(&folder).unwrap();
+
+        let mut names: Vec<String> = fs::read_dir(&folder).unwrap()
+            .map(|x| x.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        let spilled = fs::read_to_string(folder.join("file_125_2_0_part2.txt")).unwrap();
+        fs::remove_dir_all(&folder).unwrap();
+
+        // 25 rows per file with 10 rows per part
+        assert_eq!(names.len(), 6);
+        assert_eq!(names[..3], ["file_125_2_0.txt", "file_125_2_0_part1.txt", "file_125_2_0_part2.txt"]);
+        assert_eq!(spilled, "A|21\nA|22\nA|23\nA|24\nA|25\n");
+        assert!(ef.with_max_rows_per_file(0).is_err());
+    }
+}
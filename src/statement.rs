@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+use thiserror::Error;
+
+use crate::ExportFile;
+use crate::compression::crc32_update;
+use crate::manifest::sha256_hex;
+use crate::metadata::{FileMetadata, TableMetadata};
+use crate::values::parse_timestamp_micros;
+
+/// Currency of every statement.
+pub const CURRENCY: &str = "EUR";
+const JOURNAL_COLUMNS: [&str; 5] = ["transaction_id", "account_id", "side", "amount", "posted_date"];
+
+
+#[derive(Error, Debug)]
+pub enum StatementError {
+    #[error("Bank statements need a journal entry table with transaction_id, account_id, side, amount and \
+             posted_date columns, see the journal_entry preset.")]
+    NoJournal,
+    #[error("Amount {value} of table {table} is not a number.")]
+    InvalidAmount { table: String, value: String },
+    #[error("Posting date {value} of table {table} is not a YYYY-MM-DD date.")]
+    InvalidDate { table: String, value: String },
+}
+
+
+/// A booking on a statement, credits are positive and debits negative.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementLine {
+    /// `YYYY-MM-DD`.
+    pub date: String,
+    pub amount: Decimal,
+    pub transaction: String,
+    /// Id of the journal entry line.
+    pub entry: String,
+}
+
+
+/// Statement `number` of an account, the bookings of one file in date order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement {
+    pub account: String,
+    pub code: String,
+    pub name: String,
+    pub number: u64,
+    pub opening: Decimal,
+    pub lines: Vec<StatementLine>,
+}
+
+impl Statement {
+    pub fn closing(&self) -> Decimal {
+        self.opening + self.lines.iter().map(|x| x.amount).sum::<Decimal>()
+    }
+
+    /// The statement as an MT940 message, from the `:20:` reference to the closing `-`.
+    pub fn to_mt940(&self) -> String {
+        let date = |x: &str| format!("{}{}{}", &x[2..4], &x[5..7], &x[8..10]);
+        let amount = |x: Decimal| format!("{:.2}", x.abs()).replace('.', ",");
+        let mark = |x: Decimal| if x.is_sign_negative() && !x.is_zero() { "D" } else { "C" };
+        let (first, last) = match (self.lines.first(), self.lines.last()) {
+            (Some(first), Some(last)) => (date(&first.date), date(&last.date)),
+            _ => return String::new(),
+        };
+
+        let mut mt940 = format!(
+            ":20:ST{:05}{:0>9.9}\r\n:25:{}\r\n:28C:{:05}/001\r\n:60F:{}{}{}{}\r\n",
+            self.number, self.code, self.code, self.number, mark(self.opening), first, CURRENCY, amount(self.opening),
+        );
+        for line in self.lines.iter() {
+            mt940.push_str(&format!(
+                ":61:{}{}{}{}NTRF{}//{}\r\n:86:{} transaction {}\r\n",
+                date(&line.date), &date(&line.date)[2..], mark(line.amount), amount(line.amount), line.transaction,
+                line.entry, self.name, line.transaction,
+            ));
+        }
+        let closing = self.closing();
+        mt940.push_str(&format!(":62F:{}{}{}{}\r\n-\r\n", mark(closing), last, CURRENCY, amount(closing)));
+        mt940
+    }
+}
+
+
+impl ExportFile {
+    // Positions of the journal table and the table its account ids reference.
+    fn statement_tables(&self) -> Result<(usize, Option<usize>)> {
+        let journal = self.tables.iter()
+            .position(|x| JOURNAL_COLUMNS.iter().all(|y| x.columns.iter().any(|z| z.name == *y)))
+            .ok_or_else(|| Error::from(StatementError::NoJournal))?;
+        let accounts = self.tables[journal].columns.iter()
+            .find(|x| x.name == "account_id")
+            .and_then(|x| x.reference.as_ref())
+            .and_then(|x| self.tables.iter().position(|y| y.id_value == x.table));
+
+        Ok((journal, accounts))
+    }
+
+
+    /// Statements of every account booked in the journal `rows` of a file, opening with the `balances` the previous
+    /// statements closed with, which are updated to the new closing balances. Accounts open at zero on their first
+    /// statement and `accounts` maps account ids to their code and name.
+    pub fn statements(
+        &self,
+        rows: &[Vec<String>],
+        accounts: &HashMap<String, (String, String)>,
+        balances: &mut HashMap<String, Decimal>,
+        number: u64,
+    ) -> Result<Vec<Statement>> {
+        let (journal, _) = self.statement_tables()?;
+        let table = &self.tables[journal];
+        let position = |name: &str| table.columns.iter().position(|x| x.name == name).map(|x| x + 1).unwrap_or(0);
+        let [transaction, account, side, amount, date] = JOURNAL_COLUMNS.map(position);
+
+        let mut statements: Vec<Statement> = vec![];
+        let mut indices: HashMap<&str, usize> = HashMap::new();
+        for row in rows.iter() {
+            let value = match table.is_null(&table.columns[amount - 1], &row[amount]) {
+                true => Decimal::ZERO,
+                false => Decimal::from_str(&row[amount]).map_err(|_| {
+                    let (table, value) = (table.id_value.clone(), row[amount].clone());
+                    Error::from(StatementError::InvalidAmount { table, value })
+                })?,
+            };
+            if row[date].len() != 10 || parse_timestamp_micros(&format!("{} 00:00:00", row[date])).is_none() {
+                return Err(Error::from(StatementError::InvalidDate {
+                    table: table.id_value.clone(),
+                    value: row[date].clone(),
+                }));
+            }
+
+            let index = *indices.entry(row[account].as_str()).or_insert_with(|| {
+                let (code, name) = accounts.get(&row[account])
+                    .cloned()
+                    .unwrap_or_else(|| (row[account].clone(), String::new()));
+                statements.push(Statement {
+                    account: row[account].clone(),
+                    code,
+                    name,
+                    number,
+                    opening: balances.get(&row[account]).copied().unwrap_or_default(),
+                    lines: vec![],
+                });
+                statements.len() - 1
+            });
+            statements[index].lines.push(StatementLine {
+                date: row[date].clone(),
+                amount: if row[side] == "D" { -value } else { value },
+                transaction: row[transaction].clone(),
+                entry: row[0].clone(),
+            });
+        }
+
+        for statement in statements.iter_mut() {
+            statement.lines.sort_by(|x, y| x.date.cmp(&y.date));
+            balances.insert(statement.account.clone(), statement.closing());
+        }
+        Ok(statements)
+    }
+
+
+    /// Writes an MT940 file of account statements for every file, booking the journal entries of the file. Files are
+    /// generated in order so every statement opens with the balance the account's previous statement closed with.
+    pub fn generate_mt940(&self, folder_path: &Path) -> Result<()> {
+        let (journal, accounts_table) = self.statement_tables()?;
+        fs::create_dir_all(folder_path)?;
+        self.progress_start(self.number_of_files);
+
+        let mut accounts: HashMap<String, (String, String)> = HashMap::new();
+        let mut balances: HashMap<String, Decimal> = HashMap::new();
+        (0..self.number_of_files)
+            .try_for_each(|x| -> Result<()> {
+                let started = Instant::now();
+                let rows = self.generate_file_rows(x)?;
+                if let Some(index) = accounts_table {
+                    let columns = &self.tables[index].columns;
+                    let position = |name: &str| columns.iter().position(|y| y.name == name).map(|y| y + 1);
+                    let (id, code, name) = (position("id").unwrap_or(0), position("code"), position("name"));
+                    accounts.extend(rows[index].iter().map(|y| {
+                        let value = |column: Option<usize>| y[column.unwrap_or(id)].clone();
+                        (y[id].clone(), (value(code), value(name)))
+                    }));
+                }
+
+                let statements = self.statements(&rows[journal], &accounts, &mut balances, x + 1)?;
+                let data = statements.iter().map(|y| y.to_mt940()).collect::<String>().into_bytes();
+                let table = &self.tables[journal];
+                let file_path = self.binary_file_path(folder_path, table, x, "sta");
+                fs::write(&file_path, &data)?;
+                self.progress_rows(&table.id_value, rows[journal].len() as u64, data.len() as u64);
+
+                self.file_written(FileMetadata {
+                    path: file_path,
+                    file_index: x,
+                    tables: vec![TableMetadata {
+                        table: table.id_value.clone(),
+                        rows: rows[journal].len() as u64,
+                        bytes: data.len() as u64,
+                    }],
+                    bytes: data.len() as u64,
+                    checksum: crc32_update(0, &data),
+                    sha256: sha256_hex(&data),
+                    duration: started.elapsed(),
+                })
+            })
+            .inspect_err(|x| self.progress_error(x))?;
+
+        self.progress_finish();
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{finance, SizeSpec, Table};
+    use crate::generators::finance::ACCOUNTS;
+
+    // Balances of the `:60F:` and `:62F:` fields of every statement, by account.
+    fn balances(mt940: &str) -> Vec<(String, Decimal, Decimal)> {
+        let balance = |x: &str| {
+            let amount = Decimal::from_str(&x[10..].replace(',', ".")).unwrap();
+            if x.starts_with('D') { -amount } else { amount }
+        };
+        mt940.split("-\r\n")
+            .filter(|x| !x.is_empty())
+            .map(|x| {
+                let field = |tag: &str| x.lines().find_map(|y| y.strip_prefix(tag)).unwrap();
+                (field(":25:").to_string(), balance(field(":60F:")), balance(field(":62F:")))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mt940_test() {
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        let ef = ExportFile::new(finance::tables(Decimal::new(1, 1)).unwrap(), 20_000, 2).unwrap()
+            .with_seed(2)
+            .with_size_spec(SizeSpec::Rows(HashMap::from([
+                ("chart_of_accounts".to_string(), ACCOUNTS.len() as u64),
+                ("journal_entry".to_string(), 80 * finance::LINES_PER_TRANSACTION),
+            ])))
+            .unwrap();
+        ef.generate_mt940(&folder).unwrap();
+        let files: Vec<String> = (0..2)
+            .map(|x| fs::read_to_string(ef.binary_file_path(&folder, &ef.tables[1], x, "sta")).unwrap())
+            .collect();
+        fs::remove_dir_all(&folder).unwrap();
+
+        let (first, second) = (balances(&files[0]), balances(&files[1]));
+        assert!(files[0].contains(":28C:00001/001") && files[1].contains(":28C:00002/001"));
+        assert!(first.iter().all(|x| x.1.is_zero()));
+        // Journal entries balance, so do the closing balances of all accounts.
+        assert_eq!(first.iter().map(|x| x.2).sum::<Decimal>(), Decimal::ZERO);
+        for (account, opening, _) in second.iter() {
+            let previous = first.iter().find(|x| &x.0 == account).map(|x| x.2).unwrap_or_default();
+            assert_eq!(*opening, previous);
+        }
+
+        // A booking and its description for every journal line of the file.
+        let count = |tag: &str| files[0].lines().filter(|x| x.starts_with(tag)).count() as u64;
+        assert_eq!(count(":61:"), ef.table_row_count(&ef.tables[1], 0).unwrap());
+        assert_eq!(count(":86:"), count(":61:"));
+        let chart = finance::preset_columns("chart_of_accounts").unwrap();
+        let only_accounts = ExportFile::new(vec![Table::new("A".into(), chart, "|".into(), Decimal::ONE)], 1_000, 1)
+            .unwrap();
+        assert!(only_accounts.generate_mt940(&folder).is_err());
+    }
+}
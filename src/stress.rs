@@ -0,0 +1,177 @@
+use crate::Table;
+use crate::random::{mix, RandomSource, SeededRandom};
+
+// Values are stressed from their own stream so it does not depend on the seed of any column or on corruption.
+const STRESS_STREAM: u64 = u64::MAX / 13;
+
+
+fn clamp_probability(probability: f64) -> f64 {
+    if probability.is_nan() { 0.0 } else { probability.clamp(0.0, 1.0) }
+}
+
+
+/// Probabilities of rendering a value of a numeric column the way messy real exports do, each drawn per value.
+/// Values stay numbers a lenient parser accepts, unlike [`crate::corruption::Corruption::NonNumeric`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumericStress {
+    /// Rendered with the locale separators, e.g. `1.234,5` for `1234.5`.
+    pub separators: f64,
+    pub decimal_separator: char,
+    pub thousands_separator: Option<char>,
+    /// A `+` before values that are not negative.
+    pub plus_sign: f64,
+    /// A space before and after the value.
+    pub spaces: f64,
+    /// Double quotes around the value.
+    pub quotes: f64,
+}
+
+impl Default for NumericStress {
+    fn default() -> Self {
+        NumericStress {
+            separators: 0.0,
+            decimal_separator: ',',
+            thousands_separator: None,
+            plus_sign: 0.0,
+            spaces: 0.0,
+            quotes: 0.0,
+        }
+    }
+}
+
+impl NumericStress {
+    pub fn new() -> Self {
+        NumericStress::default()
+    }
+
+    pub fn with_separators(mut self, probability: f64, decimal: char, thousands: Option<char>) -> Self {
+        self.separators = clamp_probability(probability);
+        self.decimal_separator = decimal;
+        self.thousands_separator = thousands;
+        self
+    }
+
+    pub fn with_plus_sign(mut self, probability: f64) -> Self {
+        self.plus_sign = clamp_probability(probability);
+        self
+    }
+
+    pub fn with_spaces(mut self, probability: f64) -> Self {
+        self.spaces = clamp_probability(probability);
+        self
+    }
+
+    pub fn with_quotes(mut self, probability: f64) -> Self {
+        self.quotes = clamp_probability(probability);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        [self.separators, self.plus_sign, self.spaces, self.quotes].iter().all(|x| *x <= 0.0)
+    }
+
+
+    // Values that are not plain decimal numbers, e.g. with an exponent, keep their separators.
+    fn with_locale_separators(&self, value: &str) -> String {
+        let (sign, number) = value.split_at(usize::from(value.starts_with('-')));
+        let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if integer.is_empty() || !integer.chars().chain(fraction.chars()).all(|x| x.is_ascii_digit()) {
+            return value.into();
+        }
+
+        let mut rendered = sign.to_string();
+        for (i, digit) in integer.chars().enumerate() {
+            if let Some(separator) = self.thousands_separator.filter(|_| i > 0 && (integer.len() - i) % 3 == 0) {
+                rendered.push(separator);
+            }
+            rendered.push(digit);
+        }
+        if number.contains('.') {
+            rendered.push(self.decimal_separator);
+            rendered.push_str(fraction);
+        }
+        rendered
+    }
+
+
+    pub fn apply(&self, value: &str, random: &dyn RandomSource) -> String {
+        let mut value = value.to_string();
+        if random.chance(self.separators) {
+            value = self.with_locale_separators(&value);
+        }
+        if random.chance(self.plus_sign) && !value.starts_with('-') {
+            value.insert(0, '+');
+        }
+        if random.chance(self.spaces) {
+            value = format!(" {} ", value);
+        }
+        if random.chance(self.quotes) {
+            value = format!("\"{}\"", value);
+        }
+        value
+    }
+}
+
+
+impl Table {
+    /// Renders numeric values with the profile's probabilities after unique constraints and row dependent columns
+    /// saw them as generated, nulls are left alone. Like corruption it only makes sense for text output.
+    pub fn with_numeric_stress(mut self, numeric_stress: NumericStress) -> Self {
+        self.numeric_stress = numeric_stress;
+        self
+    }
+
+
+    pub(crate) fn stress_row(&self, row_index: u64, row: &mut [String]) {
+        if self.numeric_stress.is_empty() {
+            return;
+        }
+
+        for (index, column) in self.columns.iter().enumerate() {
+            if !column.column_type.is_numeric() || self.is_null(column, &row[index + 1]) {
+                continue;
+            }
+            let seed = mix(mix(self.seed.unwrap_or(0), row_index), index as u64);
+            row[index + 1] = self.numeric_stress.apply(&row[index + 1], &SeededRandom::new(mix(seed, STRESS_STREAM)));
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column};
+    use crate::types::ColumnType;
+
+    #[test]
+    fn numeric_stress_test() {
+        let random = SeededRandom::new(1);
+        let locale = NumericStress::new().with_separators(1.0, ',', Some('.'));
+        assert_eq!(locale.apply("-1234567.25", &random), "-1.234.567,25");
+        assert_eq!(locale.apply("123", &random), "123");
+        assert_eq!(locale.apply("1e-7", &random), "1e-7");
+        let all = NumericStress::new().with_plus_sign(1.0).with_spaces(1.0).with_quotes(1.0);
+        assert_eq!(all.apply("12.5", &random), "\" +12.5 \"");
+        assert_eq!(all.apply("-3", &random), "\" -3 \"");
+
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1000, 1)),
+                Column::new("code".into(), 4, ColumnType::Char(4), generators::constant("1000")),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        )
+            .with_seed(5)
+            .with_numeric_stress(NumericStress::new().with_separators(0.5, ',', Some(' ')));
+        let rows = t.generate_rows_vec(100).unwrap();
+        let stressed = rows.iter().filter(|x| x[1].contains(' ')).count();
+        assert!(stressed > 20 && stressed < 80);
+        assert!(rows.iter().all(|x| x[2] == "1000"));
+        assert_eq!(t.generate_table_row_vec_at(7).unwrap(), rows[7]);
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+use thiserror::Error;
+
+use crate::{generators, Column, ExportFile, Table};
+use crate::generators::survey::Questionnaire;
+use crate::types::{ColumnSize, ColumnType};
+
+pub const PRESETS: [&str; 1] = ["survey_response"];
+/// Answer columns of a response.
+pub const QUESTIONS: u64 = 300;
+pub const SECTIONS: u64 = 12;
+
+
+#[derive(Error, Debug)]
+pub enum SurveyPresetError {
+    #[error("Unknown preset {preset}, expected survey_response.")]
+    UnknownPreset { preset: String },
+    #[error("Table {table} has no column {column} to check the skip logic with.")]
+    MissingColumn { table: String, column: String },
+    #[error("Response {id} of table {table} breaks the skip logic, {reason}.")]
+    Broken { table: String, id: String, reason: String },
+}
+
+
+fn questionnaire() -> Result<Questionnaire> {
+    Questionnaire::new(QUESTIONS, SECTIONS)
+}
+
+
+/// Columns of a preset, the respondent's screening and an answer column for every question, most of them null.
+pub fn preset_columns(preset: &str) -> Result<Vec<Column>> {
+    let questionnaire = questionnaire()?;
+
+    match preset {
+        "survey_response" => Ok(
+            [
+                Column::new("id".into(), ColumnSize::range(1, 10), ColumnType::Int, generators::sequence(1, 1))
+                    .as_key(),
+                Column::new("screened_in".into(), ColumnSize::range(4, 5), ColumnType::Boolean,
+                            questionnaire.screened_in()),
+            ]
+                .into_iter()
+                .chain((0..QUESTIONS).map(|x| {
+                    // Gates are answered Y or N, the rest on a scale of 1 to 5.
+                    let column_type = if questionnaire.is_gate(x) { ColumnType::Char(1) } else { ColumnType::SmallInt };
+                    Column::new(questionnaire.column_name(x), 1, column_type, questionnaire.question(x))
+                }))
+                .collect()
+        ),
+        _ => Err(Error::from(SurveyPresetError::UnknownPreset { preset: preset.into() })),
+    }
+}
+
+
+pub fn tables() -> Result<Vec<Table>> {
+    Ok(vec![Table::new(PRESETS[0].into(), preset_columns(PRESETS[0])?, "|".into(), Decimal::ONE)])
+}
+
+
+pub fn export_file(file_size_bytes: u64, number_of_files: u64) -> Result<ExportFile> {
+    ExportFile::new(tables()?, file_size_bytes, number_of_files)
+}
+
+
+/// Checks generated responses of the survey table: screened out respondents answer nothing, sections whose gate is
+/// not answered yes are skipped and answers are on the 1 to 5 scale.
+pub fn check_skip_logic(table: &Table, rows: &[Vec<String>]) -> Result<()> {
+    let questionnaire = questionnaire()?;
+    let position = |name: &str| {
+        table.columns.iter()
+            .position(|x| x.name == name)
+            .map(|x| x + 1)
+            .ok_or_else(|| Error::from(SurveyPresetError::MissingColumn {
+                table: table.id_value.clone(),
+                column: name.into(),
+            }))
+    };
+    let (id, screened_in) = (position("id")?, position("screened_in")?);
+    let questions = (0..QUESTIONS)
+        .map(|x| position(&questionnaire.column_name(x)))
+        .collect::<Result<Vec<usize>>>()?;
+
+    for row in rows.iter() {
+        let broken = |reason: String| {
+            Error::from(SurveyPresetError::Broken { table: table.id_value.clone(), id: row[id].clone(), reason })
+        };
+        let answered = |question: u64| {
+            let index = questions[question as usize];
+            (!table.is_null(&table.columns[index - 1], &row[index])).then(|| row[index].as_str())
+        };
+
+        let mut gate = None;
+        for question in 0..QUESTIONS {
+            let answer = answered(question);
+            let name = questionnaire.column_name(question);
+            if row[screened_in] != "true" && answer.is_some() {
+                return Err(broken(format!("a screened out respondent answered {}", name)));
+            }
+            if questionnaire.is_gate(question) {
+                gate = answer;
+                if !matches!(answer, None | Some("Y" | "N")) {
+                    return Err(broken(format!("gate {} is not Y or N", name)));
+                }
+                continue;
+            }
+            match answer {
+                Some(_) if gate != Some("Y") => {
+                    return Err(broken(format!("{} is answered in a skipped section", name)));
+                }
+                Some(x) if !matches!(x.parse::<u64>(), Ok(1..=5)) => {
+                    return Err(broken(format!("{} is not on the 1 to 5 scale", name)));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ddl::Dialect;
+
+    #[test]
+    fn survey_preset_test() {
+        let ef = export_file(2_000_000, 2).unwrap().with_seed(7);
+        let rows: Vec<Vec<String>> = (0..2).flat_map(|x| ef.generate_file_rows(x).unwrap().swap_remove(0)).collect();
+        let table = &ef.tables[0];
+
+        assert_eq!(table.columns.len() as u64, QUESTIONS + 2);
+        assert!(rows.len() > 1000);
+        check_skip_logic(table, &rows).unwrap();
+        let nulls = rows.iter().flat_map(|x| x[3..].iter()).filter(|x| x.is_empty()).count();
+        assert!(nulls * 2 > rows.len() * QUESTIONS as usize);
+        let ddl = ef.get_schema_ddl(Dialect::Postgres).unwrap();
+        assert!(ddl.contains("    s01_q02 SMALLINT,\n") && ddl.contains("    screened_in BOOLEAN NOT NULL,\n"));
+
+        let mut skipped = rows.clone();
+        let row = skipped.iter_mut().find(|x| x[2] == "true" && x[3] == "N").unwrap();
+        row[4] = "3".into();
+        assert!(check_skip_logic(table, &skipped).is_err());
+        assert!(preset_columns("census").is_err());
+    }
+}
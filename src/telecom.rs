@@ -0,0 +1,148 @@
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+use thiserror::Error;
+
+use crate::{generators, Column, Table};
+use crate::generators::telecom::{self as calls, CallDetail, FIRST_CELL_ID};
+use crate::types::{ColumnSize, ColumnType};
+use crate::values::parse_timestamp_micros;
+
+pub const PRESETS: [&str; 2] = ["cell_tower", "call_detail_record"];
+/// Phone numbers calling each other in the call detail records of the preset.
+pub const SUBSCRIBERS: u64 = 10_000;
+/// Towers calls are made over, size the `cell_tower` table to this many rows for every cell id to be in it.
+pub const TOWERS: u64 = 100;
+
+
+#[derive(Error, Debug)]
+pub enum CdrError {
+    #[error("Unknown preset {preset}, expected cell_tower or call_detail_record.")]
+    UnknownPreset { preset: String },
+    #[error("Table {table} has no column {column} to check calls with.")]
+    MissingColumn { table: String, column: String },
+    #[error("Call {id} of table {table} is inconsistent, {reason}.")]
+    Inconsistent { table: String, id: String, reason: String },
+}
+
+
+/// Columns of a preset, call detail records hold the cell id and location area code of a `cell_tower` row.
+pub fn preset_columns(preset: &str) -> Result<Vec<Column>> {
+    let int = |name: &str, size: ColumnSize, generator| Column::new(name.into(), size, ColumnType::Int, generator);
+
+    Ok(match preset {
+        "cell_tower" => vec![
+            int("cell_id", ColumnSize::range(5, 6), calls::tower_cell_id()).as_key(),
+            int("lac", 4.into(), calls::tower_lac()),
+            Column::new("latitude".into(), 9, ColumnType::Decimal(9, 6), calls::tower_latitude()),
+            Column::new("longitude".into(), 9, ColumnType::Decimal(9, 6), calls::tower_longitude()),
+        ],
+        "call_detail_record" => {
+            let detail = CallDetail::new(SUBSCRIBERS, TOWERS)?;
+            vec![
+                int("id", ColumnSize::range(1, 10), generators::sequence(1, 1)).as_key(),
+                Column::new("caller".into(), 12, ColumnType::Char(12), detail.caller()),
+                Column::new("callee".into(), 12, ColumnType::Char(12), detail.callee()),
+                Column::new("call_type".into(), ColumnSize::range(3, 5), ColumnType::Varchar(5), detail.call_type()),
+                Column::new("start_time".into(), 19, ColumnType::Timestamp, detail.start_time()),
+                Column::new("end_time".into(), 19, ColumnType::Timestamp, detail.end_time()),
+                int("duration", ColumnSize::range(1, 4), detail.duration()),
+                int("cell_id", ColumnSize::range(5, 6), detail.cell_id()),
+                int("lac", 4.into(), detail.lac()),
+                int("end_cell_id", ColumnSize::range(5, 6), detail.end_cell_id()),
+            ]
+        }
+        _ => return Err(Error::from(CdrError::UnknownPreset { preset: preset.into() })),
+    })
+}
+
+
+/// Cell towers once and call detail records taking the rest of the export.
+pub fn tables(percent_size_towers: Decimal) -> Result<Vec<Table>> {
+    PRESETS.iter()
+        .zip([percent_size_towers, Decimal::ONE - percent_size_towers])
+        .map(|(preset, percent_size)| {
+            Ok(Table::new(preset.to_string(), preset_columns(preset)?, "|".into(), percent_size))
+        })
+        .collect()
+}
+
+
+/// Checks that every call in `rows` of a call detail record table is between two numbers, ends `duration` seconds
+/// after it starts and has the location area code of its cell, so a pipeline test can trust the data it starts from.
+/// Rows with null or corrupted values in these columns are reported as inconsistent.
+pub fn check_calls(table: &Table, rows: &[Vec<String>]) -> Result<()> {
+    let position = |name: &str| {
+        table.columns.iter()
+            .position(|x| x.name == name)
+            .map(|x| x + 1)
+            .ok_or_else(|| Error::from(CdrError::MissingColumn { table: table.id_value.clone(), column: name.into() }))
+    };
+    let (caller, callee, call_type) = (position("caller")?, position("callee")?, position("call_type")?);
+    let (start, end, duration) = (position("start_time")?, position("end_time")?, position("duration")?);
+    let (cell_id, lac) = (position("cell_id")?, position("lac")?);
+    let seconds = |x: &str| parse_timestamp_micros(x).map(|y| y.div_euclid(1_000_000));
+
+    for row in rows {
+        let seconds_taken = row[duration].parse::<i64>().ok();
+        let times = seconds(&row[start]).zip(seconds_taken).zip(seconds(&row[end]));
+        let tower = row[cell_id].parse::<u64>().ok().and_then(|x| x.checked_sub(FIRST_CELL_ID));
+
+        let reason = if row[caller] == row[callee] {
+            Some("the caller is the callee")
+        } else if times.is_none_or(|((x, y), z)| x + y != z) {
+            Some("it does not end duration seconds after it starts")
+        } else if row[call_type] == "sms" && seconds_taken != Some(0) {
+            Some("an sms has a duration")
+        } else if tower.is_none_or(|x| calls::lac(x).to_string() != row[lac]) {
+            Some("its location area code is not the one of its cell")
+        } else {
+            None
+        };
+
+        if let Some(reason) = reason {
+            return Err(Error::from(CdrError::Inconsistent {
+                table: table.id_value.clone(),
+                id: row[0].clone(),
+                reason: reason.into(),
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::*;
+    use crate::{ExportFile, SizeSpec};
+
+    #[test]
+    fn call_detail_record_test() {
+        let ef = ExportFile::new(tables(Decimal::new(1, 1)).unwrap(), 50_000, 2)
+            .unwrap()
+            .with_size_spec(SizeSpec::Rows(HashMap::from([
+                ("cell_tower".to_string(), TOWERS),
+                ("call_detail_record".to_string(), 400),
+            ])))
+            .unwrap();
+        let files = (0..2).map(|x| ef.generate_file_rows(x).unwrap()).collect::<Vec<_>>();
+        let towers: HashMap<String, String> = files.iter()
+            .flat_map(|x| x[0].iter().map(|y| (y[1].clone(), y[2].clone())))
+            .collect();
+        let records = files.iter().flat_map(|x| x[1].clone()).collect::<Vec<Vec<String>>>();
+
+        assert_eq!((towers.len(), records.len()), (TOWERS as usize, 400));
+        check_calls(&ef.tables[1], &records).unwrap();
+        // Cells and location areas of the records are the ones of the towers.
+        assert!(records.iter().all(|x| towers.get(&x[8]) == Some(&x[9]) && towers.contains_key(&x[10])));
+        assert!(records.iter().map(|x| &x[2]).collect::<HashSet<_>>().len() > 100);
+
+        let mut inconsistent = records.clone();
+        inconsistent[3][7] = "99999".into();
+        assert!(check_calls(&ef.tables[1], &inconsistent).is_err());
+        assert!(preset_columns("sms").is_err());
+    }
+}
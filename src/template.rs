@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use rust_decimal::prelude::*;
+use thiserror::Error;
+
+use crate::{Column, Table};
+use crate::builder::TableBuilder;
+use crate::compose::normalize;
+use crate::types::RecordTerminator;
+
+
+#[derive(Error, Debug)]
+pub enum TemplateError {
+    #[error("Template {template} uses parameter {parameter} which was not given.")]
+    MissingParameter { template: String, parameter: String },
+    #[error("Template {template} has an unclosed placeholder.")]
+    UnclosedPlaceholder { template: String },
+}
+
+
+pub type Parameters = HashMap<String, String>;
+
+type ColumnFactory = dyn Fn(&Parameters) -> Column + Send + Sync;
+
+
+// Replaces every `{name}` in the template with the matching parameter.
+pub(crate) fn substitute(template: &str, parameters: &Parameters) -> Result<String> {
+    let mut result = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}')
+            .ok_or_else(|| Error::from(TemplateError::UnclosedPlaceholder { template: template.into() }))?;
+        let name = &rest[start + 1..start + end];
+        let value = parameters.get(name)
+            .ok_or_else(|| Error::from(TemplateError::MissingParameter {
+                template: template.into(),
+                parameter: name.into(),
+            }))?;
+
+        result.push_str(&rest[..start]);
+        result.push_str(value);
+        rest = &rest[start + end + 1..];
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
+
+
+#[derive(Clone)]
+pub struct TableTemplate {
+    id_template: String,
+    columns: Vec<Arc<ColumnFactory>>,
+    delimiter: String,
+    terminator: RecordTerminator,
+}
+
+impl TableTemplate {
+    pub fn new(id_template: impl Into<String>) -> Self {
+        TableTemplate {
+            id_template: id_template.into(),
+            columns: vec![],
+            delimiter: "|".into(),
+            terminator: RecordTerminator::Lf,
+        }
+    }
+
+    /// Adds a column shared by every instance, `{name}` placeholders in its name are substituted.
+    pub fn add_column(self, column: Column) -> Self {
+        self.add_column_with(move |_| column.clone())
+    }
+
+    /// Adds a column built from the instance parameters, e.g. with a parameter specific value pool.
+    pub fn add_column_with(mut self, factory: impl Fn(&Parameters) -> Column + Send + Sync + 'static) -> Self {
+        self.columns.push(Arc::new(factory));
+        self
+    }
+
+    pub fn delimiter(mut self, delimiter: impl Into<String>) -> Self {
+        self.delimiter = delimiter.into();
+        self
+    }
+
+    pub fn terminator(mut self, terminator: RecordTerminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    pub fn instantiate(&self, parameters: &Parameters, percent_size: Decimal) -> Result<Table> {
+        let mut builder = TableBuilder::new(substitute(&self.id_template, parameters)?)
+            .delimiter(self.delimiter.clone())
+            .terminator(self.terminator.clone())
+            .percent_size(percent_size);
+
+        for factory in self.columns.iter() {
+            let mut column = factory(parameters);
+            column.name = substitute(&column.name, parameters)?;
+            builder = builder.add_column(column);
+        }
+
+        builder.build()
+    }
+
+    /// One table per parameter set, splitting `percent_size` evenly between them.
+    pub fn instantiate_all(&self, parameters: &[Parameters], percent_size: Decimal) -> Result<Vec<Table>> {
+        let weights = normalize(&vec![Decimal::ONE; parameters.len()])?;
+
+        parameters.iter()
+            .zip(weights)
+            .map(|(x, weight)| self.instantiate(x, weight * percent_size))
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ExportFile;
+    use crate::types::ColumnType;
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    fn region(name: &str) -> Parameters {
+        HashMap::from([("region".to_string(), name.to_string())])
+    }
+
+    #[test]
+    fn substitute_test() {
+        assert_eq!(substitute("events_{region}", &region("eu")).unwrap(), "events_eu");
+        assert!(substitute("events_{zone}", &region("eu")).is_err());
+        assert!(substitute("events_{region", &region("eu")).is_err());
+    }
+
+    #[test]
+    fn instantiate_all_test() {
+        let template = TableTemplate::new("events_{region}")
+            .add_column(Column::new("code".into(), 3, ColumnType::Char(3), simple_generator))
+            .add_column_with(|x| {
+                let region = x["region"].clone();
+                Column::new("region".into(), 2, ColumnType::Char(2), move || Ok(region.clone()))
+            });
+
+        let tables = template
+            .instantiate_all(&[region("eu"), region("us"), region("ap")], Decimal::ONE)
+            .unwrap();
+
+        assert_eq!(tables.len(), 3);
+        assert_eq!(tables[1].generate_table_row().unwrap(), "events_us|ABC|us\n");
+        assert!(ExportFile::new(tables, 1000, 1).is_ok());
+    }
+}
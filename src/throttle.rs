@@ -0,0 +1,170 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::config::parse_size;
+use crate::{ExportFile, Table};
+
+
+#[derive(Error, Debug)]
+pub enum ThrottleError {
+    #[error("Throttle rate must be a positive number per second, got {rate}.")]
+    InvalidRate { rate: f64 },
+    #[error("Unknown throttle {throttle}, expected e.g. 500rows/s or 1MB/s.")]
+    Unknown { throttle: String },
+}
+
+
+/// Rate generated data is written at, so an export trickles into its destination like a live source system.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Throttle {
+    RowsPerSecond(f64),
+    /// Uncompressed bytes.
+    BytesPerSecond(f64),
+}
+
+impl Throttle {
+    fn rate(&self) -> f64 {
+        match self {
+            Throttle::RowsPerSecond(x) | Throttle::BytesPerSecond(x) => *x,
+        }
+    }
+
+    /// Rows of `table` written in about a second, at least 1.
+    pub fn rows_per_second(&self, table: &Table) -> u64 {
+        match self {
+            Throttle::RowsPerSecond(x) => *x as u64,
+            Throttle::BytesPerSecond(x) => *x as u64 / table.row_size_bytes.max(1),
+        }.max(1)
+    }
+}
+
+impl FromStr for Throttle {
+    type Err = Error;
+
+    /// `500rows/s`, `500 rows/s` or a size per second like `1MB/s`.
+    fn from_str(throttle: &str) -> Result<Self> {
+        let unknown = || Error::from(ThrottleError::Unknown { throttle: throttle.into() });
+        let rate = throttle.trim().strip_suffix("/s").ok_or_else(unknown)?;
+
+        match rate.strip_suffix("rows") {
+            Some(rows) => Ok(Throttle::RowsPerSecond(rows.trim().parse().map_err(|_| unknown())?)),
+            None => Ok(Throttle::BytesPerSecond(parse_size(rate).map_err(|_| unknown())? as f64)),
+        }
+    }
+}
+
+impl fmt::Display for Throttle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Throttle::RowsPerSecond(x) => write!(f, "{}rows/s", x),
+            Throttle::BytesPerSecond(x) => write!(f, "{}B/s", x),
+        }
+    }
+}
+
+
+/// Keeps writers of all files together at the throttle's rate, writers wait until their rows are due.
+#[derive(Debug)]
+pub(crate) struct Pacer {
+    pub(crate) throttle: Throttle,
+    // Start of the run and rows or bytes written since.
+    state: Mutex<Option<(Instant, f64)>>,
+}
+
+impl Pacer {
+    fn pace(&self, rows: u64, bytes: u64) {
+        let amount = match self.throttle {
+            Throttle::RowsPerSecond(_) => rows as f64,
+            Throttle::BytesPerSecond(_) => bytes as f64,
+        };
+        let due = {
+            let mut state = self.state.lock().unwrap_or_else(|x| x.into_inner());
+            let (started, written) = state.get_or_insert_with(|| (Instant::now(), 0.0));
+            *written += amount;
+            *started + Duration::from_secs_f64(*written / self.throttle.rate())
+        };
+
+        thread::sleep(due.saturating_duration_since(Instant::now()));
+    }
+
+    fn restart(&self) {
+        *self.state.lock().unwrap_or_else(|x| x.into_inner()) = None;
+    }
+}
+
+
+impl ExportFile {
+    /// Writes files and loads database sinks no faster than `throttle`, with rows of a file written in chunks of
+    /// about a second of data.
+    pub fn with_throttle(mut self, throttle: Throttle) -> Result<Self> {
+        if !throttle.rate().is_finite() || throttle.rate() <= 0.0 {
+            return Err(Error::from(ThrottleError::InvalidRate { rate: throttle.rate() }));
+        }
+
+        self.throttle = Some(Pacer { throttle, state: Mutex::new(None) });
+        Ok(self)
+    }
+
+
+    // Every writer reports the rows it wrote through progress, which waits here until they are due.
+    pub(crate) fn pace(&self, rows: u64, bytes: u64) {
+        if let Some(pacer) = &self.throttle {
+            pacer.pace(rows, bytes);
+        }
+    }
+
+
+    pub(crate) fn restart_pace(&self) {
+        if let Some(pacer) = &self.throttle {
+            pacer.restart();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column};
+    use crate::types::ColumnType;
+
+    #[test]
+    fn throttle_test() {
+        assert_eq!("500rows/s".parse::<Throttle>().unwrap(), Throttle::RowsPerSecond(500.0));
+        assert_eq!(" 2.5 rows/s".parse::<Throttle>().unwrap(), Throttle::RowsPerSecond(2.5));
+        assert_eq!("1KB/s".parse::<Throttle>().unwrap(), Throttle::BytesPerSecond(1024.0));
+        assert!("1KB".parse::<Throttle>().is_err());
+        assert!("fast/s".parse::<Throttle>().is_err());
+
+        let t = Table::new(
+            "A".into(),
+            vec![Column::new("id".into(), 2, ColumnType::Int, generators::sequence(1, 1))],
+            "|".into(),
+            Decimal::ONE,
+        );
+        // 20 rows at 50 rows a second.
+        let ef = ExportFile::new(vec![t], 100, 2).unwrap()
+            .with_throttle(Throttle::RowsPerSecond(50.0))
+            .unwrap();
+        assert_eq!(ef.table_total_rows(&ef.tables[0]).unwrap(), 20);
+
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        let started = Instant::now();
+        ef.generate_all_files(&folder).unwrap();
+        let elapsed = started.elapsed();
+        fs::remove_dir_all(&folder).unwrap();
+
+        assert!(elapsed >= Duration::from_millis(390), "{:?}", elapsed);
+        assert!(ef.with_throttle(Throttle::BytesPerSecond(0.0)).is_err());
+    }
+}
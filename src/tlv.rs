@@ -0,0 +1,141 @@
+use std::path::Path;
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::{ExportFile, Table};
+
+
+#[derive(Error, Debug)]
+pub enum TlvError {
+    #[error("Table {table} has more than {} columns, column positions do not fit a tag.", u16::MAX)]
+    TooManyColumns { table: String },
+    #[error("Value of column {column} is {length} bytes, longer than a length prefix holds.")]
+    ValueTooLong { column: String, length: usize },
+    #[error("TLV data ends in the middle of a record.")]
+    Truncated,
+}
+
+
+fn length_prefix(length: usize, column: &str) -> Result<[u8; 4]> {
+    u32::try_from(length)
+        .map(|x| x.to_be_bytes())
+        .map_err(|_| Error::from(TlvError::ValueTooLong { column: column.into(), length }))
+}
+
+
+impl Table {
+    /// Length prefixed binary records: a big endian `u32` record length followed by a field per value, each a `u16`
+    /// tag of the column position counted from 1, a `u32` value length and the value in the table encoding.
+    /// Null values are left out of the record.
+    pub fn rows_to_tlv(&self, rows: &[Vec<String>]) -> Result<Vec<u8>> {
+        if self.columns.len() > u16::MAX as usize {
+            return Err(Error::from(TlvError::TooManyColumns { table: self.id_value.clone() }));
+        }
+
+        let mut data = Vec::new();
+        let mut record = Vec::new();
+        for row in rows {
+            record.clear();
+            // Column 0 of a generated row is the table id, which is the file the record is in.
+            for (index, (column, value)) in self.columns.iter().zip(row.iter().skip(1)).enumerate() {
+                if self.is_null(column, value) {
+                    continue;
+                }
+                let bytes = self.encoding.encode(value)?;
+                record.extend((index as u16 + 1).to_be_bytes());
+                record.extend(length_prefix(bytes.len(), &column.name)?);
+                record.extend(bytes.as_ref());
+            }
+            data.extend(length_prefix(record.len(), &self.id_value)?);
+            data.extend(&record);
+        }
+
+        Ok(data)
+    }
+}
+
+
+/// Fields of a record as `(tag, value)`.
+pub type TlvRecord = Vec<(u16, Vec<u8>)>;
+
+
+fn read_u32(data: &[u8], position: usize) -> Result<(u32, usize)> {
+    let bytes = data.get(position..position + 4).ok_or_else(|| Error::from(TlvError::Truncated))?;
+    Ok((u32::from_be_bytes(bytes.try_into()?), position + 4))
+}
+
+
+/// Reads records written by [`Table::rows_to_tlv`] back.
+pub fn decode_tlv(data: &[u8]) -> Result<Vec<TlvRecord>> {
+    let mut records = vec![];
+    let mut position = 0;
+    while position < data.len() {
+        let (length, start) = read_u32(data, position)?;
+        let end = start + length as usize;
+        let record = data.get(start..end).ok_or_else(|| Error::from(TlvError::Truncated))?;
+
+        let mut fields = vec![];
+        let mut field = 0;
+        while field < record.len() {
+            let tag = record.get(field..field + 2).ok_or_else(|| Error::from(TlvError::Truncated))?;
+            let (value_length, value_start) = read_u32(record, field + 2)?;
+            let value_end = value_start + value_length as usize;
+            let value = record.get(value_start..value_end).ok_or_else(|| Error::from(TlvError::Truncated))?;
+            fields.push((u16::from_be_bytes(tag.try_into()?), value.to_vec()));
+            field = value_end;
+        }
+        records.push(fields);
+        position = end;
+    }
+
+    Ok(records)
+}
+
+
+impl ExportFile {
+    pub fn generate_tlv(&self, folder_path: &Path) -> Result<()> {
+        self.write_table_files(folder_path, "tlv", |table, rows| table.rows_to_tlv(rows))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column};
+    use crate::types::ColumnType;
+
+    #[test]
+    fn tlv_test() {
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("id".into(), 2, ColumnType::Int, generators::sequence(10, 1)),
+                Column::new("note".into(), 3, ColumnType::Varchar(3), generators::constant("abc")).with_null_rate(1.0),
+                Column::new("currency".into(), 3, ColumnType::Char(3), generators::constant("EUR")),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        );
+        let rows = t.generate_rows_vec(2).unwrap();
+        let data = t.rows_to_tlv(&rows).unwrap();
+
+        assert_eq!(&data[..4], &[0, 0, 0, 17]);
+        assert_eq!(data.len(), 42);
+        let records = decode_tlv(&data).unwrap();
+        assert_eq!(records[1], vec![(1, b"11".to_vec()), (3, b"EUR".to_vec())]);
+        assert!(decode_tlv(&data[..20]).is_err());
+
+        let ef = ExportFile::new(vec![t], 200, 2).unwrap();
+        let folder = std::env::temp_dir().join(format!("diplomski_projekt_{:08x}", rand::random::<u32>()));
+        ef.generate_tlv(&folder).unwrap();
+        let files = fs::read_dir(&folder).unwrap().count();
+        fs::remove_dir_all(&folder).unwrap();
+        assert_eq!(files, 2);
+    }
+}
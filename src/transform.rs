@@ -0,0 +1,117 @@
+use std::str::FromStr;
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::Column;
+
+
+#[derive(Error, Debug)]
+pub enum TransformError {
+    #[error("Unknown transform {name}, expected upper, lower, title, trim_left or trim_right.")]
+    UnknownTransform { name: String },
+}
+
+
+/// Formatting applied to a column's generated values, in the order the transforms were added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transform {
+    Prefix(String),
+    Suffix(String),
+    Upper,
+    Lower,
+    /// Upper case at the start of every word, lower case elsewhere.
+    Title,
+    /// Drops characters from the start until the value fits the column size.
+    TrimLeft,
+    /// Drops characters from the end until the value fits the column size.
+    TrimRight,
+}
+
+impl Transform {
+    pub fn apply(&self, value: String, size: u64) -> String {
+        let size = size as usize;
+        match self {
+            Transform::Prefix(x) => format!("{}{}", x, value),
+            Transform::Suffix(x) => value + x,
+            Transform::Upper => value.to_uppercase(),
+            Transform::Lower => value.to_lowercase(),
+            Transform::Title => {
+                let mut word_start = true;
+                value.chars()
+                    .flat_map(|x| {
+                        let upper = word_start;
+                        word_start = !x.is_alphanumeric();
+                        if upper { x.to_uppercase().collect::<Vec<char>>() } else { x.to_lowercase().collect() }
+                    })
+                    .collect()
+            }
+            Transform::TrimLeft => {
+                let chars = value.chars().count();
+                value.chars().skip(chars.saturating_sub(size)).collect()
+            }
+            Transform::TrimRight => value.chars().take(size).collect(),
+        }
+    }
+}
+
+impl FromStr for Transform {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "upper" => Ok(Transform::Upper),
+            "lower" => Ok(Transform::Lower),
+            "title" => Ok(Transform::Title),
+            "trim_left" => Ok(Transform::TrimLeft),
+            "trim_right" => Ok(Transform::TrimRight),
+            _ => Err(Error::from(TransformError::UnknownTransform { name: name.into() })),
+        }
+    }
+}
+
+
+impl Column {
+    /// Applies `transform` to every generated value after the transforms added before it, nulls are left alone.
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transforms.push(transform);
+        self
+    }
+
+    pub(crate) fn transform(&self, value: String) -> String {
+        self.transforms.iter().fold(value, |x, y| y.apply(x, self.size.max()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Table};
+    use crate::types::ColumnType;
+
+    #[test]
+    fn transform_test() {
+        assert_eq!(Transform::Title.apply("jANE o'neil-smith".into(), 20), "Jane O'Neil-Smith");
+        assert_eq!(Transform::TrimLeft.apply("0012345".into(), 5), "12345");
+        assert_eq!(Transform::TrimRight.apply("čćžšđ".into(), 3), "čćž");
+        assert_eq!("Trim_Right".parse::<Transform>().unwrap(), Transform::TrimRight);
+        assert!("reverse".parse::<Transform>().is_err());
+
+        let t = Table::new(
+            "A".into(),
+            vec![
+                Column::new("code".into(), 6, ColumnType::Char(6), generators::constant("abc"))
+                    .with_transform(Transform::Prefix("id-".into()))
+                    .with_transform(Transform::Suffix("-x".into()))
+                    .with_transform(Transform::Upper)
+                    .with_transform(Transform::TrimRight),
+            ],
+            "|".into(),
+            Decimal::ONE,
+        );
+        assert_eq!(t.generate_table_row_vec().unwrap()[1], "ID-ABC");
+    }
+}
@@ -0,0 +1,405 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use anyhow::{Error, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use thiserror::Error;
+
+use crate::ExportFile;
+use crate::builder::ExportFileBuilder;
+use crate::cli::CliArgs;
+use crate::config::parse_size;
+
+const TABLES_WIDTH: u16 = 40;
+const PREVIEW_ROWS: u64 = 5;
+const HELP: &str = "j/k or <n> select, p preview, s total size, f files, : command, g generate, q quit";
+
+
+#[derive(Error, Debug)]
+pub enum TuiError {
+    #[error("Unknown command {command}, type ? for help.")]
+    UnknownCommand { command: String },
+    #[error("Invalid value {value} for {command}.")]
+    InvalidValue { command: String, value: String },
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Continue,
+    Generate,
+    Quit,
+}
+
+
+/// Full screen terminal UI over a config: browse tables and columns, preview rows, change the size and generate.
+/// Single keys run commands right away, `s`, `f` and `:` open a command line for commands that take a value.
+pub struct Tui {
+    config: String,
+    size: Option<u64>,
+    files: Option<u64>,
+    seed: Option<u64>,
+    out: PathBuf,
+    export_file: ExportFile,
+    selected: usize,
+    preview: Vec<String>,
+    status: String,
+    input: Option<String>,
+}
+
+impl Tui {
+    pub fn new(config: String, out: PathBuf) -> Result<Self> {
+        let export_file = ExportFileBuilder::from_json(&config)?.build()?;
+
+        Ok(Tui {
+            config,
+            size: None,
+            files: None,
+            seed: None,
+            out,
+            export_file,
+            selected: 0,
+            preview: vec![],
+            status: HELP.into(),
+            input: None,
+        })
+    }
+
+
+    /// The UI for `--tui`, with the size, file count and seed of the other arguments.
+    pub fn from_args(args: &CliArgs) -> Result<Self> {
+        let mut tui = Tui::new(fs::read_to_string(&args.config)?, args.out.clone())?;
+        tui.size = args.size;
+        tui.files = args.files;
+        tui.seed = args.seed;
+        tui.rebuild()?;
+        Ok(tui)
+    }
+
+
+    fn rebuild(&mut self) -> Result<()> {
+        let mut builder = ExportFileBuilder::from_json(&self.config)?;
+        if let Some(size) = self.size {
+            builder = builder.target_bytes(size);
+        }
+        if let Some(files) = self.files {
+            builder = builder.files(files);
+        }
+        if let Some(seed) = self.seed {
+            builder = builder.seed(seed);
+        }
+
+        self.export_file = builder.build()?;
+        self.preview.clear();
+        Ok(())
+    }
+
+
+    pub fn handle(&mut self, command: &str) -> Result<Action> {
+        let (name, value) = command.split_once(' ').map_or((command, ""), |(x, y)| (x, y.trim()));
+        let tables = self.export_file.tables.len().max(1);
+        let invalid = || Error::from(TuiError::InvalidValue { command: name.into(), value: value.into() });
+
+        match name {
+            "q" | "quit" => return Ok(Action::Quit),
+            "g" | "generate" => return Ok(Action::Generate),
+            "" | "j" => self.select((self.selected + 1) % tables),
+            "k" => self.select((self.selected + tables - 1) % tables),
+            "?" | "h" | "help" => self.status = HELP.into(),
+            "p" | "preview" => {
+                let rows = if value.is_empty() { PREVIEW_ROWS } else { value.parse().map_err(|_| invalid())? };
+                let table = &self.export_file.tables[self.selected];
+                self.preview = table.generate_rows(rows)?.lines().map(|x| x.to_string()).collect();
+                self.status = format!("Preview of {} rows of {}.", rows, table.id_value);
+            }
+            "s" | "size" => {
+                let previous = self.size.replace(parse_size(value).map_err(|_| invalid())?);
+                if let Err(x) = self.rebuild() {
+                    self.size = previous;
+                    return Err(x);
+                }
+                self.status = format!("Total size is {} bytes.", self.total_bytes());
+            }
+            "f" | "files" => {
+                let previous = self.files.replace(value.parse().map_err(|_| invalid())?);
+                if let Err(x) = self.rebuild() {
+                    self.files = previous;
+                    return Err(x);
+                }
+                self.status = format!("Writing {} files.", self.export_file.number_of_files);
+            }
+            x => match x.parse::<usize>() {
+                Ok(index) if (1..=tables).contains(&index) => self.select(index - 1),
+                _ => return Err(Error::from(TuiError::UnknownCommand { command: command.into() })),
+            },
+        }
+
+        Ok(Action::Continue)
+    }
+
+
+    fn select(&mut self, index: usize) {
+        self.selected = index;
+        self.preview.clear();
+    }
+
+
+    fn total_bytes(&self) -> u64 {
+        self.export_file.file_size_bytes.saturating_mul(self.export_file.number_of_files)
+    }
+
+
+    fn handle_key(&mut self, key: KeyEvent) -> Result<Action> {
+        if let Some(input) = self.input.as_mut() {
+            match key.code {
+                KeyCode::Enter => {
+                    let command = self.input.take().unwrap_or_default();
+                    return self.handle(command.trim());
+                }
+                KeyCode::Esc => self.input = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(x) => input.push(x),
+                _ => {}
+            }
+            return Ok(Action::Continue);
+        }
+
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Ok(Action::Quit),
+            KeyCode::Esc => Ok(Action::Quit),
+            KeyCode::Down => self.handle("j"),
+            KeyCode::Up => self.handle("k"),
+            KeyCode::Char(x @ ('s' | 'f')) => {
+                self.input = Some(format!("{} ", x));
+                Ok(Action::Continue)
+            }
+            KeyCode::Char(':') => {
+                self.input = Some(String::new());
+                Ok(Action::Continue)
+            }
+            KeyCode::Char(x) => self.handle(x.encode_utf8(&mut [0; 4])),
+            _ => Ok(Action::Continue),
+        }
+    }
+
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let export_file = &self.export_file;
+        let preview_height = if self.preview.is_empty() { 0 } else { self.preview.len() as u16 + 2 };
+        let [header, body, preview, status, input] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(3),
+            Constraint::Length(preview_height),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ]).areas(frame.area());
+        let [tables_area, columns_area] = Layout::horizontal([
+            Constraint::Length(TABLES_WIDTH),
+            Constraint::Min(0),
+        ]).areas(body);
+
+        frame.render_widget(
+            Line::from(vec![
+                Span::styled("datagen", Style::new().add_modifier(Modifier::BOLD)),
+                Span::raw(format!(
+                    " {} files of {} bytes, {} bytes to {}",
+                    export_file.number_of_files,
+                    export_file.file_size_bytes,
+                    self.total_bytes(),
+                    self.out.display(),
+                )),
+            ]),
+            header,
+        );
+
+        let tables: Vec<ListItem> = export_file.tables.iter()
+            .enumerate()
+            .map(|(i, x)| ListItem::new(format!(
+                "{} {} {}% {} rows/file",
+                i + 1,
+                x.id_value,
+                (x.percent_size * rust_decimal::Decimal::ONE_HUNDRED).round_dp(1),
+                export_file.table_row_count(x, 0).map_or("?".into(), |y| y.to_string()),
+            )))
+            .collect();
+        let columns: Vec<ListItem> = export_file.tables.get(self.selected)
+            .map(|x| x.columns.iter()
+                .map(|y| ListItem::new(format!(
+                    "{} {} size {}{}",
+                    y.name,
+                    y.column_type,
+                    y.size.average(),
+                    if y.key { " key" } else { "" },
+                )))
+                .collect())
+            .unwrap_or_default();
+
+        frame.render_stateful_widget(
+            List::new(tables)
+                .block(Block::bordered().title("Tables"))
+                .highlight_style(Style::new().add_modifier(Modifier::BOLD))
+                .highlight_symbol("> "),
+            tables_area,
+            &mut ListState::default().with_selected(Some(self.selected)),
+        );
+        frame.render_widget(List::new(columns).block(Block::bordered().title("Columns")), columns_area);
+
+        if !self.preview.is_empty() {
+            frame.render_widget(
+                Paragraph::new(self.preview.join("\n")).block(Block::bordered().title("Preview")),
+                preview,
+            );
+        }
+
+        frame.render_widget(Line::raw(self.status.as_str()), status);
+        if let Some(command) = self.input.as_ref() {
+            frame.render_widget(Line::raw(format!(":{}", command)), input);
+            frame.set_cursor_position((input.x + command.chars().count() as u16 + 1, input.y));
+        }
+    }
+
+
+    fn generate(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        self.status = format!("Generating {} files to {}...", self.export_file.number_of_files, self.out.display());
+        terminal.draw(|x| self.draw(x))?;
+
+        let started = Instant::now();
+        self.status = match self.export_file.generate_all_files_with_report(&self.out) {
+            Ok(report) => format!(
+                "Wrote {} files to {} in {:.1}s at {:.1} MB/s.",
+                self.export_file.number_of_files,
+                self.out.display(),
+                started.elapsed().as_secs_f64(),
+                report.throughput_mb_per_second(),
+            ),
+            Err(x) => format!("error: {}", x),
+        };
+        Ok(())
+    }
+
+
+    fn run_on(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|x| self.draw(x))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match self.handle_key(key) {
+                Ok(Action::Continue) => {}
+                Ok(Action::Quit) => return Ok(()),
+                Ok(Action::Generate) => self.generate(terminal)?,
+                Err(x) => self.status = format!("error: {}", x),
+            }
+        }
+    }
+
+
+    /// Takes over the terminal until the UI is quit, restoring it afterwards even when drawing fails.
+    pub fn run(mut self) -> Result<()> {
+        let mut terminal = ratatui::init();
+        let result = self.run_on(&mut terminal);
+        ratatui::restore();
+        result
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use super::*;
+
+    fn screen(tui: &Tui) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|x| tui.draw(x)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        buffer.content()
+            .chunks(buffer.area.width as usize)
+            .map(|x| x.iter().map(|y| y.symbol()).collect::<String>() + "\n")
+            .collect()
+    }
+
+    const CONFIG: &str = r#"{
+        "size": "1KB",
+        "files": 2,
+        "seed": 1,
+        "tables": [
+            {
+                "id": "A",
+                "percent_size": 0.5,
+                "columns": [{"name": "id", "type": "INT", "size": 4, "generator": "sequence", "key": true}]
+            },
+            {
+                "id": "B",
+                "percent_size": 0.5,
+                "columns": [{"name": "code", "type": "CHAR(3)", "size": 3, "generator": {"name": "constant", "args": ["XYZ"]}}]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn handle_test() {
+        let mut tui = Tui::new(CONFIG.into(), PathBuf::from("./export")).unwrap();
+        assert!(screen(&tui).contains("> 1 A 50.0% "));
+
+        assert_eq!(tui.handle("j").unwrap(), Action::Continue);
+        assert_eq!(tui.handle("p 2").unwrap(), Action::Continue);
+        assert_eq!(tui.preview, vec!["B|XYZ", "B|XYZ"]);
+        assert!(screen(&tui).contains("code CHAR(3) size 3"));
+        assert!(screen(&tui).contains("B|XYZ"));
+
+        tui.handle("s 4KB").unwrap();
+        tui.handle("f 4").unwrap();
+        assert_eq!(tui.total_bytes(), 4096);
+        assert!(tui.preview.is_empty());
+
+        assert!(tui.handle("s lots").is_err());
+        assert!(tui.handle("9").is_err());
+        tui.handle("1").unwrap();
+        assert_eq!(tui.selected, 0);
+        assert_eq!(tui.handle("g").unwrap(), Action::Generate);
+        assert_eq!(tui.handle("q").unwrap(), Action::Quit);
+    }
+
+    #[test]
+    fn handle_key_test() {
+        let mut tui = Tui::new(CONFIG.into(), PathBuf::from("./export")).unwrap();
+        let key = |x| KeyEvent::from(KeyCode::Char(x));
+
+        assert_eq!(tui.handle_key(KeyEvent::from(KeyCode::Down)).unwrap(), Action::Continue);
+        assert_eq!(tui.selected, 1);
+
+        tui.handle_key(key('s')).unwrap();
+        for x in "4KB".chars() {
+            tui.handle_key(key(x)).unwrap();
+        }
+        assert!(screen(&tui).contains(":s 4KB"));
+        tui.handle_key(KeyEvent::from(KeyCode::Enter)).unwrap();
+        assert_eq!(tui.total_bytes(), 4096);
+        assert!(tui.input.is_none());
+
+        tui.handle_key(key(':')).unwrap();
+        tui.handle_key(key('x')).unwrap();
+        tui.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+        assert!(tui.input.is_none());
+
+        assert_eq!(tui.handle_key(key('g')).unwrap(), Action::Generate);
+        assert_eq!(tui.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap(), Action::Quit);
+    }
+}
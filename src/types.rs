@@ -0,0 +1,365 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Error, Result};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+
+#[derive(Error, Debug)]
+pub enum AnnotationError {
+    #[error("Unknown PII classification {pii}, expected direct, indirect or sensitive.")]
+    UnknownPii { pii: String },
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ColumnType {
+    Boolean,
+    SmallInt,
+    Int,
+    BigInt,
+    Real,
+    Double,
+    Decimal(u32, u32),
+    Char(u32),
+    Varchar(u32),
+    Text,
+    Date,
+    Timestamp,
+    Custom(String),
+}
+
+impl ColumnType {
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            ColumnType::SmallInt
+                | ColumnType::Int
+                | ColumnType::BigInt
+                | ColumnType::Real
+                | ColumnType::Double
+                | ColumnType::Decimal(..)
+        )
+    }
+
+    pub fn is_textual(&self) -> bool {
+        matches!(
+            self,
+            ColumnType::Char(_) | ColumnType::Varchar(_) | ColumnType::Text | ColumnType::Custom(_)
+        )
+    }
+
+    pub fn is_temporal(&self) -> bool {
+        matches!(self, ColumnType::Date | ColumnType::Timestamp)
+    }
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnType::Boolean => write!(f, "BOOLEAN"),
+            ColumnType::SmallInt => write!(f, "SMALLINT"),
+            ColumnType::Int => write!(f, "INT"),
+            ColumnType::BigInt => write!(f, "BIGINT"),
+            ColumnType::Real => write!(f, "REAL"),
+            ColumnType::Double => write!(f, "DOUBLE PRECISION"),
+            ColumnType::Decimal(precision, scale) => write!(f, "DECIMAL({},{})", precision, scale),
+            ColumnType::Char(length) => write!(f, "CHAR({})", length),
+            ColumnType::Varchar(length) => write!(f, "VARCHAR({})", length),
+            ColumnType::Text => write!(f, "TEXT"),
+            ColumnType::Date => write!(f, "DATE"),
+            ColumnType::Timestamp => write!(f, "TIMESTAMP"),
+            ColumnType::Custom(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+// SQL spellings become the matching type and are written back in SQL notation, anything else, such as `CHAR[3]`,
+// is kept as written.
+impl From<&str> for ColumnType {
+    fn from(sql_type: &str) -> Self {
+        let upper = sql_type.trim().to_uppercase();
+        let (name, arguments) = match upper.find('(') {
+            Some(i) => (
+                upper[..i].trim(),
+                upper[i + 1..]
+                    .trim_end_matches(')')
+                    .split(',')
+                    .filter_map(|x| x.trim().parse::<u32>().ok())
+                    .collect::<Vec<u32>>()
+            ),
+            None => (upper.as_str(), vec![]),
+        };
+
+        match (name, arguments.as_slice()) {
+            ("BOOL" | "BOOLEAN" | "BIT", []) => ColumnType::Boolean,
+            ("TINYINT" | "SMALLINT" | "INT2", []) => ColumnType::SmallInt,
+            ("MEDIUMINT" | "INT" | "INTEGER" | "INT4", []) => ColumnType::Int,
+            ("BIGINT" | "INT8", []) => ColumnType::BigInt,
+            ("REAL" | "FLOAT4", []) => ColumnType::Real,
+            ("FLOAT" | "FLOAT8" | "DOUBLE" | "DOUBLE PRECISION", []) => ColumnType::Double,
+            ("DECIMAL" | "NUMERIC" | "NUMBER", []) => ColumnType::Decimal(38, 0),
+            ("DECIMAL" | "NUMERIC" | "NUMBER", [precision]) => ColumnType::Decimal(*precision, 0),
+            ("DECIMAL" | "NUMERIC" | "NUMBER", [precision, scale]) => ColumnType::Decimal(*precision, *scale),
+            ("CHAR" | "CHARACTER", [length]) => ColumnType::Char(*length),
+            ("CHAR" | "CHARACTER", []) => ColumnType::Char(1),
+            ("VARCHAR" | "VARCHAR2" | "CHARACTER VARYING", [length]) => ColumnType::Varchar(*length),
+            ("TEXT" | "STRING" | "CLOB", []) => ColumnType::Text,
+            ("DATE", []) => ColumnType::Date,
+            ("TIMESTAMP" | "DATETIME" | "TIMESTAMP WITHOUT TIME ZONE", []) => ColumnType::Timestamp,
+            _ => ColumnType::Custom(sql_type.trim().to_string()),
+        }
+    }
+}
+
+
+
+/// Characters in a column's values, tables are sized by the average of variable length values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColumnSize {
+    Fixed(u64),
+    Range { min: u64, max: u64, avg: u64 },
+}
+
+impl ColumnSize {
+    /// Values between `min` and `max` characters, averaging the midpoint.
+    pub fn range(min: u64, max: u64) -> Self {
+        let (min, max) = (min.min(max), min.max(max));
+        ColumnSize::Range { min, max, avg: min + (max - min) / 2 }
+    }
+
+    pub fn min(&self) -> u64 {
+        match self {
+            ColumnSize::Fixed(size) => *size,
+            ColumnSize::Range { min, .. } => *min,
+        }
+    }
+
+    pub fn max(&self) -> u64 {
+        match self {
+            ColumnSize::Fixed(size) => *size,
+            ColumnSize::Range { max, .. } => *max,
+        }
+    }
+
+    pub fn average(&self) -> u64 {
+        match self {
+            ColumnSize::Fixed(size) => *size,
+            ColumnSize::Range { avg, .. } => *avg,
+        }
+    }
+}
+
+impl From<u64> for ColumnSize {
+    fn from(size: u64) -> Self {
+        ColumnSize::Fixed(size)
+    }
+}
+
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum NullRepresentation {
+    #[default]
+    Empty,
+    BackslashN,
+    Null,
+    Custom(String),
+}
+
+impl NullRepresentation {
+    pub fn as_str(&self) -> &str {
+        match self {
+            NullRepresentation::Empty => "",
+            NullRepresentation::BackslashN => "\\N",
+            NullRepresentation::Null => "NULL",
+            NullRepresentation::Custom(x) => x.as_str(),
+        }
+    }
+
+    /// Whether the value is one of the built in null representations.
+    pub fn is_null_value(value: &str) -> bool {
+        matches!(value, "" | "\\N" | "NULL")
+    }
+}
+
+
+/// Bytes written after every row.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum RecordTerminator {
+    #[default]
+    Lf,
+    CrLf,
+    Custom(String),
+}
+
+impl RecordTerminator {
+    pub fn as_str(&self) -> &str {
+        match self {
+            RecordTerminator::Lf => "\n",
+            RecordTerminator::CrLf => "\r\n",
+            RecordTerminator::Custom(x) => x.as_str(),
+        }
+    }
+}
+
+impl From<&str> for RecordTerminator {
+    /// `lf` and `crlf` name the usual terminators, anything else is used as is.
+    fn from(terminator: &str) -> Self {
+        match terminator {
+            "\n" => RecordTerminator::Lf,
+            "\r\n" => RecordTerminator::CrLf,
+            x if x.eq_ignore_ascii_case("lf") => RecordTerminator::Lf,
+            x if x.eq_ignore_ascii_case("crlf") => RecordTerminator::CrLf,
+            x => RecordTerminator::Custom(x.into()),
+        }
+    }
+}
+
+
+
+/// How a column identifies a person, for data catalogs and masking rules downstream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pii {
+    /// Identifies a person on its own, e.g. a name, email or national id.
+    Direct,
+    /// Identifies a person combined with other columns, e.g. a birth date or postal code.
+    Indirect,
+    /// Special category data, e.g. health, religion or biometrics.
+    Sensitive,
+}
+
+impl Pii {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Pii::Direct => "direct",
+            Pii::Indirect => "indirect",
+            Pii::Sensitive => "sensitive",
+        }
+    }
+}
+
+impl FromStr for Pii {
+    type Err = Error;
+
+    fn from_str(pii: &str) -> Result<Self> {
+        match pii.to_ascii_lowercase().as_str() {
+            "direct" => Ok(Pii::Direct),
+            "indirect" | "quasi" => Ok(Pii::Indirect),
+            "sensitive" => Ok(Pii::Sensitive),
+            _ => Err(Error::from(AnnotationError::UnknownPii { pii: pii.into() })),
+        }
+    }
+}
+
+
+/// Documentation attached to a table or column and carried into the schema JSON, Avro schema and DDL comments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Annotations {
+    pub description: Option<String>,
+    pub owner: Option<String>,
+    pub tags: Vec<String>,
+    pub pii: Option<Pii>,
+    pub properties: BTreeMap<String, String>,
+}
+
+impl Annotations {
+    pub fn new() -> Self {
+        Annotations::default()
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner = Some(owner.into());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn with_pii(mut self, pii: Pii) -> Self {
+        self.pii = Some(pii);
+        self
+    }
+
+    pub fn with_property(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.properties.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.description.is_none()
+            && self.owner.is_none()
+            && self.tags.is_empty()
+            && self.pii.is_none()
+            && self.properties.is_empty()
+    }
+
+    /// Only the fields that are set, properties are nested so they cannot shadow the others.
+    pub fn to_json(&self) -> Map<String, Value> {
+        let mut json = Map::new();
+
+        if let Some(description) = &self.description {
+            json.insert("description".into(), description.clone().into());
+        }
+        if let Some(owner) = &self.owner {
+            json.insert("owner".into(), owner.clone().into());
+        }
+        if !self.tags.is_empty() {
+            json.insert("tags".into(), self.tags.clone().into());
+        }
+        if let Some(pii) = self.pii {
+            json.insert("pii".into(), pii.as_str().into());
+        }
+        if !self.properties.is_empty() {
+            json.insert(
+                "properties".into(),
+                Value::Object(self.properties.iter().map(|(k, v)| (k.clone(), v.clone().into())).collect()),
+            );
+        }
+
+        json
+    }
+
+    /// One line comment for DDL, the description followed by the owner, tags, PII classification and properties.
+    pub fn comment(&self) -> Option<String> {
+        let parts: Vec<String> = self.description.iter().cloned()
+            .chain(self.owner.iter().map(|x| format!("owner: {}", x)))
+            .chain((!self.tags.is_empty()).then(|| format!("tags: {}", self.tags.join(", "))))
+            .chain(self.pii.map(|x| format!("pii: {}", x.as_str())))
+            .chain(self.properties.iter().map(|(k, v)| format!("{}: {}", k, v)))
+            .collect();
+
+        (!parts.is_empty()).then(|| parts.join("; "))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_type_from_str_test() {
+        assert_eq!(ColumnType::from("char(3)"), ColumnType::Char(3));
+        assert_eq!(ColumnType::from("CHAR[3]"), ColumnType::Custom("CHAR[3]".into()));
+        assert_eq!(ColumnType::from("integer"), ColumnType::Int);
+        assert_eq!(ColumnType::from("DECIMAL(10, 2)"), ColumnType::Decimal(10, 2));
+        assert_eq!(ColumnType::from("VARCHAR(20)"), ColumnType::Varchar(20));
+        assert_eq!(ColumnType::from("geometry"), ColumnType::Custom("geometry".into()));
+    }
+
+    #[test]
+    fn column_type_display_test() {
+        assert_eq!(ColumnType::Char(3).to_string(), "CHAR(3)");
+        assert_eq!(ColumnType::Decimal(10, 2).to_string(), "DECIMAL(10,2)");
+        assert_eq!(ColumnType::from(ColumnType::Varchar(5).to_string().as_str()), ColumnType::Varchar(5));
+    }
+}
@@ -0,0 +1,558 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::{Column, ExportFile, Table};
+use crate::dictionary::Dictionary;
+use crate::random::{mix, SeededRandom};
+
+/// Times a row's unique columns are regenerated before giving up.
+pub const MAX_ATTEMPTS: u64 = 100;
+const UNIQUE_STREAM: u64 = u64::MAX / 61;
+const FEISTEL_ROUNDS: u64 = 4;
+
+
+#[derive(Error, Debug)]
+pub enum UniqueError {
+    #[error("Table {table} has no column {column} for a unique constraint.")]
+    UnknownColumn { table: String, column: String },
+    #[error("Unique constraint needs at least one column in table {table}.")]
+    EmptyConstraint { table: String },
+    #[error("Could not generate a unique ({columns}) for row {row_index} of table {table} in {MAX_ATTEMPTS} attempts.")]
+    Exhausted { table: String, columns: String, row_index: u64 },
+    #[error("Unique ({columns}) of table {table} has only {values} values for {rows} rows.")]
+    ValueSpaceExceeded { table: String, columns: String, values: u64, rows: u64 },
+}
+
+
+/// How the values of a unique constraint are kept unique.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum UniqueIndex {
+    /// Combination of the columns' values taken from a permutation of the row index, `spaces` are the values of
+    /// each column. No state is kept, row `n` of the table has the same values in whichever file or call it is.
+    Permuted { spaces: Vec<u64> },
+    /// Holds every column of a permuted constraint, so it is unique as well.
+    Implied,
+    /// Duplicates of earlier rows are generated again, see [`Table::enforce_unique_row`].
+    Regenerated,
+}
+
+
+// Single column constraints of unique columns, and the key columns taken together unless one of them is already
+// generated uniquely, e.g. by a sequence.
+pub(crate) fn unique_columns(columns: &[Column]) -> Vec<Vec<usize>> {
+    let mut unique: Vec<Vec<usize>> = columns.iter().enumerate().filter(|(_, x)| x.unique).map(|(i, _)| vec![i]).collect();
+    let key = key_columns(columns);
+    if !key.is_empty() && !key.iter().any(|x| columns[*x].is_generated_uniquely()) {
+        unique.push(key);
+    }
+
+    unique
+}
+
+
+fn key_columns(columns: &[Column]) -> Vec<usize> {
+    columns.iter().enumerate().filter(|(_, x)| x.key).map(|(i, _)| i).collect()
+}
+
+
+/// Values of the unique constraints taken by earlier rows, those of one call and of the earlier files of an export.
+pub(crate) struct UniqueSeen<'a> {
+    seen: Vec<HashSet<Vec<String>>>,
+    earlier: Option<&'a UniqueFiles>,
+    file_index: u64,
+}
+
+impl UniqueSeen<'_> {
+    fn contains(&self, constraint: usize, tuple: &Vec<String>) -> bool {
+        self.seen[constraint].contains(tuple) || self.earlier.is_some_and(|x| {
+            x.taken[constraint].get(tuple).is_some_and(|y| *y < self.file_index)
+        })
+    }
+}
+
+
+/// Values of a table's regenerated constraints by the file that took them, recorded for one file after another.
+#[derive(Debug, Default)]
+pub(crate) struct UniqueFiles {
+    files: u64,
+    taken: Vec<HashMap<Vec<String>, u64>>,
+}
+
+
+impl Table {
+    /// Values of `columns` taken together are unique among the rows of the table, across all files of an export.
+    /// Columns of built in generators that pick uniformly from a known value space, e.g. `random_int`, take each
+    /// row's combination from a permutation of the row index. Other columns are regenerated while an earlier row
+    /// has the same values, which in an export keeps every value taken by the table.
+    pub fn with_unique(mut self, columns: &[&str]) -> Result<Self> {
+        if columns.is_empty() {
+            return Err(Error::from(UniqueError::EmptyConstraint { table: self.id_value.clone() }));
+        }
+
+        let indices = columns.iter()
+            .map(|x| {
+                self.columns.iter()
+                    .position(|y| y.name == *x)
+                    .ok_or_else(|| Error::from(UniqueError::UnknownColumn {
+                        table: self.id_value.clone(),
+                        column: x.to_string(),
+                    }))
+            })
+            .collect::<Result<Vec<usize>>>()?;
+
+        self.unique.push(indices);
+        self.update_value_spaces();
+        Ok(self)
+    }
+
+
+    /// Index of the unique constraint that keeps the key unique, `None` when its columns are unique by themselves.
+    pub(crate) fn key_constraint(&self) -> Option<usize> {
+        let key = key_columns(&self.columns);
+        self.unique.iter().position(|x| !key.is_empty() && *x == key)
+    }
+
+
+    /// Combinations the key's generators can produce when they are known, see [`Table::check_value_space`].
+    pub fn key_value_space(&self) -> Option<u64> {
+        self.value_spaces[self.key_constraint()?]
+    }
+
+
+    /// Columns of the declared unique constraints, without the one that keeps the key unique.
+    pub fn unique_constraints(&self) -> Vec<Vec<&str>> {
+        let key = self.key_constraint();
+        self.unique.iter()
+            .enumerate()
+            .filter(|x| Some(x.0) != key)
+            .map(|x| x.1.iter().map(|y| self.columns[*y].name.as_str()).collect())
+            .collect()
+    }
+
+
+    fn constraint_columns(&self, constraint: usize) -> String {
+        self.unique[constraint].iter().map(|x| self.columns[*x].name.as_str()).collect::<Vec<&str>>().join(", ")
+    }
+
+
+    // Combinations a constraint's generators can produce, known when every generator's spec bounds its values or
+    // a dictionary probe sees all of them, and how each constraint is kept unique.
+    pub(crate) fn update_value_spaces(&mut self) {
+        self.value_spaces = self.unique.iter()
+            .map(|x| {
+                x.iter()
+                    .map(|y| {
+                        let generator = &self.columns[*y].generator;
+                        generator.value_space()
+                            .or_else(|| Dictionary::probe(generator).map(|z| z.values().len() as u64))
+                    })
+                    .try_fold(1u64, |product, y| Some(product.saturating_mul(y?)))
+            })
+            .collect();
+        self.unique_index = self.unique_indices();
+    }
+
+
+    // A constraint is permuted when every column's values can be indexed and it shares no column with another
+    // constraint, unless the other one holds all of its columns and is then implied by it.
+    fn unique_indices(&self) -> Vec<UniqueIndex> {
+        let subset = |x: &[usize], y: &[usize]| x.iter().all(|z| y.contains(z));
+        let disjoint = |x: &[usize], y: &[usize]| !x.iter().any(|z| y.contains(z));
+        let mut permuted: Vec<usize> = vec![];
+
+        for (i, columns) in self.unique.iter().enumerate() {
+            let alone = self.unique.iter()
+                .enumerate()
+                .all(|(j, x)| i == j || subset(columns, x) || disjoint(columns, x));
+            let implied = permuted.iter().any(|x| subset(&self.unique[*x], columns));
+            if alone && !implied && self.indexed_spaces(columns).is_some() {
+                permuted.push(i);
+            }
+        }
+
+        self.unique.iter()
+            .enumerate()
+            .map(|(i, columns)| match self.indexed_spaces(columns) {
+                Some(spaces) if permuted.contains(&i) => UniqueIndex::Permuted { spaces },
+                _ if permuted.iter().any(|x| subset(&self.unique[*x], columns)) => UniqueIndex::Implied,
+                _ => UniqueIndex::Regenerated,
+            })
+            .collect()
+    }
+
+
+    // Values of each column when all of them can be indexed and written as generated, and their combinations fit
+    // into a u64.
+    fn indexed_spaces(&self, columns: &[usize]) -> Option<Vec<u64>> {
+        let spaces = columns.iter()
+            .map(|x| {
+                let column = &self.columns[*x];
+                let indexed = column.transforms.is_empty() && !column.is_nullable() && column.generator.value_at(0).is_some();
+                column.generator.value_space().filter(|y| indexed && *y > 0)
+            })
+            .collect::<Option<Vec<u64>>>()?;
+        spaces.iter().try_fold(1u64, |product, x| product.checked_mul(*x))?;
+
+        Some(spaces)
+    }
+
+
+    /// Whether a unique constraint is kept by regenerating duplicates, which needs the table's rows in order.
+    pub(crate) fn has_regenerated_unique(&self) -> bool {
+        self.unique_index.contains(&UniqueIndex::Regenerated)
+    }
+
+
+    pub(crate) fn is_permuted(&self, column_index: usize) -> bool {
+        self.unique_index.iter()
+            .zip(self.unique.iter())
+            .any(|(x, y)| matches!(x, UniqueIndex::Permuted { .. }) && y.contains(&column_index))
+    }
+
+
+    // Key of the permutations, drawn once from the table's random source when it has no seed and shared by its
+    // clones, so every file of an export permutes the same way.
+    fn unique_key(&self) -> u64 {
+        match self.seed {
+            Some(seed) => mix(seed, UNIQUE_STREAM),
+            None => *self.unique_key.get_or_init(|| self.random.next_u64()),
+        }
+    }
+
+
+    /// Seed of the streams values are drawn from, `None` draws them from the table's random source. Regenerated
+    /// constraints generate earlier files again and need the same values every time, so an unseeded table with one
+    /// takes its streams from a key drawn once from its random source.
+    pub(crate) fn stream_seed(&self) -> Option<u64> {
+        self.seed.or_else(|| self.has_regenerated_unique().then(|| self.unique_key()))
+    }
+
+
+    /// Index into the value space of each column of a permuted constraint at a row, `None` for other columns. Rows
+    /// past the constraint's value space repeat the combinations from the start.
+    pub(crate) fn permuted_digits(&self, row_index: u64) -> Vec<Option<u64>> {
+        let mut digits: Vec<Option<u64>> = vec![];
+
+        for (constraint, index) in self.unique_index.iter().enumerate() {
+            let UniqueIndex::Permuted { spaces } = index else {
+                continue;
+            };
+            if digits.is_empty() {
+                digits = vec![None; self.columns.len()];
+            }
+
+            let size = spaces.iter().product::<u64>();
+            let mut value = permute(row_index % size, size, mix(self.unique_key(), constraint as u64));
+            for (column, space) in self.unique[constraint].iter().zip(spaces) {
+                digits[*column] = Some(value % space);
+                value /= space;
+            }
+        }
+
+        digits
+    }
+
+
+    /// Value `digit` of a permuted column as written, see [`Table::permuted_digits`].
+    pub(crate) fn permuted_value(&self, column_index: usize, row_index: u64, digit: u64) -> Result<String> {
+        let value = self.columns[column_index].generator.value_at(digit)
+            .ok_or_else(|| Error::from(UniqueError::Exhausted {
+                table: self.id_value.clone(),
+                columns: self.columns[column_index].name.clone(),
+                row_index,
+            }))?;
+
+        Ok(self.namespaced(column_index, value))
+    }
+
+
+    /// Fails when a unique constraint can not have `row_count` distinct values, instead of running out of attempts.
+    pub fn check_value_space(&self, row_count: u64) -> Result<()> {
+        match self.value_spaces.iter().position(|x| x.is_some_and(|y| y < row_count)) {
+            Some(constraint) => Err(Error::from(UniqueError::ValueSpaceExceeded {
+                table: self.id_value.clone(),
+                columns: self.constraint_columns(constraint),
+                values: self.value_spaces[constraint].unwrap_or(0),
+                rows: row_count,
+            })),
+            None => Ok(()),
+        }
+    }
+
+
+    // Walks rows in order so the kept row of a duplicate pair does not depend on threading.
+    pub(crate) fn enforce_unique(&self, first_row_index: u64, rows: &mut [Vec<String>], seen: &mut UniqueSeen) -> Result<()> {
+        for (offset, row) in rows.iter_mut().enumerate() {
+            self.enforce_unique_row(first_row_index + offset as u64, row, seen)?;
+        }
+
+        Ok(())
+    }
+
+
+    pub(crate) fn unique_seen(&self) -> UniqueSeen<'static> {
+        UniqueSeen { seen: vec![HashSet::new(); self.unique.len()], earlier: None, file_index: 0 }
+    }
+
+
+    /// Regenerates the unique columns of a row until no earlier row in `seen` has the same values.
+    pub(crate) fn enforce_unique_row(&self, row_index: u64, row: &mut [String], seen: &mut UniqueSeen) -> Result<()> {
+        let mut attempt = 0;
+
+        let regenerated: Vec<usize> = (0..self.unique.len())
+            .filter(|x| self.unique_index[*x] == UniqueIndex::Regenerated)
+            .collect();
+
+        while let Some(constraint) = regenerated.iter().copied().find(|x| seen.contains(*x, &tuple(row, &self.unique[*x]))) {
+            attempt += 1;
+            if attempt > MAX_ATTEMPTS {
+                return Err(Error::from(UniqueError::Exhausted {
+                    table: self.id_value.clone(),
+                    columns: self.constraint_columns(constraint),
+                    row_index,
+                }));
+            }
+
+            for &column_index in self.unique[constraint].iter() {
+                let value_random = self.stream_seed()
+                    .map(|x| SeededRandom::new(mix(mix(mix(x, row_index), column_index as u64), attempt)));
+                row[column_index + 1] = self.generate_value_with(
+                    column_index,
+                    row_index,
+                    value_random,
+                    &row[1..column_index + 1],
+                )?;
+            }
+            self.regenerate_dependents(&self.unique[constraint], row_index, row)?;
+        }
+
+        for constraint in regenerated {
+            seen.seen[constraint].insert(tuple(row, &self.unique[constraint]));
+        }
+
+        Ok(())
+    }
+}
+
+
+impl ExportFile {
+    // Rows of a table in one file whose unique values are also unique against the table's earlier files. Permuted
+    // constraints are unique by row index alone. For regenerated ones the earlier files are generated again, once
+    // each, in file order, and every value they take is kept, so memory grows with the rows of the whole export.
+    // Values are generated in parallel before taking the lock, duplicates are replaced one row after another while
+    // holding it.
+    pub(crate) fn generate_unique_rows(&self, table_index: usize, table: &Table, file_index: u64) -> Result<Vec<Vec<String>>> {
+        table.check_value_space(self.table_total_rows(table)?)?;
+        if !table.has_regenerated_unique() {
+            return table.generate_values_from(
+                self.table_row_offset(table, file_index)?,
+                self.table_row_count(table, file_index)?,
+            );
+        }
+        let lock = || self.unique_files[table_index].lock().unwrap_or_else(|x| x.into_inner());
+
+        loop {
+            let files = lock().files;
+            if files >= file_index {
+                break;
+            }
+
+            let parents = self.generate_tables(files, &self.parents(table_index))?;
+            let earlier = self.with_reference_pools(&self.tables[table_index], &parents, files)?;
+            let mut rows = earlier.generate_values_from(
+                self.table_row_offset(&earlier, files)?,
+                self.table_row_count(&earlier, files)?,
+            )?;
+            self.take_unique(&earlier, files, &mut rows, &mut lock())?;
+        }
+
+        let mut rows = table.generate_values_from(
+            self.table_row_offset(table, file_index)?,
+            self.table_row_count(table, file_index)?,
+        )?;
+        self.take_unique(table, file_index, &mut rows, &mut lock())?;
+        Ok(rows)
+    }
+
+
+    // Makes the rows of a file unique against its earlier files, and records their values when it is the next file.
+    fn take_unique(&self, table: &Table, file_index: u64, rows: &mut [Vec<String>], files: &mut UniqueFiles) -> Result<()> {
+        if files.taken.is_empty() {
+            files.taken = vec![HashMap::new(); table.unique.len()];
+        }
+        let mut seen = UniqueSeen { earlier: Some(files), file_index, ..table.unique_seen() };
+        table.enforce_unique(self.table_row_offset(table, file_index)?, rows, &mut seen)?;
+        let seen = seen.seen;
+
+        if files.files == file_index {
+            for (taken, seen) in files.taken.iter_mut().zip(seen) {
+                taken.extend(seen.into_iter().map(|x| (x, file_index)));
+            }
+            files.files += 1;
+        }
+
+        Ok(())
+    }
+
+
+    // Tables `table_index` references, directly or through other tables, without the table itself.
+    fn parents(&self, table_index: usize) -> Vec<bool> {
+        let mut wanted = self.with_references(table_index);
+        wanted[table_index] = false;
+        wanted
+    }
+
+
+    pub(crate) fn reset_unique(&self) {
+        for files in self.unique_files.iter() {
+            *files.lock().unwrap_or_else(|x| x.into_inner()) = UniqueFiles::default();
+        }
+    }
+}
+
+
+fn tuple(row: &[String], columns: &[usize]) -> Vec<String> {
+    columns.iter().map(|x| row[x + 1].clone()).collect()
+}
+
+
+// Bijection of `0..size` keyed by `key`: a balanced Feistel network over the smallest even number of bits holding
+// `size`, repeated until the value is back inside the range, which takes fewer than four rounds on average.
+fn permute(index: u64, size: u64, key: u64) -> u64 {
+    let half = (64 - size.saturating_sub(1).leading_zeros()).div_ceil(2).max(1);
+    let mask = (1u64 << half) - 1;
+    let mut value = index;
+
+    loop {
+        let (mut left, mut right) = (value >> half, value & mask);
+        for round in 0..FEISTEL_ROUNDS {
+            (left, right) = (right, left ^ (mix(key, round << 32 | right) & mask));
+        }
+        value = left << half | right;
+        if value < size {
+            return value;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::*;
+
+    use super::*;
+    use crate::{generators, Column, SizeSpec};
+    use crate::types::ColumnType;
+
+    #[test]
+    fn unique_test() {
+        let table = Table::new(
+            "A".into(),
+            vec![
+                Column::new("customer_id".into(), 2, ColumnType::Int, generators::random_int(1, 10)),
+                Column::new("day".into(), 2, ColumnType::Int, generators::random_int(1, 30)),
+            ],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        )
+            .with_seed(7)
+            .with_unique(&["customer_id", "day"])
+            .unwrap();
+
+        let rows = table.generate_rows_vec(200).unwrap();
+        let pairs: HashSet<(&String, &String)> = rows.iter().map(|x| (&x[1], &x[2])).collect();
+
+        assert_eq!(pairs.len(), 200);
+        assert_eq!(rows, table.generate_rows_vec(200).unwrap());
+
+        assert!(table.clone().with_unique(&["missing"]).is_err());
+        assert!(table.generate_rows_vec(301).is_err());
+    }
+
+    #[test]
+    fn unique_column_test() {
+        let table = Table::new(
+            "A".into(),
+            vec![
+                Column::new("code".into(), 2, ColumnType::Int, generators::random_int(1, 50)).unique(true),
+                Column::new("day".into(), 2, ColumnType::Int, generators::random_int(1, 30)),
+            ],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        )
+            .with_seed(3);
+
+        let rows = table.generate_rows_vec(40).unwrap();
+        assert_eq!(rows.iter().map(|x| &x[1]).collect::<HashSet<&String>>().len(), 40);
+        assert_eq!(table.unique_constraints(), vec![vec!["code"]]);
+
+        let error = table.generate_rows_vec(51).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<UniqueError>(),
+            Some(UniqueError::ValueSpaceExceeded { values: 50, rows: 51, .. })
+        ));
+        assert!(table.rows_from(0, 51).next().unwrap().is_err());
+    }
+
+    #[test]
+    fn unique_across_files_test() {
+        let table = || Table::new(
+            "A".into(),
+            vec![
+                Column::new("code".into(), 2, ColumnType::Int, generators::random_int(1, 100)).unique(true),
+                Column::new("day".into(), 2, ColumnType::Int, generators::random_int(1, 30)),
+            ],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+        let export = |rows: u64| ExportFile::new(vec![table()], 3_000, 3).unwrap()
+            .with_size_spec(SizeSpec::Rows(HashMap::from([("A".to_string(), rows)]))).unwrap();
+
+        let ef = export(90);
+        let codes = |ef: &ExportFile| (0..3)
+            .flat_map(|x| ef.generate_file_rows(x).unwrap().swap_remove(0))
+            .map(|x| x[1].clone())
+            .collect::<Vec<String>>();
+        let first = codes(&ef);
+
+        assert_eq!(first.len(), 90);
+        assert_eq!(first.iter().collect::<HashSet<&String>>().len(), 90);
+        // Codes are a permutation of the row index, generating the files again gives the same rows.
+        assert_eq!(first, codes(&ef));
+        assert_eq!(codes(&export(90).with_seed(1)), codes(&export(90).with_seed(1)));
+
+        let error = export(101).generate_file_rows(2).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<UniqueError>(),
+            Some(UniqueError::ValueSpaceExceeded { values: 100, rows: 101, .. })
+        ));
+    }
+
+    #[test]
+    fn regenerated_unique_across_files_test() {
+        // Zipf values can not be drawn by index, duplicates are regenerated against earlier files.
+        let table = Table::new(
+            "A".into(),
+            vec![Column::new("rank".into(), 3, ColumnType::Int, generators::zipf(200, 0.5)).unique(true)],
+            "|".into(),
+            Decimal::from_str("1.0").unwrap(),
+        );
+        let ef = ExportFile::new(vec![table], 3_000, 3).unwrap()
+            .with_size_spec(SizeSpec::Rows(HashMap::from([("A".to_string(), 90)]))).unwrap();
+        assert!(ef.tables[0].has_regenerated_unique());
+
+        let ranks = |ef: &ExportFile| (0..3)
+            .flat_map(|x| ef.generate_file_rows(x).unwrap().swap_remove(0))
+            .map(|x| x[1].clone())
+            .collect::<Vec<String>>();
+        let first = ranks(&ef);
+
+        assert_eq!(first.iter().collect::<HashSet<&String>>().len(), 90);
+        // Unseeded tables draw the stream of regenerated values once, files checked against earlier files
+        // generated again get the same rows.
+        ef.reset_unique();
+        assert_eq!(first, ranks(&ef));
+    }
+}
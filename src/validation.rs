@@ -0,0 +1,363 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use rust_decimal::prelude::*;
+use serde_json::{json, Value};
+
+use crate::{ExportFile, Table};
+use crate::builder::ExportFileBuilder;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// `ExportFile::new` rejects the spec.
+    Error,
+    /// The spec is accepted but likely not what was meant.
+    Warning,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    NoFiles,
+    ZeroSize,
+    TooManyFiles { files: u64, data_size_bytes: u64 },
+    NoTables,
+    DuplicateTable { table: String },
+    DuplicateColumn { table: String, column: String },
+    EmptyTable { table: String },
+    InvalidPercentSize { table: String, percent_size: Decimal },
+    PercentSizeSum { sum_percent_size: Decimal },
+    TableDoesNotFit { table: String, needed: u64, available: u64 },
+    ZeroSizeColumn { table: String, column: String },
+    NullableNotNull { table: String, column: String },
+    KeyNotUnique { table: String, column: String, values: u64, rows: u64 },
+}
+
+impl ValidationIssue {
+    /// Stable identifier of the kind of issue, for tools reading the report.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidationIssue::NoFiles => "no_files",
+            ValidationIssue::ZeroSize => "zero_size",
+            ValidationIssue::TooManyFiles { .. } => "too_many_files",
+            ValidationIssue::NoTables => "no_tables",
+            ValidationIssue::DuplicateTable { .. } => "duplicate_table",
+            ValidationIssue::DuplicateColumn { .. } => "duplicate_column",
+            ValidationIssue::EmptyTable { .. } => "empty_table",
+            ValidationIssue::InvalidPercentSize { .. } => "invalid_percent_size",
+            ValidationIssue::PercentSizeSum { .. } => "percent_size_sum",
+            ValidationIssue::TableDoesNotFit { .. } => "table_does_not_fit",
+            ValidationIssue::ZeroSizeColumn { .. } => "zero_size_column",
+            ValidationIssue::NullableNotNull { .. } => "nullable_not_null",
+            ValidationIssue::KeyNotUnique { .. } => "key_not_unique",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            ValidationIssue::ZeroSizeColumn { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    pub fn table(&self) -> Option<&str> {
+        match self {
+            ValidationIssue::DuplicateTable { table }
+            | ValidationIssue::DuplicateColumn { table, .. }
+            | ValidationIssue::EmptyTable { table }
+            | ValidationIssue::InvalidPercentSize { table, .. }
+            | ValidationIssue::TableDoesNotFit { table, .. }
+            | ValidationIssue::ZeroSizeColumn { table, .. }
+            | ValidationIssue::NullableNotNull { table, .. }
+            | ValidationIssue::KeyNotUnique { table, .. } => Some(table),
+            _ => None,
+        }
+    }
+
+    pub fn column(&self) -> Option<&str> {
+        match self {
+            ValidationIssue::DuplicateColumn { column, .. }
+            | ValidationIssue::ZeroSizeColumn { column, .. }
+            | ValidationIssue::NullableNotNull { column, .. }
+            | ValidationIssue::KeyNotUnique { column, .. } => Some(column),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::NoFiles => write!(f, "Export needs at least one file."),
+            ValidationIssue::ZeroSize => write!(f, "Export size is 0 bytes."),
+            ValidationIssue::TooManyFiles { files, data_size_bytes } => write!(
+                f,
+                "{} files do not fit into {} bytes, every file needs at least one byte.",
+                files, data_size_bytes
+            ),
+            ValidationIssue::NoTables => write!(f, "Export has no tables."),
+            ValidationIssue::DuplicateTable { table } => write!(f, "Table {} is defined more than once.", table),
+            ValidationIssue::DuplicateColumn { table, column } => {
+                write!(f, "Table {} has a duplicate column {}.", table, column)
+            }
+            ValidationIssue::EmptyTable { table } => write!(f, "Table {} has no columns.", table),
+            ValidationIssue::InvalidPercentSize { table, percent_size } => {
+                write!(f, "Table {} has percent size {}, it must be above 0.", table, percent_size)
+            }
+            ValidationIssue::PercentSizeSum { sum_percent_size } => {
+                write!(f, "Sum of table percentage sizes must be equal 1. It was {}.", sum_percent_size)
+            }
+            ValidationIssue::TableDoesNotFit { table, needed, available } => write!(
+                f,
+                "Table {} needs {} bytes per row but gets {} bytes per file.",
+                table, needed, available
+            ),
+            ValidationIssue::ZeroSizeColumn { table, column } => {
+                write!(f, "Column {} of table {} has size 0 and is not accounted for in sizing.", column, table)
+            }
+            ValidationIssue::NullableNotNull { table, column } => {
+                write!(f, "Column {} of table {} is a key or not null, but it can generate nulls.", column, table)
+            }
+            ValidationIssue::KeyNotUnique { table, column, values, rows } => write!(
+                f,
+                "Key ({}) of table {} has only {} values for {} rows, it can not be kept unique.",
+                column, table, values, rows
+            ),
+        }
+    }
+}
+
+
+/// Every problem of a spec at once, in the order tables and columns are defined.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Whether `ExportFile::new` accepts the spec, warnings do not count.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|x| x.severity() == Severity::Error)
+    }
+
+    pub fn to_json(&self) -> Value {
+        Value::Array(self.issues.iter().map(|x| json!({
+            "code": x.code(),
+            "severity": match x.severity() {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            },
+            "table": x.table(),
+            "column": x.column(),
+            "message": x.to_string(),
+        })).collect())
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in self.issues.iter() {
+            writeln!(f, "{}: {}", issue.code(), issue)?;
+        }
+        Ok(())
+    }
+}
+
+
+impl ExportFile {
+    /// Checks a spec the way `new` and `build_schema` do, but collects every issue instead of stopping at the first.
+    pub fn validate(tables: &[Table], data_size_bytes: u64, number_of_files: u64) -> ValidationReport {
+        let mut issues = vec![];
+
+        if number_of_files == 0 {
+            issues.push(ValidationIssue::NoFiles);
+        }
+        if data_size_bytes == 0 {
+            issues.push(ValidationIssue::ZeroSize);
+        } else if number_of_files >= data_size_bytes {
+            issues.push(ValidationIssue::TooManyFiles { files: number_of_files, data_size_bytes });
+        }
+        if tables.is_empty() {
+            issues.push(ValidationIssue::NoTables);
+        }
+
+        let mut table_ids = HashSet::new();
+        for table in tables.iter() {
+            if !table_ids.insert(table.id_value.as_str()) {
+                issues.push(ValidationIssue::DuplicateTable { table: table.id_value.clone() });
+            }
+            if table.columns.is_empty() {
+                issues.push(ValidationIssue::EmptyTable { table: table.id_value.clone() });
+            }
+
+            let mut columns = HashSet::new();
+            for column in table.columns.iter() {
+                if !columns.insert(column.name.as_str()) {
+                    issues.push(ValidationIssue::DuplicateColumn {
+                        table: table.id_value.clone(),
+                        column: column.name.clone(),
+                    });
+                }
+                if column.size.max() == 0 {
+                    issues.push(ValidationIssue::ZeroSizeColumn {
+                        table: table.id_value.clone(),
+                        column: column.name.clone(),
+                    });
+                }
+                if column.is_not_null() && column.is_nullable() {
+                    issues.push(ValidationIssue::NullableNotNull {
+                        table: table.id_value.clone(),
+                        column: column.name.clone(),
+                    });
+                }
+            }
+
+            // Keys are kept unique across files like unique constraints, which fails when there are too few values.
+            if number_of_files > 0 && data_size_bytes > 0 {
+                let rows = table.row_count(data_size_bytes / number_of_files).unwrap_or(0).saturating_mul(number_of_files);
+                if let Some(values) = table.key_value_space().filter(|x| *x < rows) {
+                    issues.push(ValidationIssue::KeyNotUnique {
+                        table: table.id_value.clone(),
+                        column: table.columns.iter().filter(|x| x.key).map(|x| x.name.as_str()).collect::<Vec<&str>>().join(", "),
+                        values,
+                        rows,
+                    });
+                }
+            }
+
+            if table.percent_size <= Decimal::ZERO {
+                issues.push(ValidationIssue::InvalidPercentSize {
+                    table: table.id_value.clone(),
+                    percent_size: table.percent_size,
+                });
+            } else if number_of_files > 0 && data_size_bytes > 0 && !table.columns.is_empty() {
+                let available = Decimal::from(data_size_bytes / number_of_files) * table.percent_size;
+                if available < Decimal::from(table.row_size_bytes) {
+                    issues.push(ValidationIssue::TableDoesNotFit {
+                        table: table.id_value.clone(),
+                        needed: table.row_size_bytes,
+                        available: available.floor().to_u64().unwrap_or(0),
+                    });
+                }
+            }
+        }
+
+        let sum_percent_size: Decimal = tables.iter().map(|x| x.percent_size).sum();
+        if !tables.is_empty() && sum_percent_size != Decimal::ONE {
+            issues.push(ValidationIssue::PercentSizeSum { sum_percent_size });
+        }
+
+        ValidationReport { issues }
+    }
+}
+
+
+impl ExportFileBuilder {
+    /// Validates the tables, size and files `build` would use, a missing target size is reported as 0 bytes.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ExportFile::validate(&self.tables, self.target_bytes.unwrap_or(0), self.files.unwrap_or(1));
+        if self.normalize_percent_size {
+            report.issues.retain(|x| !matches!(x, ValidationIssue::PercentSizeSum { .. }));
+        }
+        report
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generators, Column};
+    use crate::types::ColumnType;
+
+    fn table(id: &str, columns: &[(&str, u64)], percent_size: &str) -> Table {
+        Table::new(
+            id.into(),
+            columns.iter()
+                .map(|(x, size)| Column::new((*x).into(), *size, ColumnType::Char(3), generators::constant("ABC")))
+                .collect(),
+            "|".into(),
+            Decimal::from_str(percent_size).unwrap(),
+        )
+    }
+
+    #[test]
+    fn validate_test() {
+        let tables = vec![
+            table("A", &[("a", 3), ("a", 3), ("b", 0)], "0.5"),
+            table("A", &[("a", 3)], "0"),
+            table("B", &[], "0.25"),
+            table("C", &[("c", 3)], "0.01"),
+        ];
+        let report = ExportFile::validate(&tables, 1000, 2);
+        let codes = report.issues.iter().map(|x| x.code()).collect::<Vec<&str>>();
+
+        assert_eq!(codes, vec![
+            "duplicate_column",
+            "zero_size_column",
+            "duplicate_table",
+            "invalid_percent_size",
+            "empty_table",
+            "table_does_not_fit",
+            "percent_size_sum",
+        ]);
+        assert!(!report.is_valid());
+        assert_eq!(report.to_json()[0]["column"], "a");
+        assert_eq!(report.to_json()[1]["severity"], "warning");
+        assert!(report.to_string().starts_with("duplicate_column: Table A has a duplicate column a.\n"));
+
+        let valid = ExportFile::validate(&[table("A", &[("a", 3), ("b", 0)], "1")], 1000, 2);
+        assert!(valid.is_valid());
+        assert_eq!(valid.issues.len(), 1);
+        assert!(ExportFile::new(vec![table("A", &[("a", 3)], "1")], 1000, 2).is_ok());
+
+        let builder = ExportFile::builder().add_table(table("A", &[("a", 3)], "2")).files(2);
+        let codes = builder.validate().errors().map(|x| x.code()).collect::<Vec<&str>>();
+        assert_eq!(codes, ["zero_size", "percent_size_sum"]);
+        assert!(builder.target_bytes(1000).normalize_percent_size(true).validate().is_valid());
+    }
+
+
+    #[test]
+    fn constraints_test() {
+        let columns = |null_rate: f64| vec![
+            Column::new("id".into(), 4, ColumnType::Int, generators::sequence(1, 1)).as_key(),
+            Column::new("code".into(), 3, ColumnType::Char(3), generators::random_string(3)).unique(true),
+            Column::new("name".into(), 3, ColumnType::Char(3), generators::constant("ABC"))
+                .as_not_null()
+                .with_null_rate(null_rate),
+        ];
+        let table = |columns: Vec<Column>| Table::new("A".into(), columns, "|".into(), Decimal::ONE);
+
+        let ef = ExportFile::new(vec![table(columns(0.0))], 1000, 2).unwrap();
+        assert!(ExportFile::validate(&ef.tables, 1000, 2).issues.is_empty());
+        let schema: Value = serde_json::from_str(&ef.get_schema_json_str().unwrap()).unwrap();
+        assert_eq!(schema["A"]["id"], json!({"not_null": true, "type": "INT"}));
+        assert_eq!(schema["A"]["code"], "CHAR(3)");
+        assert_eq!(schema["A"]["name"], json!({"not_null": true, "type": "CHAR(3)"}));
+        assert_eq!(schema["A"]["$table"], json!({"primary_key": ["id"], "unique": [["code"]]}));
+
+        // Nulls in a not null column are an error, so is a key with fewer values than rows.
+        let mut columns = columns(0.1);
+        columns[0] = Column::new("id".into(), 4, ColumnType::Int, generators::random_int(1, 9)).as_key();
+        let report = ExportFile::validate(&[table(columns.clone())], 1000, 2);
+        let codes = report.issues.iter().map(|x| x.code()).collect::<Vec<&str>>();
+        assert_eq!(codes, ["nullable_not_null", "key_not_unique"]);
+        assert_eq!(report.errors().count(), 2);
+        assert!(ExportFile::new(vec![table(columns.clone())], 1000, 2).unwrap().build_schema().is_err());
+
+        // The key is kept unique like a unique constraint, in every file.
+        let ef = ExportFile::new(vec![table(columns[..2].to_vec())], 100, 2).unwrap();
+        let keys = (0..2)
+            .flat_map(|x| ef.generate_file_rows(x).unwrap().swap_remove(0))
+            .map(|x| x[1].clone())
+            .collect::<Vec<String>>();
+        assert_eq!(keys.iter().collect::<HashSet<&String>>().len(), keys.len());
+        assert_eq!(ef.tables[0].key_value_space(), Some(9));
+    }
+}
@@ -0,0 +1,97 @@
+use crate::file_name::civil_from_days;
+
+pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "t" | "1" | "y" | "yes" => Some(true),
+        "false" | "f" | "0" | "n" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+pub(crate) fn parse_date(value: &str) -> Option<i64> {
+    let mut parts = value.splitn(3, '-');
+    let year = parts.next()?.parse::<i64>().ok()?;
+    let month = parts.next()?.parse::<i64>().ok()?;
+    let day = parts.next()?.parse::<i64>().ok()?;
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    Some(days_from_civil(year, month, day))
+}
+
+pub(crate) fn parse_timestamp_micros(value: &str) -> Option<i64> {
+    let (date, time) = value.split_once([' ', 'T']).unwrap_or((value, "00:00:00"));
+    let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+
+    let mut parts = time.splitn(3, ':');
+    let hours = parts.next()?.parse::<i64>().ok()?;
+    let minutes = parts.next()?.parse::<i64>().ok()?;
+    let seconds = parts.next().unwrap_or("0").parse::<i64>().ok()?;
+
+    if hours > 23 || minutes > 59 || seconds > 60 || fraction.len() > 6 {
+        return None;
+    }
+
+    let micros = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse::<i64>().ok()? * 10i64.pow(6 - fraction.len() as u32)
+    };
+
+    Some(
+        ((parse_date(date)? * 24 + hours) * 60 + minutes) * 60_000_000
+            + seconds * 1_000_000
+            + micros
+    )
+}
+
+pub(crate) fn format_date(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// `YYYY-MM-DD HH:MM:SS` of seconds since the epoch.
+pub(crate) fn format_timestamp(seconds: i64) -> String {
+    let time = seconds.rem_euclid(86400);
+
+    format!(
+        "{} {:02}:{:02}:{:02}",
+        format_date(seconds.div_euclid(86400)),
+        time / 3600,
+        time / 60 % 60,
+        time % 60,
+    )
+}
+
+/// `YYYY-MM-DD HH:MM:SS.ffffff`, the inverse of `parse_timestamp_micros`.
+pub(crate) fn format_timestamp_micros(micros: i64) -> String {
+    format!("{}.{:06}", format_timestamp(micros.div_euclid(1_000_000)), micros.rem_euclid(1_000_000))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_date_test() {
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("2024-01-18"), Some(19740));
+        assert_eq!(parse_timestamp_micros("1970-01-02 00:00:01.5"), Some(86_401_500_000));
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(format_timestamp_micros(86_401_500_000), "1970-01-02 00:00:01.500000");
+        let micros = 1_718_000_123_456_789;
+        assert_eq!(parse_timestamp_micros(&format_timestamp_micros(micros)), Some(micros));
+    }
+}
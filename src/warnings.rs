@@ -0,0 +1,144 @@
+use std::fmt;
+
+use anyhow::Result;
+use rust_decimal::prelude::*;
+
+use crate::{ExportFile, SizeSpec};
+use crate::builder::ExportFileBuilder;
+
+const SMALL_TABLE_ROWS: u64 = 10;
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecWarning {
+    TruncatedRowCount { table: String, unused_bytes: u64, share_bytes: u64 },
+    SmallTable { table: String, rows: u64 },
+    UnusedColumn { table: String, column: String },
+}
+
+impl fmt::Display for SpecWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecWarning::TruncatedRowCount { table, unused_bytes, share_bytes } => write!(
+                f,
+                "Table {} leaves {} of its {} bytes per file unused because rows do not divide its share.",
+                table, unused_bytes, share_bytes
+            ),
+            SpecWarning::SmallTable { table, rows } => write!(
+                f,
+                "Table {} only gets {} rows in some files.",
+                table, rows
+            ),
+            SpecWarning::UnusedColumn { table, column } => write!(
+                f,
+                "Column {} of table {} has size 0 and is not accounted for in sizing.",
+                column, table
+            ),
+        }
+    }
+}
+
+
+impl ExportFile {
+    pub fn warnings(&self) -> Vec<SpecWarning> {
+        let mut warnings = vec![];
+
+        for table in self.tables.iter() {
+            for column in table.columns.iter() {
+                if column.size.max() == 0 {
+                    warnings.push(SpecWarning::UnusedColumn {
+                        table: table.id_value.clone(),
+                        column: column.name.clone(),
+                    });
+                }
+            }
+
+            let sized_by_rows = matches!(&self.size_spec, SizeSpec::Rows(x) if x.contains_key(&table.id_value));
+
+            if !sized_by_rows && table.row_size_bytes > 0 {
+                let share_bytes = (Decimal::from(self.file_size_bytes) * table.percent_size)
+                    .to_u64()
+                    .unwrap_or(0);
+                let unused_bytes = share_bytes % table.row_size_bytes;
+
+                if unused_bytes * 100 >= share_bytes {
+                    warnings.push(SpecWarning::TruncatedRowCount {
+                        table: table.id_value.clone(),
+                        unused_bytes,
+                        share_bytes,
+                    });
+                }
+            }
+
+            let rows = (0..self.number_of_files)
+                .filter_map(|x| self.table_row_count(table, x).ok())
+                .min()
+                .unwrap_or(0);
+
+            if rows < SMALL_TABLE_ROWS {
+                warnings.push(SpecWarning::SmallTable { table: table.id_value.clone(), rows });
+            }
+        }
+
+        warnings
+    }
+}
+
+
+impl ExportFileBuilder {
+    pub fn build_with_warnings(self) -> Result<(ExportFile, Vec<SpecWarning>)> {
+        let export_file = self.build()?;
+        let warnings = export_file.warnings();
+
+        Ok((export_file, warnings))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Column, Table};
+    use crate::types::ColumnType;
+
+    fn simple_generator() -> Result<String> {
+        Ok("ABC".into())
+    }
+
+    #[test]
+    fn warnings_test() {
+        let t1 = Table::new(
+            "A".into(),
+            vec![
+                Column::new("a".into(), 3, ColumnType::Char(3), simple_generator),
+                Column::new("b".into(), 0, ColumnType::Char(3), simple_generator),
+            ],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+        let t2 = Table::new(
+            "B".into(),
+            vec![Column::new("id".into(), 4, ColumnType::Int, simple_generator).as_key()],
+            "|".into(),
+            Decimal::from_str("0.5").unwrap(),
+        );
+
+        let (_, warnings) = ExportFile::builder()
+            .add_table(t1)
+            .add_table(t2)
+            .target_bytes(100)
+            .build_with_warnings()
+            .unwrap();
+
+        assert_eq!(
+            warnings,
+            vec![
+                SpecWarning::UnusedColumn { table: "A".into(), column: "b".into() },
+                SpecWarning::TruncatedRowCount { table: "A".into(), unused_bytes: 1, share_bytes: 50 },
+                SpecWarning::SmallTable { table: "A".into(), rows: 7 },
+                SpecWarning::TruncatedRowCount { table: "B".into(), unused_bytes: 1, share_bytes: 50 },
+                SpecWarning::SmallTable { table: "B".into(), rows: 7 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::env;
+use std::path::Path;
+
+use anyhow::{Error, Result};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::ExportFile;
+use crate::health::Heartbeat;
+use crate::report::GenerationReport;
+use crate::shard::Shard;
+
+pub const SHARD_ENV: &str = "DIPLOMSKI_SHARD";
+pub const SHARD_COUNT_ENV: &str = "DIPLOMSKI_SHARD_COUNT";
+/// Set by Kubernetes for every pod of an indexed Job.
+pub const JOB_COMPLETION_INDEX_ENV: &str = "JOB_COMPLETION_INDEX";
+
+
+#[derive(Error, Debug)]
+pub enum WorkerError {
+    #[error("{variable} is set but {SHARD_COUNT_ENV} is missing or invalid.")]
+    MissingShardCount { variable: String },
+    #[error("Argument --shard needs a value of the form index/count.")]
+    MissingShardArgument,
+    #[error("A job needs at least one shard.")]
+    NoShards,
+}
+
+
+impl Shard {
+    /// Reads `--shard i/n` or `--shard=i/n` from command line arguments.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Option<Shard>> {
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if let Some(shard) = arg.strip_prefix("--shard=") {
+                return Ok(Some(shard.parse()?));
+            }
+            if arg == "--shard" {
+                let shard = args.next().ok_or_else(|| Error::from(WorkerError::MissingShardArgument))?;
+                return Ok(Some(shard.parse()?));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Reads `DIPLOMSKI_SHARD=i/n`, or the indexed Job completion index together with `DIPLOMSKI_SHARD_COUNT`.
+    pub fn from_env() -> Result<Option<Shard>> {
+        Shard::from_vars(|x| env::var(x).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Option<Shard>> {
+        if let Some(shard) = var(SHARD_ENV) {
+            return Ok(Some(shard.parse()?));
+        }
+
+        match var(JOB_COMPLETION_INDEX_ENV) {
+            Some(index) => {
+                let count = var(SHARD_COUNT_ENV)
+                    .ok_or_else(|| Error::from(WorkerError::MissingShardCount { variable: JOB_COMPLETION_INDEX_ENV.into() }))?;
+                Ok(Some(format!("{}/{}", index.trim(), count.trim()).parse()?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Command line arguments take precedence over the environment.
+    pub fn from_worker_config() -> Result<Option<Shard>> {
+        match Shard::from_args(env::args().skip(1))? {
+            Some(x) => Ok(Some(x)),
+            None => Shard::from_env(),
+        }
+    }
+}
+
+
+impl ExportFile {
+    /// Generates the shard this process is configured for, or every file when it is not a sharded worker.
+    /// Reports progress on a health endpoint, heartbeat file or metrics endpoint when those are configured in the environment.
+    pub fn generate_worker(self, folder_path: &Path) -> Result<GenerationReport> {
+        let shard = Shard::from_worker_config()?;
+        let files_total = match shard {
+            Some(shard) => shard.files(self.number_of_files).len() as u64,
+            None => self.number_of_files,
+        };
+
+        let export_file = match Heartbeat::from_env(files_total)? {
+            Some(heartbeat) => self.with_heartbeat(heartbeat),
+            None => self,
+        };
+        #[cfg(feature = "prometheus")]
+        let export_file = match crate::metrics::Metrics::from_env()? {
+            Some(metrics) => export_file.with_progress(metrics),
+            None => export_file,
+        };
+
+        match shard {
+            Some(shard) => export_file.generate_shard(folder_path, shard),
+            None => export_file.generate_all_files_with_report(folder_path),
+        }
+    }
+}
+
+
+pub struct JobManifest {
+    name: String,
+    image: String,
+    shards: u64,
+    parallelism: Option<u64>,
+    command: Vec<String>,
+}
+
+impl JobManifest {
+    pub fn new(name: impl Into<String>, image: impl Into<String>, shards: u64) -> Self {
+        JobManifest {
+            name: name.into(),
+            image: image.into(),
+            shards,
+            parallelism: None,
+            command: vec![],
+        }
+    }
+
+    /// Pods running at the same time, all shards by default.
+    pub fn parallelism(mut self, parallelism: u64) -> Self {
+        self.parallelism = Some(parallelism);
+        self
+    }
+
+    pub fn command(mut self, command: Vec<String>) -> Self {
+        self.command = command;
+        self
+    }
+
+    /// Indexed `batch/v1` Job running one pod per shard, as JSON which `kubectl apply` accepts like YAML.
+    pub fn to_json(&self) -> Result<String> {
+        if self.shards == 0 {
+            return Err(Error::from(WorkerError::NoShards));
+        }
+
+        let mut container = json!({
+            "name": "worker",
+            "image": self.image,
+            "env": [{"name": SHARD_COUNT_ENV, "value": self.shards.to_string()}],
+        });
+        if !self.command.is_empty() {
+            container["command"] = json!(self.command);
+        }
+
+        let manifest = json!({
+            "apiVersion": "batch/v1",
+            "kind": "Job",
+            "metadata": {"name": self.name},
+            "spec": {
+                "completionMode": "Indexed",
+                "completions": self.shards,
+                "parallelism": self.parallelism.unwrap_or(self.shards).min(self.shards),
+                "template": {
+                    "spec": {
+                        "restartPolicy": "Never",
+                        "containers": [container],
+                    },
+                },
+            },
+        });
+
+        Ok(serde_json::to_string_pretty(&manifest)?)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn shard_from_args_test() {
+        let args = |x: &[&str]| x.iter().map(|y| y.to_string()).collect::<Vec<String>>();
+
+        assert_eq!(Shard::from_args(args(&["--shard", "1/4"])).unwrap(), Some(Shard::new(1, 4).unwrap()));
+        assert_eq!(Shard::from_args(args(&["out", "--shard=2/4"])).unwrap(), Some(Shard::new(2, 4).unwrap()));
+        assert_eq!(Shard::from_args(args(&["out"])).unwrap(), None);
+        assert!(Shard::from_args(args(&["--shard"])).is_err());
+    }
+
+    #[test]
+    fn shard_from_vars_test() {
+        let vars = HashMap::from([
+            (JOB_COMPLETION_INDEX_ENV, "3".to_string()),
+            (SHARD_COUNT_ENV, "8".to_string()),
+        ]);
+
+        assert_eq!(Shard::from_vars(|x| vars.get(x).cloned()).unwrap(), Some(Shard::new(3, 8).unwrap()));
+        assert!(Shard::from_vars(|x| vars.get(x).filter(|_| x != SHARD_COUNT_ENV).cloned()).is_err());
+        assert_eq!(Shard::from_vars(|_| None).unwrap(), None);
+    }
+
+    #[test]
+    fn job_manifest_test() {
+        let manifest: serde_json::Value = serde_json::from_str(
+            &JobManifest::new("export", "registry/export:1", 4).parallelism(2).to_json().unwrap()
+        ).unwrap();
+
+        assert_eq!(manifest["spec"]["completionMode"], "Indexed");
+        assert_eq!(manifest["spec"]["completions"], 4);
+        assert_eq!(manifest["spec"]["parallelism"], 2);
+        assert_eq!(manifest["spec"]["template"]["spec"]["containers"][0]["env"][0]["value"], "4");
+        assert!(JobManifest::new("export", "registry/export:1", 0).to_json().is_err());
+    }
+}